@@ -0,0 +1,277 @@
+//	fadt.rs (acpi crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  Fixed ACPI Description Table
+//  - power management registers, DSDT pointer and legacy device flags
+//  - older (shorter) revisions are padded with zeros
+
+
+use core::mem::size_of;
+use core::ptr::copy_nonoverlapping;
+
+use crate::{GenericAddress, SdtHeader};
+
+
+pub const SIGNATURE: &[u8; 4] = b"FACP";
+
+/// IA-PC boot architecture flag: ISA devices (LPC) are present
+pub const BOOT_LEGACY_DEVICES: u16 = 1 << 0;
+/// IA-PC boot architecture flag: 8042 keyboard controller is present
+pub const BOOT_8042: u16 = 1 << 1;
+/// IA-PC boot architecture flag: VGA must not be probed
+pub const BOOT_NO_VGA: u16 = 1 << 2;
+/// IA-PC boot architecture flag: MSIs must not be enabled
+pub const BOOT_NO_MSI: u16 = 1 << 3;
+/// IA-PC boot architecture flag: CMOS RTC is not present
+pub const BOOT_NO_CMOS_RTC: u16 = 1 << 5;
+
+/// fixed feature flag: PM timer is 32 bits wide (24 otherwise)
+pub const FLAG_TMR_VAL_EXT: u32 = 1 << 8;
+/// fixed feature flag: `reset_register` is supported
+pub const FLAG_RESET_REG_SUP: u32 = 1 << 10;
+/// fixed feature flag: hardware reduced ACPI (no fixed hardware)
+pub const FLAG_HW_REDUCED_ACPI: u32 = 1 << 20;
+
+
+/// FADT data (after the header), layout of ACPI 6.x
+#[repr(C, packed)]
+#[derive(Copy, Clone)]
+struct FadtData {
+    firmware_ctrl: u32,
+    dsdt: u32,
+    _reserved0: u8,
+    preferred_pm_profile: u8,
+    sci_interrupt: u16,
+    smi_command: u32,
+    acpi_enable: u8,
+    acpi_disable: u8,
+    s4bios_req: u8,
+    pstate_control: u8,
+    pm1a_event_block: u32,
+    pm1b_event_block: u32,
+    pm1a_control_block: u32,
+    pm1b_control_block: u32,
+    pm2_control_block: u32,
+    pm_timer_block: u32,
+    gpe0_block: u32,
+    gpe1_block: u32,
+    pm1_event_length: u8,
+    pm1_control_length: u8,
+    pm2_control_length: u8,
+    pm_timer_length: u8,
+    gpe0_block_length: u8,
+    gpe1_block_length: u8,
+    gpe1_base: u8,
+    c_state_control: u8,
+    worst_c2_latency: u16,
+    worst_c3_latency: u16,
+    flush_size: u16,
+    flush_stride: u16,
+    duty_offset: u8,
+    duty_width: u8,
+    day_alarm: u8,
+    month_alarm: u8,
+    century: u8,
+    iapc_boot_arch: u16,
+    _reserved1: u8,
+    flags: u32,
+    reset_register: GenericAddress,
+    reset_value: u8,
+    arm_boot_arch: u16,
+    minor_version: u8,
+
+    //  revision 2+
+    x_firmware_ctrl: u64,
+    x_dsdt: u64,
+    x_pm1a_event_block: GenericAddress,
+    x_pm1b_event_block: GenericAddress,
+    x_pm1a_control_block: GenericAddress,
+    x_pm1b_control_block: GenericAddress,
+    x_pm2_control_block: GenericAddress,
+    x_pm_timer_block: GenericAddress,
+    x_gpe0_block: GenericAddress,
+    x_gpe1_block: GenericAddress,
+
+    //  revision 5+
+    sleep_control_register: GenericAddress,
+    sleep_status_register: GenericAddress,
+    hypervisor_vendor_id: u64,
+}
+
+
+/// # Fixed ACPI Description Table
+pub struct Fadt {
+    header: &'static SdtHeader,
+    data: FadtData,
+}
+
+impl Fadt {
+    pub(crate) fn new(header: &'static SdtHeader) -> Self {
+        //  fields missing in older revisions stay zero
+        let mut data: FadtData = unsafe { core::mem::zeroed() };
+        let len = header.data_length().min(size_of::<FadtData>());
+        unsafe { copy_nonoverlapping(header.data(), &raw mut data as *mut u8, len) };
+
+        Self { header, data }
+    }
+
+    #[inline]
+    pub fn header(&self) -> &'static SdtHeader {
+        self.header
+    }
+
+    /// physical address of the DSDT
+    /// - prefers the 64-bit field if it is set
+    pub fn dsdt_address(&self) -> u64 {
+        match self.data.x_dsdt {
+            0 => self.data.dsdt as u64,
+            x => x,
+        }
+    }
+
+    /// physical address of the Firmware ACPI Control Structure
+    pub fn facs_address(&self) -> u64 {
+        match self.data.x_firmware_ctrl {
+            0 => self.data.firmware_ctrl as u64,
+            x => x,
+        }
+    }
+
+    /// preferred power management profile (0 unspecified, 1 desktop, 2 mobile, ...)
+    #[inline]
+    pub fn preferred_pm_profile(&self) -> u8 {
+        self.data.preferred_pm_profile
+    }
+
+    /// system vector (in 8259 mode) or global system interrupt of the SCI
+    #[inline]
+    pub fn sci_interrupt(&self) -> u16 {
+        self.data.sci_interrupt
+    }
+
+    /// IO port used to switch between legacy and ACPI mode
+    /// - 0 if the system is always in ACPI mode
+    #[inline]
+    pub fn smi_command(&self) -> u32 {
+        self.data.smi_command
+    }
+
+    #[inline]
+    pub fn acpi_enable(&self) -> u8 {
+        self.data.acpi_enable
+    }
+
+    #[inline]
+    pub fn acpi_disable(&self) -> u8 {
+        self.data.acpi_disable
+    }
+
+    /// power management timer block
+    /// - returns `None` if the system has no PM timer
+    pub fn pm_timer(&self) -> Option<GenericAddress> {
+        let x = self.data.x_pm_timer_block;
+        if !x.is_null() {
+            return Some(x);
+        }
+        Self::legacy_block(self.data.pm_timer_block, self.data.pm_timer_length)
+    }
+
+    /// the PM timer counts 32 bits (24 bits otherwise)
+    #[inline]
+    pub fn pm_timer_is_32bit(&self) -> bool {
+        self.flags() & FLAG_TMR_VAL_EXT != 0
+    }
+
+    /// PM1a control block (sleep states, SCI enable)
+    pub fn pm1a_control(&self) -> Option<GenericAddress> {
+        let x = self.data.x_pm1a_control_block;
+        if !x.is_null() {
+            return Some(x);
+        }
+        Self::legacy_block(self.data.pm1a_control_block, self.data.pm1_control_length)
+    }
+
+    /// PM1a event block (status and enable registers)
+    pub fn pm1a_event(&self) -> Option<GenericAddress> {
+        let x = self.data.x_pm1a_event_block;
+        if !x.is_null() {
+            return Some(x);
+        }
+        Self::legacy_block(self.data.pm1a_event_block, self.data.pm1_event_length)
+    }
+
+    /// general purpose event block 0
+    pub fn gpe0(&self) -> Option<GenericAddress> {
+        let x = self.data.x_gpe0_block;
+        if !x.is_null() {
+            return Some(x);
+        }
+        Self::legacy_block(self.data.gpe0_block, self.data.gpe0_block_length)
+    }
+
+    /// converts 32-bit IO port block to generic address
+    fn legacy_block(port: u32, length: u8) -> Option<GenericAddress> {
+        if port == 0 {
+            return None;
+        }
+        Some(GenericAddress {
+            address_space: crate::ADDRESS_SPACE_IO,
+            bit_width: length.wrapping_mul(8),
+            bit_offset: 0,
+            access_size: 0,
+            address: port as u64,
+        })
+    }
+
+    /// index of the CMOS century register (0 if not supported)
+    #[inline]
+    pub fn century(&self) -> u8 {
+        self.data.century
+    }
+
+    /// IA-PC boot architecture flags (`BOOT_*`)
+    #[inline]
+    pub fn boot_arch(&self) -> u16 {
+        self.data.iapc_boot_arch
+    }
+
+    /// checks if the 8042 (PS/2) controller is present
+    /// - ACPI 1.0 tables do not report it, in that case it is assumed present
+    pub fn has_8042(&self) -> bool {
+        self.header.revision < 2 || self.boot_arch() & BOOT_8042 != 0
+    }
+
+    /// checks if legacy ISA devices (PIT, PIC, ...) are present
+    pub fn has_legacy_devices(&self) -> bool {
+        self.header.revision < 2 || self.boot_arch() & BOOT_LEGACY_DEVICES != 0
+    }
+
+    /// fixed feature flags (`FLAG_*`)
+    #[inline]
+    pub fn flags(&self) -> u32 {
+        self.data.flags
+    }
+
+    /// register used to reset the system and the value that must be written to it
+    /// - returns `None` if reset through the register is not supported
+    pub fn reset(&self) -> Option<(GenericAddress, u8)> {
+        let reg = self.data.reset_register;
+        if self.flags() & FLAG_RESET_REG_SUP == 0 || reg.is_null() {
+            return None;
+        }
+        Some((reg, self.data.reset_value))
+    }
+
+    /// returns FADT version as `(major, minor)`
+    #[inline]
+    pub fn version(&self) -> (u8, u8) {
+        (self.header.revision, self.data.minor_version & 0xf)
+    }
+
+    /// vendor of the hypervisor (0 if not reported)
+    #[inline]
+    pub fn hypervisor_vendor_id(&self) -> u64 {
+        self.data.hypervisor_vendor_id
+    }
+}
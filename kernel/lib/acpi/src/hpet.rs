@@ -0,0 +1,77 @@
+//	hpet.rs (acpi crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  High Precision Event Timer description table
+
+
+use core::ptr::read_unaligned;
+
+use crate::SdtHeader;
+pub use crate::{GenericAddress, ADDRESS_SPACE_MEMORY};
+
+
+pub const SIGNATURE: &[u8; 4] = b"HPET";
+
+
+/// HPET table data (after the header)
+#[repr(C, packed)]
+#[derive(Copy, Clone)]
+struct HpetData {
+    event_timer_block_id: u32,
+    base_address: GenericAddress,
+    hpet_number: u8,
+    minimum_tick: u16,
+    page_protection: u8,
+}
+
+
+/// # HPET Description Table
+pub struct Hpet {
+    header: &'static SdtHeader,
+    data: HpetData,
+}
+
+impl Hpet {
+    /// - returns `None` if the table is too short
+    pub(crate) fn new(header: &'static SdtHeader) -> Option<Self> {
+        if header.data_length() < size_of::<HpetData>() {
+            return None;
+        }
+        let data = unsafe { read_unaligned(header.data() as *const HpetData) };
+        Some(Self { header, data })
+    }
+
+    #[inline]
+    pub fn header(&self) -> &'static SdtHeader {
+        self.header
+    }
+
+    /// hardware revision, comparator count, counter size, vendor (see HPET spec)
+    #[inline]
+    pub fn event_timer_block_id(&self) -> u32 {
+        self.data.event_timer_block_id
+    }
+
+    /// registers of the timer block
+    #[inline]
+    pub fn base_address(&self) -> GenericAddress {
+        self.data.base_address
+    }
+
+    #[inline]
+    pub fn hpet_number(&self) -> u8 {
+        self.data.hpet_number
+    }
+
+    /// minimal periodic tick (in main counter ticks)
+    #[inline]
+    pub fn minimum_tick(&self) -> u16 {
+        self.data.minimum_tick
+    }
+
+    #[inline]
+    pub fn page_protection(&self) -> u8 {
+        self.data.page_protection
+    }
+}
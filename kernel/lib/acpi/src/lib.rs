@@ -0,0 +1,319 @@
+//	lib.rs (acpi crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+#![cfg_attr(not(test), no_std)]
+
+//  ACPI table parser
+//  - the crate does not allocate and does not depend on the bootloader
+//  - physical addresses found in the tables are translated using fixed offset (HHDM)
+//  - checksums of the RSDP and the RSDT/XSDT are verified, other tables are skipped if invalid
+
+use core::mem::size_of;
+use core::ptr::read_unaligned;
+
+pub mod madt;
+pub use madt::Madt;
+pub mod hpet;
+pub use hpet::Hpet;
+pub mod fadt;
+pub use fadt::Fadt;
+pub mod mcfg;
+pub use mcfg::Mcfg;
+pub mod srat;
+pub use srat::Srat;
+
+#[cfg(test)]
+mod tests;
+
+
+/// # Root System Description Pointer
+/// revision 0 ends after `rsdt_address`, revision 2+ contains the rest
+#[repr(C, packed)]
+#[derive(Copy, Clone)]
+pub struct Rsdp {
+    pub signature: [u8; 8],
+    pub checksum: u8,
+    pub oem_id: [u8; 6],
+    pub revision: u8,
+    pub rsdt_address: u32,
+
+    //  revision 2+
+    pub length: u32,
+    pub xsdt_address: u64,
+    pub extended_checksum: u8,
+    _reserved: [u8; 3],
+}
+
+pub const RSDP_SIGNATURE: &[u8; 8] = b"RSD PTR ";
+
+/// length of revision 0 RSDP (covered by `checksum`)
+pub const RSDP_V1_LENGTH: usize = 20;
+
+
+/// returns sum of `len` bytes at `ptr` (valid tables sum to 0)
+///
+/// # Safety
+/// - `ptr` must be valid for reads of `len` bytes (no alignment is required)
+/// - the bytes must be initialized and must not be written meanwhile
+pub unsafe fn checksum(ptr: *const u8, len: usize) -> u8 {
+    let mut sum = 0u8;
+    for i in 0..len {
+        sum = sum.wrapping_add(unsafe { *ptr.add(i) });
+    }
+    sum
+}
+
+
+/// address space of [`GenericAddress`] in system memory
+pub const ADDRESS_SPACE_MEMORY: u8 = 0;
+/// address space of [`GenericAddress`] in IO ports
+pub const ADDRESS_SPACE_IO: u8 = 1;
+/// address space of [`GenericAddress`] in PCI configuration space
+pub const ADDRESS_SPACE_PCI: u8 = 2;
+
+
+/// # Generic Address Structure
+#[repr(C, packed)]
+#[derive(Copy, Clone)]
+pub struct GenericAddress {
+    pub address_space: u8,
+    pub bit_width: u8,
+    pub bit_offset: u8,
+    pub access_size: u8,
+    pub address: u64,
+}
+
+impl GenericAddress {
+    /// register is not implemented
+    #[inline]
+    pub fn is_null(&self) -> bool {
+        let address = self.address;
+        address == 0
+    }
+}
+
+
+/// # System Description Table header
+/// common header of all tables (except RSDP)
+#[repr(C, packed)]
+#[derive(Copy, Clone)]
+pub struct SdtHeader {
+    pub signature: [u8; 4],
+    pub length: u32,
+    pub revision: u8,
+    pub checksum: u8,
+    pub oem_id: [u8; 6],
+    pub oem_table_id: [u8; 8],
+    pub oem_revision: u32,
+    pub creator_id: u32,
+    pub creator_revision: u32,
+}
+
+impl SdtHeader {
+    /// returns the table signature as string
+    #[inline]
+    pub fn signature(&self) -> &str {
+        core::str::from_utf8(&self.signature).unwrap_or("????")
+    }
+
+    /// returns total length of the table (including header)
+    #[inline]
+    pub fn length(&self) -> usize {
+        self.length as usize
+    }
+
+    /// returns pointer to the data right after the header
+    #[inline]
+    pub fn data(&self) -> *const u8 {
+        unsafe { (self as *const Self as *const u8).add(size_of::<Self>()) }
+    }
+
+    /// returns length of the data behind the header
+    #[inline]
+    pub fn data_length(&self) -> usize {
+        self.length().saturating_sub(size_of::<Self>())
+    }
+
+    /// checks if the table is at least as long as its header and sums to 0
+    pub fn is_valid(&self) -> bool {
+        self.length() >= size_of::<Self>()
+            && unsafe { checksum(self as *const Self as *const u8, self.length()) } == 0
+    }
+}
+
+
+/// # ACPI tables
+/// Entry point to all tables, constructed from the RSDP
+pub struct AcpiTables {
+    root: &'static SdtHeader,
+    extended: bool,
+    offset: usize,
+    revision: u8,
+}
+
+impl AcpiTables {
+
+    /// Constructs `AcpiTables` from the RSDP
+    /// - `rsdp` is **virtual** address of the RSDP
+    /// - `offset` is added to every physical address found in the tables
+    /// - returns `Err` if the RSDP signature or any checksum of the RSDP or the RSDT/XSDT does not match
+    ///
+    /// # Safety
+    /// - `rsdp` must be readable for 20 bytes, or for `length` bytes if its revision is 2+
+    /// - all tables must be mapped at `physical + offset` for as long as `AcpiTables` is used,
+    ///   each readable for the length in its header
+    #[allow(clippy::result_unit_err)]
+    pub unsafe fn from_rsdp(rsdp: usize, offset: usize) -> Result<Self, ()> {
+        let ptr = rsdp as *const Rsdp;
+
+        //  revision 0 RSDP is shorter, do not read past it
+        let signature = unsafe { read_unaligned(&raw const (*ptr).signature) };
+        if &signature != RSDP_SIGNATURE {
+            return Err(());
+        }
+        if unsafe { checksum(ptr as *const u8, RSDP_V1_LENGTH) } != 0 {
+            return Err(());
+        }
+
+        let revision = unsafe { read_unaligned(&raw const (*ptr).revision) };
+        if revision >= 2 {
+            let length = unsafe { read_unaligned(&raw const (*ptr).length) } as usize;
+            if length < size_of::<Rsdp>() || unsafe { checksum(ptr as *const u8, length) } != 0 {
+                return Err(());
+            }
+        }
+
+        let (root, extended) = if revision >= 2 {
+            (unsafe { read_unaligned(&raw const (*ptr).xsdt_address) }, true)
+        } else {
+            (unsafe { read_unaligned(&raw const (*ptr).rsdt_address) } as u64, false)
+        };
+
+        if root == 0 {
+            return Err(());
+        }
+
+        let root = unsafe { &*((root as usize + offset) as *const SdtHeader) };
+
+        let expected = if extended { b"XSDT" } else { b"RSDT" };
+        if &root.signature != expected || !root.is_valid() {
+            return Err(());
+        }
+
+        Ok(Self {
+            root,
+            extended,
+            offset,
+            revision,
+        })
+    }
+
+    /// returns the ACPI revision found in the RSDP
+    #[inline]
+    pub fn revision(&self) -> u8 {
+        self.revision
+    }
+
+    /// indicates if XSDT (64-bit pointers) is used
+    #[inline]
+    pub fn is_extended(&self) -> bool {
+        self.extended
+    }
+
+    /// returns the RSDT/XSDT header
+    #[inline]
+    pub fn root(&self) -> &'static SdtHeader {
+        self.root
+    }
+
+    /// translates physical address found in the tables to virtual
+    #[inline]
+    pub fn phys_to_virt(&self, phys: u64) -> usize {
+        phys as usize + self.offset
+    }
+
+    /// returns iterator over all tables listed in the RSDT/XSDT
+    /// - checksums are not verified (see [`SdtHeader::is_valid`])
+    pub fn tables(&self) -> Tables<'_> {
+        let entry_size = if self.extended { 8 } else { 4 };
+        Tables {
+            acpi: self,
+            index: 0,
+            count: self.root.data_length() / entry_size,
+        }
+    }
+
+    /// finds the first valid table with `signature`
+    pub fn find(&self, signature: &[u8; 4]) -> Option<&'static SdtHeader> {
+        self.tables().find(|t| &t.signature == signature && t.is_valid())
+    }
+
+    /// returns the Multiple APIC Description Table
+    pub fn madt(&self) -> Option<Madt> {
+        self.find(madt::SIGNATURE).map(Madt::new)
+    }
+
+    /// returns the HPET description table
+    /// - returns `None` if the table is missing or too short
+    pub fn hpet(&self) -> Option<Hpet> {
+        self.find(hpet::SIGNATURE).and_then(Hpet::new)
+    }
+
+    /// returns the Fixed ACPI Description Table
+    pub fn fadt(&self) -> Option<Fadt> {
+        self.find(fadt::SIGNATURE).map(Fadt::new)
+    }
+
+    /// returns the Differentiated System Description Table (located through the FADT)
+    /// - returns `None` if the table is missing or invalid
+    pub fn dsdt(&self) -> Option<&'static SdtHeader> {
+        let phys = self.fadt()?.dsdt_address();
+        if phys == 0 {
+            return None;
+        }
+        let dsdt = unsafe { &*(self.phys_to_virt(phys) as *const SdtHeader) };
+        (&dsdt.signature == b"DSDT" && dsdt.is_valid()).then_some(dsdt)
+    }
+
+    /// returns the PCI Express memory mapped configuration table
+    pub fn mcfg(&self) -> Option<Mcfg> {
+        self.find(mcfg::SIGNATURE).map(Mcfg::new)
+    }
+
+    /// returns the System Resource Affinity Table
+    pub fn srat(&self) -> Option<Srat> {
+        self.find(srat::SIGNATURE).map(Srat::new)
+    }
+}
+
+
+/// Iterator over tables listed in RSDT/XSDT
+pub struct Tables<'l> {
+    acpi: &'l AcpiTables,
+    index: usize,
+    count: usize,
+}
+
+impl<'l> Iterator for Tables<'l> {
+    type Item = &'static SdtHeader;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.index < self.count {
+            let data = self.acpi.root.data();
+            let phys = unsafe {
+                if self.acpi.extended {
+                    read_unaligned(data.add(self.index * 8) as *const u64)
+                } else {
+                    read_unaligned(data.add(self.index * 4) as *const u32) as u64
+                }
+            };
+            self.index += 1;
+
+            if phys != 0 {
+                return Some(unsafe { &*(self.acpi.phys_to_virt(phys) as *const SdtHeader) });
+            }
+        }
+        None
+    }
+}
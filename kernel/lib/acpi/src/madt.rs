@@ -0,0 +1,302 @@
+//	madt.rs (acpi crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  Multiple APIC Description Table
+//  - describes local APICs, IO APICs and how legacy IRQs are wired
+
+use core::ptr::read_unaligned;
+
+use crate::SdtHeader;
+
+pub const SIGNATURE: &[u8; 4] = b"APIC";
+
+/// MADT flag: system also has dual 8259 PICs
+pub const PCAT_COMPAT: u32 = 1;
+
+
+/// Local APIC (or x2APIC) of one processor
+#[derive(Copy, Clone)]
+pub struct LocalApic {
+    pub processor_id: u32,
+    pub apic_id: u32,
+    pub flags: u32,
+}
+
+impl LocalApic {
+    /// processor is ready to use
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.flags & 1 != 0
+    }
+
+    /// processor can be enabled at runtime
+    #[inline]
+    pub fn is_online_capable(&self) -> bool {
+        self.flags & 2 != 0
+    }
+}
+
+/// IO APIC
+#[derive(Copy, Clone)]
+pub struct IoApic {
+    pub id: u8,
+    pub address: u32,
+    /// first global system interrupt handled by this IO APIC
+    pub gsi_base: u32,
+}
+
+/// Polarity of an interrupt line
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Polarity {
+    /// conforms to the bus specification (active high for ISA)
+    Conforming,
+    ActiveHigh,
+    ActiveLow,
+}
+
+/// Trigger mode of an interrupt line
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum TriggerMode {
+    /// conforms to the bus specification (edge for ISA)
+    Conforming,
+    Edge,
+    Level,
+}
+
+/// MPS INTI flags used by overrides and NMI entries
+#[derive(Copy, Clone)]
+pub struct IntiFlags(pub u16);
+
+impl IntiFlags {
+    pub fn polarity(&self) -> Polarity {
+        match self.0 & 0b11 {
+            0b01 => Polarity::ActiveHigh,
+            0b11 => Polarity::ActiveLow,
+            _ => Polarity::Conforming,
+        }
+    }
+
+    pub fn trigger_mode(&self) -> TriggerMode {
+        match (self.0 >> 2) & 0b11 {
+            0b01 => TriggerMode::Edge,
+            0b11 => TriggerMode::Level,
+            _ => TriggerMode::Conforming,
+        }
+    }
+}
+
+/// Maps ISA IRQ (`source`) to global system interrupt
+#[derive(Copy, Clone)]
+pub struct InterruptSourceOverride {
+    pub bus: u8,
+    pub source: u8,
+    pub gsi: u32,
+    pub flags: IntiFlags,
+}
+
+/// Global system interrupt that should be configured as NMI
+#[derive(Copy, Clone)]
+pub struct NmiSource {
+    pub flags: IntiFlags,
+    pub gsi: u32,
+}
+
+/// Local APIC LINT pin connected to NMI
+/// - `processor_id` 0xff (or 0xffffffff for x2APIC) means all processors
+#[derive(Copy, Clone)]
+pub struct LocalApicNmi {
+    pub processor_id: u32,
+    pub flags: IntiFlags,
+    pub lint: u8,
+}
+
+impl LocalApicNmi {
+    /// entry applies to all processors
+    #[inline]
+    pub fn is_broadcast(&self) -> bool {
+        self.processor_id == 0xff || self.processor_id == 0xffff_ffff
+    }
+}
+
+
+/// One entry of the MADT
+#[derive(Copy, Clone)]
+pub enum MadtEntry {
+    LocalApic(LocalApic),
+    IoApic(IoApic),
+    InterruptSourceOverride(InterruptSourceOverride),
+    NmiSource(NmiSource),
+    LocalApicNmi(LocalApicNmi),
+    /// 64-bit local APIC address overriding the one in the header
+    LocalApicAddressOverride(u64),
+    LocalX2Apic(LocalApic),
+    LocalX2ApicNmi(LocalApicNmi),
+    /// entry type that is not decoded
+    Unknown(u8),
+}
+
+
+/// # Multiple APIC Description Table
+pub struct Madt {
+    header: &'static SdtHeader,
+}
+
+impl Madt {
+    pub(crate) fn new(header: &'static SdtHeader) -> Self {
+        Self { header }
+    }
+
+    #[inline]
+    pub fn header(&self) -> &'static SdtHeader {
+        self.header
+    }
+
+    /// physical address of local APIC (32-bit, see [`Madt::local_apic_address`])
+    #[inline]
+    pub fn local_apic_address_32(&self) -> u32 {
+        unsafe { read_unaligned(self.header.data() as *const u32) }
+    }
+
+    /// physical address of local APIC
+    /// - respects the local APIC address override entry
+    pub fn local_apic_address(&self) -> u64 {
+        for i in self.entries() {
+            if let MadtEntry::LocalApicAddressOverride(addr) = i {
+                return addr;
+            }
+        }
+        self.local_apic_address_32() as u64
+    }
+
+    #[inline]
+    pub fn flags(&self) -> u32 {
+        unsafe { read_unaligned(self.header.data().add(4) as *const u32) }
+    }
+
+    /// system also has the legacy 8259 PICs (they should be masked)
+    #[inline]
+    pub fn has_legacy_pics(&self) -> bool {
+        self.flags() & PCAT_COMPAT != 0
+    }
+
+    /// returns iterator over all entries
+    pub fn entries(&self) -> MadtEntries {
+        MadtEntries {
+            //  skip local APIC address and flags
+            ptr: unsafe { self.header.data().add(8) },
+            remaining: self.header.data_length().saturating_sub(8),
+        }
+    }
+
+    /// returns iterator over local APICs and x2APICs (one per processor)
+    pub fn local_apics(&self) -> impl Iterator<Item = LocalApic> {
+        self.entries().filter_map(|e| match e {
+            MadtEntry::LocalApic(l) | MadtEntry::LocalX2Apic(l) => Some(l),
+            _ => None,
+        })
+    }
+
+    /// returns iterator over IO APICs
+    pub fn io_apics(&self) -> impl Iterator<Item = IoApic> {
+        self.entries().filter_map(|e| match e {
+            MadtEntry::IoApic(i) => Some(i),
+            _ => None,
+        })
+    }
+
+    /// returns iterator over interrupt source overrides
+    pub fn overrides(&self) -> impl Iterator<Item = InterruptSourceOverride> {
+        self.entries().filter_map(|e| match e {
+            MadtEntry::InterruptSourceOverride(o) => Some(o),
+            _ => None,
+        })
+    }
+
+    /// returns the override for ISA `irq` if there is any
+    pub fn isa_override(&self, irq: u8) -> Option<InterruptSourceOverride> {
+        self.entries().find_map(|e| match e {
+            MadtEntry::InterruptSourceOverride(o) if o.bus == 0 && o.source == irq => Some(o),
+            _ => None,
+        })
+    }
+}
+
+
+/// Iterator over MADT entries
+pub struct MadtEntries {
+    ptr: *const u8,
+    remaining: usize,
+}
+
+#[inline(always)]
+unsafe fn read<T: Copy>(ptr: *const u8, offset: usize) -> T {
+    unsafe { read_unaligned(ptr.add(offset) as *const T) }
+}
+
+impl Iterator for MadtEntries {
+    type Item = MadtEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining < 2 {
+            return None;
+        }
+
+        let p = self.ptr;
+        let kind: u8 = unsafe { read(p, 0) };
+        let len: u8 = unsafe { read(p, 1) };
+
+        //  malformed entry, stop iterating
+        if len < 2 || len as usize > self.remaining {
+            self.remaining = 0;
+            return None;
+        }
+
+        self.ptr = unsafe { p.add(len as usize) };
+        self.remaining -= len as usize;
+
+        let entry = unsafe {
+            match (kind, len) {
+                (0, 8..) => MadtEntry::LocalApic(LocalApic {
+                    processor_id: read::<u8>(p, 2) as u32,
+                    apic_id: read::<u8>(p, 3) as u32,
+                    flags: read(p, 4),
+                }),
+                (1, 12..) => MadtEntry::IoApic(IoApic {
+                    id: read(p, 2),
+                    address: read(p, 4),
+                    gsi_base: read(p, 8),
+                }),
+                (2, 10..) => MadtEntry::InterruptSourceOverride(InterruptSourceOverride {
+                    bus: read(p, 2),
+                    source: read(p, 3),
+                    gsi: read(p, 4),
+                    flags: IntiFlags(read(p, 8)),
+                }),
+                (3, 8..) => MadtEntry::NmiSource(NmiSource {
+                    flags: IntiFlags(read(p, 2)),
+                    gsi: read(p, 4),
+                }),
+                (4, 6..) => MadtEntry::LocalApicNmi(LocalApicNmi {
+                    processor_id: read::<u8>(p, 2) as u32,
+                    flags: IntiFlags(read(p, 3)),
+                    lint: read(p, 5),
+                }),
+                (5, 12..) => MadtEntry::LocalApicAddressOverride(read(p, 4)),
+                (9, 16..) => MadtEntry::LocalX2Apic(LocalApic {
+                    apic_id: read(p, 4),
+                    flags: read(p, 8),
+                    processor_id: read(p, 12),
+                }),
+                (10, 12..) => MadtEntry::LocalX2ApicNmi(LocalApicNmi {
+                    flags: IntiFlags(read(p, 2)),
+                    processor_id: read(p, 4),
+                    lint: read(p, 8),
+                }),
+                _ => MadtEntry::Unknown(kind),
+            }
+        };
+
+        Some(entry)
+    }
+}
@@ -0,0 +1,93 @@
+//	mcfg.rs (acpi crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  PCI Express memory mapped configuration table
+//  - lists ECAM regions (one per segment group and bus range)
+
+
+use core::mem::size_of;
+use core::ptr::read_unaligned;
+
+use crate::SdtHeader;
+
+
+pub const SIGNATURE: &[u8; 4] = b"MCFG";
+
+/// reserved bytes between the header and the first entry
+const RESERVED: usize = 8;
+
+
+/// Memory mapped configuration space of one PCI segment group
+#[repr(C, packed)]
+#[derive(Copy, Clone)]
+pub struct McfgEntry {
+    /// physical address of the configuration space of bus 0 (even if `start_bus` is not 0)
+    pub base_address: u64,
+    pub segment: u16,
+    pub start_bus: u8,
+    pub end_bus: u8,
+    _reserved: u32,
+}
+
+impl McfgEntry {
+    /// returns physical address of the configuration space of given function
+    /// - returns `None` if the bus is not covered by this entry or the device/function is invalid
+    pub fn address(&self, bus: u8, device: u8, function: u8) -> Option<u64> {
+        if bus < self.start_bus || bus > self.end_bus || device >= 32 || function >= 8 {
+            return None;
+        }
+        let base = self.base_address;
+        Some(base + ((bus as u64) << 20 | (device as u64) << 15 | (function as u64) << 12))
+    }
+}
+
+
+/// # PCI Express memory mapped configuration table
+pub struct Mcfg {
+    header: &'static SdtHeader,
+}
+
+impl Mcfg {
+    pub(crate) fn new(header: &'static SdtHeader) -> Self {
+        Self { header }
+    }
+
+    #[inline]
+    pub fn header(&self) -> &'static SdtHeader {
+        self.header
+    }
+
+    /// returns the number of entries
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.header.data_length().saturating_sub(RESERVED) / size_of::<McfgEntry>()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// returns entry at `index`
+    pub fn get(&self, index: usize) -> Option<McfgEntry> {
+        if index >= self.len() {
+            return None;
+        }
+        let ptr = unsafe { self.header.data().add(RESERVED + index * size_of::<McfgEntry>()) };
+        Some(unsafe { read_unaligned(ptr as *const McfgEntry) })
+    }
+
+    /// returns iterator over all entries
+    pub fn entries(&self) -> impl Iterator<Item = McfgEntry> + '_ {
+        (0..self.len()).filter_map(|i| self.get(i))
+    }
+
+    /// finds entry covering `bus` of `segment`
+    pub fn find(&self, segment: u16, bus: u8) -> Option<McfgEntry> {
+        self.entries().find(|e| {
+            let s = e.segment;
+            s == segment && e.start_bus <= bus && bus <= e.end_bus
+        })
+    }
+}
@@ -0,0 +1,203 @@
+//	srat.rs (acpi crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  System Resource Affinity Table
+//  - assigns processors and memory ranges to NUMA proximity domains
+
+
+use core::ptr::read_unaligned;
+
+use crate::SdtHeader;
+
+
+pub const SIGNATURE: &[u8; 4] = b"SRAT";
+
+/// reserved bytes between the header and the first entry
+const RESERVED: usize = 12;
+
+/// affinity flag: entry is enabled (must be ignored otherwise)
+pub const AFFINITY_ENABLED: u32 = 1 << 0;
+/// memory affinity flag: the range is hot-pluggable
+pub const MEMORY_HOT_PLUGGABLE: u32 = 1 << 1;
+/// memory affinity flag: the range is non-volatile
+pub const MEMORY_NON_VOLATILE: u32 = 1 << 2;
+
+
+/// Proximity domain of one processor (local APIC or x2APIC)
+#[derive(Copy, Clone)]
+pub struct ProcessorAffinity {
+    pub proximity_domain: u32,
+    pub apic_id: u32,
+    pub flags: u32,
+    pub clock_domain: u32,
+}
+
+impl ProcessorAffinity {
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.flags & AFFINITY_ENABLED != 0
+    }
+}
+
+/// Proximity domain of a physical memory range
+#[derive(Copy, Clone)]
+pub struct MemoryAffinity {
+    pub proximity_domain: u32,
+    pub base: u64,
+    pub length: u64,
+    pub flags: u32,
+}
+
+impl MemoryAffinity {
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.flags & AFFINITY_ENABLED != 0
+    }
+
+    #[inline]
+    pub fn is_hot_pluggable(&self) -> bool {
+        self.flags & MEMORY_HOT_PLUGGABLE != 0
+    }
+
+    #[inline]
+    pub fn is_non_volatile(&self) -> bool {
+        self.flags & MEMORY_NON_VOLATILE != 0
+    }
+
+    /// checks if the physical `address` is in the range
+    #[inline]
+    pub fn contains(&self, address: u64) -> bool {
+        address >= self.base && address - self.base < self.length
+    }
+}
+
+
+/// One entry of the SRAT
+#[derive(Copy, Clone)]
+pub enum SratEntry {
+    LocalApic(ProcessorAffinity),
+    Memory(MemoryAffinity),
+    LocalX2Apic(ProcessorAffinity),
+    /// entry type that is not decoded
+    Unknown(u8),
+}
+
+
+/// # System Resource Affinity Table
+pub struct Srat {
+    header: &'static SdtHeader,
+}
+
+impl Srat {
+    pub(crate) fn new(header: &'static SdtHeader) -> Self {
+        Self { header }
+    }
+
+    #[inline]
+    pub fn header(&self) -> &'static SdtHeader {
+        self.header
+    }
+
+    /// returns iterator over all entries
+    pub fn entries(&self) -> SratEntries {
+        SratEntries {
+            ptr: unsafe { self.header.data().add(RESERVED) },
+            remaining: self.header.data_length().saturating_sub(RESERVED),
+        }
+    }
+
+    /// returns iterator over enabled processor entries
+    pub fn processors(&self) -> impl Iterator<Item = ProcessorAffinity> {
+        self.entries().filter_map(|e| match e {
+            SratEntry::LocalApic(p) | SratEntry::LocalX2Apic(p) if p.is_enabled() => Some(p),
+            _ => None,
+        })
+    }
+
+    /// returns iterator over enabled memory entries
+    pub fn memory(&self) -> impl Iterator<Item = MemoryAffinity> {
+        self.entries().filter_map(|e| match e {
+            SratEntry::Memory(m) if m.is_enabled() => Some(m),
+            _ => None,
+        })
+    }
+
+    /// returns proximity domain of the processor with `apic_id`
+    pub fn processor_domain(&self, apic_id: u32) -> Option<u32> {
+        self.processors().find(|p| p.apic_id == apic_id).map(|p| p.proximity_domain)
+    }
+
+    /// returns proximity domain of physical `address`
+    pub fn memory_domain(&self, address: u64) -> Option<u32> {
+        self.memory().find(|m| m.contains(address)).map(|m| m.proximity_domain)
+    }
+}
+
+
+/// Iterator over SRAT entries
+pub struct SratEntries {
+    ptr: *const u8,
+    remaining: usize,
+}
+
+#[inline(always)]
+unsafe fn read<T: Copy>(ptr: *const u8, offset: usize) -> T {
+    unsafe { read_unaligned(ptr.add(offset) as *const T) }
+}
+
+impl Iterator for SratEntries {
+    type Item = SratEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining < 2 {
+            return None;
+        }
+
+        let p = self.ptr;
+        let kind: u8 = unsafe { read(p, 0) };
+        let len: u8 = unsafe { read(p, 1) };
+
+        //  malformed entry, stop iterating
+        if len < 2 || len as usize > self.remaining {
+            self.remaining = 0;
+            return None;
+        }
+
+        self.ptr = unsafe { p.add(len as usize) };
+        self.remaining -= len as usize;
+
+        let entry = unsafe {
+            match (kind, len) {
+                (0, 16..) => {
+                    //  proximity domain is split into bits 0..8 and 8..32
+                    let low = read::<u8>(p, 2) as u32;
+                    let high = read::<u8>(p, 9) as u32
+                        | (read::<u8>(p, 10) as u32) << 8
+                        | (read::<u8>(p, 11) as u32) << 16;
+                    SratEntry::LocalApic(ProcessorAffinity {
+                        proximity_domain: low | high << 8,
+                        apic_id: read::<u8>(p, 3) as u32,
+                        flags: read(p, 4),
+                        clock_domain: read(p, 12),
+                    })
+                }
+                (1, 40..) => SratEntry::Memory(MemoryAffinity {
+                    proximity_domain: read(p, 2),
+                    base: read(p, 8),
+                    length: read(p, 16),
+                    flags: read(p, 28),
+                }),
+                (2, 24..) => SratEntry::LocalX2Apic(ProcessorAffinity {
+                    proximity_domain: read(p, 4),
+                    apic_id: read(p, 8),
+                    flags: read(p, 12),
+                    clock_domain: read(p, 16),
+                }),
+                _ => SratEntry::Unknown(kind),
+            }
+        };
+
+        Some(entry)
+    }
+}
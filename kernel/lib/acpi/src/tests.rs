@@ -0,0 +1,555 @@
+//	tests.rs (acpi crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  host tests of the parser
+//  - well-formed tables are binary dumps (see `testdata/README.md`), only the RSDP and
+//    the RSDT/XSDT around them are built here
+//  - hand-written tables (QEMU q35 layout) are used for the malformed cases
+//    and for tables there is no dump of yet (HPET, SRAT, MADT overrides, FADT fixed hardware)
+//    headers, lengths and checksums of those are filled in by `Image`
+//  - "physical memory" is a leaked buffer, its address is used as the HHDM offset
+
+use std::boxed::Box;
+use std::vec;
+use std::vec::Vec;
+
+use crate::*;
+use crate::fadt::{BOOT_NO_VGA, FLAG_HW_REDUCED_ACPI};
+use crate::madt::{MadtEntry, Polarity, TriggerMode};
+use crate::srat::SratEntry;
+
+
+const RSDP_AT: usize = 0x100;
+const RSDT_AT: usize = 0x200;
+const XSDT_AT: usize = 0x300;
+const FACS_AT: usize = 0x1000;
+const DSDT_AT: usize = 0x1100;
+const FADT_AT: usize = 0x2000;
+const MADT_AT: usize = 0x2200;
+const HPET_AT: usize = 0x2300;
+const MCFG_AT: usize = 0x2400;
+const SRAT_AT: usize = 0x2500;
+const IMAGE_SIZE: usize = 0x3000;
+
+
+/// tables of a Firecracker microVM
+mod firecracker {
+    pub const MADT: &[u8] = include_bytes!("../testdata/firecracker/APIC.dat");
+    pub const FADT: &[u8] = include_bytes!("../testdata/firecracker/FACP.dat");
+    pub const DSDT: &[u8] = include_bytes!("../testdata/firecracker/DSDT.dat");
+    pub const MCFG: &[u8] = include_bytes!("../testdata/firecracker/MCFG.dat");
+
+    /// `x_dsdt` of the FADT, the DSDT is placed there
+    pub fn dsdt_at() -> usize {
+        u64::from_le_bytes(FADT[140..148].try_into().unwrap()) as usize
+    }
+}
+
+
+/// MADT body: local APIC address, flags, 2 CPUs, IO APIC, ISA overrides, LINT1 NMI
+const MADT: &[u8] = &[
+    0x00, 0x00, 0xe0, 0xfe,  0x01, 0x00, 0x00, 0x00,
+    0x00, 0x08, 0x00, 0x00,  0x01, 0x00, 0x00, 0x00,
+    0x00, 0x08, 0x01, 0x01,  0x01, 0x00, 0x00, 0x00,
+    0x01, 0x0c, 0x00, 0x00,  0x00, 0x00, 0xc0, 0xfe,  0x00, 0x00, 0x00, 0x00,
+    0x02, 0x0a, 0x00, 0x00,  0x02, 0x00, 0x00, 0x00,  0x00, 0x00,
+    0x02, 0x0a, 0x00, 0x05,  0x05, 0x00, 0x00, 0x00,  0x0d, 0x00,
+    0x02, 0x0a, 0x00, 0x09,  0x09, 0x00, 0x00, 0x00,  0x0d, 0x00,
+    0x02, 0x0a, 0x00, 0x0a,  0x0a, 0x00, 0x00, 0x00,  0x0d, 0x00,
+    0x02, 0x0a, 0x00, 0x0b,  0x0b, 0x00, 0x00, 0x00,  0x0d, 0x00,
+    0x04, 0x06, 0xff, 0x00,  0x00, 0x01,
+];
+
+/// HPET body: block ID, base address (system memory), number, minimum tick, protection
+const HPET: &[u8] = &[
+    0x01, 0xa2, 0x86, 0x80,
+    0x00, 0x00, 0x00, 0x00,  0x00, 0x00, 0xd0, 0xfe,  0x00, 0x00, 0x00, 0x00,
+    0x00,
+    0x00, 0x00,
+    0x00,
+];
+
+/// MCFG body: reserved, one ECAM region for segment 0, buses 0..=255
+const MCFG: &[u8] = &[
+    0x00, 0x00, 0x00, 0x00,  0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0xb0,  0x00, 0x00, 0x00, 0x00,  0x00, 0x00,  0x00, 0xff,  0x00, 0x00, 0x00, 0x00,
+];
+
+/// minimal DSDT body (AML `Name (_S5, Package (0x02) {0x05, 0x05})` would follow in QEMU's)
+const DSDT: &[u8] = &[0x08, 0x5f, 0x53, 0x35, 0x5f, 0x12, 0x06, 0x02, 0x0a, 0x05, 0x0a, 0x05];
+
+
+/// little endian helpers
+fn put(buf: &mut [u8], at: usize, bytes: &[u8]) {
+    buf[at..at + bytes.len()].copy_from_slice(bytes);
+}
+
+fn gas(space: u8, width: u8, access: u8, address: u64) -> [u8; 12] {
+    let mut g = [0u8; 12];
+    g[0] = space;
+    g[1] = width;
+    g[3] = access;
+    g[4..].copy_from_slice(&address.to_le_bytes());
+    g
+}
+
+/// FADT body of revision 3 (244 bytes in total), offsets are from the table start
+fn fadt_body() -> Vec<u8> {
+    let mut t = vec![0u8; 244];
+    put(&mut t, 36, &(FACS_AT as u32).to_le_bytes());
+    put(&mut t, 40, &(DSDT_AT as u32).to_le_bytes());
+    put(&mut t, 46, &9u16.to_le_bytes());           //  SCI
+    put(&mut t, 48, &0xb2u32.to_le_bytes());        //  SMI command port
+    t[52] = 0xf1;                                   //  ACPI enable
+    t[53] = 0xf0;                                   //  ACPI disable
+    put(&mut t, 56, &0x600u32.to_le_bytes());       //  PM1a event
+    put(&mut t, 64, &0x604u32.to_le_bytes());       //  PM1a control
+    put(&mut t, 76, &0x608u32.to_le_bytes());       //  PM timer
+    put(&mut t, 80, &0x620u32.to_le_bytes());       //  GPE0
+    t[88] = 4;
+    t[89] = 2;
+    t[91] = 4;
+    t[92] = 0x10;
+    put(&mut t, 96, &0x0fffu16.to_le_bytes());
+    put(&mut t, 98, &0x0fffu16.to_le_bytes());
+    t[108] = 0x32;                                  //  century
+    put(&mut t, 109, &0x0002u16.to_le_bytes());     //  8042
+    put(&mut t, 112, &0x0000_84a5u32.to_le_bytes());
+    put(&mut t, 116, &gas(1, 8, 0, 0xcf9));         //  reset register
+    t[128] = 0x0f;
+    put(&mut t, 132, &(FACS_AT as u64).to_le_bytes());
+    put(&mut t, 140, &(DSDT_AT as u64).to_le_bytes());
+    put(&mut t, 148, &gas(1, 32, 0, 0x600));
+    put(&mut t, 172, &gas(1, 16, 0, 0x604));
+    put(&mut t, 208, &gas(1, 32, 0, 0x608));
+    put(&mut t, 220, &gas(1, 128, 0, 0x620));
+    t.drain(..36);
+    t
+}
+
+fn srat_cpu(domain: u8, apic: u8) -> [u8; 16] {
+    let mut e = [0u8; 16];
+    e[0] = 0;
+    e[1] = 16;
+    e[2] = domain;
+    e[3] = apic;
+    e[4] = 1;
+    e
+}
+
+fn srat_memory(domain: u32, base: u64, length: u64, flags: u32) -> [u8; 40] {
+    let mut e = [0u8; 40];
+    e[0] = 1;
+    e[1] = 40;
+    put(&mut e, 2, &domain.to_le_bytes());
+    put(&mut e, 8, &base.to_le_bytes());
+    put(&mut e, 16, &length.to_le_bytes());
+    put(&mut e, 28, &flags.to_le_bytes());
+    e
+}
+
+/// SRAT body: reserved (first dword is 1), CPU 0 in node 0, CPU 1 in node 1, memory ranges
+fn srat_body() -> Vec<u8> {
+    let mut t = vec![1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    t.extend_from_slice(&srat_cpu(0, 0));
+    t.extend_from_slice(&srat_cpu(1, 1));
+    t.extend_from_slice(&srat_memory(0, 0, 0xa0000, 1));
+    t.extend_from_slice(&srat_memory(0, 0x10_0000, 0x7ff0_0000, 1));
+    t.extend_from_slice(&srat_memory(1, 0x8000_0000, 0x8000_0000, 1));
+    //  QEMU pads with disabled entries
+    t.extend_from_slice(&srat_memory(0, 0, 0, 0));
+    t
+}
+
+
+/// fake physical memory
+struct Image {
+    mem: Vec<u8>,
+}
+
+impl Image {
+    fn new(size: usize) -> Self {
+        Self { mem: vec![0u8; size] }
+    }
+
+    fn fix_checksum(&mut self, at: usize, len: usize, field: usize) {
+        self.mem[at + field] = 0;
+        let sum = unsafe { checksum(self.mem.as_ptr().add(at), len) };
+        self.mem[at + field] = 0u8.wrapping_sub(sum);
+    }
+
+    /// writes table with QEMU's header values
+    fn table(&mut self, at: usize, signature: &[u8; 4], revision: u8, body: &[u8]) {
+        let len = 36 + body.len();
+        let m = &mut self.mem;
+        put(m, at, signature);
+        put(m, at + 4, &(len as u32).to_le_bytes());
+        m[at + 8] = revision;
+        put(m, at + 10, b"BOCHS ");
+        put(m, at + 16, b"BXPC    ");
+        put(m, at + 24, &1u32.to_le_bytes());
+        put(m, at + 28, b"BXPC");
+        put(m, at + 32, &1u32.to_le_bytes());
+        put(m, at + 36, body);
+        self.fix_checksum(at, len, 9);
+    }
+
+    fn root(&mut self, at: usize, extended: bool, tables: &[usize]) {
+        let mut body = Vec::new();
+        for &t in tables {
+            if extended {
+                body.extend_from_slice(&(t as u64).to_le_bytes());
+            } else {
+                body.extend_from_slice(&(t as u32).to_le_bytes());
+            }
+        }
+        let signature = if extended { b"XSDT" } else { b"RSDT" };
+        self.table(at, signature, 1, &body);
+    }
+
+    fn rsdp(&mut self, revision: u8) {
+        let at = RSDP_AT;
+        let m = &mut self.mem;
+        put(m, at, RSDP_SIGNATURE);
+        put(m, at + 9, b"BOCHS ");
+        m[at + 15] = revision;
+        put(m, at + 16, &(RSDT_AT as u32).to_le_bytes());
+        put(m, at + 20, &36u32.to_le_bytes());
+        put(m, at + 24, &(XSDT_AT as u64).to_le_bytes());
+        self.fix_checksum(at, RSDP_V1_LENGTH, 8);
+        self.fix_checksum(at, 36, 32);
+    }
+
+    /// complete q35 image
+    fn q35(revision: u8) -> Self {
+        let mut image = Self::new(IMAGE_SIZE);
+        put(&mut image.mem, FACS_AT, b"FACS");
+        image.table(DSDT_AT, b"DSDT", 1, DSDT);
+        image.table(FADT_AT, b"FACP", 3, &fadt_body());
+        image.table(MADT_AT, b"APIC", 1, MADT);
+        image.table(HPET_AT, b"HPET", 1, HPET);
+        image.table(MCFG_AT, b"MCFG", 1, MCFG);
+        image.table(SRAT_AT, b"SRAT", 1, &srat_body());
+        let tables = [FADT_AT, MADT_AT, HPET_AT, SRAT_AT, MCFG_AT];
+        image.root(RSDT_AT, false, &tables);
+        image.root(XSDT_AT, true, &tables);
+        image.rsdp(revision);
+        image
+    }
+
+    /// Firecracker dumps, listed in both root tables
+    /// - the image reaches up to the DSDT, its address is taken from the FADT
+    fn firecracker(revision: u8) -> Self {
+        let dsdt = firecracker::dsdt_at();
+        let mut image = Self::new(dsdt + firecracker::DSDT.len());
+        put(&mut image.mem, dsdt, firecracker::DSDT);
+        put(&mut image.mem, FADT_AT, firecracker::FADT);
+        put(&mut image.mem, MADT_AT, firecracker::MADT);
+        put(&mut image.mem, MCFG_AT, firecracker::MCFG);
+        let tables = [FADT_AT, MADT_AT, MCFG_AT];
+        image.root(RSDT_AT, false, &tables);
+        image.root(XSDT_AT, true, &tables);
+        image.rsdp(revision);
+        image
+    }
+
+    /// leaks the memory and parses it
+    fn parse(self) -> Result<AcpiTables, ()> {
+        let mem: &'static mut [u8] = Box::leak(self.mem.into_boxed_slice());
+        let offset = mem.as_ptr() as usize;
+        unsafe { AcpiTables::from_rsdp(offset + RSDP_AT, offset) }
+    }
+}
+
+
+#[test]
+fn rsdt_root() {
+    let acpi = Image::firecracker(0).parse().expect("valid tables rejected");
+    assert_eq!(acpi.revision(), 0);
+    assert!(!acpi.is_extended());
+    assert_eq!(acpi.root().signature(), "RSDT");
+
+    //  checksums are the firmware's own
+    let names: Vec<&str> = acpi.tables().map(|t| t.signature()).collect();
+    assert_eq!(names, ["FACP", "APIC", "MCFG"]);
+    assert!(acpi.tables().all(|t| t.is_valid()));
+}
+
+#[test]
+fn xsdt_root() {
+    let acpi = Image::firecracker(2).parse().expect("valid tables rejected");
+    assert_eq!(acpi.revision(), 2);
+    assert!(acpi.is_extended());
+    assert_eq!(acpi.root().signature(), "XSDT");
+    assert_eq!(acpi.tables().count(), 3);
+
+    let madt = acpi.madt().expect("MADT not found");
+    assert_eq!(&madt.header().oem_id, b"FIRECK");
+}
+
+#[test]
+fn q35_root() {
+    let acpi = Image::q35(0).parse().expect("valid tables rejected");
+    let names: Vec<&str> = acpi.tables().map(|t| t.signature()).collect();
+    assert_eq!(names, ["FACP", "APIC", "HPET", "SRAT", "MCFG"]);
+    assert!(acpi.tables().all(|t| t.is_valid()));
+}
+
+#[test]
+fn bad_rsdp_checksum() {
+    let mut image = Image::q35(0);
+    image.mem[RSDP_AT + 8] ^= 0xff;
+    assert!(image.parse().is_err());
+}
+
+#[test]
+fn bad_extended_checksum() {
+    let mut image = Image::q35(2);
+    image.mem[RSDP_AT + 32] ^= 0xff;
+    assert!(image.parse().is_err());
+}
+
+#[test]
+fn bad_signature() {
+    let mut image = Image::q35(0);
+    image.mem[RSDP_AT] = b'X';
+    image.fix_checksum(RSDP_AT, RSDP_V1_LENGTH, 8);
+    assert!(image.parse().is_err());
+}
+
+#[test]
+fn bad_root_checksum() {
+    let mut image = Image::q35(0);
+    image.mem[RSDT_AT + 36] ^= 0x01;
+    assert!(image.parse().is_err());
+}
+
+#[test]
+fn bad_table_checksum_is_skipped() {
+    let mut image = Image::q35(0);
+    image.mem[HPET_AT + 40] ^= 0x01;
+    let acpi = image.parse().unwrap();
+
+    //  still listed, but not returned
+    assert!(acpi.tables().any(|t| t.signature() == "HPET" && !t.is_valid()));
+    assert!(acpi.hpet().is_none());
+    assert!(acpi.madt().is_some());
+}
+
+#[test]
+fn madt() {
+    let acpi = Image::firecracker(2).parse().unwrap();
+    let madt = acpi.madt().expect("MADT not found");
+
+    assert_eq!(madt.local_apic_address(), 0xfee0_0000);
+    assert!(!madt.has_legacy_pics());
+
+    let cpus: Vec<(u32, u32)> = madt.local_apics()
+        .filter(|l| l.is_enabled())
+        .map(|l| (l.processor_id, l.apic_id))
+        .collect();
+    assert_eq!(cpus, [(0, 0)]);
+
+    let ioapics: Vec<_> = madt.io_apics().collect();
+    assert_eq!(ioapics.len(), 1);
+    assert_eq!(ioapics[0].id, 0);
+    assert_eq!(ioapics[0].address, 0xfec0_0000);
+    assert_eq!(ioapics[0].gsi_base, 0);
+
+    assert_eq!(madt.entries().count(), 2);
+    assert!(madt.isa_override(0).is_none());
+}
+
+#[test]
+fn madt_overrides() {
+    let acpi = Image::q35(0).parse().unwrap();
+    let madt = acpi.madt().expect("MADT not found");
+
+    assert_eq!(madt.local_apic_address(), 0xfee0_0000);
+    assert!(madt.has_legacy_pics());
+
+    let cpus: Vec<(u32, u32)> = madt.local_apics()
+        .filter(|l| l.is_enabled())
+        .map(|l| (l.processor_id, l.apic_id))
+        .collect();
+    assert_eq!(cpus, [(0, 0), (1, 1)]);
+
+    let ioapics: Vec<_> = madt.io_apics().collect();
+    assert_eq!(ioapics.len(), 1);
+    assert_eq!(ioapics[0].id, 0);
+    assert_eq!(ioapics[0].address, 0xfec0_0000);
+    assert_eq!(ioapics[0].gsi_base, 0);
+
+    assert_eq!(madt.overrides().count(), 5);
+    let timer = madt.isa_override(0).expect("PIT override missing");
+    assert_eq!(timer.gsi, 2);
+    assert!(timer.flags.polarity() == Polarity::Conforming);
+    let sci = madt.isa_override(9).expect("SCI override missing");
+    assert_eq!(sci.gsi, 9);
+    assert!(sci.flags.polarity() == Polarity::ActiveHigh);
+    assert!(sci.flags.trigger_mode() == TriggerMode::Level);
+    assert!(madt.isa_override(1).is_none());
+
+    let nmi = madt.entries().find_map(|e| match e {
+        MadtEntry::LocalApicNmi(n) => Some(n),
+        _ => None,
+    }).expect("LINT NMI missing");
+    assert!(nmi.is_broadcast());
+    assert_eq!(nmi.lint, 1);
+}
+
+#[test]
+fn madt_malformed_entry() {
+    let mut image = Image::q35(0);
+    //  length 0 of the IO APIC entry
+    image.mem[MADT_AT + 36 + 24 + 1] = 0;
+    image.fix_checksum(MADT_AT, 36 + MADT.len(), 9);
+    let madt = image.parse().unwrap().madt().unwrap();
+
+    assert_eq!(madt.entries().count(), 2);
+    assert!(madt.io_apics().next().is_none());
+}
+
+#[test]
+fn fadt() {
+    let acpi = Image::firecracker(2).parse().unwrap();
+    let fadt = acpi.fadt().expect("FADT not found");
+
+    assert_eq!(fadt.version(), (6, 5));
+    assert_eq!(fadt.header().length(), 276);
+    assert_eq!(fadt.dsdt_address(), firecracker::dsdt_at() as u64);
+    assert_eq!(fadt.facs_address(), 0);
+    assert_eq!(fadt.hypervisor_vendor_id(), u64::from_le_bytes(*b"FIRECKVM"));
+
+    //  hardware reduced: no fixed registers, no legacy devices
+    assert!(fadt.flags() & FLAG_HW_REDUCED_ACPI != 0);
+    assert_eq!(fadt.boot_arch(), BOOT_NO_VGA);
+    assert!(!fadt.has_8042());
+    assert!(!fadt.has_legacy_devices());
+    assert!(fadt.pm_timer().is_none());
+    assert!(fadt.pm1a_control().is_none());
+    assert!(fadt.reset().is_none());
+
+    let dsdt = acpi.dsdt().expect("DSDT not found");
+    assert_eq!(dsdt.signature(), "DSDT");
+    assert_eq!(dsdt.length(), firecracker::DSDT.len());
+}
+
+#[test]
+fn fadt_fixed_hardware() {
+    let acpi = Image::q35(0).parse().unwrap();
+    let fadt = acpi.fadt().expect("FADT not found");
+
+    assert_eq!(fadt.version(), (3, 0));
+    assert_eq!(fadt.dsdt_address(), DSDT_AT as u64);
+    assert_eq!(fadt.facs_address(), FACS_AT as u64);
+    assert_eq!(fadt.sci_interrupt(), 9);
+    assert_eq!(fadt.smi_command(), 0xb2);
+    assert_eq!(fadt.century(), 0x32);
+    assert!(fadt.has_8042());
+    assert!(!fadt.has_legacy_devices());
+    assert!(!fadt.pm_timer_is_32bit());
+
+    let timer = fadt.pm_timer().expect("PM timer missing");
+    let address = timer.address;
+    assert_eq!(timer.address_space, ADDRESS_SPACE_IO);
+    assert_eq!(address, 0x608);
+
+    let (reset, value) = fadt.reset().expect("reset register missing");
+    let address = reset.address;
+    assert_eq!(address, 0xcf9);
+    assert_eq!(value, 0x0f);
+
+    let dsdt = acpi.dsdt().expect("DSDT not found");
+    assert_eq!(dsdt.signature(), "DSDT");
+    assert_eq!(dsdt.data_length(), DSDT.len());
+}
+
+#[test]
+fn fadt_revision_1() {
+    //  116 byte table without the extended fields
+    let mut image = Image::q35(0);
+    let body = fadt_body();
+    image.table(FADT_AT, b"FACP", 1, &body[..80]);
+    let acpi = image.parse().unwrap();
+    let fadt = acpi.fadt().unwrap();
+
+    assert_eq!(fadt.header().length(), 116);
+    assert_eq!(fadt.dsdt_address(), DSDT_AT as u64);
+    assert!(fadt.reset().is_none());
+    assert!(fadt.has_8042());
+
+    //  legacy port block is converted
+    let timer = fadt.pm_timer().unwrap();
+    let address = timer.address;
+    assert_eq!(address, 0x608);
+    assert_eq!(timer.bit_width, 32);
+}
+
+#[test]
+fn hpet() {
+    let acpi = Image::q35(0).parse().unwrap();
+    let hpet = acpi.hpet().expect("HPET not found");
+
+    assert_eq!(hpet.event_timer_block_id(), 0x8086_a201);
+    let base = hpet.base_address();
+    let address = base.address;
+    assert_eq!(base.address_space, ADDRESS_SPACE_MEMORY);
+    assert_eq!(address, 0xfed0_0000);
+    assert_eq!(hpet.hpet_number(), 0);
+}
+
+#[test]
+fn hpet_truncated() {
+    let mut image = Image::q35(0);
+    //  base address is cut off
+    image.table(HPET_AT, b"HPET", 1, &HPET[..8]);
+    let acpi = image.parse().unwrap();
+
+    assert!(acpi.tables().any(|t| t.signature() == "HPET" && t.is_valid()));
+    assert!(acpi.hpet().is_none());
+}
+
+#[test]
+fn mcfg() {
+    let acpi = Image::firecracker(2).parse().unwrap();
+    let mcfg = acpi.mcfg().expect("MCFG not found");
+
+    assert_eq!(mcfg.len(), 1);
+    let entry = mcfg.find(0, 0).expect("segment 0 missing");
+    let (base, segment) = (entry.base_address, entry.segment);
+    assert_eq!(base, 0xeec0_0000);
+    assert_eq!(segment, 0);
+    assert_eq!((entry.start_bus, entry.end_bus), (0, 0));
+
+    assert_eq!(entry.address(0, 0, 0), Some(0xeec0_0000));
+    assert_eq!(entry.address(0, 2, 3), Some(0xeec0_0000 + (2 << 15) + (3 << 12)));
+    assert_eq!(entry.address(0, 32, 0), None);
+    assert_eq!(entry.address(1, 0, 0), None);
+    assert!(mcfg.find(0, 1).is_none());
+    assert!(mcfg.find(1, 0).is_none());
+    assert!(mcfg.get(1).is_none());
+}
+
+#[test]
+fn srat() {
+    let acpi = Image::q35(0).parse().unwrap();
+    let srat = acpi.srat().expect("SRAT not found");
+
+    assert_eq!(srat.entries().count(), 6);
+    assert_eq!(srat.processors().count(), 2);
+    assert_eq!(srat.processor_domain(0), Some(0));
+    assert_eq!(srat.processor_domain(1), Some(1));
+    assert_eq!(srat.processor_domain(2), None);
+
+    //  the disabled entry is filtered out
+    assert_eq!(srat.memory().count(), 3);
+    assert_eq!(srat.memory_domain(0x1000), Some(0));
+    assert_eq!(srat.memory_domain(0xb0000), None);
+    assert_eq!(srat.memory_domain(0x4000_0000), Some(0));
+    assert_eq!(srat.memory_domain(0x9000_0000), Some(1));
+    assert_eq!(srat.memory_domain(0x1_0000_0000), None);
+
+    assert!(srat.entries().any(|e| matches!(e, SratEntry::Memory(m) if !m.is_enabled())));
+}
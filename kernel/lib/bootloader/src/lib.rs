@@ -10,7 +10,8 @@ pub use limine_rs;
 
 use limine_rs::request::{FramebufferRequest, HhdmRequest,
     MemoryMapRequest, RequestsEndMarker,
-    RequestsStartMarker};
+    RequestsStartMarker, RsdpRequest, PagingModeRequest, MpRequest, ModuleRequest};
+use limine_rs::paging::Mode;
 
 
 #[unsafe(link_section = ".requests_start_marker")]
@@ -40,4 +41,22 @@ pub static HHDM: HhdmRequest = HhdmRequest::new();
 #[unsafe(link_section = ".requests")]
 pub static MEMMAP: MemoryMapRequest = MemoryMapRequest::new();
 
+/// reserved by [`ministd::acpi`]
+/// - interrupt controllers are discovered through the MADT
+#[unsafe(link_section = ".requests")]
+pub static RSDP: RsdpRequest = RsdpRequest::new();
+
+/// reserved by [`ministd::mem::paging`]
+/// - 5-level paging is used if the bootloader enables it
+#[unsafe(link_section = ".requests")]
+pub static PAGING_MODE: PagingModeRequest = PagingModeRequest::new().with_max_mode(Mode::FIVE_LEVEL);
 
+/// reserved by [`ministd::smp`]
+/// - application processors wait for a jump address until [`ministd::init::smp()`]
+#[unsafe(link_section = ".requests")]
+pub static MP: MpRequest = MpRequest::new();
+
+/// reserved by [`ministd::renderer::font`]
+/// - PSF fonts can be loaded as modules (`module_path:` in `limine.conf`)
+#[unsafe(link_section = ".requests")]
+pub static MODULES: ModuleRequest = ModuleRequest::new();
@@ -0,0 +1,29 @@
+//	acpi.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  access to ACPI tables found by the bootloader
+//  - parsing is done by the local `acpi` crate
+
+
+pub use ::acpi::*;
+
+use spin::Once;
+
+
+static TABLES: Once<Option<AcpiTables>> = Once::new();
+
+
+/// returns ACPI tables located through the RSDP request
+/// - returns `None` if the bootloader did not provide RSDP or it is invalid
+pub fn tables() -> Option<&'static AcpiTables> {
+    TABLES.call_once(|| {
+        let rsdp = bootloader::RSDP.get_response()?.address();
+        let offset = crate::mem::hhdm_offset()?;
+
+        //  older revisions of the protocol report physical address
+        let rsdp = if rsdp < offset { rsdp + offset } else { rsdp };
+
+        unsafe { AcpiTables::from_rsdp(rsdp, offset) }.ok()
+    }).as_ref()
+}
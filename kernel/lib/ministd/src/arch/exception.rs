@@ -0,0 +1,163 @@
+//	arch/exception.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  handlers for the 32 architectural CPU exceptions
+//  - every exception is reported through `panic!` so it ends up in the kernel `panic_handler`
+
+
+use core::arch::naked_asm;
+
+use crate::arch::cr2;
+use crate::arch::interrupt::{InterruptFrame, interrupt_common};
+
+
+pub const DIVIDE_ERROR: u8 = 0;
+pub const DEBUG: u8 = 1;
+pub const NMI: u8 = 2;
+pub const BREAKPOINT: u8 = 3;
+pub const OVERFLOW: u8 = 4;
+pub const BOUND_RANGE: u8 = 5;
+pub const INVALID_OPCODE: u8 = 6;
+pub const DEVICE_NOT_AVAILABLE: u8 = 7;
+pub const DOUBLE_FAULT: u8 = 8;
+pub const INVALID_TSS: u8 = 10;
+pub const SEGMENT_NOT_PRESENT: u8 = 11;
+pub const STACK_SEGMENT_FAULT: u8 = 12;
+pub const GENERAL_PROTECTION: u8 = 13;
+pub const PAGE_FAULT: u8 = 14;
+pub const X87_FLOATING_POINT: u8 = 16;
+pub const ALIGNMENT_CHECK: u8 = 17;
+pub const MACHINE_CHECK: u8 = 18;
+pub const SIMD_FLOATING_POINT: u8 = 19;
+pub const VIRTUALIZATION: u8 = 20;
+pub const CONTROL_PROTECTION: u8 = 21;
+pub const HYPERVISOR_INJECTION: u8 = 28;
+pub const VMM_COMMUNICATION: u8 = 29;
+pub const SECURITY: u8 = 30;
+
+/// number of architectural exceptions
+pub const EXCEPTION_COUNT: usize = 32;
+
+/// names of the exceptions (indexed by vector)
+pub const EXCEPTION_NAMES: [&str; EXCEPTION_COUNT] = [
+    "Divide Error",
+    "Debug",
+    "Non-Maskable Interrupt",
+    "Breakpoint",
+    "Overflow",
+    "Bound Range Exceeded",
+    "Invalid Opcode",
+    "Device Not Available",
+    "Double Fault",
+    "Coprocessor Segment Overrun",
+    "Invalid TSS",
+    "Segment Not Present",
+    "Stack-Segment Fault",
+    "General Protection Fault",
+    "Page Fault",
+    "Reserved",
+    "x87 Floating-Point Exception",
+    "Alignment Check",
+    "Machine Check",
+    "SIMD Floating-Point Exception",
+    "Virtualization Exception",
+    "Control Protection Exception",
+    "Reserved",
+    "Reserved",
+    "Reserved",
+    "Reserved",
+    "Reserved",
+    "Reserved",
+    "Hypervisor Injection Exception",
+    "VMM Communication Exception",
+    "Security Exception",
+    "Reserved",
+];
+
+
+/// checks if the CPU pushes error code for `vector`
+pub const fn has_error_code(vector: u8) -> bool {
+    matches!(vector, 8 | 10..=14 | 17 | 21 | 29 | 30)
+}
+
+
+/// reports the exception through `panic!`
+pub(crate) fn handle(frame: &InterruptFrame) -> ! {
+    let vector = frame.vector as u8;
+    let name = EXCEPTION_NAMES[vector as usize];
+
+    if vector == PAGE_FAULT {
+        panic!("CPU exception {} ({}), error code: {:#x}, cr2: {:#018x}\n{}", vector, name, frame.error_code, cr2(), frame);
+    } else if has_error_code(vector) {
+        panic!("CPU exception {} ({}), error code: {:#x}\n{}", vector, name, frame.error_code, frame);
+    } else {
+        panic!("CPU exception {} ({})\n{}", vector, name, frame);
+    }
+}
+
+
+/// generates entry stub for exception vector
+/// - pushes dummy error code if the CPU does not push one
+macro_rules! exception_stub {
+    ($name:ident, $vector:literal) => {
+        #[unsafe(naked)]
+        unsafe extern "C" fn $name() {
+            naked_asm!(
+                ".if {has_error} == 0",
+                "push 0",
+                ".endif",
+                "push {vector}",
+                "jmp {common}",
+                has_error = const has_error_code($vector) as u8,
+                vector = const $vector,
+                common = sym interrupt_common,
+            );
+        }
+    };
+}
+
+exception_stub!(exception_0, 0);
+exception_stub!(exception_1, 1);
+exception_stub!(exception_2, 2);
+exception_stub!(exception_3, 3);
+exception_stub!(exception_4, 4);
+exception_stub!(exception_5, 5);
+exception_stub!(exception_6, 6);
+exception_stub!(exception_7, 7);
+exception_stub!(exception_8, 8);
+exception_stub!(exception_9, 9);
+exception_stub!(exception_10, 10);
+exception_stub!(exception_11, 11);
+exception_stub!(exception_12, 12);
+exception_stub!(exception_13, 13);
+exception_stub!(exception_14, 14);
+exception_stub!(exception_15, 15);
+exception_stub!(exception_16, 16);
+exception_stub!(exception_17, 17);
+exception_stub!(exception_18, 18);
+exception_stub!(exception_19, 19);
+exception_stub!(exception_20, 20);
+exception_stub!(exception_21, 21);
+exception_stub!(exception_22, 22);
+exception_stub!(exception_23, 23);
+exception_stub!(exception_24, 24);
+exception_stub!(exception_25, 25);
+exception_stub!(exception_26, 26);
+exception_stub!(exception_27, 27);
+exception_stub!(exception_28, 28);
+exception_stub!(exception_29, 29);
+exception_stub!(exception_30, 30);
+exception_stub!(exception_31, 31);
+
+/// entry stubs for all exceptions (indexed by vector)
+pub(crate) static STUBS: [unsafe extern "C" fn(); EXCEPTION_COUNT] = [
+    exception_0, exception_1, exception_2, exception_3,
+    exception_4, exception_5, exception_6, exception_7,
+    exception_8, exception_9, exception_10, exception_11,
+    exception_12, exception_13, exception_14, exception_15,
+    exception_16, exception_17, exception_18, exception_19,
+    exception_20, exception_21, exception_22, exception_23,
+    exception_24, exception_25, exception_26, exception_27,
+    exception_28, exception_29, exception_30, exception_31,
+];
@@ -0,0 +1,260 @@
+//	arch/gdt.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  Global Descriptor Table and Task State Segment
+//  - replaces the GDT left behind by the bootloader
+//  - provides kernel and user segments and the TSS with Interrupt Stack Table
+
+
+use core::arch::asm;
+use core::mem::size_of;
+use spin::Once;
+
+use crate::arch::{DescriptorTablePointer, PrivilegeLevel};
+use crate::mem::frame::FRAME_SIZE;
+
+
+/// size of each Interrupt Stack Table stack (in bytes)
+pub const IST_STACK_SIZE: usize = 5 * 4096;
+
+/// IST index used by the double fault handler
+/// - indexes start at 1 (as the IDT expects them), 0 means "do not switch stacks"
+pub const DOUBLE_FAULT_IST: u8 = 1;
+/// IST index used by the non-maskable interrupt handler
+pub const NMI_IST: u8 = 2;
+/// IST index used by the machine check handler
+pub const MACHINE_CHECK_IST: u8 = 3;
+
+/// number of IST stacks that are used
+const IST_USED: usize = 3;
+
+
+pub const KERNEL_CODE_SELECTOR: u16 = 0x08;
+pub const KERNEL_DATA_SELECTOR: u16 = 0x10;
+/// user data must be placed right before user code (`sysret` expects it)
+pub const USER_DATA_SELECTOR: u16 = 0x18 | PrivilegeLevel::User as u16;
+pub const USER_CODE_SELECTOR: u16 = 0x20 | PrivilegeLevel::User as u16;
+pub const TSS_SELECTOR: u16 = 0x28;
+
+
+//  flat segment descriptors (base and limit are ignored in long mode)
+const NULL_DESCRIPTOR: u64 = 0;
+const KERNEL_CODE: u64 = 0x00af_9a00_0000_ffff;
+const KERNEL_DATA: u64 = 0x00cf_9200_0000_ffff;
+const USER_DATA: u64 = 0x00cf_f200_0000_ffff;
+const USER_CODE: u64 = 0x00af_fa00_0000_ffff;
+
+/// number of 8-byte GDT slots (TSS descriptor takes two)
+const GDT_SIZE: usize = 7;
+
+
+/// # Task State Segment
+/// In long mode it only holds stack pointers
+/// - `rsp[n]` is used when switching to privilege level `n`
+/// - `ist[n]` is used by interrupts with IST index `n + 1`
+#[repr(C, packed(4))]
+pub struct Tss {
+    _reserved1: u32,
+    pub rsp: [u64; 3],
+    _reserved2: u64,
+    pub ist: [u64; 7],
+    _reserved3: u64,
+    _reserved4: u16,
+    pub iomap_base: u16,
+}
+
+impl Tss {
+    pub const fn new() -> Self {
+        Self {
+            _reserved1: 0,
+            rsp: [0; 3],
+            _reserved2: 0,
+            ist: [0; 7],
+            _reserved3: 0,
+            _reserved4: 0,
+            //  no IO permission bitmap
+            iomap_base: size_of::<Tss>() as u16,
+        }
+    }
+}
+
+impl Default for Tss {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// # Global Descriptor Table
+/// Layout:
+/// - `0x00`: null
+/// - `0x08`: kernel code
+/// - `0x10`: kernel data
+/// - `0x18`: user data
+/// - `0x20`: user code
+/// - `0x28`: TSS (16 bytes)
+#[repr(C, align(16))]
+pub struct Gdt {
+    entries: [u64; GDT_SIZE],
+}
+
+impl Gdt {
+    pub const fn new() -> Self {
+        Self {
+            entries: [NULL_DESCRIPTOR, KERNEL_CODE, KERNEL_DATA, USER_DATA, USER_CODE, 0, 0],
+        }
+    }
+
+    /// writes the TSS descriptor pointing to `tss`
+    pub fn set_tss(&mut self, tss: &'static Tss) {
+        let base = tss as *const Tss as u64;
+        let limit = (size_of::<Tss>() - 1) as u64;
+
+        let low = (limit & 0xffff)
+            | ((base & 0xff_ffff) << 16)
+            | (0x89 << 40)                      //  present, 64-bit available TSS
+            | (((limit >> 16) & 0xf) << 48)
+            | (((base >> 24) & 0xff) << 56);
+
+        let index = (TSS_SELECTOR >> 3) as usize;
+        self.entries[index] = low;
+        self.entries[index + 1] = base >> 32;
+    }
+
+    /// loads the GDT and reloads all segment registers
+    /// # Safety
+    /// - the GDT must stay at its address as long as it is loaded
+    /// - its TSS descriptor (if set) must point to a valid TSS
+    pub unsafe fn load(&'static self) {
+        let ptr = DescriptorTablePointer {
+            limit: (size_of::<Self>() - 1) as u16,
+            base: self as *const Self as u64,
+        };
+
+        unsafe {
+            asm!("lgdt [{}]", in(reg) &ptr, options(readonly, nostack, preserves_flags));
+
+            //  reload CS using far return
+            asm!(
+                "push {sel}",
+                "lea {tmp}, [rip + 2f]",
+                "push {tmp}",
+                "retfq",
+                "2:",
+                sel = in(reg) KERNEL_CODE_SELECTOR as u64,
+                tmp = lateout(reg) _,
+                options(preserves_flags),
+            );
+
+            asm!(
+                "mov ds, {0:x}",
+                "mov es, {0:x}",
+                "mov ss, {0:x}",
+                "mov fs, {1:x}",
+                "mov gs, {1:x}",
+                in(reg) KERNEL_DATA_SELECTOR,
+                in(reg) 0u16,
+                options(nostack, preserves_flags),
+            );
+        }
+    }
+}
+
+impl Default for Gdt {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+#[repr(C, align(16))]
+struct Stack([u8; IST_STACK_SIZE]);
+
+static mut IST_STACKS: [Stack; IST_USED] = [const { Stack([0; IST_STACK_SIZE]) }; IST_USED];
+
+static TSS: Once<Tss> = Once::new();
+static GDT: Once<Gdt> = Once::new();
+
+
+/// loads the task register
+#[inline(always)]
+unsafe fn load_tss(sel: u16) {
+    unsafe { asm!("ltr {0:x}", in(reg) sel, options(nostack, preserves_flags)); }
+}
+
+
+/// initializes the GDT and TSS for the bootstrap processor
+/// - fills IST entries with dedicated stacks
+pub(crate) fn init() {
+    let tss = TSS.call_once(|| {
+        let mut tss = Tss::new();
+        //  `Tss` is packed, the entries are copied out to borrow them
+        let mut ist = tss.ist;
+        for (i, entry) in ist.iter_mut().take(IST_USED).enumerate() {
+            //  stacks grow down
+            *entry = unsafe { (&raw const IST_STACKS[i]) as u64 } + IST_STACK_SIZE as u64;
+        }
+        tss.ist = ist;
+        tss
+    });
+
+    let gdt = GDT.call_once(|| {
+        let mut gdt = Gdt::new();
+        gdt.set_tss(tss);
+        gdt
+    });
+
+    unsafe {
+        gdt.load();
+        load_tss(TSS_SELECTOR);
+    }
+}
+
+/// descriptor tables of an application processor
+/// - placed right after its IST stacks
+#[repr(C)]
+struct ApTables {
+    gdt: Gdt,
+    tss: Tss,
+}
+
+/// number of frames holding the IST stacks and descriptor tables of an application processor
+pub const AP_FRAMES: usize = (IST_STACK_SIZE * IST_USED + size_of::<ApTables>()).div_ceil(FRAME_SIZE);
+
+/// sets up the IST stacks, TSS and GDT of an application processor in `memory`
+/// - called by the bootstrap processor, so the AP does not allocate anything itself
+/// - returns the GDT, which is loaded by the AP using [`load_ap`]
+/// # Safety
+/// - `memory` must be the virtual address of [`AP_FRAMES`] unused frames
+/// - the frames must not be freed while the GDT is loaded
+pub(crate) unsafe fn prepare_ap(memory: usize) -> &'static Gdt {
+    let mut tss = Tss::new();
+    let mut ist = tss.ist;
+    for (i, entry) in ist.iter_mut().take(IST_USED).enumerate() {
+        //  stacks grow down
+        *entry = (memory + (i + 1) * IST_STACK_SIZE) as u64;
+    }
+    tss.ist = ist;
+
+    let tables = (memory + IST_STACK_SIZE * IST_USED) as *mut ApTables;
+    unsafe {
+        (&raw mut (*tables).tss).write(tss);
+        let mut gdt = Gdt::new();
+        gdt.set_tss(&(*tables).tss);
+        (&raw mut (*tables).gdt).write(gdt);
+        &(*tables).gdt
+    }
+}
+
+/// loads the GDT and TSS of an application processor
+/// # Safety
+/// - `gdt` must be returned by [`prepare_ap`] and must not be loaded on any other processor
+pub(crate) unsafe fn load_ap(gdt: &'static Gdt) {
+    unsafe {
+        gdt.load();
+        load_tss(TSS_SELECTOR);
+    }
+}
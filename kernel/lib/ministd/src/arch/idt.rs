@@ -0,0 +1,163 @@
+//	arch/idt.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  Interrupt Descriptor Table
+//  - the table itself is static, handlers are installed by the `init` function
+
+
+use core::arch::asm;
+use core::mem::size_of;
+use spin::Mutex;
+
+use crate::arch::{DescriptorTablePointer, PrivilegeLevel, gdt, exception, interrupt};
+
+
+/// number of entries in the IDT
+pub const IDT_SIZE: usize = 256;
+
+
+/// Gate type of an IDT entry
+#[derive(Copy, Clone, PartialEq, Eq)]
+#[repr(u8)]
+pub enum GateType {
+    /// disables interrupts when entered
+    Interrupt = 0xe,
+    /// leaves `IF` untouched
+    Trap = 0xf,
+}
+
+
+/// # IDT entry (gate descriptor)
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct Entry {
+    offset_low: u16,
+    selector: u16,
+    ist: u8,
+    attributes: u8,
+    offset_mid: u16,
+    offset_high: u32,
+    _reserved: u32,
+}
+
+impl Entry {
+    /// constructs not-present entry
+    pub const fn missing() -> Self {
+        Self {
+            offset_low: 0,
+            selector: 0,
+            ist: 0,
+            attributes: 0,
+            offset_mid: 0,
+            offset_high: 0,
+            _reserved: 0,
+        }
+    }
+
+    /// constructs present entry pointing to `handler`
+    /// - `ist` is the Interrupt Stack Table index (0 = do not switch stacks)
+    pub const fn new(handler: usize, ist: u8, gate: GateType, dpl: PrivilegeLevel) -> Self {
+        Self {
+            offset_low: handler as u16,
+            selector: gdt::KERNEL_CODE_SELECTOR,
+            ist: ist & 0b111,
+            attributes: 0x80 | ((dpl as u8) << 5) | gate as u8,
+            offset_mid: (handler >> 16) as u16,
+            offset_high: (handler >> 32) as u32,
+            _reserved: 0,
+        }
+    }
+
+    #[inline]
+    pub fn is_present(&self) -> bool {
+        self.attributes & 0x80 != 0
+    }
+
+    /// returns address of the handler
+    #[inline]
+    pub fn handler(&self) -> usize {
+        self.offset_low as usize | (self.offset_mid as usize) << 16 | (self.offset_high as usize) << 32
+    }
+}
+
+
+/// # Interrupt Descriptor Table
+#[repr(C, align(16))]
+pub struct Idt {
+    entries: [Entry; IDT_SIZE],
+}
+
+impl Idt {
+    pub const fn new() -> Self {
+        Self {
+            entries: [Entry::missing(); IDT_SIZE],
+        }
+    }
+
+    /// sets entry at `vector`
+    #[inline]
+    pub fn set(&mut self, vector: u8, entry: Entry) {
+        self.entries[vector as usize] = entry;
+    }
+
+    #[inline]
+    pub fn get(&self, vector: u8) -> &Entry {
+        &self.entries[vector as usize]
+    }
+
+    /// loads the IDT
+    /// # Safety
+    /// - the IDT must stay at its address as long as it is loaded
+    /// - every present entry must point to a handler that follows the interrupt calling convention
+    pub unsafe fn load(&self) {
+        let ptr = DescriptorTablePointer {
+            limit: (size_of::<Self>() - 1) as u16,
+            base: self as *const Self as u64,
+        };
+        unsafe { asm!("lidt [{}]", in(reg) &ptr, options(readonly, nostack, preserves_flags)); }
+    }
+}
+
+impl Default for Idt {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// the IDT used by all processors
+pub static IDT: Mutex<Idt> = Mutex::new(Idt::new());
+
+
+/// installs exception handlers and IRQ stubs and loads the IDT
+/// - GDT must be loaded (uses its code selector and IST indexes)
+pub(crate) fn init() {
+    let mut idt = IDT.lock();
+
+    for (vector, stub) in exception::STUBS.iter().enumerate() {
+        let ist = match vector as u8 {
+            exception::NMI => gdt::NMI_IST,
+            exception::DOUBLE_FAULT => gdt::DOUBLE_FAULT_IST,
+            exception::MACHINE_CHECK => gdt::MACHINE_CHECK_IST,
+            _ => 0,
+        };
+        //  breakpoint may be triggered from user mode (int3)
+        let dpl = if vector as u8 == exception::BREAKPOINT { PrivilegeLevel::User } else { PrivilegeLevel::Kernel };
+
+        idt.set(vector as u8, Entry::new(*stub as usize, ist, GateType::Interrupt, dpl));
+    }
+
+    //  handlers are attached using `io::int::register_irq_handler`
+    for vector in exception::EXCEPTION_COUNT..IDT_SIZE {
+        idt.set(vector as u8, Entry::new(interrupt::irq_stub(vector as u8), 0, GateType::Interrupt, PrivilegeLevel::Kernel));
+    }
+
+    unsafe { idt.load() };
+}
+
+/// loads the (already initialized) IDT on an application processor
+pub(crate) fn load() {
+    unsafe { IDT.lock().load() };
+}
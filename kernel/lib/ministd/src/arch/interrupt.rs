@@ -0,0 +1,157 @@
+//	arch/interrupt.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  common entry path of all interrupts
+//  - saves the interrupted state into `InterruptFrame`
+//  - exceptions (vectors 0..32) are handled by `arch::exception`
+//  - everything else is passed to the interrupt controller layer (`io::int`)
+
+
+use core::arch::{global_asm, naked_asm};
+use core::fmt::{self, Display};
+
+use crate::arch::exception::{self, EXCEPTION_COUNT};
+use crate::arch::idt::IDT_SIZE;
+
+
+/// size of each IRQ entry stub (in bytes)
+const IRQ_STUB_SIZE: usize = 16;
+
+
+/// # Interrupt frame
+/// State of the interrupted code as it is saved on the stack
+/// - general purpose registers are pushed by the stub
+/// - `vector` and `error_code` are pushed by the stub (`error_code` is 0 if the CPU does not push one)
+/// - the rest is pushed by the CPU
+#[repr(C)]
+pub struct InterruptFrame {
+    pub r15: u64,
+    pub r14: u64,
+    pub r13: u64,
+    pub r12: u64,
+    pub r11: u64,
+    pub r10: u64,
+    pub r9: u64,
+    pub r8: u64,
+    pub rbp: u64,
+    pub rdi: u64,
+    pub rsi: u64,
+    pub rdx: u64,
+    pub rcx: u64,
+    pub rbx: u64,
+    pub rax: u64,
+
+    pub vector: u64,
+    pub error_code: u64,
+
+    pub rip: u64,
+    pub cs: u64,
+    pub rflags: u64,
+    pub rsp: u64,
+    pub ss: u64,
+}
+
+impl Display for InterruptFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "rip: {:#018x}  cs: {:#06x}  rflags: {:#018x}", self.rip, self.cs, self.rflags)?;
+        writeln!(f, "rsp: {:#018x}  ss: {:#06x}", self.rsp, self.ss)?;
+        writeln!(f, "rax: {:#018x}  rbx: {:#018x}  rcx: {:#018x}", self.rax, self.rbx, self.rcx)?;
+        writeln!(f, "rdx: {:#018x}  rsi: {:#018x}  rdi: {:#018x}", self.rdx, self.rsi, self.rdi)?;
+        writeln!(f, "rbp: {:#018x}  r8:  {:#018x}  r9:  {:#018x}", self.rbp, self.r8, self.r9)?;
+        writeln!(f, "r10: {:#018x}  r11: {:#018x}  r12: {:#018x}", self.r10, self.r11, self.r12)?;
+        write!(f, "r13: {:#018x}  r14: {:#018x}  r15: {:#018x}", self.r13, self.r14, self.r15)
+    }
+}
+
+
+/// Saves general purpose registers, calls `dispatch` and restores the state
+/// - expects `vector` and `error_code` to be already pushed
+#[unsafe(naked)]
+pub(crate) unsafe extern "C" fn interrupt_common() {
+    naked_asm!(
+        "push rax",
+        "push rbx",
+        "push rcx",
+        "push rdx",
+        "push rsi",
+        "push rdi",
+        "push rbp",
+        "push r8",
+        "push r9",
+        "push r10",
+        "push r11",
+        "push r12",
+        "push r13",
+        "push r14",
+        "push r15",
+        "mov rdi, rsp",
+        "cld",
+        "call {dispatch}",
+        "pop r15",
+        "pop r14",
+        "pop r13",
+        "pop r12",
+        "pop r11",
+        "pop r10",
+        "pop r9",
+        "pop r8",
+        "pop rbp",
+        "pop rdi",
+        "pop rsi",
+        "pop rdx",
+        "pop rcx",
+        "pop rbx",
+        "pop rax",
+        //  vector and error code
+        "add rsp, 16",
+        "iretq",
+        dispatch = sym dispatch,
+    );
+}
+
+
+/// Rust side of every interrupt
+extern "C" fn dispatch(frame: &mut InterruptFrame) {
+    if frame.vector < EXCEPTION_COUNT as u64 {
+        exception::handle(frame);
+    } else {
+        crate::io::int::handle_irq(frame);
+    }
+}
+
+
+//  entry stubs for vectors 32..256
+//  - each stub is exactly `IRQ_STUB_SIZE` bytes long
+//  - `push imm8` and `push imm32` are encoded by hand so the vector can be taken from the counter
+global_asm!(
+    ".pushsection .text.ministd_irq_stubs, \"ax\"",
+    ".balign {size}",
+    ".global __ministd_irq_stubs",
+    "__ministd_irq_stubs:",
+    ".set ministd_irq_vector, {first}",
+    ".rept {count}",
+    ".balign {size}",
+    ".byte 0x6a, 0x00",
+    ".byte 0x68",
+    ".long ministd_irq_vector",
+    "jmp {common}",
+    ".set ministd_irq_vector, ministd_irq_vector + 1",
+    ".endr",
+    ".popsection",
+    size = const IRQ_STUB_SIZE,
+    first = const EXCEPTION_COUNT,
+    count = const IDT_SIZE - EXCEPTION_COUNT,
+    common = sym interrupt_common,
+);
+
+unsafe extern "C" {
+    fn __ministd_irq_stubs();
+}
+
+/// returns address of the entry stub for `vector`
+/// - `vector` must not be an exception vector
+pub(crate) fn irq_stub(vector: u8) -> usize {
+    debug_assert!(vector as usize >= EXCEPTION_COUNT);
+    __ministd_irq_stubs as *const () as usize + (vector as usize - EXCEPTION_COUNT) * IRQ_STUB_SIZE
+}
@@ -0,0 +1,123 @@
+//	arch/mod.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  this module provides structures the CPU itself needs (descriptor tables, ...)
+//  - only x86_64 is supported at the moment
+
+use core::arch::asm;
+
+pub mod gdt;
+pub mod idt;
+pub mod exception;
+pub mod interrupt;
+
+pub use core::arch::x86_64::{CpuidResult, __cpuid_count as cpuid_count};
+
+
+/// Privilege level the code runs at
+#[derive(Copy, Clone, PartialEq, Eq)]
+#[repr(u8)]
+pub enum PrivilegeLevel {
+    Kernel = 0,
+    User = 3,
+}
+
+
+/// Pointer structure used by `lgdt` and `lidt` instructions
+#[repr(C, packed)]
+pub struct DescriptorTablePointer {
+    pub limit: u16,
+    pub base: u64,
+}
+
+
+/// reads the current code segment selector
+#[inline(always)]
+pub fn cs() -> u16 {
+    let cs: u16;
+    unsafe { asm!("mov {0:x}, cs", out(reg) cs, options(nomem, nostack, preserves_flags)); }
+    cs
+}
+
+/// reads the `cr2` register (address that caused the last page fault)
+#[inline(always)]
+pub fn cr2() -> u64 {
+    let cr2: u64;
+    unsafe { asm!("mov {}, cr2", out(reg) cr2, options(nomem, nostack, preserves_flags)); }
+    cr2
+}
+
+/// reads the `cr3` register (physical address of the root page table + flags)
+#[inline(always)]
+pub fn cr3() -> u64 {
+    let cr3: u64;
+    unsafe { asm!("mov {}, cr3", out(reg) cr3, options(nomem, nostack, preserves_flags)); }
+    cr3
+}
+
+/// writes the `cr3` register
+/// - flushes all non-global TLB entries
+/// # Safety
+/// - `value` must point to a valid PML4 that maps the running code, its stack and data
+#[inline(always)]
+pub unsafe fn set_cr3(value: u64) {
+    unsafe { asm!("mov cr3, {}", in(reg) value, options(nostack, preserves_flags)); }
+}
+
+/// reads the `cr4` register
+#[inline(always)]
+pub fn cr4() -> u64 {
+    let cr4: u64;
+    unsafe { asm!("mov {}, cr4", out(reg) cr4, options(nomem, nostack, preserves_flags)); }
+    cr4
+}
+
+/// invalidates TLB entry of page containing `address`
+#[inline(always)]
+pub fn invlpg(address: usize) {
+    unsafe { asm!("invlpg [{}]", in(reg) address, options(nostack, preserves_flags)); }
+}
+
+
+/// executes `cpuid` with `leaf` (subleaf 0)
+#[inline(always)]
+pub fn cpuid(leaf: u32) -> CpuidResult {
+    cpuid_count(leaf, 0)
+}
+
+/// reads model specific register
+/// # Safety
+/// - `msr` must exist on this CPU, otherwise `#GP` is raised
+/// - reading some registers has side effects
+#[inline(always)]
+pub unsafe fn rdmsr(msr: u32) -> u64 {
+    let (low, high): (u32, u32);
+    unsafe { asm!("rdmsr", in("ecx") msr, out("eax") low, out("edx") high, options(nomem, nostack, preserves_flags)); }
+    (high as u64) << 32 | low as u64
+}
+
+/// writes model specific register
+/// # Safety
+/// - `msr` must exist on this CPU and accept `value`, otherwise `#GP` is raised
+/// - the write can change how the CPU runs (e.g. `EFER`, `GS` base), which the caller must account for
+#[inline(always)]
+pub unsafe fn wrmsr(msr: u32, value: u64) {
+    unsafe { asm!("wrmsr", in("ecx") msr, in("eax") value as u32, in("edx") (value >> 32) as u32, options(nostack, preserves_flags)); }
+}
+
+/// reads the time stamp counter
+#[inline(always)]
+pub fn rdtsc() -> u64 {
+    let (low, high): (u32, u32);
+    unsafe { asm!("rdtsc", out("eax") low, out("edx") high, options(nomem, nostack, preserves_flags)); }
+    (high as u64) << 32 | low as u64
+}
+
+/// reads the `rflags` register
+#[inline(always)]
+pub fn rflags() -> u64 {
+    let r: u64;
+    unsafe { asm!("pushfq", "pop {}", out(reg) r, options(nomem, preserves_flags)); }
+    r
+}
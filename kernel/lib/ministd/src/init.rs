@@ -5,8 +5,10 @@
 
 //  declares ministd initialization functions
 
-use crate::mem::alloc;
+use crate::mem::{alloc, frame, paging};
 use crate::renderer;
+use crate::arch;
+use crate::io::serial;
 
 /// initializes allocator
 #[inline]
@@ -14,7 +16,116 @@ pub fn allocator() -> Result<(), ()> {
     alloc::init()
 }
 
+/// gives all usable physical memory (except the heap) to the frame allocator
+/// - requires [`allocator()`] to be called first
+#[inline]
+pub fn frames() -> Result<(), ()> {
+    frame::init()
+}
+
+/// takes over the page tables set up by the bootloader
+/// - new page tables are allocated from the frame allocator (see [`frames()`])
+#[inline]
+pub fn paging() -> Result<(), ()> {
+    paging::init()
+}
+
 #[inline]
 pub fn renderer() -> Result<(), ()> {
     renderer::init()
-}
\ No newline at end of file
+}
+
+/// gives the renderer a back buffer in RAM so scrolling does not read video memory
+/// - requires [`renderer()`] and [`frames()`] to be called first
+#[inline]
+pub fn back_buffer() -> Result<(), ()> {
+    renderer::init_back_buffer()
+}
+
+/// creates virtual consoles up to `count` in total (console 0 is the one set up by [`renderer()`])
+/// - every console takes as much memory as the framebuffer
+/// - requires [`renderer()`] and [`frames()`] to be called first
+#[inline]
+pub fn consoles(count: usize) -> Result<(), ()> {
+    renderer::init_consoles(count)
+}
+
+/// initializes COM1 with 115200 baud 8N1
+/// - output is not copied there until [`crate::io::serial::set_tee()`] is called
+#[inline]
+pub fn serial() -> Result<(), ()> {
+    serial::init(serial::DEFAULT_CONFIG)
+}
+
+/// initializes COM1 with custom baud rate and line format
+#[inline]
+pub fn serial_with(config: serial::LineConfig) -> Result<(), ()> {
+    serial::init(config)
+}
+
+/// loads own GDT with kernel/user segments and TSS
+/// - must be called before any interrupt handling is set up
+#[inline]
+pub fn gdt() {
+    arch::gdt::init()
+}
+
+/// loads the IDT with handlers for all CPU exceptions
+/// - exceptions are reported through `panic!`
+/// - requires [`gdt()`] to be called first
+#[inline]
+pub fn idt() {
+    arch::idt::init()
+}
+
+/// masks legacy PICs and sets up local APIC and IO APICs
+/// - ISA IRQs are routed according to MADT overrides but stay masked
+/// - interrupts are not enabled (see [`crate::io::int::enable()`])
+#[inline]
+pub fn interrupts() -> Result<(), ()> {
+    crate::io::int::init()
+}
+
+/// calibrates TSC and local APIC timer and starts the timer interrupt
+/// - requires [`interrupts()`] to be called first
+#[inline]
+pub fn time() -> Result<(), ()> {
+    crate::time::init()
+}
+
+/// turns the current context into the boot thread and enables preemptive scheduling
+/// - requires [`time()`] to be called first
+#[inline]
+pub fn threads() -> Result<(), ()> {
+    crate::thread::init()
+}
+
+/// starts application processors (each gets its own GDT, TSS and local APIC setup)
+/// - requires [`threads()`] to be called first
+/// - returns the number of online processors
+#[inline]
+pub fn smp() -> Result<usize, ()> {
+    crate::smp::init()
+}
+
+/// enumerates PCI buses (ECAM if MCFG table is present, legacy ports otherwise)
+/// - requires [`paging()`] and [`allocator()`] to be called first
+/// - returns the number of found functions
+#[inline]
+pub fn pci() -> Result<usize, ()> {
+    crate::io::pci::init()
+}
+
+/// initializes the PS/2 controller and keyboard (input is read through [`crate::io::read_key()`] etc.)
+/// - requires [`interrupts()`] to be called first
+#[inline]
+pub fn keyboard() -> Result<(), ()> {
+    crate::io::keyboard::init()
+}
+
+/// registers the built-in shell commands (the shell is started with [`crate::shell::run()`])
+/// - requires [`allocator()`] to be called first
+#[inline]
+pub fn shell() -> Result<(), ()> {
+    crate::shell::init()
+}
@@ -0,0 +1,285 @@
+//	io/int/apic.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  Local APIC driver
+//  - uses x2APIC (MSR access) if the CPU supports it, xAPIC (MMIO) otherwise
+//  - xAPIC registers are accessed through HHDM
+
+
+use core::ptr::{read_volatile, write_volatile};
+use spin::Once;
+
+use crate::acpi::madt::{Madt, MadtEntry, Polarity, TriggerMode};
+use crate::arch::{cpuid, rdmsr, wrmsr};
+use crate::io::int::SPURIOUS_VECTOR;
+
+
+const IA32_APIC_BASE: u32 = 0x1b;
+const APIC_BASE_ENABLE: u64 = 1 << 11;
+const APIC_BASE_X2APIC: u64 = 1 << 10;
+const APIC_BASE_ADDRESS: u64 = 0x000f_ffff_ffff_f000;
+
+/// first MSR of x2APIC registers
+const X2APIC_MSR_BASE: u32 = 0x800;
+
+/// CPUID.1:ECX x2APIC bit
+const CPUID_X2APIC: u32 = 1 << 21;
+
+
+/// Local APIC register offsets (xAPIC layout)
+pub mod reg {
+    pub const ID: u32 = 0x20;
+    pub const VERSION: u32 = 0x30;
+    pub const TPR: u32 = 0x80;
+    pub const EOI: u32 = 0xb0;
+    pub const SVR: u32 = 0xf0;
+    pub const ESR: u32 = 0x280;
+    pub const ICR_LOW: u32 = 0x300;
+    pub const ICR_HIGH: u32 = 0x310;
+    pub const LVT_TIMER: u32 = 0x320;
+    pub const LVT_THERMAL: u32 = 0x330;
+    pub const LVT_PERF: u32 = 0x340;
+    pub const LVT_LINT0: u32 = 0x350;
+    pub const LVT_LINT1: u32 = 0x360;
+    pub const LVT_ERROR: u32 = 0x370;
+    pub const TIMER_INITIAL: u32 = 0x380;
+    pub const TIMER_CURRENT: u32 = 0x390;
+    pub const TIMER_DIVIDE: u32 = 0x3e0;
+}
+
+/// LVT entry is masked
+pub const LVT_MASKED: u32 = 1 << 16;
+/// LVT delivery mode NMI
+pub const LVT_NMI: u32 = 0b100 << 8;
+/// LVT/ICR polarity active low
+pub const LVT_ACTIVE_LOW: u32 = 1 << 13;
+/// LVT/ICR level triggered
+pub const LVT_LEVEL: u32 = 1 << 15;
+
+/// LVT timer periodic mode
+pub const TIMER_PERIODIC: u32 = 1 << 17;
+/// divide configuration value for divide by 16
+const TIMER_DIVIDE_16: u32 = 0b0011;
+/// divider used for the local APIC timer
+pub const TIMER_DIVIDER: u32 = 16;
+
+/// SVR: APIC software enable
+const SVR_ENABLE: u32 = 1 << 8;
+
+/// ICR: delivery status (xAPIC only)
+const ICR_PENDING: u32 = 1 << 12;
+
+
+/// How the local APIC is accessed
+#[derive(Copy, Clone)]
+pub enum Mode {
+    /// memory mapped registers at virtual address
+    XApic(usize),
+    /// registers are MSRs
+    X2Apic,
+}
+
+
+/// # Local APIC
+/// Each processor has its own, but all of them are accessed the same way
+/// - use [`lapic()`] to get the instance
+pub struct LocalApic {
+    mode: Mode,
+}
+
+impl LocalApic {
+
+    #[inline]
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    #[inline]
+    pub fn is_x2apic(&self) -> bool {
+        matches!(self.mode, Mode::X2Apic)
+    }
+
+    /// reads register at `reg` offset (xAPIC layout)
+    #[inline]
+    pub fn read(&self, reg: u32) -> u32 {
+        match self.mode {
+            Mode::XApic(base) => unsafe { read_volatile((base + reg as usize) as *const u32) },
+            Mode::X2Apic => unsafe { rdmsr(X2APIC_MSR_BASE + (reg >> 4)) as u32 },
+        }
+    }
+
+    /// writes register at `reg` offset (xAPIC layout)
+    #[inline]
+    pub fn write(&self, reg: u32, value: u32) {
+        match self.mode {
+            Mode::XApic(base) => unsafe { write_volatile((base + reg as usize) as *mut u32, value) },
+            Mode::X2Apic => unsafe { wrmsr(X2APIC_MSR_BASE + (reg >> 4), value as u64) },
+        }
+    }
+
+    /// returns ID of the local APIC of the current processor
+    #[inline]
+    pub fn id(&self) -> u32 {
+        match self.mode {
+            Mode::XApic(_) => self.read(reg::ID) >> 24,
+            Mode::X2Apic => self.read(reg::ID),
+        }
+    }
+
+    /// signals end of interrupt
+    #[inline(always)]
+    pub fn eoi(&self) {
+        self.write(reg::EOI, 0);
+    }
+
+    /// sends inter-processor interrupt
+    /// - `command` is the low part of ICR (vector, delivery mode, ...)
+    pub fn send_ipi(&self, dest: u32, command: u32) {
+        match self.mode {
+            Mode::XApic(_) => {
+                self.write(reg::ICR_HIGH, dest << 24);
+                self.write(reg::ICR_LOW, command);
+                while self.read(reg::ICR_LOW) & ICR_PENDING != 0 {
+                    core::hint::spin_loop();
+                }
+            },
+            Mode::X2Apic => unsafe {
+                //  ICR is single 64-bit MSR in x2APIC mode
+                wrmsr(X2APIC_MSR_BASE + (reg::ICR_LOW >> 4), (dest as u64) << 32 | command as u64);
+            },
+        }
+    }
+
+    /// enables the local APIC of the current processor
+    /// - masks all local interrupts and configures LINT pins according to the MADT
+    /// # Safety
+    /// - must run on the processor whose APIC is enabled, with interrupts disabled
+    /// - the IDT must handle the spurious vector before interrupts are enabled
+    pub unsafe fn enable(&self, madt: Option<&Madt>) {
+        let base = unsafe { rdmsr(IA32_APIC_BASE) } | APIC_BASE_ENABLE;
+        unsafe { wrmsr(IA32_APIC_BASE, base) };
+
+        //  x2APIC can only be entered from enabled xAPIC mode
+        if self.is_x2apic() {
+            unsafe { wrmsr(IA32_APIC_BASE, base | APIC_BASE_X2APIC) };
+        }
+
+        for lvt in [reg::LVT_TIMER, reg::LVT_THERMAL, reg::LVT_PERF, reg::LVT_LINT0, reg::LVT_LINT1, reg::LVT_ERROR] {
+            self.write(lvt, LVT_MASKED);
+        }
+
+        if let Some(madt) = madt {
+            self.configure_nmi(madt);
+        }
+
+        //  clear errors (needs two writes)
+        self.write(reg::ESR, 0);
+        self.write(reg::ESR, 0);
+
+        self.write(reg::TPR, 0);
+        self.write(reg::SVR, SVR_ENABLE | SPURIOUS_VECTOR as u32);
+
+        self.eoi();
+    }
+
+    /// starts the timer (clock divided by [`TIMER_DIVIDER`])
+    /// - `periodic`: reloads `initial` after each interrupt
+    /// - `vector` is not delivered if `masked` is set (used for calibration)
+    pub fn start_timer(&self, vector: u8, initial: u32, periodic: bool, masked: bool) {
+        let mut lvt = vector as u32;
+        if periodic {
+            lvt |= TIMER_PERIODIC;
+        }
+        if masked {
+            lvt |= LVT_MASKED;
+        }
+        self.write(reg::TIMER_DIVIDE, TIMER_DIVIDE_16);
+        self.write(reg::LVT_TIMER, lvt);
+        self.write(reg::TIMER_INITIAL, initial);
+    }
+
+    /// stops and masks the timer
+    #[inline]
+    pub fn stop_timer(&self) {
+        self.write(reg::LVT_TIMER, LVT_MASKED);
+        self.write(reg::TIMER_INITIAL, 0);
+    }
+
+    /// returns the current count of the timer
+    #[inline]
+    pub fn timer_count(&self) -> u32 {
+        self.read(reg::TIMER_CURRENT)
+    }
+
+    /// sets LINT pins connected to NMI (as described by the MADT)
+    fn configure_nmi(&self, madt: &Madt) {
+        let id = self.id();
+
+        //  find ACPI processor ID of this processor
+        let processor = madt.entries().find_map(|e| match e {
+            MadtEntry::LocalApic(l) | MadtEntry::LocalX2Apic(l) if l.apic_id == id => Some(l.processor_id),
+            _ => None,
+        });
+
+        for entry in madt.entries() {
+            let nmi = match entry {
+                MadtEntry::LocalApicNmi(n) | MadtEntry::LocalX2ApicNmi(n) => n,
+                _ => continue,
+            };
+
+            if !nmi.is_broadcast() && Some(nmi.processor_id) != processor {
+                continue;
+            }
+
+            let mut value = LVT_NMI;
+            if nmi.flags.polarity() == Polarity::ActiveLow {
+                value |= LVT_ACTIVE_LOW;
+            }
+            if nmi.flags.trigger_mode() == TriggerMode::Level {
+                value |= LVT_LEVEL;
+            }
+
+            match nmi.lint {
+                0 => self.write(reg::LVT_LINT0, value),
+                1 => self.write(reg::LVT_LINT1, value),
+                _ => {},
+            }
+        }
+    }
+}
+
+
+static LAPIC: Once<LocalApic> = Once::new();
+
+
+/// returns the local APIC (if initialized)
+#[inline]
+pub fn lapic() -> Option<&'static LocalApic> {
+    LAPIC.get()
+}
+
+/// checks if the CPU supports x2APIC
+#[inline]
+pub fn supports_x2apic() -> bool {
+    cpuid(1).ecx & CPUID_X2APIC != 0
+}
+
+
+/// initializes the local APIC of the bootstrap processor
+pub(crate) fn init(madt: Option<&Madt>) -> Result<&'static LocalApic, ()> {
+    let mode = if supports_x2apic() {
+        Mode::X2Apic
+    } else {
+        let phys = match madt {
+            Some(m) => m.local_apic_address(),
+            None => (unsafe { rdmsr(IA32_APIC_BASE) }) & APIC_BASE_ADDRESS,
+        };
+        Mode::XApic(phys as usize + crate::mem::hhdm_offset().ok_or(())?)
+    };
+
+    let lapic = LAPIC.call_once(|| LocalApic { mode });
+    unsafe { lapic.enable(madt) };
+
+    Ok(lapic)
+}
@@ -0,0 +1,283 @@
+//	io/int/ioapic.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  IO APIC driver
+//  - routes global system interrupts (GSI) to vectors of local APICs
+//  - ISA IRQs are translated to GSIs using the MADT interrupt source overrides
+
+
+use core::ptr::{read_volatile, write_volatile};
+use spin::Mutex;
+
+use crate::acpi::madt::{Madt, MadtEntry, Polarity, TriggerMode};
+
+
+/// maximum number of IO APICs that can be registered
+pub const MAX_IOAPICS: usize = 8;
+/// number of legacy ISA IRQs
+pub const ISA_IRQS: usize = 16;
+
+const IOREGSEL: usize = 0x00;
+const IOWIN: usize = 0x10;
+
+const REG_ID: u32 = 0x00;
+const REG_VERSION: u32 = 0x01;
+const REG_REDIRECTION: u32 = 0x10;
+
+const ENTRY_NMI: u64 = 0b100 << 8;
+const ENTRY_ACTIVE_LOW: u64 = 1 << 13;
+const ENTRY_LEVEL: u64 = 1 << 15;
+const ENTRY_MASKED: u64 = 1 << 16;
+
+
+/// # IO APIC
+#[derive(Copy, Clone)]
+pub struct IoApic {
+    id: u8,
+    base: usize,
+    gsi_base: u32,
+    entries: u32,
+}
+
+impl IoApic {
+
+    /// constructs `IoApic` from its virtual address
+    /// # Safety
+    /// - `base` must be the virtual address of the IOAPIC registers and stay mapped
+    /// - only one `IoApic` may exist per IOAPIC
+    pub unsafe fn new(id: u8, base: usize, gsi_base: u32) -> Self {
+        let mut ioapic = Self {
+            id,
+            base,
+            gsi_base,
+            entries: 0,
+        };
+        ioapic.entries = ((ioapic.read(REG_VERSION) >> 16) & 0xff) + 1;
+        ioapic
+    }
+
+    #[inline]
+    pub fn id(&self) -> u8 {
+        self.id
+    }
+
+    #[inline]
+    pub fn gsi_base(&self) -> u32 {
+        self.gsi_base
+    }
+
+    /// returns number of redirection entries
+    #[inline]
+    pub fn entries(&self) -> u32 {
+        self.entries
+    }
+
+    /// checks if `gsi` is handled by this IO APIC
+    #[inline]
+    pub fn handles(&self, gsi: u32) -> bool {
+        gsi >= self.gsi_base && gsi < self.gsi_base + self.entries
+    }
+
+    pub fn read(&self, reg: u32) -> u32 {
+        unsafe {
+            write_volatile((self.base + IOREGSEL) as *mut u32, reg);
+            read_volatile((self.base + IOWIN) as *const u32)
+        }
+    }
+
+    pub fn write(&self, reg: u32, value: u32) {
+        unsafe {
+            write_volatile((self.base + IOREGSEL) as *mut u32, reg);
+            write_volatile((self.base + IOWIN) as *mut u32, value);
+        }
+    }
+
+    /// returns the ID stored in the IO APIC itself
+    #[inline]
+    pub fn hardware_id(&self) -> u8 {
+        ((self.read(REG_ID) >> 24) & 0xf) as u8
+    }
+
+    /// reads redirection entry at `index`
+    pub fn entry(&self, index: u32) -> u64 {
+        let low = self.read(REG_REDIRECTION + index * 2);
+        let high = self.read(REG_REDIRECTION + index * 2 + 1);
+        (high as u64) << 32 | low as u64
+    }
+
+    /// writes redirection entry at `index`
+    /// - high part is written first so the entry is never unmasked with old destination
+    pub fn set_entry(&self, index: u32, value: u64) {
+        self.write(REG_REDIRECTION + index * 2 + 1, (value >> 32) as u32);
+        self.write(REG_REDIRECTION + index * 2, value as u32);
+    }
+
+    #[inline]
+    pub fn mask(&self, index: u32) {
+        self.set_entry(index, self.entry(index) | ENTRY_MASKED);
+    }
+
+    #[inline]
+    pub fn unmask(&self, index: u32) {
+        self.set_entry(index, self.entry(index) & !ENTRY_MASKED);
+    }
+
+    /// masks all redirection entries
+    pub fn mask_all(&self) {
+        for i in 0..self.entries {
+            self.set_entry(i, ENTRY_MASKED);
+        }
+    }
+}
+
+
+/// How an ISA IRQ is wired to the IO APIC
+#[derive(Copy, Clone)]
+pub struct IsaRoute {
+    pub gsi: u32,
+    pub polarity: Polarity,
+    pub trigger: TriggerMode,
+}
+
+impl IsaRoute {
+    /// identity mapped, active high, edge triggered
+    const fn default_for(irq: u8) -> Self {
+        Self {
+            gsi: irq as u32,
+            polarity: Polarity::Conforming,
+            trigger: TriggerMode::Conforming,
+        }
+    }
+}
+
+
+static IOAPICS: Mutex<[Option<IoApic>; MAX_IOAPICS]> = Mutex::new([None; MAX_IOAPICS]);
+static ISA_ROUTES: Mutex<[IsaRoute; ISA_IRQS]> = Mutex::new({
+    let mut routes = [IsaRoute::default_for(0); ISA_IRQS];
+    let mut i = 0;
+    while i < ISA_IRQS {
+        routes[i] = IsaRoute::default_for(i as u8);
+        i += 1;
+    }
+    routes
+});
+
+
+/// calls `f` with IO APIC handling `gsi` and index of its redirection entry
+fn with_gsi<R, F: FnOnce(&IoApic, u32) -> R>(gsi: u32, f: F) -> Result<R, ()> {
+    let ioapics = IOAPICS.lock();
+    for i in ioapics.iter().flatten() {
+        if i.handles(gsi) {
+            return Ok(f(i, gsi - i.gsi_base));
+        }
+    }
+    Err(())
+}
+
+/// builds redirection entry
+/// - `Conforming` polarity and trigger mode mean ISA defaults (active high, edge)
+fn redirection_entry(vector: u8, dest: u32, polarity: Polarity, trigger: TriggerMode) -> u64 {
+    let mut entry = vector as u64 | ((dest as u64 & 0xff) << 56);
+    if polarity == Polarity::ActiveLow {
+        entry |= ENTRY_ACTIVE_LOW;
+    }
+    if trigger == TriggerMode::Level {
+        entry |= ENTRY_LEVEL;
+    }
+    entry
+}
+
+
+/// routes global system interrupt `gsi` to `vector` of local APIC `dest`
+/// - the entry is unmasked
+/// - returns `Err` if no IO APIC handles `gsi`
+pub fn route(gsi: u32, vector: u8, dest: u32, polarity: Polarity, trigger: TriggerMode) -> Result<(), ()> {
+    let entry = redirection_entry(vector, dest, polarity, trigger);
+    with_gsi(gsi, |ioapic, index| ioapic.set_entry(index, entry))
+}
+
+/// masks global system interrupt `gsi`
+pub fn mask(gsi: u32) -> Result<(), ()> {
+    with_gsi(gsi, |ioapic, index| ioapic.mask(index))
+}
+
+/// unmasks global system interrupt `gsi`
+pub fn unmask(gsi: u32) -> Result<(), ()> {
+    with_gsi(gsi, |ioapic, index| ioapic.unmask(index))
+}
+
+/// returns how ISA `irq` is wired
+/// - returns `None` if `irq` is not ISA IRQ
+pub fn isa_route(irq: u8) -> Option<IsaRoute> {
+    ISA_ROUTES.lock().get(irq as usize).copied()
+}
+
+/// routes ISA `irq` to `vector` of local APIC `dest`
+/// - respects MADT interrupt source overrides
+pub fn route_isa(irq: u8, vector: u8, dest: u32) -> Result<(), ()> {
+    let r = isa_route(irq).ok_or(())?;
+    route(r.gsi, vector, dest, r.polarity, r.trigger)
+}
+
+/// masks ISA `irq`
+pub fn mask_isa(irq: u8) -> Result<(), ()> {
+    mask(isa_route(irq).ok_or(())?.gsi)
+}
+
+/// calls `f` for every registered IO APIC
+pub fn for_each<F: FnMut(&IoApic)>(mut f: F) {
+    for i in IOAPICS.lock().iter().flatten() {
+        f(i);
+    }
+}
+
+
+/// registers all IO APICs found in the MADT and masks all their entries
+/// - reads interrupt source overrides and configures NMI sources
+/// - NMIs are delivered to local APIC `bsp`
+pub(crate) fn init(madt: &Madt, hhdm: usize, bsp: u32) -> Result<(), ()> {
+    let mut count = 0;
+    {
+        let mut ioapics = IOAPICS.lock();
+        for entry in madt.entries() {
+            if let MadtEntry::IoApic(i) = entry {
+                if count >= MAX_IOAPICS {
+                    break;
+                }
+                let ioapic = unsafe { IoApic::new(i.id, i.address as usize + hhdm, i.gsi_base) };
+                ioapic.mask_all();
+                ioapics[count] = Some(ioapic);
+                count += 1;
+            }
+        }
+    }
+
+    if count == 0 {
+        return Err(());
+    }
+
+    let mut routes = ISA_ROUTES.lock();
+    for entry in madt.entries() {
+        match entry {
+            MadtEntry::InterruptSourceOverride(o) if o.bus == 0 && (o.source as usize) < ISA_IRQS => {
+                routes[o.source as usize] = IsaRoute {
+                    gsi: o.gsi,
+                    polarity: o.flags.polarity(),
+                    trigger: o.flags.trigger_mode(),
+                };
+            },
+            MadtEntry::NmiSource(n) => {
+                let mut entry = redirection_entry(0, bsp, n.flags.polarity(), n.flags.trigger_mode()) | ENTRY_NMI;
+                //  NMI sources are edge triggered unless told otherwise
+                if n.flags.trigger_mode() == TriggerMode::Conforming {
+                    entry &= !ENTRY_LEVEL;
+                }
+                _ = with_gsi(n.gsi, |ioapic, index| ioapic.set_entry(index, entry));
+            },
+            _ => {},
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,159 @@
+//	io/int/mod.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  interrupt controller layer
+//  - legacy PICs are remapped and masked
+//  - local APIC (or x2APIC) and IO APICs are used for all hardware interrupts
+//  - drivers attach to vectors using `register_irq_handler`
+
+
+use core::arch::asm;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::arch::interrupt::InterruptFrame;
+use crate::arch::idt::IDT_SIZE;
+use crate::arch::exception::EXCEPTION_COUNT;
+
+pub mod pic;
+pub mod apic;
+pub mod ioapic;
+
+pub use apic::{LocalApic, lapic};
+
+
+/// vectors the (masked) legacy PICs are remapped to
+/// - only spurious interrupts can arrive here
+pub const PIC_VECTOR_BASE: u8 = 0x20;
+/// ISA IRQ `n` is delivered to `ISA_VECTOR_BASE + n`
+pub const ISA_VECTOR_BASE: u8 = 0x30;
+/// first vector that is free for drivers
+pub const FREE_VECTOR_BASE: u8 = 0x40;
+/// vector of the local APIC timer (see [`crate::time`])
+pub const TIMER_VECTOR: u8 = 0xf0;
+/// spurious interrupt vector of local APICs
+pub const SPURIOUS_VECTOR: u8 = 0xff;
+
+
+/// Function that handles hardware interrupt
+/// - EOI is sent automatically after the handler returns
+pub type IrqHandler = fn(&mut InterruptFrame);
+
+/// handlers indexed by vector (0 = no handler)
+static HANDLERS: [AtomicUsize; IDT_SIZE] = [const { AtomicUsize::new(0) }; IDT_SIZE];
+
+
+#[inline(always)]
+pub fn disable() {
+    unsafe { asm!("cli"); }
+}
+
+#[inline(always)]
+pub fn enable() {
+    unsafe { asm!("sti"); }
+}
+
+/// checks if interrupts are enabled on the current processor
+#[inline(always)]
+pub fn are_enabled() -> bool {
+    crate::arch::rflags() & (1 << 9) != 0
+}
+
+/// runs `f` with interrupts disabled
+/// - restores the previous state afterwards
+#[inline]
+pub fn without_interrupts<R, F: FnOnce() -> R>(f: F) -> R {
+    let enabled = are_enabled();
+    if enabled {
+        disable();
+    }
+    let ret = f();
+    if enabled {
+        enable();
+    }
+    ret
+}
+
+
+/// attaches `handler` to `vector`
+/// - returns `Err` if `vector` is reserved for CPU exceptions, is the spurious vector or already has handler
+pub fn register_irq_handler(vector: u8, handler: IrqHandler) -> Result<(), ()> {
+    if (vector as usize) < EXCEPTION_COUNT || vector == SPURIOUS_VECTOR {
+        return Err(());
+    }
+    HANDLERS[vector as usize].compare_exchange(0, handler as usize, Ordering::AcqRel, Ordering::Acquire)
+        .map(|_| ())
+        .map_err(|_| ())
+}
+
+/// detaches handler from `vector`
+pub fn unregister_irq_handler(vector: u8) {
+    HANDLERS[vector as usize].store(0, Ordering::Release);
+}
+
+/// checks if `vector` has a handler attached
+#[inline]
+pub fn has_irq_handler(vector: u8) -> bool {
+    HANDLERS[vector as usize].load(Ordering::Acquire) != 0
+}
+
+/// routes ISA `irq` to `ISA_VECTOR_BASE + irq` on the current processor
+/// - returns the vector
+pub fn enable_isa_irq(irq: u8) -> Result<u8, ()> {
+    let lapic = lapic().ok_or(())?;
+    let vector = ISA_VECTOR_BASE.checked_add(irq).filter(|v| *v < FREE_VECTOR_BASE).ok_or(())?;
+    ioapic::route_isa(irq, vector, lapic.id())?;
+    Ok(vector)
+}
+
+/// masks ISA `irq`
+#[inline]
+pub fn disable_isa_irq(irq: u8) -> Result<(), ()> {
+    ioapic::mask_isa(irq)
+}
+
+/// signals end of interrupt to the local APIC
+#[inline]
+pub fn end_of_interrupt() {
+    if let Some(l) = lapic() {
+        l.eoi();
+    }
+}
+
+
+/// called for every non-exception vector (see `arch::interrupt`)
+pub(crate) fn handle_irq(frame: &mut InterruptFrame) {
+    let vector = frame.vector as usize;
+
+    //  spurious interrupts must not be acknowledged
+    if vector == SPURIOUS_VECTOR as usize
+    || (vector >= PIC_VECTOR_BASE as usize && vector < PIC_VECTOR_BASE as usize + 16) {
+        return;
+    }
+
+    let handler = HANDLERS[vector].load(Ordering::Acquire);
+    if handler != 0 {
+        let handler: IrqHandler = unsafe { core::mem::transmute(handler) };
+        handler(frame);
+    }
+
+    end_of_interrupt();
+
+    //  the interrupted thread continues once it is scheduled again
+    crate::thread::preempt();
+}
+
+
+/// initializes interrupt controllers
+/// - returns `Err` if ACPI tables or IO APIC are not found
+/// - interrupts stay disabled
+pub(crate) fn init() -> Result<(), ()> {
+    pic::remap(PIC_VECTOR_BASE);
+
+    let madt = crate::acpi::tables().and_then(|t| t.madt());
+
+    let lapic = apic::init(madt.as_ref())?;
+
+    let madt = madt.ok_or(())?;
+    ioapic::init(&madt, crate::mem::hhdm_offset().ok_or(())?, lapic.id())
+}
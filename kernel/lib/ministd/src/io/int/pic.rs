@@ -0,0 +1,54 @@
+//	io/int/pic.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  legacy 8259 Programmable Interrupt Controller
+//  - it is only remapped (away from exception vectors) and masked, APIC is used instead
+
+use crate::io::{outb, wait};
+
+
+const PIC1_COMMAND: u16 = 0x20;
+const PIC1_DATA: u16 = 0x21;
+const PIC2_COMMAND: u16 = 0xa0;
+const PIC2_DATA: u16 = 0xa1;
+
+/// ICW1: initialization, ICW4 will be sent
+const ICW1_INIT: u8 = 0x11;
+/// ICW4: 8086 mode
+const ICW4_8086: u8 = 0x01;
+
+
+/// remaps both PICs to `offset..offset + 16` vectors
+/// - masks all IRQs
+pub fn remap(offset: u8) {
+    outb(PIC1_COMMAND, ICW1_INIT);
+    wait();
+    outb(PIC2_COMMAND, ICW1_INIT);
+    wait();
+
+    outb(PIC1_DATA, offset);
+    wait();
+    outb(PIC2_DATA, offset + 8);
+    wait();
+
+    //  slave is connected to IRQ 2
+    outb(PIC1_DATA, 1 << 2);
+    wait();
+    outb(PIC2_DATA, 2);
+    wait();
+
+    outb(PIC1_DATA, ICW4_8086);
+    wait();
+    outb(PIC2_DATA, ICW4_8086);
+    wait();
+
+    mask_all();
+}
+
+/// masks all IRQs on both PICs
+#[inline]
+pub fn mask_all() {
+    outb(PIC1_DATA, 0xff);
+    outb(PIC2_DATA, 0xff);
+}
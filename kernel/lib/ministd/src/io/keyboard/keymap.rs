@@ -0,0 +1,214 @@
+//	io/keyboard/keymap.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  keyboard layouts
+//  - a keymap assigns up to three characters (plain, shift, AltGr) to each printable key
+//  - keys that are not in the map (and control keys) produce no character
+//  - custom layouts are built with [`Keymap::new`] and activated with [`set_keymap`](super::set_keymap)
+//  - registered layouts (see [`register_keymap`]) can be found by name, e.g. by the `keymap` shell command
+
+
+use spin::Mutex;
+
+use crate::io::keyboard::{KeyCode, KEY_COUNT};
+use crate::mem::vec::Vec;
+
+
+/// no character
+const NONE: char = '\0';
+
+
+/// # Keymap
+pub struct Keymap {
+    pub name: &'static str,
+    /// `[plain, shift, altgr]` indexed by key code
+    chars: [[char; 3]; KEY_COUNT],
+}
+
+impl Keymap {
+    /// constructs keymap from `(key, [plain, shift, altgr])` pairs
+    /// - use `'\0'` for missing characters
+    pub const fn new(name: &'static str, keys: &[(KeyCode, [char; 3])]) -> Self {
+        let mut chars = [[NONE; 3]; KEY_COUNT];
+        let mut i = 0;
+        while i < keys.len() {
+            chars[keys[i].0 as usize] = keys[i].1;
+            i += 1;
+        }
+        Self { name, chars }
+    }
+
+    /// returns character produced by `key`
+    /// - caps lock swaps shift for letters only
+    pub fn translate(&self, key: KeyCode, shift: bool, altgr: bool, caps_lock: bool) -> Option<char> {
+        let [plain, shifted, alt] = self.chars[key as usize];
+
+        let c = if altgr {
+            alt
+        } else {
+            let upper = shift ^ (caps_lock && plain.is_alphabetic());
+            if upper { shifted } else { plain }
+        };
+
+        if c == NONE { None } else { Some(c) }
+    }
+}
+
+
+/// US QWERTY (ANSI, the ISO key repeats backslash)
+pub static US: Keymap = Keymap::new("us", &[
+    (KeyCode::Backtick, ['`', '~', NONE]),
+    (KeyCode::Num1, ['1', '!', NONE]),
+    (KeyCode::Num2, ['2', '@', NONE]),
+    (KeyCode::Num3, ['3', '#', NONE]),
+    (KeyCode::Num4, ['4', '$', NONE]),
+    (KeyCode::Num5, ['5', '%', NONE]),
+    (KeyCode::Num6, ['6', '^', NONE]),
+    (KeyCode::Num7, ['7', '&', NONE]),
+    (KeyCode::Num8, ['8', '*', NONE]),
+    (KeyCode::Num9, ['9', '(', NONE]),
+    (KeyCode::Num0, ['0', ')', NONE]),
+    (KeyCode::Minus, ['-', '_', NONE]),
+    (KeyCode::Equals, ['=', '+', NONE]),
+    (KeyCode::Q, ['q', 'Q', NONE]),
+    (KeyCode::W, ['w', 'W', NONE]),
+    (KeyCode::E, ['e', 'E', NONE]),
+    (KeyCode::R, ['r', 'R', NONE]),
+    (KeyCode::T, ['t', 'T', NONE]),
+    (KeyCode::Y, ['y', 'Y', NONE]),
+    (KeyCode::U, ['u', 'U', NONE]),
+    (KeyCode::I, ['i', 'I', NONE]),
+    (KeyCode::O, ['o', 'O', NONE]),
+    (KeyCode::P, ['p', 'P', NONE]),
+    (KeyCode::LeftBracket, ['[', '{', NONE]),
+    (KeyCode::RightBracket, [']', '}', NONE]),
+    (KeyCode::Backslash, ['\\', '|', NONE]),
+    (KeyCode::A, ['a', 'A', NONE]),
+    (KeyCode::S, ['s', 'S', NONE]),
+    (KeyCode::D, ['d', 'D', NONE]),
+    (KeyCode::F, ['f', 'F', NONE]),
+    (KeyCode::G, ['g', 'G', NONE]),
+    (KeyCode::H, ['h', 'H', NONE]),
+    (KeyCode::J, ['j', 'J', NONE]),
+    (KeyCode::K, ['k', 'K', NONE]),
+    (KeyCode::L, ['l', 'L', NONE]),
+    (KeyCode::Semicolon, [';', ':', NONE]),
+    (KeyCode::Quote, ['\'', '"', NONE]),
+    (KeyCode::NonUsBackslash, ['\\', '|', NONE]),
+    (KeyCode::Z, ['z', 'Z', NONE]),
+    (KeyCode::X, ['x', 'X', NONE]),
+    (KeyCode::C, ['c', 'C', NONE]),
+    (KeyCode::V, ['v', 'V', NONE]),
+    (KeyCode::B, ['b', 'B', NONE]),
+    (KeyCode::N, ['n', 'N', NONE]),
+    (KeyCode::M, ['m', 'M', NONE]),
+    (KeyCode::Comma, [',', '<', NONE]),
+    (KeyCode::Period, ['.', '>', NONE]),
+    (KeyCode::Slash, ['/', '?', NONE]),
+    (KeyCode::Space, [' ', ' ', NONE]),
+]);
+
+/// German QWERTZ (ISO, dead keys produce the accent itself)
+pub static DE: Keymap = Keymap::new("de", &[
+    (KeyCode::Backtick, ['^', '°', NONE]),
+    (KeyCode::Num1, ['1', '!', NONE]),
+    (KeyCode::Num2, ['2', '"', '²']),
+    (KeyCode::Num3, ['3', '§', '³']),
+    (KeyCode::Num4, ['4', '$', NONE]),
+    (KeyCode::Num5, ['5', '%', NONE]),
+    (KeyCode::Num6, ['6', '&', NONE]),
+    (KeyCode::Num7, ['7', '/', '{']),
+    (KeyCode::Num8, ['8', '(', '[']),
+    (KeyCode::Num9, ['9', ')', ']']),
+    (KeyCode::Num0, ['0', '=', '}']),
+    (KeyCode::Minus, ['ß', '?', '\\']),
+    (KeyCode::Equals, ['´', '`', NONE]),
+    (KeyCode::Q, ['q', 'Q', '@']),
+    (KeyCode::W, ['w', 'W', NONE]),
+    (KeyCode::E, ['e', 'E', '€']),
+    (KeyCode::R, ['r', 'R', NONE]),
+    (KeyCode::T, ['t', 'T', NONE]),
+    (KeyCode::Y, ['z', 'Z', NONE]),
+    (KeyCode::U, ['u', 'U', NONE]),
+    (KeyCode::I, ['i', 'I', NONE]),
+    (KeyCode::O, ['o', 'O', NONE]),
+    (KeyCode::P, ['p', 'P', NONE]),
+    (KeyCode::LeftBracket, ['ü', 'Ü', NONE]),
+    (KeyCode::RightBracket, ['+', '*', '~']),
+    (KeyCode::Backslash, ['#', '\'', NONE]),
+    (KeyCode::A, ['a', 'A', NONE]),
+    (KeyCode::S, ['s', 'S', NONE]),
+    (KeyCode::D, ['d', 'D', NONE]),
+    (KeyCode::F, ['f', 'F', NONE]),
+    (KeyCode::G, ['g', 'G', NONE]),
+    (KeyCode::H, ['h', 'H', NONE]),
+    (KeyCode::J, ['j', 'J', NONE]),
+    (KeyCode::K, ['k', 'K', NONE]),
+    (KeyCode::L, ['l', 'L', NONE]),
+    (KeyCode::Semicolon, ['ö', 'Ö', NONE]),
+    (KeyCode::Quote, ['ä', 'Ä', NONE]),
+    (KeyCode::NonUsBackslash, ['<', '>', '|']),
+    (KeyCode::Z, ['y', 'Y', NONE]),
+    (KeyCode::X, ['x', 'X', NONE]),
+    (KeyCode::C, ['c', 'C', NONE]),
+    (KeyCode::V, ['v', 'V', NONE]),
+    (KeyCode::B, ['b', 'B', NONE]),
+    (KeyCode::N, ['n', 'N', NONE]),
+    (KeyCode::M, ['m', 'M', 'µ']),
+    (KeyCode::Comma, [',', ';', NONE]),
+    (KeyCode::Period, ['.', ':', NONE]),
+    (KeyCode::Slash, ['-', '_', NONE]),
+    (KeyCode::Space, [' ', ' ', NONE]),
+]);
+
+/// built-in layouts (see [`find`])
+pub static LAYOUTS: [&Keymap; 2] = [&US, &DE];
+
+/// layouts added at runtime
+static REGISTERED: Mutex<Vec<&'static Keymap>> = Mutex::new(Vec::new());
+
+
+/// makes `keymap` available to [`find`]
+/// - returns `Err` if a layout with the same name exists or the table can not grow
+pub fn register_keymap(keymap: &'static Keymap) -> Result<(), ()> {
+    let mut registered = REGISTERED.lock();
+    if find_in(&registered, keymap.name).is_some() {
+        return Err(());
+    }
+    registered.try_push(keymap).map_err(|_| ())
+}
+
+/// removes registered layout `name`
+/// - returns `Err` if there is no such layout (built-in layouts can not be removed)
+/// - the layout stays active if it is
+pub fn unregister_keymap(name: &str) -> Result<(), ()> {
+    let mut registered = REGISTERED.lock();
+    let index = registered.as_slice()
+        .and_then(|k| k.iter().position(|k| k.name == name))
+        .ok_or(())?;
+    registered.remove(index);
+    Ok(())
+}
+
+/// finds built-in or registered layout by name
+pub fn find(name: &str) -> Option<&'static Keymap> {
+    find_in(&REGISTERED.lock(), name)
+}
+
+fn find_in(registered: &Vec<&'static Keymap>, name: &str) -> Option<&'static Keymap> {
+    LAYOUTS.iter()
+        .chain(registered.as_slice().unwrap_or_default())
+        .copied()
+        .find(|k| k.name == name)
+}
+
+/// calls `f` for every built-in and registered layout
+/// - the table is locked meanwhile
+pub fn for_each_keymap<F: FnMut(&'static Keymap)>(f: F) {
+    let registered = REGISTERED.lock();
+    LAYOUTS.iter()
+        .chain(registered.as_slice().unwrap_or_default())
+        .copied()
+        .for_each(f);
+}
@@ -0,0 +1,419 @@
+//	io/keyboard/mod.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  PS/2 keyboard
+//  - the IRQ handler decodes scan codes, tracks modifiers and queues key events
+//  - readers take events from a lock-free queue (see [`queue`]), blocking readers halt meanwhile
+//  - characters are produced by the active keymap (see [`keymap`])
+//  - Alt+F1..F12 switches virtual consoles and Shift+PageUp/PageDown scroll their history,
+//    those presses are not queued
+
+
+use core::sync::atomic::{AtomicBool, AtomicPtr, AtomicU16, Ordering};
+use spin::Mutex;
+
+use crate::arch::interrupt::InterruptFrame;
+use crate::io::int::{self, ISA_VECTOR_BASE};
+use crate::io::ps2::{self, ScancodeSet};
+use crate::renderer::console;
+use crate::String;
+
+pub mod scancode;
+pub mod keymap;
+pub mod queue;
+
+pub use keymap::Keymap;
+use queue::EventQueue;
+use scancode::Decoder;
+
+
+/// ISA IRQ of the first PS/2 port
+pub const KEYBOARD_IRQ: u8 = 1;
+/// number of events the queue holds
+pub const QUEUE_SIZE: usize = 128;
+
+
+/// Physical key (named after the US layout)
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum KeyCode {
+    Escape, F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+    PrintScreen, ScrollLock, Pause,
+    Backtick, Num1, Num2, Num3, Num4, Num5, Num6, Num7, Num8, Num9, Num0, Minus, Equals, Backspace,
+    Tab, Q, W, E, R, T, Y, U, I, O, P, LeftBracket, RightBracket, Backslash,
+    CapsLock, A, S, D, F, G, H, J, K, L, Semicolon, Quote, Enter,
+    LeftShift, NonUsBackslash, Z, X, C, V, B, N, M, Comma, Period, Slash, RightShift,
+    LeftCtrl, LeftGui, LeftAlt, Space, RightAlt, RightGui, Menu, RightCtrl,
+    Insert, Home, PageUp, Delete, End, PageDown, Up, Left, Down, Right,
+    NumLock, KeypadDivide, KeypadMultiply, KeypadMinus, KeypadPlus, KeypadEnter, KeypadPeriod,
+    Keypad0, Keypad1, Keypad2, Keypad3, Keypad4, Keypad5, Keypad6, Keypad7, Keypad8, Keypad9,
+}
+
+/// number of [`KeyCode`] variants
+pub const KEY_COUNT: usize = KeyCode::Keypad9 as usize + 1;
+
+impl KeyCode {
+    /// converts raw value back to key
+    #[inline]
+    pub fn from_u8(value: u8) -> Option<Self> {
+        if (value as usize) < KEY_COUNT {
+            //  `repr(u8)` with contiguous discriminants
+            Some(unsafe { core::mem::transmute::<u8, Self>(value) })
+        } else {
+            None
+        }
+    }
+
+    /// character the key produces independently of the keymap
+    fn fixed_char(&self, num_lock: bool) -> Option<char> {
+        use KeyCode::*;
+        let c = match self {
+            Enter | KeypadEnter => '\n',
+            Tab => '\t',
+            Backspace => '\x08',
+            Escape => '\x1b',
+            KeypadDivide => '/',
+            KeypadMultiply => '*',
+            KeypadMinus => '-',
+            KeypadPlus => '+',
+            _ if !num_lock => return None,
+            KeypadPeriod => '.',
+            Keypad0 => '0',
+            Keypad1 => '1',
+            Keypad2 => '2',
+            Keypad3 => '3',
+            Keypad4 => '4',
+            Keypad5 => '5',
+            Keypad6 => '6',
+            Keypad7 => '7',
+            Keypad8 => '8',
+            Keypad9 => '9',
+            _ => return None,
+        };
+        Some(c)
+    }
+}
+
+
+/// State of modifier and lock keys
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Modifiers(pub u16);
+
+impl Modifiers {
+    pub const LEFT_SHIFT: u16 = 1 << 0;
+    pub const RIGHT_SHIFT: u16 = 1 << 1;
+    pub const LEFT_CTRL: u16 = 1 << 2;
+    pub const RIGHT_CTRL: u16 = 1 << 3;
+    pub const LEFT_ALT: u16 = 1 << 4;
+    /// AltGr
+    pub const RIGHT_ALT: u16 = 1 << 5;
+    pub const LEFT_GUI: u16 = 1 << 6;
+    pub const RIGHT_GUI: u16 = 1 << 7;
+    pub const CAPS_LOCK: u16 = 1 << 8;
+    pub const NUM_LOCK: u16 = 1 << 9;
+    pub const SCROLL_LOCK: u16 = 1 << 10;
+
+    #[inline(always)]
+    pub fn contains(&self, flags: u16) -> bool {
+        self.0 & flags != 0
+    }
+
+    #[inline(always)] pub fn shift(&self) -> bool { self.contains(Self::LEFT_SHIFT | Self::RIGHT_SHIFT) }
+    #[inline(always)] pub fn ctrl(&self) -> bool { self.contains(Self::LEFT_CTRL | Self::RIGHT_CTRL) }
+    #[inline(always)] pub fn alt(&self) -> bool { self.contains(Self::LEFT_ALT) }
+    #[inline(always)] pub fn altgr(&self) -> bool { self.contains(Self::RIGHT_ALT) }
+    #[inline(always)] pub fn gui(&self) -> bool { self.contains(Self::LEFT_GUI | Self::RIGHT_GUI) }
+    #[inline(always)] pub fn caps_lock(&self) -> bool { self.contains(Self::CAPS_LOCK) }
+    #[inline(always)] pub fn num_lock(&self) -> bool { self.contains(Self::NUM_LOCK) }
+    #[inline(always)] pub fn scroll_lock(&self) -> bool { self.contains(Self::SCROLL_LOCK) }
+
+    /// returns the flag held down by `key`
+    fn held(key: KeyCode) -> u16 {
+        match key {
+            KeyCode::LeftShift => Self::LEFT_SHIFT,
+            KeyCode::RightShift => Self::RIGHT_SHIFT,
+            KeyCode::LeftCtrl => Self::LEFT_CTRL,
+            KeyCode::RightCtrl => Self::RIGHT_CTRL,
+            KeyCode::LeftAlt => Self::LEFT_ALT,
+            KeyCode::RightAlt => Self::RIGHT_ALT,
+            KeyCode::LeftGui => Self::LEFT_GUI,
+            KeyCode::RightGui => Self::RIGHT_GUI,
+            _ => 0,
+        }
+    }
+
+    /// returns the flag toggled by `key`
+    fn toggled(key: KeyCode) -> u16 {
+        match key {
+            KeyCode::CapsLock => Self::CAPS_LOCK,
+            KeyCode::NumLock => Self::NUM_LOCK,
+            KeyCode::ScrollLock => Self::SCROLL_LOCK,
+            _ => 0,
+        }
+    }
+
+    /// returns the keyboard LED byte
+    fn leds(&self) -> u8 {
+        self.scroll_lock() as u8 | (self.num_lock() as u8) << 1 | (self.caps_lock() as u8) << 2
+    }
+}
+
+
+/// Key press or release
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct KeyEvent {
+    pub key: KeyCode,
+    pub pressed: bool,
+    /// modifiers after the event was applied
+    pub modifiers: Modifiers,
+    /// character produced by the key (presses only)
+    pub ch: Option<char>,
+}
+
+impl KeyEvent {
+    /// packs the event for the queue
+    fn pack(&self) -> u64 {
+        self.key as u64
+            | (self.pressed as u64) << 8
+            | (self.modifiers.0 as u64) << 16
+            | (self.ch.map_or(u32::MAX, |c| c as u32) as u64) << 32
+    }
+
+    fn unpack(value: u64) -> Option<Self> {
+        Some(Self {
+            key: KeyCode::from_u8(value as u8)?,
+            pressed: (value >> 8) & 1 != 0,
+            modifiers: Modifiers((value >> 16) as u16),
+            ch: char::from_u32((value >> 32) as u32),
+        })
+    }
+}
+
+
+static QUEUE: EventQueue<QUEUE_SIZE> = EventQueue::new();
+/// locked by the IRQ handler, other users must disable interrupts first
+static DECODER: Mutex<Decoder> = Mutex::new(Decoder::new(ScancodeSet::Set1));
+static MODIFIERS: AtomicU16 = AtomicU16::new(Modifiers::NUM_LOCK);
+static KEYMAP: AtomicPtr<Keymap> = AtomicPtr::new(&keymap::US as *const Keymap as *mut Keymap);
+static INITIALIZED: AtomicBool = AtomicBool::new(false);
+
+
+/// activates `keymap` for following key presses
+#[inline]
+pub fn set_keymap(keymap: &'static Keymap) {
+    KEYMAP.store(keymap as *const Keymap as *mut Keymap, Ordering::Release);
+}
+
+/// returns the active keymap
+#[inline]
+pub fn keymap() -> &'static Keymap {
+    unsafe { &*KEYMAP.load(Ordering::Acquire) }
+}
+
+/// returns the current modifier state
+#[inline]
+pub fn modifiers() -> Modifiers {
+    Modifiers(MODIFIERS.load(Ordering::Relaxed))
+}
+
+#[inline]
+pub fn is_initialized() -> bool {
+    INITIALIZED.load(Ordering::Acquire)
+}
+
+/// returns scan code set in use
+pub fn scancode_set() -> ScancodeSet {
+    int::without_interrupts(|| DECODER.lock().set())
+}
+
+
+/// applies decoded key to the modifier state and builds event
+fn process(key: KeyCode, pressed: bool) -> KeyEvent {
+    let held = Modifiers::held(key);
+    let toggled = Modifiers::toggled(key);
+
+    let mut mods = modifiers();
+    if held != 0 {
+        if pressed { mods.0 |= held } else { mods.0 &= !held }
+    }
+    if toggled != 0 && pressed {
+        mods.0 ^= toggled;
+        //  no answer is awaited, the IRQ handler drops the ACKs
+        let _ = ps2::keyboard_write(ps2::keyboard::SET_LEDS);
+        let _ = ps2::keyboard_write(mods.leds());
+    }
+    MODIFIERS.store(mods.0, Ordering::Relaxed);
+
+    let ch = if pressed {
+        key.fixed_char(mods.num_lock())
+            .or_else(|| keymap().translate(key, mods.shift(), mods.altgr(), mods.caps_lock()))
+    } else {
+        None
+    };
+
+    KeyEvent {
+        key,
+        pressed,
+        modifiers: mods,
+        ch,
+    }
+}
+
+fn handle_irq(_frame: &mut InterruptFrame) {
+    let Some(byte) = ps2::try_read_data() else { return };
+
+    //  answers to commands
+    if matches!(byte, ps2::ACK | ps2::RESEND | ps2::ECHO | 0x00 | 0xff) {
+        return;
+    }
+
+    let decoded = DECODER.lock().feed(byte);
+    if let Some((key, pressed)) = decoded {
+        let event = process(key, pressed);
+        if console_chord(&event) || scroll_chord(&event) {
+            return;
+        }
+        //  the event is lost if nobody reads
+        let _ = QUEUE.push(event.pack());
+    }
+}
+
+/// switches console on Alt+F1..F12
+/// - returns `true` if the event was used
+fn console_chord(event: &KeyEvent) -> bool {
+    let (first, last) = (KeyCode::F1 as usize, KeyCode::F12 as usize);
+    let key = event.key as usize;
+    if !event.pressed || !event.modifiers.alt() || !(first..=last).contains(&key) {
+        return false;
+    }
+    console::request_switch(key - first).is_ok()
+}
+
+/// scrolls the view of the active console on Shift+PageUp/PageDown
+/// - returns `true` if the event was used
+fn scroll_chord(event: &KeyEvent) -> bool {
+    if !event.pressed || !event.modifiers.shift() {
+        return false;
+    }
+    match event.key {
+        KeyCode::PageUp => console::request_scroll(1),
+        KeyCode::PageDown => console::request_scroll(-1),
+        _ => return false,
+    }
+    true
+}
+
+
+/// takes the oldest event if there is any
+/// - also finishes console switches that could not be done in the IRQ handler
+#[inline]
+pub fn poll_event() -> Option<KeyEvent> {
+    console::poll();
+    QUEUE.pop().and_then(KeyEvent::unpack)
+}
+
+/// drops all queued events
+#[inline]
+pub fn clear() {
+    QUEUE.clear();
+}
+
+/// waits for more input
+fn wait() {
+    if int::are_enabled() {
+        crate::thread::yield_now();
+        if QUEUE.is_empty() {
+            unsafe { core::arch::asm!("hlt", options(nomem, nostack)) };
+        }
+    } else {
+        core::hint::spin_loop();
+    }
+}
+
+/// waits for next event (press or release)
+/// - never returns if interrupts are disabled and the keyboard is the only source of events
+pub fn read_event() -> KeyEvent {
+    loop {
+        if let Some(e) = poll_event() {
+            return e;
+        }
+        wait();
+    }
+}
+
+/// waits for next key press
+pub fn read_key() -> KeyEvent {
+    loop {
+        let e = read_event();
+        if e.pressed {
+            return e;
+        }
+    }
+}
+
+/// waits for next key press that produces character
+pub fn read_char() -> char {
+    loop {
+        if let Some(c) = read_key().ch {
+            return c;
+        }
+    }
+}
+
+/// reads line into `buffer` (without the newline)
+/// - typed characters are echoed, backspace removes the last one
+/// - returns the number of bytes appended
+/// - returns `Err` if `buffer` can not grow (the line is still consumed)
+pub fn read_line(buffer: &mut String) -> Result<usize, ()> {
+    let start = buffer.len();
+    let mut result = Ok(());
+
+    loop {
+        match read_char() {
+            '\n' => {
+                crate::print!("\n");
+                break;
+            }
+            '\x08' => {
+                if buffer.len() > start {
+                    //  remove whole UTF-8 sequence
+                    while let Some(b) = buffer.pop_ret() {
+                        if b & 0xc0 != 0x80 {
+                            break;
+                        }
+                    }
+                    crate::print!("\x08 \x08");
+                }
+            }
+            c if c.is_control() => {}
+            c => {
+                let mut encoded = [0u8; 4];
+                if result.is_ok() && buffer.try_push_str(c.encode_utf8(&mut encoded)).is_ok() {
+                    crate::print!("{}", c);
+                } else {
+                    result = Err(());
+                }
+            }
+        }
+    }
+
+    result.map(|_| buffer.len() - start)
+}
+
+
+/// initializes the PS/2 controller and starts receiving keyboard interrupts
+/// - returns `Err` if there is no working controller or keyboard
+pub(crate) fn init() -> Result<(), ()> {
+    let set = ps2::init()?;
+    int::without_interrupts(|| *DECODER.lock() = Decoder::new(set));
+
+    let vector = ISA_VECTOR_BASE + KEYBOARD_IRQ;
+    if !int::has_irq_handler(vector) {
+        int::register_irq_handler(vector, handle_irq)?;
+    }
+    int::enable_isa_irq(KEYBOARD_IRQ)?;
+
+    INITIALIZED.store(true, Ordering::Release);
+    Ok(())
+}
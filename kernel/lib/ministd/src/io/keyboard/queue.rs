@@ -0,0 +1,73 @@
+//	io/keyboard/queue.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  lock-free event queue
+//  - one producer (the IRQ handler), any number of consumers
+//  - events are packed into `u64` so every slot is a single atomic
+//  - the queue never blocks, new events are dropped when it is full
+
+
+use core::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+
+pub struct EventQueue<const N: usize> {
+    slots: [AtomicU64; N],
+    /// index of the next slot to read
+    head: AtomicUsize,
+    /// index of the next slot to write
+    tail: AtomicUsize,
+}
+
+impl<const N: usize> EventQueue<N> {
+    pub const fn new() -> Self {
+        Self {
+            slots: [const { AtomicU64::new(0) }; N],
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        }
+    }
+
+    /// appends `value`
+    /// - returns `Err` if the queue is full
+    /// - must not be called from two contexts at the same time
+    pub fn push(&self, value: u64) -> Result<(), ()> {
+        let tail = self.tail.load(Ordering::Relaxed);
+        if tail.wrapping_sub(self.head.load(Ordering::Acquire)) >= N {
+            return Err(());
+        }
+        self.slots[tail % N].store(value, Ordering::Relaxed);
+        self.tail.store(tail.wrapping_add(1), Ordering::Release);
+        Ok(())
+    }
+
+    /// removes the oldest value
+    pub fn pop(&self) -> Option<u64> {
+        loop {
+            let head = self.head.load(Ordering::Acquire);
+            if head == self.tail.load(Ordering::Acquire) {
+                return None;
+            }
+            //  the slot is not reused before `head` moves past it
+            let value = self.slots[head % N].load(Ordering::Relaxed);
+            if self.head.compare_exchange(head, head.wrapping_add(1), Ordering::AcqRel, Ordering::Relaxed).is_ok() {
+                return Some(value);
+            }
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.tail.load(Ordering::Acquire).wrapping_sub(self.head.load(Ordering::Acquire)).min(N)
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// drops all values
+    pub fn clear(&self) {
+        while self.pop().is_some() {}
+    }
+}
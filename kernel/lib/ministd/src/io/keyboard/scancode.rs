@@ -0,0 +1,342 @@
+//	io/keyboard/scancode.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  scan code decoding
+//  - set 1 (XT, what the controller translates to) and set 2 (AT, the keyboard default)
+//  - prefixes (`0xe0`, `0xe1`, `0xf0`) are kept in the decoder state
+
+
+use crate::io::keyboard::KeyCode;
+use crate::io::ps2::ScancodeSet;
+
+
+const EXTENDED: u8 = 0xe0;
+const PAUSE: u8 = 0xe1;
+const SET2_RELEASE: u8 = 0xf0;
+const SET1_RELEASE: u8 = 0x80;
+
+/// bytes that follow `0xe1` of the pause key
+const SET1_PAUSE_LENGTH: u8 = 5;
+const SET2_PAUSE_LENGTH: u8 = 7;
+
+
+/// Decoder of one scan code stream
+pub struct Decoder {
+    set: ScancodeSet,
+    extended: bool,
+    release: bool,
+    /// bytes of the current sequence that are ignored
+    skip: u8,
+}
+
+impl Decoder {
+    pub const fn new(set: ScancodeSet) -> Self {
+        Self {
+            set,
+            extended: false,
+            release: false,
+            skip: 0,
+        }
+    }
+
+    #[inline]
+    pub fn set(&self) -> ScancodeSet {
+        self.set
+    }
+
+    /// forgets partially received sequence
+    pub fn reset(&mut self) {
+        self.extended = false;
+        self.release = false;
+        self.skip = 0;
+    }
+
+    /// feeds one byte of the stream
+    /// - returns key and whether it was pressed once a sequence is complete
+    pub fn feed(&mut self, byte: u8) -> Option<(KeyCode, bool)> {
+        if self.skip > 0 {
+            self.skip -= 1;
+            return None;
+        }
+
+        match byte {
+            EXTENDED => {
+                self.extended = true;
+                return None;
+            }
+            PAUSE => {
+                //  pause has no release code
+                self.skip = match self.set {
+                    ScancodeSet::Set1 => SET1_PAUSE_LENGTH,
+                    ScancodeSet::Set2 => SET2_PAUSE_LENGTH,
+                };
+                self.reset_prefix();
+                return Some((KeyCode::Pause, true));
+            }
+            SET2_RELEASE if self.set == ScancodeSet::Set2 => {
+                self.release = true;
+                return None;
+            }
+            _ => {}
+        }
+
+        let extended = self.extended;
+        let (code, pressed) = match self.set {
+            ScancodeSet::Set1 => (set1(byte & !SET1_RELEASE, extended), byte & SET1_RELEASE == 0),
+            ScancodeSet::Set2 => (set2(byte, extended), !self.release),
+        };
+        self.reset_prefix();
+
+        code.map(|c| (c, pressed))
+    }
+
+    #[inline]
+    fn reset_prefix(&mut self) {
+        self.extended = false;
+        self.release = false;
+    }
+}
+
+
+/// translates set 1 make code (without the release bit)
+/// - fake shifts around extended keys yield `None`
+pub fn set1(code: u8, extended: bool) -> Option<KeyCode> {
+    use KeyCode::*;
+
+    if extended {
+        return match code {
+            0x1c => Some(KeypadEnter),
+            0x1d => Some(RightCtrl),
+            0x35 => Some(KeypadDivide),
+            0x37 => Some(PrintScreen),
+            0x38 => Some(RightAlt),
+            0x47 => Some(Home),
+            0x48 => Some(Up),
+            0x49 => Some(PageUp),
+            0x4b => Some(Left),
+            0x4d => Some(Right),
+            0x4f => Some(End),
+            0x50 => Some(Down),
+            0x51 => Some(PageDown),
+            0x52 => Some(Insert),
+            0x53 => Some(Delete),
+            0x5b => Some(LeftGui),
+            0x5c => Some(RightGui),
+            0x5d => Some(Menu),
+            _ => None,
+        };
+    }
+
+    let key = match code {
+        0x01 => Escape,
+        0x02 => Num1,
+        0x03 => Num2,
+        0x04 => Num3,
+        0x05 => Num4,
+        0x06 => Num5,
+        0x07 => Num6,
+        0x08 => Num7,
+        0x09 => Num8,
+        0x0a => Num9,
+        0x0b => Num0,
+        0x0c => Minus,
+        0x0d => Equals,
+        0x0e => Backspace,
+        0x0f => Tab,
+        0x10 => Q,
+        0x11 => W,
+        0x12 => E,
+        0x13 => R,
+        0x14 => T,
+        0x15 => Y,
+        0x16 => U,
+        0x17 => I,
+        0x18 => O,
+        0x19 => P,
+        0x1a => LeftBracket,
+        0x1b => RightBracket,
+        0x1c => Enter,
+        0x1d => LeftCtrl,
+        0x1e => A,
+        0x1f => S,
+        0x20 => D,
+        0x21 => F,
+        0x22 => G,
+        0x23 => H,
+        0x24 => J,
+        0x25 => K,
+        0x26 => L,
+        0x27 => Semicolon,
+        0x28 => Quote,
+        0x29 => Backtick,
+        0x2a => LeftShift,
+        0x2b => Backslash,
+        0x2c => Z,
+        0x2d => X,
+        0x2e => C,
+        0x2f => V,
+        0x30 => B,
+        0x31 => N,
+        0x32 => M,
+        0x33 => Comma,
+        0x34 => Period,
+        0x35 => Slash,
+        0x36 => RightShift,
+        0x37 => KeypadMultiply,
+        0x38 => LeftAlt,
+        0x39 => Space,
+        0x3a => CapsLock,
+        0x3b => F1,
+        0x3c => F2,
+        0x3d => F3,
+        0x3e => F4,
+        0x3f => F5,
+        0x40 => F6,
+        0x41 => F7,
+        0x42 => F8,
+        0x43 => F9,
+        0x44 => F10,
+        0x45 => NumLock,
+        0x46 => ScrollLock,
+        0x47 => Keypad7,
+        0x48 => Keypad8,
+        0x49 => Keypad9,
+        0x4a => KeypadMinus,
+        0x4b => Keypad4,
+        0x4c => Keypad5,
+        0x4d => Keypad6,
+        0x4e => KeypadPlus,
+        0x4f => Keypad1,
+        0x50 => Keypad2,
+        0x51 => Keypad3,
+        0x52 => Keypad0,
+        0x53 => KeypadPeriod,
+        0x56 => NonUsBackslash,
+        0x57 => F11,
+        0x58 => F12,
+        _ => return None,
+    };
+    Some(key)
+}
+
+/// translates set 2 make code
+/// - fake shifts around extended keys yield `None`
+pub fn set2(code: u8, extended: bool) -> Option<KeyCode> {
+    use KeyCode::*;
+
+    if extended {
+        return match code {
+            0x11 => Some(RightAlt),
+            0x14 => Some(RightCtrl),
+            0x1f => Some(LeftGui),
+            0x27 => Some(RightGui),
+            0x2f => Some(Menu),
+            0x4a => Some(KeypadDivide),
+            0x5a => Some(KeypadEnter),
+            0x69 => Some(End),
+            0x6b => Some(Left),
+            0x6c => Some(Home),
+            0x70 => Some(Insert),
+            0x71 => Some(Delete),
+            0x72 => Some(Down),
+            0x74 => Some(Right),
+            0x75 => Some(Up),
+            0x7a => Some(PageDown),
+            0x7c => Some(PrintScreen),
+            0x7d => Some(PageUp),
+            _ => None,
+        };
+    }
+
+    let key = match code {
+        0x01 => F9,
+        0x03 => F5,
+        0x04 => F3,
+        0x05 => F1,
+        0x06 => F2,
+        0x07 => F12,
+        0x09 => F10,
+        0x0a => F8,
+        0x0b => F6,
+        0x0c => F4,
+        0x0d => Tab,
+        0x0e => Backtick,
+        0x11 => LeftAlt,
+        0x12 => LeftShift,
+        0x14 => LeftCtrl,
+        0x15 => Q,
+        0x16 => Num1,
+        0x1a => Z,
+        0x1b => S,
+        0x1c => A,
+        0x1d => W,
+        0x1e => Num2,
+        0x21 => C,
+        0x22 => X,
+        0x23 => D,
+        0x24 => E,
+        0x25 => Num4,
+        0x26 => Num3,
+        0x29 => Space,
+        0x2a => V,
+        0x2b => F,
+        0x2c => T,
+        0x2d => R,
+        0x2e => Num5,
+        0x31 => N,
+        0x32 => B,
+        0x33 => H,
+        0x34 => G,
+        0x35 => Y,
+        0x36 => Num6,
+        0x3a => M,
+        0x3b => J,
+        0x3c => U,
+        0x3d => Num7,
+        0x3e => Num8,
+        0x41 => Comma,
+        0x42 => K,
+        0x43 => I,
+        0x44 => O,
+        0x45 => Num0,
+        0x46 => Num9,
+        0x49 => Period,
+        0x4a => Slash,
+        0x4b => L,
+        0x4c => Semicolon,
+        0x4d => P,
+        0x4e => Minus,
+        0x52 => Quote,
+        0x54 => LeftBracket,
+        0x55 => Equals,
+        0x58 => CapsLock,
+        0x59 => RightShift,
+        0x5a => Enter,
+        0x5b => RightBracket,
+        0x5d => Backslash,
+        0x61 => NonUsBackslash,
+        0x66 => Backspace,
+        0x69 => Keypad1,
+        0x6b => Keypad4,
+        0x6c => Keypad7,
+        0x70 => Keypad0,
+        0x71 => KeypadPeriod,
+        0x72 => Keypad2,
+        0x73 => Keypad5,
+        0x74 => Keypad6,
+        0x75 => Keypad8,
+        0x76 => Escape,
+        0x77 => NumLock,
+        0x78 => F11,
+        0x79 => KeypadPlus,
+        0x7a => Keypad3,
+        0x7b => KeypadMinus,
+        0x7c => KeypadMultiply,
+        0x7d => Keypad9,
+        0x7e => ScrollLock,
+        0x83 => F7,
+        _ => return None,
+    };
+    Some(key)
+}
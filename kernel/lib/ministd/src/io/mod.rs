@@ -13,96 +13,71 @@ use core::arch::asm;
 pub mod text;
 
 
-pub mod int {
-    use core::arch::asm;
-    
-    #[inline(always)]
-    pub fn disable() {
-        unsafe { asm!("cli"); }
-    }
+pub mod int;
+pub mod serial;
+pub mod pci;
+pub mod ps2;
+pub mod keyboard;
+
+pub use keyboard::{KeyCode, KeyEvent, poll_event, read_event, read_key, read_char, read_line};
 
-    #[inline(always)]
-    pub fn enable() {
-        unsafe { asm!("sti"); }
-    }
-}
 
 pub fn outb(port: u16, data: u8) {
     unsafe {
-        asm!("out %0, %1",
+        asm!("out dx, al",
         in("al") data,
         in("dx") port,
-        options(nostack));
+        options(nomem, nostack, preserves_flags));
     }
 }
 
 pub fn inb(port: u16) -> u8 {
     let mut ret: u8;
     unsafe {
-        asm!("in %1, %0",
+        asm!("in al, dx",
         out("al") ret,
         in("dx")port,
-        options(nostack));
+        options(nomem, nostack, preserves_flags));
     }
     ret
 }
 
 pub fn outw(port: u16, data: u16) {
     unsafe {
-        asm!("out %0, %1",
+        asm!("out dx, ax",
         in("ax") data,
         in("dx") port,
-        options(nostack));
+        options(nomem, nostack, preserves_flags));
     }
 }
 
 pub fn inw(port: u16) -> u16 {
     let mut ret: u16;
     unsafe {
-        asm!("in %1, %0",
+        asm!("in ax, dx",
         out("ax") ret,
         in("dx") port,
-        options(nostack));
+        options(nomem, nostack, preserves_flags));
     }
     ret
 }
 
 pub fn outd(port: u16, data: u32) {
     unsafe {
-        asm!("out %0, %1",
+        asm!("out dx, eax",
         in("eax") data,
         in("dx") port,
-        options(nostack));
+        options(nomem, nostack, preserves_flags));
     }
 }
 
 pub fn ind(port: u16) -> u32 {
     let mut ret: u32;
     unsafe {
-        asm!("in %1, %0",
+        asm!("in eax, dx",
         out("eax") ret,
         in("dx") port,
-        options(nostack));
-    }
-    ret
-}
-
-pub fn outq(port: u16, data: u64) {
-    unsafe {
-        asm!("out %0, %1",
-        in("rax") data,
-        in("dx") port,
-        options(nostack));
-    }
-}
-
-pub fn inq(port: u16) -> u64 {
-    let mut ret: u64;
-    unsafe {
-        asm!("in %1, %0",
-        out("rax") ret,
-        in("dx") port,
-        options(nostack));
+        options(nomem, nostack, preserves_flags));
     }
     ret
 }
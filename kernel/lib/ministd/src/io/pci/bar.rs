@@ -0,0 +1,140 @@
+//	io/pci/bar.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  base address registers
+//  - sizes are probed by writing all ones (decoding is disabled meanwhile)
+//  - 64-bit memory BARs occupy two slots, the upper one is reported as `Bar::None`
+
+
+use crate::io::pci::{config, PciAddress, reg, COMMAND_IO, COMMAND_MEMORY};
+
+
+/// maximum number of BARs (header type 0)
+pub const MAX_BARS: usize = 6;
+
+const BAR_IO: u32 = 1 << 0;
+const BAR_TYPE_MASK: u32 = 0b11 << 1;
+const BAR_TYPE_64: u32 = 0b10 << 1;
+const BAR_PREFETCHABLE: u32 = 1 << 3;
+const BAR_IO_MASK: u32 = !0b11;
+const BAR_MEMORY_MASK: u32 = !0b1111;
+
+
+/// Decoded base address register
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum Bar {
+    /// not implemented (or upper half of 64-bit BAR)
+    #[default]
+    None,
+    Io {
+        port: u32,
+        size: u32,
+    },
+    Memory {
+        address: u64,
+        size: u64,
+        prefetchable: bool,
+        is_64bit: bool,
+    },
+}
+
+impl Bar {
+    #[inline]
+    pub fn is_none(&self) -> bool {
+        *self == Bar::None
+    }
+
+    /// returns the size of the decoded range (0 if unused)
+    pub fn size(&self) -> u64 {
+        match *self {
+            Bar::None => 0,
+            Bar::Io { size, .. } => size as u64,
+            Bar::Memory { size, .. } => size,
+        }
+    }
+
+    /// returns physical address of memory BAR
+    pub fn memory_address(&self) -> Option<u64> {
+        match *self {
+            Bar::Memory { address, .. } => Some(address),
+            _ => None,
+        }
+    }
+
+    /// returns base port of IO BAR
+    pub fn io_port(&self) -> Option<u16> {
+        match *self {
+            Bar::Io { port, .. } => u16::try_from(port).ok(),
+            _ => None,
+        }
+    }
+}
+
+
+/// writes all ones to the BAR at `offset` and returns what the device kept
+/// - the original value is restored
+fn probe(address: PciAddress, offset: u16) -> u32 {
+    let original = config::read32(address, offset);
+    config::write32(address, offset, u32::MAX);
+    let mask = config::read32(address, offset);
+    config::write32(address, offset, original);
+    mask
+}
+
+/// decodes `count` BARs of the function
+/// - IO and memory decoding is disabled while sizes are probed
+pub(crate) fn decode(address: PciAddress, count: usize) -> [Bar; MAX_BARS] {
+    let mut bars = [Bar::None; MAX_BARS];
+
+    let command = config::read16(address, reg::COMMAND);
+    config::write32(address, reg::COMMAND, (command & !(COMMAND_IO | COMMAND_MEMORY)) as u32);
+
+    let mut index = 0;
+    while index < count.min(MAX_BARS) {
+        let offset = reg::BAR0 + index as u16 * 4;
+        let low = config::read32(address, offset);
+
+        if low & BAR_IO != 0 {
+            let mask = probe(address, offset) & BAR_IO_MASK & 0xffff;
+            if mask != 0 {
+                bars[index] = Bar::Io {
+                    port: low & BAR_IO_MASK,
+                    size: (!mask & 0xffff) + 1,
+                };
+            }
+            index += 1;
+            continue;
+        }
+
+        let is_64bit = low & BAR_TYPE_MASK == BAR_TYPE_64 && index + 1 < count;
+        let prefetchable = low & BAR_PREFETCHABLE != 0;
+
+        let (base, mask) = if is_64bit {
+            let high = config::read32(address, offset + 4);
+            let mask_low = probe(address, offset) & BAR_MEMORY_MASK;
+            let mask_high = probe(address, offset + 4);
+            (
+                (high as u64) << 32 | (low & BAR_MEMORY_MASK) as u64,
+                (mask_high as u64) << 32 | mask_low as u64,
+            )
+        } else {
+            let mask = probe(address, offset) & BAR_MEMORY_MASK;
+            //  sign extend, upper bits are not implemented
+            ((low & BAR_MEMORY_MASK) as u64, mask as u64 | 0xffff_ffff_0000_0000)
+        };
+
+        if mask & 0xffff_ffff != 0 || (is_64bit && mask != 0) {
+            bars[index] = Bar::Memory {
+                address: base,
+                size: (!mask).wrapping_add(1),
+                prefetchable,
+                is_64bit,
+            };
+        }
+        index += if is_64bit { 2 } else { 1 };
+    }
+
+    config::write32(address, reg::COMMAND, command as u32);
+    bars
+}
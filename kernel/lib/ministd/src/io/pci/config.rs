@@ -0,0 +1,247 @@
+//	io/pci/config.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  access to PCI configuration space
+//  - ECAM (memory mapped, 4 KiB per function) if the MCFG table describes it
+//  - legacy 0xcf8/0xcfc ports otherwise (only the first 256 bytes, segment 0)
+
+
+use core::ptr::{read_volatile, write_volatile};
+use spin::{Mutex, Once};
+
+use crate::io::{ind, outd};
+use crate::io::pci::PciAddress;
+use crate::mem::paging;
+
+
+pub const CONFIG_ADDRESS: u16 = 0xcf8;
+pub const CONFIG_DATA: u16 = 0xcfc;
+
+/// size of configuration space of one function (ECAM)
+pub const EXTENDED_CONFIG_SIZE: u16 = 4096;
+/// size of configuration space reachable through the legacy ports
+pub const LEGACY_CONFIG_SIZE: u16 = 256;
+
+/// maximum number of ECAM regions (MCFG entries) that are used
+pub const MAX_ECAM_REGIONS: usize = 8;
+
+const ENABLE: u32 = 1 << 31;
+
+
+/// Memory mapped configuration space of one segment group
+#[derive(Copy, Clone, Debug)]
+pub struct EcamRegion {
+    /// physical address of bus 0
+    pub base: u64,
+    pub segment: u16,
+    pub start_bus: u8,
+    pub end_bus: u8,
+}
+
+impl EcamRegion {
+    #[inline]
+    fn contains(&self, address: PciAddress) -> bool {
+        address.segment == self.segment && address.bus >= self.start_bus && address.bus <= self.end_bus
+    }
+
+    #[inline]
+    fn phys(&self, address: PciAddress) -> usize {
+        self.base as usize
+            + ((address.bus as usize) << 20 | (address.device as usize) << 15 | (address.function as usize) << 12)
+    }
+}
+
+
+/// Mechanism used to reach configuration space
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum AccessMethod {
+    Legacy,
+    Ecam,
+}
+
+
+/// # Configuration space
+pub struct ConfigSpace {
+    regions: [Option<EcamRegion>; MAX_ECAM_REGIONS],
+    method: AccessMethod,
+}
+
+impl ConfigSpace {
+    /// uses the legacy ports only
+    pub const fn legacy() -> Self {
+        Self {
+            regions: [None; MAX_ECAM_REGIONS],
+            method: AccessMethod::Legacy,
+        }
+    }
+
+    /// uses ECAM regions from the MCFG table
+    /// - returns `Err` if the table is empty
+    pub fn from_mcfg(mcfg: &crate::acpi::Mcfg) -> Result<Self, ()> {
+        let mut space = Self::legacy();
+        for (slot, entry) in space.regions.iter_mut().zip(mcfg.entries()) {
+            *slot = Some(EcamRegion {
+                base: entry.base_address,
+                segment: entry.segment,
+                start_bus: entry.start_bus,
+                end_bus: entry.end_bus,
+            });
+        }
+        if space.regions[0].is_none() {
+            return Err(());
+        }
+        space.method = AccessMethod::Ecam;
+        Ok(space)
+    }
+
+    #[inline(always)]
+    pub fn method(&self) -> AccessMethod {
+        self.method
+    }
+
+    /// returns iterator over ECAM regions (empty for legacy access)
+    pub fn regions(&self) -> impl Iterator<Item = &EcamRegion> {
+        self.regions.iter().flatten()
+    }
+
+    /// returns the size of configuration space of one function
+    pub fn size(&self, address: PciAddress) -> u16 {
+        if self.ecam(address).is_some() {
+            EXTENDED_CONFIG_SIZE
+        } else {
+            LEGACY_CONFIG_SIZE
+        }
+    }
+
+    #[inline]
+    fn ecam(&self, address: PciAddress) -> Option<&EcamRegion> {
+        self.regions().find(|r| r.contains(address))
+    }
+
+    /// returns virtual address of the dword at `offset`
+    /// - the page is mapped on first access
+    fn ecam_address(&self, address: PciAddress, offset: u16) -> Option<usize> {
+        let region = self.ecam(address)?;
+        let phys = region.phys(address);
+        paging::map_mmio(phys, EXTENDED_CONFIG_SIZE as usize).ok()
+            .map(|virt| virt + (offset & !3) as usize)
+    }
+
+    /// reads dword at `offset` (rounded down to 4)
+    /// - returns `u32::MAX` (like absent devices do) if the offset is not reachable
+    pub fn read(&self, address: PciAddress, offset: u16) -> u32 {
+        if let Some(ptr) = self.ecam_address(address, offset) {
+            return unsafe { read_volatile(ptr as *const u32) };
+        }
+        if address.segment != 0 || offset >= LEGACY_CONFIG_SIZE {
+            return u32::MAX;
+        }
+
+        let _guard = LEGACY.lock();
+        outd(CONFIG_ADDRESS, legacy_address(address, offset));
+        ind(CONFIG_DATA)
+    }
+
+    /// writes dword at `offset` (rounded down to 4)
+    /// - the write is dropped if the offset is not reachable
+    pub fn write(&self, address: PciAddress, offset: u16, value: u32) {
+        if let Some(ptr) = self.ecam_address(address, offset) {
+            unsafe { write_volatile(ptr as *mut u32, value) };
+            return;
+        }
+        if address.segment != 0 || offset >= LEGACY_CONFIG_SIZE {
+            return;
+        }
+
+        let _guard = LEGACY.lock();
+        outd(CONFIG_ADDRESS, legacy_address(address, offset));
+        outd(CONFIG_DATA, value);
+    }
+}
+
+#[inline]
+fn legacy_address(address: PciAddress, offset: u16) -> u32 {
+    ENABLE
+        | (address.bus as u32) << 16
+        | (address.device as u32) << 11
+        | (address.function as u32) << 8
+        | (offset as u32 & 0xfc)
+}
+
+
+/// serializes the two-step legacy access
+static LEGACY: Mutex<()> = Mutex::new(());
+static CONFIG: Once<ConfigSpace> = Once::new();
+
+
+/// checks if the legacy configuration mechanism responds
+pub fn legacy_present() -> bool {
+    let _guard = LEGACY.lock();
+    let old = ind(CONFIG_ADDRESS);
+    outd(CONFIG_ADDRESS, ENABLE);
+    let present = ind(CONFIG_ADDRESS) == ENABLE;
+    outd(CONFIG_ADDRESS, old);
+    present
+}
+
+/// returns the configuration space selected by [`init`]
+#[inline]
+pub fn config() -> Option<&'static ConfigSpace> {
+    CONFIG.get()
+}
+
+/// reads dword of configuration space
+/// - returns `u32::MAX` if PCI is not initialized
+#[inline]
+pub fn read32(address: PciAddress, offset: u16) -> u32 {
+    config().map_or(u32::MAX, |c| c.read(address, offset))
+}
+
+#[inline]
+pub fn read16(address: PciAddress, offset: u16) -> u16 {
+    (read32(address, offset) >> ((offset & 2) * 8)) as u16
+}
+
+#[inline]
+pub fn read8(address: PciAddress, offset: u16) -> u8 {
+    (read32(address, offset) >> ((offset & 3) * 8)) as u8
+}
+
+/// writes dword of configuration space
+#[inline]
+pub fn write32(address: PciAddress, offset: u16, value: u32) {
+    if let Some(c) = config() {
+        c.write(address, offset, value);
+    }
+}
+
+/// writes word (read-modify-write of the containing dword)
+pub fn write16(address: PciAddress, offset: u16, value: u16) {
+    let shift = (offset & 2) * 8;
+    let old = read32(address, offset) & !(0xffff << shift);
+    write32(address, offset, old | (value as u32) << shift);
+}
+
+/// writes byte (read-modify-write of the containing dword)
+pub fn write8(address: PciAddress, offset: u16, value: u8) {
+    let shift = (offset & 3) * 8;
+    let old = read32(address, offset) & !(0xff << shift);
+    write32(address, offset, old | (value as u32) << shift);
+}
+
+
+/// selects ECAM if there is MCFG table, legacy ports otherwise
+/// - returns `Err` if neither is available
+pub(crate) fn init() -> Result<AccessMethod, ()> {
+    let ecam = crate::acpi::tables()
+        .and_then(|t| t.mcfg())
+        .and_then(|m| ConfigSpace::from_mcfg(&m).ok());
+
+    let space = match ecam {
+        Some(s) => s,
+        None if legacy_present() => ConfigSpace::legacy(),
+        None => return Err(()),
+    };
+    Ok(CONFIG.call_once(|| space).method())
+}
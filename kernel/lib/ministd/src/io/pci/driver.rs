@@ -0,0 +1,166 @@
+//	io/pci/driver.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  driver registry
+//  - drivers list IDs they support, the first matching driver whose `probe` succeeds is bound
+//  - devices found later and drivers registered later are matched as well
+
+
+use spin::Mutex;
+
+use crate::Vec;
+use crate::io::pci::{self, Device};
+
+
+/// Device match rule, `None` fields match anything
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct DeviceId {
+    pub vendor: Option<u16>,
+    pub device: Option<u16>,
+    pub class: Option<u8>,
+    pub subclass: Option<u8>,
+    pub prog_if: Option<u8>,
+}
+
+impl DeviceId {
+    /// matches every device
+    pub const ANY: Self = Self {
+        vendor: None,
+        device: None,
+        class: None,
+        subclass: None,
+        prog_if: None,
+    };
+
+    /// matches exact vendor and device ID
+    pub const fn new(vendor: u16, device: u16) -> Self {
+        Self {
+            vendor: Some(vendor),
+            device: Some(device),
+            ..Self::ANY
+        }
+    }
+
+    /// matches every device of the vendor
+    pub const fn vendor(vendor: u16) -> Self {
+        Self {
+            vendor: Some(vendor),
+            ..Self::ANY
+        }
+    }
+
+    /// matches class and subclass
+    pub const fn class(class: u8, subclass: u8) -> Self {
+        Self {
+            class: Some(class),
+            subclass: Some(subclass),
+            ..Self::ANY
+        }
+    }
+
+    /// also requires programming interface
+    pub const fn with_prog_if(self, prog_if: u8) -> Self {
+        Self {
+            prog_if: Some(prog_if),
+            ..self
+        }
+    }
+
+    pub fn matches(&self, device: &Device) -> bool {
+        fn check<T: PartialEq>(rule: Option<T>, value: T) -> bool {
+            rule.is_none_or(|r| r == value)
+        }
+        check(self.vendor, device.vendor_id)
+            && check(self.device, device.device_id)
+            && check(self.class, device.class.class)
+            && check(self.subclass, device.class.subclass)
+            && check(self.prog_if, device.class.prog_if)
+    }
+}
+
+
+/// PCI driver
+#[derive(Copy, Clone)]
+pub struct Driver {
+    pub name: &'static str,
+    pub ids: &'static [DeviceId],
+    /// initializes the device
+    /// - returning `Err` lets other drivers try
+    pub probe: fn(&Device) -> Result<(), ()>,
+}
+
+impl Driver {
+    #[inline]
+    pub fn matches(&self, device: &Device) -> bool {
+        self.ids.iter().any(|id| id.matches(device))
+    }
+}
+
+
+static DRIVERS: Mutex<Vec<Driver>> = Mutex::new(Vec::new());
+
+
+/// registers `driver` and probes it against unbound devices
+/// - returns `Err` if the driver is already registered or there is not enough memory
+/// - returns the number of devices the driver was bound to
+pub fn register_driver(driver: Driver) -> Result<usize, ()> {
+    {
+        let mut drivers = DRIVERS.lock();
+        if drivers.as_slice().unwrap_or(&[]).iter().any(|d| d.name == driver.name) {
+            return Err(());
+        }
+        drivers.try_push(driver).map_err(|_| ())?;
+    }
+    Ok(bind(Some(&driver)))
+}
+
+/// returns the number of registered drivers
+#[inline]
+pub fn driver_count() -> usize {
+    DRIVERS.lock().len()
+}
+
+/// finds driver for every unbound device
+/// - only `only` is tried if given
+/// - returns the number of newly bound devices
+pub(crate) fn bind(only: Option<&Driver>) -> usize {
+    let mut bound = 0;
+    let mut index = 0;
+
+    //  neither lock is held while `probe` runs
+    while let Some(device) = pci::get(index) {
+        index += 1;
+        if device.driver.is_some() {
+            continue;
+        }
+
+        let found = match only {
+            Some(d) => try_driver(d, &device),
+            None => {
+                let mut i = 0;
+                loop {
+                    let Some(d) = DRIVERS.lock().as_slice().and_then(|s| s.get(i).copied()) else { break None };
+                    i += 1;
+                    if let Some(name) = try_driver(&d, &device) {
+                        break Some(name);
+                    }
+                }
+            }
+        };
+
+        if let Some(name) = found {
+            pci::set_driver(device.address, name);
+            bound += 1;
+        }
+    }
+    bound
+}
+
+fn try_driver(driver: &Driver, device: &Device) -> Option<&'static str> {
+    if driver.matches(device) && (driver.probe)(device).is_ok() {
+        Some(driver.name)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,575 @@
+//	io/pci/mod.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  PCI and PCI Express bus
+//  - configuration space is accessed through ECAM (MCFG) or the legacy ports (see [`config`])
+//  - every bus reachable from the host bridges is scanned once at [`init`], bridges are followed
+//  - found functions are kept in a list, drivers are matched against it (see [`driver`])
+
+
+use core::fmt;
+use spin::Mutex;
+
+use crate::Vec;
+
+pub mod config;
+pub mod bar;
+pub mod driver;
+
+pub use config::AccessMethod;
+pub use bar::{Bar, MAX_BARS};
+pub use driver::{Driver, DeviceId, register_driver};
+
+
+/// offsets of configuration space registers
+pub mod reg {
+    pub const VENDOR_ID: u16 = 0x00;
+    pub const DEVICE_ID: u16 = 0x02;
+    pub const COMMAND: u16 = 0x04;
+    pub const STATUS: u16 = 0x06;
+    pub const REVISION: u16 = 0x08;
+    pub const PROG_IF: u16 = 0x09;
+    pub const SUBCLASS: u16 = 0x0a;
+    pub const CLASS: u16 = 0x0b;
+    pub const HEADER_TYPE: u16 = 0x0e;
+    pub const BAR0: u16 = 0x10;
+    /// PCI-to-PCI bridge (header type 1)
+    pub const PRIMARY_BUS: u16 = 0x18;
+    pub const SECONDARY_BUS: u16 = 0x19;
+    pub const SUBORDINATE_BUS: u16 = 0x1a;
+    /// header type 0
+    pub const SUBSYSTEM_VENDOR_ID: u16 = 0x2c;
+    pub const SUBSYSTEM_ID: u16 = 0x2e;
+    pub const CAPABILITIES: u16 = 0x34;
+    /// header type 2
+    pub const CARDBUS_CAPABILITIES: u16 = 0x14;
+    pub const INTERRUPT_LINE: u16 = 0x3c;
+    pub const INTERRUPT_PIN: u16 = 0x3d;
+    /// first extended capability (PCI Express)
+    pub const EXTENDED_CAPABILITIES: u16 = 0x100;
+}
+
+/// capability IDs
+pub mod cap {
+    pub const POWER_MANAGEMENT: u8 = 0x01;
+    pub const MSI: u8 = 0x05;
+    pub const VENDOR: u8 = 0x09;
+    pub const PCI_EXPRESS: u8 = 0x10;
+    pub const MSI_X: u8 = 0x11;
+}
+
+pub const COMMAND_IO: u16 = 1 << 0;
+pub const COMMAND_MEMORY: u16 = 1 << 1;
+pub const COMMAND_BUS_MASTER: u16 = 1 << 2;
+pub const COMMAND_INTX_DISABLE: u16 = 1 << 10;
+pub const STATUS_CAPABILITIES: u16 = 1 << 4;
+
+/// vendor ID read from absent functions
+pub const INVALID_VENDOR: u16 = 0xffff;
+
+pub const MAX_DEVICES: usize = 32;
+pub const MAX_FUNCTIONS: usize = 8;
+
+/// class code of PCI-to-PCI bridges
+const CLASS_BRIDGE: u8 = 0x06;
+const SUBCLASS_PCI_BRIDGE: u8 = 0x04;
+
+/// upper bound of capability list length (malformed lists may loop)
+const MAX_CAPABILITIES: usize = 48;
+
+
+/// Location of one function
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct PciAddress {
+    pub segment: u16,
+    pub bus: u8,
+    pub device: u8,
+    pub function: u8,
+}
+
+impl PciAddress {
+    pub const fn new(segment: u16, bus: u8, device: u8, function: u8) -> Self {
+        Self { segment, bus, device, function }
+    }
+}
+
+impl fmt::Display for PciAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04x}:{:02x}:{:02x}.{}", self.segment, self.bus, self.device, self.function)
+    }
+}
+
+
+/// Class, subclass and programming interface
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ClassCode {
+    pub class: u8,
+    pub subclass: u8,
+    pub prog_if: u8,
+}
+
+impl ClassCode {
+    /// returns human readable name of the class (subclass for the common ones)
+    pub fn name(&self) -> &'static str {
+        match (self.class, self.subclass) {
+            (0x01, 0x01) => "IDE controller",
+            (0x01, 0x06) => "SATA controller",
+            (0x01, 0x08) => "NVMe controller",
+            (0x01, _) => "mass storage controller",
+            (0x02, 0x00) => "ethernet controller",
+            (0x02, _) => "network controller",
+            (0x03, 0x00) => "VGA controller",
+            (0x03, _) => "display controller",
+            (0x04, _) => "multimedia controller",
+            (0x05, _) => "memory controller",
+            (0x06, 0x00) => "host bridge",
+            (0x06, 0x01) => "ISA bridge",
+            (0x06, 0x04) => "PCI bridge",
+            (0x06, _) => "bridge",
+            (0x07, _) => "communication controller",
+            (0x08, _) => "system peripheral",
+            (0x09, _) => "input device controller",
+            (0x0c, 0x03) => "USB controller",
+            (0x0c, 0x05) => "SMBus controller",
+            (0x0c, _) => "serial bus controller",
+            (0x0d, _) => "wireless controller",
+            _ => "unknown device",
+        }
+    }
+}
+
+
+/// Layout of the configuration header
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum HeaderType {
+    General,
+    PciBridge,
+    CardBusBridge,
+    Unknown(u8),
+}
+
+impl HeaderType {
+    #[inline]
+    fn from_raw(raw: u8) -> Self {
+        match raw & 0x7f {
+            0 => Self::General,
+            1 => Self::PciBridge,
+            2 => Self::CardBusBridge,
+            x => Self::Unknown(x),
+        }
+    }
+
+    /// returns the number of BARs of the header
+    #[inline]
+    pub fn bar_count(&self) -> usize {
+        match self {
+            Self::General => 6,
+            Self::PciBridge => 2,
+            _ => 0,
+        }
+    }
+}
+
+
+/// Entry of the capability list
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Capability {
+    pub id: u16,
+    /// offset in configuration space
+    pub offset: u16,
+    /// version (extended capabilities only)
+    pub version: u8,
+}
+
+
+/// # PCI function
+/// One enumerated function (copied out of the device list)
+#[derive(Copy, Clone, Debug)]
+pub struct Device {
+    pub address: PciAddress,
+    pub vendor_id: u16,
+    pub device_id: u16,
+    pub class: ClassCode,
+    pub revision: u8,
+    pub header_type: HeaderType,
+    pub multifunction: bool,
+    pub bars: [Bar; MAX_BARS],
+    pub interrupt_line: u8,
+    /// 0 = none, 1..=4 = INTA..INTD
+    pub interrupt_pin: u8,
+    /// `(secondary, subordinate)` bus of bridges
+    pub bridge_buses: Option<(u8, u8)>,
+    /// name of the driver bound to the function
+    pub driver: Option<&'static str>,
+}
+
+impl Device {
+    /// reads the function's configuration header
+    /// - returns `None` if there is no function at `address`
+    pub fn probe(address: PciAddress) -> Option<Self> {
+        let id = config::read32(address, reg::VENDOR_ID);
+        let vendor_id = id as u16;
+        if vendor_id == INVALID_VENDOR || vendor_id == 0 {
+            return None;
+        }
+
+        let class = config::read32(address, reg::REVISION);
+        let raw_header = config::read8(address, reg::HEADER_TYPE);
+        let header_type = HeaderType::from_raw(raw_header);
+
+        let bridge_buses = match header_type {
+            HeaderType::PciBridge | HeaderType::CardBusBridge => Some((
+                config::read8(address, reg::SECONDARY_BUS),
+                config::read8(address, reg::SUBORDINATE_BUS),
+            )),
+            _ => None,
+        };
+
+        Some(Self {
+            address,
+            vendor_id,
+            device_id: (id >> 16) as u16,
+            class: ClassCode {
+                class: (class >> 24) as u8,
+                subclass: (class >> 16) as u8,
+                prog_if: (class >> 8) as u8,
+            },
+            revision: class as u8,
+            header_type,
+            multifunction: raw_header & 0x80 != 0,
+            bars: bar::decode(address, header_type.bar_count()),
+            interrupt_line: config::read8(address, reg::INTERRUPT_LINE),
+            interrupt_pin: config::read8(address, reg::INTERRUPT_PIN),
+            bridge_buses,
+            driver: None,
+        })
+    }
+
+    #[inline]
+    pub fn read32(&self, offset: u16) -> u32 {
+        config::read32(self.address, offset)
+    }
+    #[inline]
+    pub fn read16(&self, offset: u16) -> u16 {
+        config::read16(self.address, offset)
+    }
+    #[inline]
+    pub fn read8(&self, offset: u16) -> u8 {
+        config::read8(self.address, offset)
+    }
+    #[inline]
+    pub fn write32(&self, offset: u16, value: u32) {
+        config::write32(self.address, offset, value)
+    }
+    #[inline]
+    pub fn write16(&self, offset: u16, value: u16) {
+        config::write16(self.address, offset, value)
+    }
+    #[inline]
+    pub fn write8(&self, offset: u16, value: u8) {
+        config::write8(self.address, offset, value)
+    }
+
+    /// returns BAR at `index`
+    #[inline]
+    pub fn bar(&self, index: usize) -> Bar {
+        self.bars.get(index).copied().unwrap_or_default()
+    }
+
+    #[inline]
+    pub fn command(&self) -> u16 {
+        self.read16(reg::COMMAND)
+    }
+
+    /// writes the command register
+    /// - the status register (write 1 to clear) is left untouched
+    #[inline]
+    pub fn set_command(&self, command: u16) {
+        self.write32(reg::COMMAND, command as u32);
+    }
+
+    #[inline]
+    pub fn status(&self) -> u16 {
+        self.read16(reg::STATUS)
+    }
+
+    /// enables memory and IO decoding and bus mastering (DMA)
+    pub fn enable(&self) {
+        self.set_command(self.command() | COMMAND_IO | COMMAND_MEMORY | COMMAND_BUS_MASTER);
+    }
+
+    #[inline]
+    pub fn is_bridge(&self) -> bool {
+        self.bridge_buses.is_some()
+            || (self.class.class == CLASS_BRIDGE && self.class.subclass == SUBCLASS_PCI_BRIDGE)
+    }
+
+    /// returns iterator over the capability list
+    pub fn capabilities(&self) -> Capabilities {
+        let pointer = if self.status() & STATUS_CAPABILITIES == 0 {
+            0
+        } else if self.header_type == HeaderType::CardBusBridge {
+            self.read8(reg::CARDBUS_CAPABILITIES)
+        } else {
+            self.read8(reg::CAPABILITIES)
+        };
+
+        Capabilities {
+            address: self.address,
+            next: pointer as u16 & !3,
+            remaining: MAX_CAPABILITIES,
+        }
+    }
+
+    /// returns iterator over PCI Express extended capabilities
+    /// - empty if configuration space is accessed through the legacy ports
+    pub fn extended_capabilities(&self) -> ExtendedCapabilities {
+        let extended = config::config().is_some_and(|c| c.size(self.address) > config::LEGACY_CONFIG_SIZE);
+        ExtendedCapabilities {
+            address: self.address,
+            next: if extended { reg::EXTENDED_CAPABILITIES } else { 0 },
+            remaining: (config::EXTENDED_CONFIG_SIZE - config::LEGACY_CONFIG_SIZE) as usize / 4,
+        }
+    }
+
+    /// finds capability with `id`
+    pub fn find_capability(&self, id: u8) -> Option<Capability> {
+        self.capabilities().find(|c| c.id == id as u16)
+    }
+
+    /// finds extended capability with `id`
+    pub fn find_extended_capability(&self, id: u16) -> Option<Capability> {
+        self.extended_capabilities().find(|c| c.id == id)
+    }
+
+    /// checks if the function is PCI Express
+    #[inline]
+    pub fn is_express(&self) -> bool {
+        self.find_capability(cap::PCI_EXPRESS).is_some()
+    }
+}
+
+impl fmt::Display for Device {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} [{:04x}:{:04x}] {} ({:02x}{:02x}{:02x})",
+            self.address, self.vendor_id, self.device_id, self.class.name(),
+            self.class.class, self.class.subclass, self.class.prog_if)?;
+        if let Some(driver) = self.driver {
+            write!(f, " driver: {}", driver)?;
+        }
+        Ok(())
+    }
+}
+
+
+/// Iterator over the capability list
+pub struct Capabilities {
+    address: PciAddress,
+    next: u16,
+    remaining: usize,
+}
+
+impl Iterator for Capabilities {
+    type Item = Capability;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next < 0x40 || self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+
+        let offset = self.next;
+        let header = config::read16(self.address, offset);
+        self.next = (header >> 8) & !3;
+
+        Some(Capability {
+            id: header & 0xff,
+            offset,
+            version: 0,
+        })
+    }
+}
+
+/// Iterator over the extended capability list
+pub struct ExtendedCapabilities {
+    address: PciAddress,
+    next: u16,
+    remaining: usize,
+}
+
+impl Iterator for ExtendedCapabilities {
+    type Item = Capability;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next < reg::EXTENDED_CAPABILITIES || self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+
+        let offset = self.next;
+        let header = config::read32(self.address, offset);
+        //  no extended capabilities (or not PCI Express)
+        if header == 0 || header == u32::MAX {
+            return None;
+        }
+        self.next = (header >> 20) as u16 & !3;
+
+        Some(Capability {
+            id: header as u16,
+            offset,
+            version: ((header >> 16) & 0xf) as u8,
+        })
+    }
+}
+
+
+static DEVICES: Mutex<Vec<Device>> = Mutex::new(Vec::new());
+
+
+/// returns copy of the function at `index` of the device list
+pub fn get(index: usize) -> Option<Device> {
+    DEVICES.lock().as_slice().and_then(|d| d.get(index).copied())
+}
+
+/// returns the number of found functions
+#[inline]
+pub fn count() -> usize {
+    DEVICES.lock().len()
+}
+
+/// returns copy of the function at `address`
+pub fn at(address: PciAddress) -> Option<Device> {
+    find_by(|d| d.address == address)
+}
+
+/// returns the first function with `vendor` and `device` ID
+pub fn find(vendor: u16, device: u16) -> Option<Device> {
+    find_by(|d| d.vendor_id == vendor && d.device_id == device)
+}
+
+/// returns the first function of `class` and `subclass`
+pub fn find_class(class: u8, subclass: u8) -> Option<Device> {
+    find_by(|d| d.class.class == class && d.class.subclass == subclass)
+}
+
+/// returns the first function `f` returns `true` for
+pub fn find_by<F: FnMut(&Device) -> bool>(mut f: F) -> Option<Device> {
+    DEVICES.lock().as_slice()?.iter().find(|d| f(d)).copied()
+}
+
+/// calls `f` for every found function
+/// - the device list is locked meanwhile
+pub fn for_each<F: FnMut(&Device)>(f: F) {
+    if let Some(devices) = DEVICES.lock().as_slice() {
+        devices.iter().for_each(f);
+    }
+}
+
+pub(crate) fn set_driver(address: PciAddress, driver: &'static str) {
+    if let Some(devices) = DEVICES.lock().as_mut_slice()
+        && let Some(d) = devices.iter_mut().find(|d| d.address == address) {
+        d.driver = Some(driver);
+    }
+}
+
+
+/// Bus scan state
+struct Scanner {
+    segment: u16,
+    /// bitmap of visited buses
+    visited: [u64; 4],
+    devices: Vec<Device>,
+}
+
+impl Scanner {
+    fn bus(&mut self, bus: u8) -> Result<(), ()> {
+        let (word, bit) = (bus as usize / 64, bus as usize % 64);
+        if self.visited[word] & 1 << bit != 0 {
+            return Ok(());
+        }
+        self.visited[word] |= 1 << bit;
+
+        for device in 0..MAX_DEVICES as u8 {
+            self.device(bus, device)?;
+        }
+        Ok(())
+    }
+
+    fn device(&mut self, bus: u8, device: u8) -> Result<(), ()> {
+        let Some(first) = Device::probe(PciAddress::new(self.segment, bus, device, 0)) else {
+            return Ok(());
+        };
+        let functions = if first.multifunction { MAX_FUNCTIONS as u8 } else { 1 };
+
+        self.function(first)?;
+        for function in 1..functions {
+            if let Some(d) = Device::probe(PciAddress::new(self.segment, bus, device, function)) {
+                self.function(d)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn function(&mut self, device: Device) -> Result<(), ()> {
+        self.devices.try_push(device).map_err(|_| ())?;
+
+        if let Some((secondary, _)) = device.bridge_buses {
+            //  unconfigured bridge
+            if secondary != 0 && secondary > device.address.bus {
+                self.bus(secondary)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// scans every bus of the segment starting at `start_bus`
+    fn segment(&mut self, segment: u16, start_bus: u8) -> Result<(), ()> {
+        self.segment = segment;
+        self.visited = [0; 4];
+
+        //  multifunction host bridge: function `n` is the host controller of bus `start_bus + n`
+        let root = PciAddress::new(segment, start_bus, 0, 0);
+        let multifunction = config::read8(root, reg::HEADER_TYPE) & 0x80 != 0;
+        if !multifunction {
+            return self.bus(start_bus);
+        }
+        for function in 0..MAX_FUNCTIONS as u8 {
+            let host = PciAddress::new(segment, start_bus, 0, function);
+            if config::read16(host, reg::VENDOR_ID) != INVALID_VENDOR {
+                self.bus(start_bus.saturating_add(function))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+
+/// selects configuration access method and enumerates all functions
+/// - returns the number of found functions
+/// - returns `Err` if there is no PCI or the device list can not be allocated
+pub(crate) fn init() -> Result<usize, ()> {
+    let method = config::init()?;
+
+    let mut scanner = Scanner {
+        segment: 0,
+        visited: [0; 4],
+        devices: Vec::new(),
+    };
+
+    match method {
+        AccessMethod::Legacy => scanner.segment(0, 0)?,
+        AccessMethod::Ecam => {
+            let config = config::config().ok_or(())?;
+            for region in config.regions() {
+                scanner.segment(region.segment, region.start_bus)?;
+            }
+        }
+    }
+
+    let count = scanner.devices.len();
+    *DEVICES.lock() = scanner.devices;
+
+    driver::bind(None);
+    Ok(count)
+}
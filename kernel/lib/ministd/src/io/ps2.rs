@@ -0,0 +1,210 @@
+//	io/ps2.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  i8042 PS/2 controller
+//  - only the first port (keyboard) is used, the second one stays disabled
+//  - all waits are bounded, missing or broken controllers are reported as `Err`
+
+
+use crate::io::{inb, outb};
+
+
+pub const DATA_PORT: u16 = 0x60;
+/// status register (read), command register (write)
+pub const COMMAND_PORT: u16 = 0x64;
+
+const STATUS_OUTPUT_FULL: u8 = 1 << 0;
+const STATUS_INPUT_FULL: u8 = 1 << 1;
+
+mod command {
+    pub const READ_CONFIG: u8 = 0x20;
+    pub const WRITE_CONFIG: u8 = 0x60;
+    pub const DISABLE_SECOND: u8 = 0xa7;
+    pub const SELF_TEST: u8 = 0xaa;
+    pub const TEST_FIRST: u8 = 0xab;
+    pub const DISABLE_FIRST: u8 = 0xad;
+    pub const ENABLE_FIRST: u8 = 0xae;
+}
+
+const CONFIG_FIRST_IRQ: u8 = 1 << 0;
+const CONFIG_SECOND_IRQ: u8 = 1 << 1;
+const CONFIG_FIRST_CLOCK_OFF: u8 = 1 << 4;
+const CONFIG_TRANSLATION: u8 = 1 << 6;
+
+const SELF_TEST_PASSED: u8 = 0x55;
+
+/// keyboard responses
+pub const ACK: u8 = 0xfa;
+pub const RESEND: u8 = 0xfe;
+pub const ECHO: u8 = 0xee;
+const RESET_PASSED: u8 = 0xaa;
+
+/// keyboard commands
+pub mod keyboard {
+    pub const SET_LEDS: u8 = 0xed;
+    pub const SCANCODE_SET: u8 = 0xf0;
+    pub const ENABLE_SCANNING: u8 = 0xf4;
+    pub const DISABLE_SCANNING: u8 = 0xf5;
+    pub const RESET: u8 = 0xff;
+}
+
+/// maximum number of status polls
+const TIMEOUT: usize = 100_000;
+/// number of attempts of a keyboard command that is answered with `RESEND`
+const RETRIES: usize = 3;
+
+
+#[inline]
+fn status() -> u8 {
+    inb(COMMAND_PORT)
+}
+
+/// waits until the controller accepts a byte
+fn wait_write() -> Result<(), ()> {
+    for _ in 0..TIMEOUT {
+        if status() & STATUS_INPUT_FULL == 0 {
+            return Ok(());
+        }
+        core::hint::spin_loop();
+    }
+    Err(())
+}
+
+/// waits until the controller has a byte
+fn wait_read() -> Result<(), ()> {
+    for _ in 0..TIMEOUT {
+        if status() & STATUS_OUTPUT_FULL != 0 {
+            return Ok(());
+        }
+        core::hint::spin_loop();
+    }
+    Err(())
+}
+
+/// sends command to the controller
+pub fn send_command(cmd: u8) -> Result<(), ()> {
+    wait_write()?;
+    outb(COMMAND_PORT, cmd);
+    Ok(())
+}
+
+/// writes byte to the data port (to the first device if no command is pending)
+pub fn write_data(data: u8) -> Result<(), ()> {
+    wait_write()?;
+    outb(DATA_PORT, data);
+    Ok(())
+}
+
+/// reads byte from the data port
+pub fn read_data() -> Result<u8, ()> {
+    wait_read()?;
+    Ok(inb(DATA_PORT))
+}
+
+/// reads byte from the data port if there is one
+#[inline]
+pub fn try_read_data() -> Option<u8> {
+    if status() & STATUS_OUTPUT_FULL != 0 {
+        Some(inb(DATA_PORT))
+    } else {
+        None
+    }
+}
+
+/// drops everything in the output buffer
+pub fn flush() {
+    for _ in 0..16 {
+        if try_read_data().is_none() {
+            break;
+        }
+    }
+}
+
+fn read_config() -> Result<u8, ()> {
+    send_command(command::READ_CONFIG)?;
+    read_data()
+}
+
+fn write_config(config: u8) -> Result<(), ()> {
+    send_command(command::WRITE_CONFIG)?;
+    write_data(config)
+}
+
+/// sends byte to the keyboard and waits for `ACK`
+/// - resends the byte on `RESEND`
+/// - must not be used while the keyboard IRQ is enabled (the handler would consume the answer)
+pub fn keyboard_command(byte: u8) -> Result<(), ()> {
+    for _ in 0..RETRIES {
+        write_data(byte)?;
+        match read_data()? {
+            ACK => return Ok(()),
+            RESEND => continue,
+            _ => return Err(()),
+        }
+    }
+    Err(())
+}
+
+/// sends byte to the keyboard without waiting for the answer
+/// - used from the IRQ handler (e.g. to update LEDs)
+#[inline]
+pub fn keyboard_write(byte: u8) -> Result<(), ()> {
+    write_data(byte)
+}
+
+
+/// Scan code set the keyboard sends
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ScancodeSet {
+    Set1,
+    Set2,
+}
+
+
+/// initializes the controller and the keyboard
+/// - the keyboard IRQ is enabled in the controller (but not routed)
+/// - scan code set 2 is requested, if the keyboard refuses the controller translates to set 1
+pub(crate) fn init() -> Result<ScancodeSet, ()> {
+    send_command(command::DISABLE_FIRST)?;
+    send_command(command::DISABLE_SECOND)?;
+    flush();
+
+    let mut config = read_config()?;
+    config &= !(CONFIG_FIRST_IRQ | CONFIG_SECOND_IRQ | CONFIG_TRANSLATION);
+    write_config(config)?;
+
+    send_command(command::SELF_TEST)?;
+    if read_data()? != SELF_TEST_PASSED {
+        return Err(());
+    }
+    //  the self test may reset the controller
+    write_config(config)?;
+
+    send_command(command::TEST_FIRST)?;
+    if read_data()? != 0 {
+        return Err(());
+    }
+
+    send_command(command::ENABLE_FIRST)?;
+    config &= !CONFIG_FIRST_CLOCK_OFF;
+    write_config(config)?;
+
+    keyboard_command(keyboard::RESET)?;
+    if read_data()? != RESET_PASSED {
+        return Err(());
+    }
+
+    keyboard_command(keyboard::DISABLE_SCANNING)?;
+    let set = if keyboard_command(keyboard::SCANCODE_SET).and_then(|_| keyboard_command(2)).is_ok() {
+        ScancodeSet::Set2
+    } else {
+        config |= CONFIG_TRANSLATION;
+        ScancodeSet::Set1
+    };
+    keyboard_command(keyboard::ENABLE_SCANNING)?;
+    flush();
+
+    write_config(config | CONFIG_FIRST_IRQ)?;
+    Ok(set)
+}
@@ -0,0 +1,297 @@
+//	io/serial.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  16550 UART driver
+//  - works without framebuffer (e.g. `qemu -nographic`)
+//  - output of `print!`-like macros can be copied here (see [`set_tee`])
+
+
+use core::sync::atomic::{AtomicBool, Ordering};
+use spin::Mutex;
+
+use crate::io::{inb, outb};
+
+
+pub const COM1_PORT: u16 = 0x3f8;
+pub const COM2_PORT: u16 = 0x2f8;
+
+/// UART input clock divided by 16
+pub const MAX_BAUD: u32 = 115200;
+
+pub const DEFAULT_CONFIG: LineConfig = LineConfig::new(MAX_BAUD);
+
+
+/// register offsets from the base port
+mod reg {
+    pub const DATA: u16 = 0;
+    pub const INT_ENABLE: u16 = 1;
+    /// divisor latch (low/high) when DLAB is set
+    pub const DIVISOR_LOW: u16 = 0;
+    pub const DIVISOR_HIGH: u16 = 1;
+    pub const FIFO_CONTROL: u16 = 2;
+    pub const LINE_CONTROL: u16 = 3;
+    pub const MODEM_CONTROL: u16 = 4;
+    pub const LINE_STATUS: u16 = 5;
+    pub const SCRATCH: u16 = 7;
+}
+
+const LINE_DLAB: u8 = 1 << 7;
+const STATUS_DATA_READY: u8 = 1 << 0;
+const STATUS_TRANSMIT_EMPTY: u8 = 1 << 5;
+/// enable + clear both FIFOs + 14 byte threshold
+const FIFO_ENABLE: u8 = 0xc7;
+/// DTR + RTS + OUT2
+const MODEM_NORMAL: u8 = 0x0b;
+/// RTS + OUT1 + OUT2 + LOOP
+const MODEM_LOOPBACK: u8 = 0x1e;
+
+/// maximum number of status polls before a byte is dropped
+const TIMEOUT: usize = 100_000;
+
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum DataBits {
+    Five = 0,
+    Six = 1,
+    Seven = 2,
+    Eight = 3,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum Parity {
+    None = 0,
+    Odd = 0b001,
+    Even = 0b011,
+    Mark = 0b101,
+    Space = 0b111,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum StopBits {
+    One = 0,
+    /// 1.5 stop bits with [`DataBits::Five`]
+    Two = 1,
+}
+
+
+/// baud rate and line format of a serial port
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct LineConfig {
+    pub baud: u32,
+    pub data_bits: DataBits,
+    pub parity: Parity,
+    pub stop_bits: StopBits,
+}
+
+impl LineConfig {
+    /// 8N1 with given baud rate
+    pub const fn new(baud: u32) -> Self {
+        Self {
+            baud,
+            data_bits: DataBits::Eight,
+            parity: Parity::None,
+            stop_bits: StopBits::One,
+        }
+    }
+
+    /// returns the divisor latch value
+    /// - returns `None` if the baud rate can not be set exactly enough
+    pub const fn divisor(&self) -> Option<u16> {
+        if self.baud == 0 || self.baud > MAX_BAUD || MAX_BAUD / self.baud > u16::MAX as u32 {
+            return None;
+        }
+        Some((MAX_BAUD / self.baud) as u16)
+    }
+
+    #[inline]
+    const fn line_control(&self) -> u8 {
+        self.data_bits as u8 | (self.stop_bits as u8) << 2 | (self.parity as u8) << 3
+    }
+}
+
+impl Default for LineConfig {
+    #[inline(always)]
+    fn default() -> Self {
+        DEFAULT_CONFIG
+    }
+}
+
+
+pub struct SerialPort {
+    base: u16,
+    config: LineConfig,
+    initialized: bool,
+}
+
+impl SerialPort {
+    pub const fn new(base: u16) -> Self {
+        Self {
+            base,
+            config: DEFAULT_CONFIG,
+            initialized: false,
+        }
+    }
+
+    #[inline(always)] pub fn base(&self) -> u16 { self.base }
+    #[inline(always)] pub fn config(&self) -> LineConfig { self.config }
+    #[inline(always)] pub fn is_initialized(&self) -> bool { self.initialized }
+
+    #[inline(always)]
+    fn write_reg(&self, reg: u16, value: u8) {
+        outb(self.base + reg, value);
+    }
+    #[inline(always)]
+    fn read_reg(&self, reg: u16) -> u8 {
+        inb(self.base + reg)
+    }
+
+    /// programs the UART with `config`
+    /// - returns `Err` if the configuration is invalid or the chip does not respond
+    /// - interrupts of the UART stay disabled
+    pub fn init(&mut self, config: LineConfig) -> Result<(), ()> {
+        self.initialized = false;
+        let divisor = config.divisor().ok_or(())?;
+
+        //  no UART behind the port
+        self.write_reg(reg::SCRATCH, 0x5a);
+        if self.read_reg(reg::SCRATCH) != 0x5a {
+            return Err(());
+        }
+
+        self.write_reg(reg::INT_ENABLE, 0);
+
+        self.write_reg(reg::LINE_CONTROL, LINE_DLAB);
+        self.write_reg(reg::DIVISOR_LOW, divisor as u8);
+        self.write_reg(reg::DIVISOR_HIGH, (divisor >> 8) as u8);
+        self.write_reg(reg::LINE_CONTROL, config.line_control());
+
+        self.write_reg(reg::FIFO_CONTROL, FIFO_ENABLE);
+
+        //  loopback test
+        self.write_reg(reg::MODEM_CONTROL, MODEM_LOOPBACK);
+        self.write_reg(reg::DATA, 0xae);
+        if self.read_reg(reg::DATA) != 0xae {
+            return Err(());
+        }
+
+        self.write_reg(reg::MODEM_CONTROL, MODEM_NORMAL);
+
+        self.config = config;
+        self.initialized = true;
+        Ok(())
+    }
+
+    #[inline]
+    fn can_send(&self) -> bool {
+        self.read_reg(reg::LINE_STATUS) & STATUS_TRANSMIT_EMPTY != 0
+    }
+
+    /// checks if a byte was received
+    #[inline]
+    pub fn has_data(&self) -> bool {
+        self.initialized && self.read_reg(reg::LINE_STATUS) & STATUS_DATA_READY != 0
+    }
+
+    /// sends raw byte
+    /// - the byte is dropped if the port is not initialized or the transmitter is stuck
+    pub fn send_raw(&mut self, byte: u8) {
+        if !self.initialized {
+            return;
+        }
+        for _ in 0..TIMEOUT {
+            if self.can_send() {
+                self.write_reg(reg::DATA, byte);
+                return;
+            }
+            core::hint::spin_loop();
+        }
+    }
+
+    /// sends byte, translating `\n` into `\r\n`
+    #[inline]
+    pub fn send(&mut self, byte: u8) {
+        if byte == b'\n' {
+            self.send_raw(b'\r');
+        }
+        self.send_raw(byte);
+    }
+
+    /// receives byte if there is one
+    #[inline]
+    pub fn try_receive(&mut self) -> Option<u8> {
+        if self.has_data() {
+            Some(self.read_reg(reg::DATA))
+        } else {
+            None
+        }
+    }
+
+    /// waits for byte
+    /// - returns `None` if the port is not initialized
+    pub fn receive(&mut self) -> Option<u8> {
+        if !self.initialized {
+            return None;
+        }
+        loop {
+            if let Some(b) = self.try_receive() {
+                return Some(b);
+            }
+            core::hint::spin_loop();
+        }
+    }
+}
+
+impl core::fmt::Write for SerialPort {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        for b in s.bytes() {
+            self.send(b);
+        }
+        Ok(())
+    }
+}
+
+
+pub static COM1: Mutex<SerialPort> = Mutex::new(SerialPort::new(COM1_PORT));
+pub static COM2: Mutex<SerialPort> = Mutex::new(SerialPort::new(COM2_PORT));
+
+/// if set, text macros also write to [`COM1`]
+static TEE: AtomicBool = AtomicBool::new(false);
+
+
+/// enables/disables copying of `print!`, `eprint!`, `dbg!` (and panic) output to [`COM1`]
+#[inline]
+pub fn set_tee(enabled: bool) {
+    TEE.store(enabled, Ordering::Relaxed);
+}
+
+#[inline]
+pub fn tee_enabled() -> bool {
+    TEE.load(Ordering::Relaxed)
+}
+
+/// used by text macros
+#[doc(hidden)]
+pub fn _tee(args: core::fmt::Arguments) {
+    if tee_enabled() {
+        _print(args);
+    }
+}
+
+/// used by `serial_print!`
+#[doc(hidden)]
+pub fn _print(args: core::fmt::Arguments) {
+    use core::fmt::Write;
+    let _ = COM1.lock().write_fmt(args);
+}
+
+
+/// initializes [`COM1`] with `config`
+#[inline]
+pub(crate) fn init(config: LineConfig) -> Result<(), ()> {
+    COM1.lock().init(config)
+}
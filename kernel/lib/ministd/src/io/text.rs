@@ -5,100 +5,83 @@
 
 pub use core::fmt::write;
 
-/// formats and renders stuff onto the screen
+/// formats and renders stuff onto the console of the running thread (see [`crate::thread::set_console()`])
 #[macro_export]
 macro_rules! print {
-    ($($arg:tt)*) => {{
-        use core::fmt::Write;
-        let _ = write!(*$crate::renderer::RENDERER.lock(), $($arg)*);
-    }};
+    ($($arg:tt)*) => {
+        $crate::io::text::_print(format_args!($($arg)*))
+    };
 }
 
 #[macro_export]
 macro_rules! println {
-    ($($arg:tt)*) => {{
-        use core::fmt::Write;
-        _ = writeln!(*$crate::renderer::RENDERER.lock(), $($arg)*);
-    }};
+    () => {
+        $crate::print!("\n")
+    };
+    ($($arg:tt)*) => {
+        $crate::io::text::_print(format_args!("{}\n", format_args!($($arg)*)))
+    };
 }
 
 /// uses lock renderer to print to screen
 #[macro_export]
 macro_rules! locked_print {
-    ($guard:expr, $($arg:tt)*) => {{
-        use core::fmt::Write;
-        _ = write!($guard, $($arg)*);
-    }};
+    ($guard:expr, $($arg:tt)*) => {
+        $crate::io::text::_locked_print(&mut *$guard, format_args!($($arg)*))
+    };
 }
 
 #[macro_export]
 macro_rules! locked_println {
-    ($guard:expr, $($arg:tt)*) => {{
-        use core::fmt::Write;
-        let _ = writeln!($guard, $($arg)*);
-    }};
+    ($guard:expr, $($arg:tt)*) => {
+        $crate::io::text::_locked_print(&mut *$guard, format_args!("{}\n", format_args!($($arg)*)))
+    };
 }
 
 
-#[macro_export]
-macro_rules! eprint {
-    ($($arg:tt)*) => {{
-        use core::fmt::Write;
-        let mut rend = $crate::renderer::RENDERER.lock();
-
-        let c = rend.color();
+/// used by `print!` and `println!`
+#[doc(hidden)]
+pub fn _print(args: core::fmt::Arguments) {
+    use core::fmt::Write;
+    let _ = crate::renderer::console::output().lock().write_fmt(args);
+    crate::io::serial::_tee(args);
+}
 
-        rend.set_color(0xff9a9a);
+/// used by `locked_print!` and `locked_println!`
+#[doc(hidden)]
+pub fn _locked_print(out: &mut impl core::fmt::Write, args: core::fmt::Arguments) {
+    let _ = out.write_fmt(args);
+    crate::io::serial::_tee(args);
+}
 
-        let _ = write!(*rend, $($arg)*);
 
-        rend.set_color(c.as_int());
-    }};
+/// same as `print!`, but in the error color (see [`crate::renderer::ansi::ERROR`])
+#[macro_export]
+macro_rules! eprint {
+    ($($arg:tt)*) => {
+        $crate::print!("{}{}{}", $crate::renderer::ansi::ERROR, format_args!($($arg)*), $crate::renderer::ansi::DEFAULT_FG)
+    };
 }
 
 #[macro_export]
 macro_rules! eprintln {
-    ($($arg:tt)*) => {{
-        use core::fmt::Write;
-        let mut rend = $crate::renderer::RENDERER.lock();
-
-        let c = rend.color();
-
-        rend.set_color(0xff9a9a);
-
-        let _ = writeln!(*rend, $($arg)*);
-        
-        rend.set_color(c.as_int());
-    }};
+    ($($arg:tt)*) => {
+        $crate::println!("{}{}{}", $crate::renderer::ansi::ERROR, format_args!($($arg)*), $crate::renderer::ansi::DEFAULT_FG)
+    };
 }
 
 #[macro_export]
 macro_rules! locked_eprint {
-    ($guard:expr, $($arg:tt)*) => {{
-        use core::fmt::Write;
-
-        let c = $guard.color();
-
-        $guard.set_color(0xff9a9a);
-
-        let _ = write!(*$guard, $($arg)*);
-
-        $guard.set_color(c.as_int());
-    }};
+    ($guard:expr, $($arg:tt)*) => {
+        $crate::locked_print!($guard, "{}{}{}", $crate::renderer::ansi::ERROR, format_args!($($arg)*), $crate::renderer::ansi::DEFAULT_FG)
+    };
 }
 
 #[macro_export]
 macro_rules! locked_eprintln {
-    ($guard:expr, $($arg:tt)*) => {{
-        use core::fmt::Write;
-        let c = $guard.color();
-
-        $guard.set_color(0xff9a9a);
-
-        let _ = writeln!(*$guard, $($arg)*);
-        
-        $guard.set_color(c.as_int());
-    }};
+    ($guard:expr, $($arg:tt)*) => {
+        $crate::locked_println!($guard, "{}{}{}", $crate::renderer::ansi::ERROR, format_args!($($arg)*), $crate::renderer::ansi::DEFAULT_FG)
+    };
 }
 
 
@@ -109,16 +92,10 @@ macro_rules! dbg {
         $crate::eprintln!("[{}:{}:{}]", core::file!(), core::line!(), core::column!());
     };
     ($val:expr $(,)?) => {{
-
-        let mut rend = $crate::renderer::RENDERER.lock();
-        let color = rend.color();
-        rend.set_color(0xff9a9a);
-
         let value = &$val;
 
-        $crate::locked_eprint!(rend, "[{}:{}:{}] {} = {:#?}", core::file!(), core::line!(), core::column!(), core::stringify!($val),
+        $crate::eprint!("[{}:{}:{}] {} = {:#?}", core::file!(), core::line!(), core::column!(), core::stringify!($val),
         &&value as &dyn core::fmt::Debug);
-        rend.set_color(color.as_int());
     }};
 }
 
@@ -129,13 +106,28 @@ macro_rules! locked_dbg {
         $crate::locked_eprintln!($guard, "[{}:{}:{}]", core::file!(), core::line!(), core::column!());
     };
     ($guard:expr, $val:expr $(,)?) => {{
-        let color = $guard.color();
-        $guard.set_color(0xff9a9a);
-
         let value = &$val;
 
         $crate::locked_eprint!($guard, "[{}:{}:{}] {} = {:#?}", core::file!(), core::line!(), core::column!(), core::stringify!($val),
         &&value as &dyn core::fmt::Debug);
-        $guard.set_color(color.as_int());
     }};
-}
\ No newline at end of file
+}
+
+
+/// formats and sends stuff to the first serial port
+#[macro_export]
+macro_rules! serial_print {
+    ($($arg:tt)*) => {
+        $crate::io::serial::_print(format_args!($($arg)*))
+    };
+}
+
+#[macro_export]
+macro_rules! serial_println {
+    () => {
+        $crate::serial_print!("\n")
+    };
+    ($($arg:tt)*) => {
+        $crate::io::serial::_print(format_args!("{}\n", format_args!($($arg)*)))
+    };
+}
@@ -2,8 +2,9 @@
 //	this file originally belonged to baseOS project
 //		an OS template on which to build
 
-#![no_std]
-#![no_main]
+//  host tests (`cargo test`) are built with std
+#![cfg_attr(not(test), no_std)]
+#![cfg_attr(not(test), no_main)]
 //#![deny(static_mut_refs)]
 
 
@@ -25,12 +26,21 @@ pub mod renderer;
 pub mod io;
 pub mod convert;
 pub mod init;
+pub mod arch;
+pub mod acpi;
+pub mod time;
+pub mod thread;
+pub mod smp;
+pub mod shell;
 
 //  modules
 pub use mem::string::String;
 pub use mem::boxed::Box;
 pub use mem::vec::Vec;
+pub use mem::vec_deque::VecDeque;
+pub use mem::binary_heap::BinaryHeap;
 pub use mem::array::Array;
+pub use mem::arc::{Arc, Weak};
 pub use mem::alloc::{self, ALLOCATOR, Allocator};
 
 //  local crates
@@ -38,14 +48,16 @@ pub use bootloader;
 pub use limine_rs as limine;
 pub use buddy_system_allocator as allocator;
 pub use spin;
-pub use hashbrown;
 
 
 pub use spin::{Mutex, MutexGuard,
     RwLock, RwLockReadGuard, RwLockWriteGuard, RwLockUpgradableGuard,
     Lazy, Barrier, Once};
 
-pub use hashbrown::{HashMap, HashSet, HashTable};
+pub use mem::hash_map::HashMap;
+pub use mem::hash_set::HashSet;
+pub use mem::btree_map::BTreeMap;
+pub use mem::btree_set::BTreeSet;
 
 use core::arch::asm;
 use core::hint::spin_loop;
@@ -27,16 +27,83 @@ use crate::Immutable;
 
 pub type LockedHeap = allocator::LockedHeap<32>;
 pub type Heap = allocator::Heap<32>;
+
+/// maximum number of memory regions the heap can consist of
+pub const MAX_HEAP_REGIONS: usize = 64;
+
+
+/// List of memory regions given to the heap
+/// - fixed capacity, because it can not allocate from the heap it describes
+#[derive(Copy, Clone)]
+pub struct HeapRegions {
+    list: [Region; MAX_HEAP_REGIONS],
+    len: usize,
+}
+
+impl HeapRegions {
+    pub const fn new() -> Self {
+        Self {
+            list: [Region::empty(); MAX_HEAP_REGIONS],
+            len: 0,
+        }
+    }
+
+    #[inline(always)] pub fn len(&self) -> usize { self.len }
+    #[inline(always)] pub fn is_empty(&self) -> bool { self.len == 0 }
+    #[inline(always)] pub fn is_full(&self) -> bool { self.len == MAX_HEAP_REGIONS }
+
+    #[inline(always)]
+    pub fn as_slice(&self) -> &[Region] {
+        &self.list[..self.len]
+    }
+
+    #[inline(always)]
+    pub fn iter(&self) -> core::slice::Iter<'_, Region> {
+        self.as_slice().iter()
+    }
+
+    /// checks if `region` overlaps any region in the list
+    pub fn overlaps(&self, region: &Region) -> bool {
+        self.iter().any(|r| r.overlaps(region))
+    }
+
+    /// returns the sum of sizes of all regions
+    pub fn total_size(&self) -> usize {
+        self.iter().map(|r| r.size()).sum()
+    }
+
+    /// appends `region`
+    /// - returns `Err` if the list is full, the region is empty or overlaps another one
+    pub fn push(&mut self, region: Region) -> Result<(), ()> {
+        if self.is_full() || region.size() == 0 || self.overlaps(&region) {
+            return Err(());
+        }
+        self.list[self.len] = region;
+        self.len += 1;
+        Ok(())
+    }
+}
+
+impl<'a> IntoIterator for &'a HeapRegions {
+    type Item = &'a Region;
+    type IntoIter = core::slice::Iter<'a, Region>;
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+
 pub struct Allocator {
     alloc: LockedHeap,
-    regions: Mutex<Region>,     //  TODO: use vector
+    regions: Mutex<HeapRegions>,
 }
 
 impl Allocator {
     pub(crate) const fn new() -> Self {
         Self {
             alloc: allocator::LockedHeap::new(),
-            regions: Mutex::new(Region::empty()),
+            regions: Mutex::new(HeapRegions::new()),
         }
     }
 
@@ -46,9 +113,9 @@ impl Allocator {
     pub unsafe fn allocate<T: Sized>(&self, val: T) -> Result<NonNull<T>, ()> {
         let layout = Layout::new::<T>();
 
-        if let Ok(d) = self.alloc.lock().alloc(layout) {
+        if let Ok(d) = self.alloc_or_grow(layout) {
             let data = unsafe { NonNull::new_unchecked(d.as_ptr() as *mut T) };
-            unsafe { *data.as_ptr() = val };
+            unsafe { data.as_ptr().write(val) };
             
             Ok(data)
         } else {
@@ -61,7 +128,7 @@ impl Allocator {
     #[inline]
     pub unsafe fn allocate_uninit<T: Sized>(&self) -> Result<NonNull<MaybeUninit<T>>, ()> {
         let layout = unsafe { Layout::from_size_align_unchecked(size_of::<T>(), align_of::<T>()) };
-        if let Ok(d) = self.alloc.lock().alloc(layout) {
+        if let Ok(d) = self.alloc_or_grow(layout) {
             let data = unsafe { NonNull::new_unchecked(d.as_ptr() as *mut MaybeUninit<T>) };
             Ok(data)
         } else {
@@ -86,13 +153,13 @@ impl Allocator {
 
     /// gets immutable reference to regions
     #[inline]
-    pub fn get_regions(&self) -> Immutable<MutexGuard<Region>> {
+    pub fn get_regions(&self) -> Immutable<MutexGuard<HeapRegions>> {
         Immutable::new(self.regions.lock())
     }
     
     /// try to obtain regions
     #[inline]
-    pub fn try_get_regions(&self) -> Option<Immutable<MutexGuard<Region>>> {
+    pub fn try_get_regions(&self) -> Option<Immutable<MutexGuard<HeapRegions>>> {
         if let Some(guard) = self.regions.try_lock() {
             Some(Immutable::new(guard))
         } else {
@@ -100,18 +167,49 @@ impl Allocator {
         }
     }
 
+    /// returns copy of the region list
+    #[inline]
+    pub fn regions(&self) -> HeapRegions {
+        *self.regions.lock()
+    }
+
+    /// adds `region` to the heap and to the region list
+    /// - returns `Err` if the region overlaps memory already in the heap or the list is full
+    #[inline]
+    pub unsafe fn add_region(&self, region: Region) -> Result<(), ()> {
+        let mut heap = self.alloc.lock();
+        unsafe { self.add_region_locked(&mut heap, region) }
+    }
+
+    /// same as [`Allocator::add_region`] but uses already locked heap
+    pub unsafe fn add_region_locked(&self, guard: &mut MutexGuard<Heap>, region: Region) -> Result<(), ()> {
+        self.regions.lock().push(region)?;
+        unsafe { guard.add_to_heap(region.start(), region.end()) };
+        Ok(())
+    }
+
     /// add range of addresses to heap  
-    /// also pushes into the regions vector
+    /// also pushes into the regions list
     #[inline(always)]
-    pub unsafe fn add_to_heap(&self, start: usize, end: usize) {
-        //  once using vector for regions: push
-        unsafe { self.alloc.lock().add_to_heap(start, end) };
+    pub unsafe fn add_to_heap(&self, start: usize, end: usize) -> Result<(), ()> {
+        unsafe { self.add_region(Region::new(start, end.checked_sub(start).ok_or(())?)) }
     }
 
     #[inline(always)]
-    pub unsafe fn add_to_heap_locked(&self, guard: &mut MutexGuard<Heap>, start: usize, end: usize) {
-        //  push into vector
-        unsafe { guard.add_to_heap(start, end) };
+    pub unsafe fn add_to_heap_locked(&self, guard: &mut MutexGuard<Heap>, start: usize, end: usize) -> Result<(), ()> {
+        unsafe { self.add_region_locked(guard, Region::new(start, end.checked_sub(start).ok_or(())?)) }
+    }
+
+    /// allocates `layout`, growing the heap through the `out_of_memory_handler` if needed
+    /// - the heap is unlocked while the handler runs (it may allocate itself, e.g. frames)
+    fn alloc_or_grow(&self, layout: Layout) -> Result<NonNull<u8>, ()> {
+        if let Ok(data) = self.alloc.lock().alloc(layout) {
+            return Ok(data);
+        }
+
+        //  run out_of_memory routine and try again
+        unsafe { out_of_memory_handler(self, layout) }?;
+        self.alloc.lock().alloc(layout)
     }
 
     /// returns the actual number of bytes in the heap
@@ -128,15 +226,14 @@ impl Allocator {
 
     /// reallocates memory to an new layout
     pub unsafe fn realloc_layout(&self, old: *mut u8, old_l: Layout, new_l: Layout) -> *mut u8 {
-        let new = unsafe { self.alloc.alloc(new_l) };
-
-        if new.is_null() {
-            return core::ptr::null_mut();
-        }
+        let new = match self.alloc_or_grow(new_l) {
+            Ok(data) => data.as_ptr(),
+            Err(_) => return null_mut(),
+        };
 
         unsafe {
             core::ptr::copy_nonoverlapping(old, new, core::cmp::min(old_l.size(), new_l.size()));
-            ALLOCATOR.dealloc(old, old_l);
+            self.dealloc(old, old_l);
         }
 
         new
@@ -155,51 +252,20 @@ unsafe impl GlobalAlloc for Allocator {
     ///   - success: try allocation again
     ///   - failure: returns null
     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
-
-        crate::println!("ALLOC");
-
-        match self.alloc.lock().alloc(layout) {
+        match self.alloc_or_grow(layout) {
             Ok(data) => data.as_ptr(),
-            Err(_) => {
-                //  run out_of_memory routine and try again
-                
-                let mut alloc = self.alloc.lock();
-                if let Ok(_) = unsafe { out_of_memory_handler(&mut alloc, &self) }{
-                    match alloc.alloc(layout) {
-                        Ok(data) => data.as_ptr(),
-                        Err(_) => null_mut(),
-                    }
-                } else {
-                    null_mut()
-                }
-
-            },
+            Err(_) => null_mut(),
         }
     }
 
     /// same as `alloc` but zeroes the allocated buffer
     unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
-        match self.alloc.lock().alloc(layout) {
+        match self.alloc_or_grow(layout) {
             Ok(d) => {
-                unsafe { core::ptr::write_bytes(d.as_ptr() as *mut usize, 0, layout.size()/size_of::<usize>()) };
+                unsafe { core::ptr::write_bytes(d.as_ptr(), 0, layout.size()) };
                 d.as_ptr()
             },
-            Err(_) => {
-                //  run out_of_memory routine and try again
-                
-                let mut alloc = self.alloc.lock();
-                if let Ok(_) = unsafe { out_of_memory_handler(&mut alloc, &self) } {
-                    match alloc.alloc(layout) {
-                        Ok(d) => {
-                            unsafe { core::ptr::write_bytes(d.as_ptr() as *mut usize, 0, layout.size()/size_of::<usize>()) };
-                            d.as_ptr()
-                        }
-                        Err(_) => null_mut(),
-                    }
-                } else {
-                    null_mut()
-                }
-            },
+            Err(_) => null_mut(),
         }
     }
 
@@ -213,7 +279,7 @@ unsafe impl GlobalAlloc for Allocator {
     /// 
     /// used layout: `Layout::from_size_unchecked(new_size, layout.align())`
     unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
-        let new = match self.alloc.lock().alloc(unsafe { Layout::from_size_align_unchecked(new_size, layout.align()) } ) {
+        let new = match self.alloc_or_grow(unsafe { Layout::from_size_align_unchecked(new_size, layout.align()) } ) {
             Ok(data) => data.as_ptr(),
             Err(_) => return null_mut(),
         };
@@ -229,10 +295,9 @@ unsafe impl GlobalAlloc for Allocator {
 }
 
 
-#[global_allocator]
+//  host tests keep the allocator of std as the global one
+#[cfg_attr(not(test), global_allocator)]
 pub static ALLOCATOR: Allocator = Allocator::new();
-pub static REGIONS: Mutex<Region> = Mutex::new(Region::empty());
-    // use Vec later
 
 
 unsafe extern "Rust" {
@@ -240,30 +305,121 @@ unsafe extern "Rust" {
     //  functions defined by the developer in the main crate
 
      pub(crate) fn find_heap_region() -> Result<Region, ()>;
-     pub(crate) fn out_of_memory_handler(heap: &mut MutexGuard<Heap>, allocator: &Allocator) -> Result<(), ()>;
+     pub(crate) fn out_of_memory_handler(allocator: &Allocator, layout: Layout) -> Result<(), ()>;
 }
 
 
 
-//  TODO: use mutex<Vec<Region>> for heap mapping
-
-
-
 /// `init` initializes heap  
 /// **IMPORTANT**
 /// - this function uses the [`mem::find_heap_region`] function from the main crate
 ///   - rewrite this function to change the default behaviour
 /// 
-/// You can check where the heap is with the [`ministd::mem::heap::REGION`] variable
-/// - please do not change it
+/// You can check where the heap is with [`Allocator::get_regions`]
 pub(crate) fn init() -> Result<(), ()> {
 
     if let Ok(reg) = unsafe { find_heap_region() } {
-        *REGIONS.lock() = reg;
-        let mut alloc = ALLOCATOR.alloc.lock();
-        unsafe { alloc.init(reg.start, reg.size); }
-        return Ok(());
+        return unsafe { ALLOCATOR.add_region(reg) };
     }
     Err(())
 
-}
\ No newline at end of file
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mem::{KB, MB};
+
+    /// host tests grow the heap with memory from the allocator of std
+    #[unsafe(no_mangle)]
+    extern "Rust" fn out_of_memory_handler(allocator: &Allocator, layout: Layout) -> Result<(), ()> {
+        let size = layout.size().max(layout.align()).next_power_of_two().max(4 * MB);
+        let memory = unsafe { std::alloc::alloc(Layout::from_size_align(size, size).map_err(|_| ())?) };
+        if memory.is_null() {
+            return Err(());
+        }
+        unsafe { allocator.add_region(Region::new(memory as usize, size)) }
+    }
+
+
+    const BUFFER_SIZE: usize = 64 * KB;
+
+    #[repr(C, align(4096))]
+    struct Buffer([u8; BUFFER_SIZE]);
+
+    /// heap over its own static buffer
+    fn heap(buffer: *mut Buffer) -> (Allocator, Region) {
+        let heap = Allocator::new();
+        let region = Region::new(buffer as usize, BUFFER_SIZE);
+        unsafe { heap.add_region(region) }.unwrap();
+        (heap, region)
+    }
+
+    #[test]
+    fn alloc_and_dealloc() {
+        static mut BUFFER: Buffer = Buffer([0; BUFFER_SIZE]);
+        let (heap, region) = heap(&raw mut BUFFER);
+        assert_eq!(heap.total_bytes(), BUFFER_SIZE);
+
+        let layouts = [Layout::new::<u8>(), Layout::new::<[u64; 5]>(), Layout::from_size_align(100, 64).unwrap()];
+        let ptrs = layouts.map(|l| {
+            let ptr = unsafe { heap.alloc(l) };
+            assert!(!ptr.is_null());
+            assert_eq!(ptr as usize % l.align(), 0);
+            assert!(region.start() <= ptr as usize && ptr as usize + l.size() <= region.end());
+            ptr
+        });
+        assert!(heap.allocated_bytes() >= layouts.iter().map(|l| l.size()).sum());
+
+        for (ptr, layout) in ptrs.into_iter().zip(layouts) {
+            unsafe { heap.dealloc(ptr, layout) };
+        }
+        assert_eq!(heap.allocated_bytes(), 0);
+    }
+
+    #[test]
+    fn realloc() {
+        static mut BUFFER: Buffer = Buffer([0; BUFFER_SIZE]);
+        let (heap, _) = heap(&raw mut BUFFER);
+
+        let layout = Layout::new::<[u8; 16]>();
+        let old = unsafe { heap.alloc_zeroed(layout) };
+        unsafe { *old.add(3) = 7 };
+
+        let new = unsafe { heap.realloc(old, layout, 256) };
+        assert!(!new.is_null());
+        assert_eq!(unsafe { *new.add(3) }, 7);
+        assert_eq!(unsafe { *new.add(4) }, 0);
+
+        let moved = unsafe { heap.realloc_layout(new, Layout::from_size_align(256, 1).unwrap(), Layout::new::<[u8; 8]>()) };
+        assert_eq!(unsafe { *moved.add(3) }, 7);
+        unsafe { heap.dealloc(moved, Layout::new::<[u8; 8]>()) };
+        assert_eq!(heap.allocated_bytes(), 0);
+    }
+
+    #[test]
+    fn regions() {
+        static mut BUFFER: Buffer = Buffer([0; BUFFER_SIZE]);
+        let (heap, region) = heap(&raw mut BUFFER);
+
+        let inner = Region::new(region.start() + KB, KB);
+        assert!(unsafe { heap.add_region(inner) }.is_err());
+        assert!(unsafe { heap.add_to_heap(region.end(), region.start()) }.is_err());
+        assert_eq!(heap.regions().len(), 1);
+        assert_eq!(heap.total_bytes(), BUFFER_SIZE);
+    }
+
+    #[test]
+    fn grows_when_full() {
+        static mut BUFFER: Buffer = Buffer([0; BUFFER_SIZE]);
+        let (heap, region) = heap(&raw mut BUFFER);
+
+        let layout = Layout::from_size_align(2 * BUFFER_SIZE, 8).unwrap();
+        let ptr = unsafe { heap.alloc(layout) };
+        assert!(!ptr.is_null());
+        assert!(ptr as usize >= region.end() || (ptr as usize) < region.start());
+        assert_eq!(heap.regions().len(), 2);
+        unsafe { heap.dealloc(ptr, layout) };
+    }
+}
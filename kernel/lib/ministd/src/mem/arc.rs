@@ -0,0 +1,596 @@
+//	mem/arc.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  atomically reference counting pointers `Arc<T>` and `Weak<T>`
+//  - same layout and rules as [`crate::mem::rc`], but the counters are atomic so the pointers can cross cores
+//  - increments are relaxed, the last decrement synchronizes (release + acquire fence) with all previous ones
+//  - the weak count is locked (`usize::MAX`) while uniqueness is checked, so `get_mut` can not race with `downgrade`
+//  - use `Arc<Mutex<T>>` to mutate shared state, [`Arc::with`] keeps interrupt handlers out while the lock is held
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::fmt::{Debug, Display};
+use core::hint::spin_loop;
+use core::mem::ManuallyDrop;
+use core::ops::Deref;
+use core::ptr::{self, NonNull};
+use core::sync::atomic::{self, AtomicUsize, Ordering};
+
+use spin::Mutex;
+
+use crate::{TryClone, Nothing};
+use crate::mem::alloc::ALLOCATOR;
+
+
+/// counts above this are treated as a leak
+const MAX_REFCOUNT: usize = isize::MAX as usize;
+/// weak count while it is locked by `is_unique`
+const LOCKED: usize = usize::MAX;
+
+
+struct ArcInner<T> {
+    strong: AtomicUsize,
+    /// number of `Weak` pointers + 1 while there is any `Arc`
+    weak: AtomicUsize,
+    value: ManuallyDrop<T>,
+}
+
+impl<T> ArcInner<T> {
+    const fn layout() -> Layout {
+        Layout::new::<ArcInner<T>>()
+    }
+
+    /// allocates inner with strong and weak count 1
+    /// - gives `value` back if allocation fails
+    fn try_allocate(value: T) -> Result<NonNull<ArcInner<T>>, T> {
+        let data = unsafe { ALLOCATOR.alloc(Self::layout()) } as *mut ArcInner<T>;
+        let Some(data) = NonNull::new(data) else { return Err(value) };
+        unsafe {
+            data.as_ptr().write(ArcInner {
+                strong: AtomicUsize::new(1),
+                weak: AtomicUsize::new(1),
+                value: ManuallyDrop::new(value),
+            });
+        }
+        Ok(data)
+    }
+
+    /// decrements the weak count and frees the inner if it was the last reference
+    /// - `this` must not be used afterwards
+    unsafe fn release_weak(this: NonNull<ArcInner<T>>) {
+        if unsafe { this.as_ref() }.weak.fetch_sub(1, Ordering::Release) == 1 {
+            atomic::fence(Ordering::Acquire);
+            unsafe { ALLOCATOR.dealloc(this.as_ptr() as *mut u8, Self::layout()) };
+        }
+    }
+}
+
+
+/// `Arc` shares ownership of a heap allocated value between threads, cores and interrupt handlers
+/// - cloning only increments the reference count
+/// - the value can not be mutated unless it is unique (see [`Arc::get_mut`] and [`Arc::make_mut`]), use a lock otherwise
+pub struct Arc<T> {
+    ptr: NonNull<ArcInner<T>>,
+}
+
+unsafe impl<T: Send + Sync> Send for Arc<T> {}
+unsafe impl<T: Send + Sync> Sync for Arc<T> {}
+
+impl<T> Arc<T> {
+
+    /// Allocates `value` on heap
+    /// - panics if allocation fails
+    pub fn new(value: T) -> Self {
+        match Self::try_new(value) {
+            Ok(arc) => arc,
+            Err(_) => panic!("failed to allocate memory for Arc"),
+        }
+    }
+
+    /// Tries to allocate `value` on heap
+    /// - returns `Err` if allocation fails
+    pub fn try_new(value: T) -> Result<Self, ()> {
+        ArcInner::try_allocate(value)
+            .map(|ptr| Self { ptr })
+            .map_err(|_| ())
+    }
+
+    #[inline(always)]
+    fn inner(&self) -> &ArcInner<T> {
+        unsafe { self.ptr.as_ref() }
+    }
+
+    /// returns pointer to the value
+    /// - no reference to the whole `ArcInner` is made, other pointers may be using the counts
+    #[inline(always)]
+    fn value_ptr(&self) -> *mut T {
+        unsafe { (&raw mut (*self.ptr.as_ptr()).value).cast::<T>() }
+    }
+
+    /// returns the number of `Arc` pointers to the value
+    /// - other threads may change it right away
+    #[inline]
+    pub fn strong_count(this: &Self) -> usize {
+        this.inner().strong.load(Ordering::Acquire)
+    }
+
+    /// returns the number of `Weak` pointers to the value
+    /// - other threads may change it right away
+    #[inline]
+    pub fn weak_count(this: &Self) -> usize {
+        match this.inner().weak.load(Ordering::Acquire) {
+            //  locked by `is_unique`, which only succeeds if there is no `Weak`
+            LOCKED => 0,
+            weak => weak - 1,
+        }
+    }
+
+    /// checks if both point to the same allocation
+    #[inline]
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        this.ptr == other.ptr
+    }
+
+    /// returns pointer to the value
+    #[inline]
+    pub fn as_ptr(this: &Self) -> *const T {
+        &*this.inner().value as *const T
+    }
+
+    /// creates `Weak` pointer to the value
+    pub fn downgrade(this: &Self) -> Weak<T> {
+        let weak = &this.inner().weak;
+        let mut current = weak.load(Ordering::Relaxed);
+        loop {
+            if current == LOCKED {
+                spin_loop();
+                current = weak.load(Ordering::Relaxed);
+                continue;
+            }
+            assert!(current <= MAX_REFCOUNT, "Arc weak count overflow");
+            match weak.compare_exchange_weak(current, current + 1, Ordering::Acquire, Ordering::Relaxed) {
+                Ok(_) => return Weak { ptr: Some(this.ptr) },
+                Err(old) => current = old,
+            }
+        }
+    }
+
+    /// checks if there is no other `Arc` or `Weak` pointer
+    fn is_unique(this: &Self) -> bool {
+        let inner = this.inner();
+        //  no `Weak` can be created while the weak count is locked
+        if inner.weak.compare_exchange(1, LOCKED, Ordering::Acquire, Ordering::Relaxed).is_err() {
+            return false;
+        }
+        let unique = inner.strong.load(Ordering::Acquire) == 1;
+        inner.weak.store(1, Ordering::Release);
+        unique
+    }
+
+    /// returns mutable reference if there is no other `Arc` or `Weak` pointer
+    pub fn get_mut(this: &mut Self) -> Option<&mut T> {
+        if Self::is_unique(this) {
+            Some(unsafe { &mut *this.value_ptr() })
+        } else {
+            None
+        }
+    }
+
+    /// returns the value if this is the only `Arc`, otherwise gives the `Arc` back
+    /// - `Weak` pointers can not upgrade afterwards
+    pub fn try_unwrap(this: Self) -> Result<T, Self> {
+        if this.inner().strong.compare_exchange(1, 0, Ordering::Relaxed, Ordering::Relaxed).is_err() {
+            return Err(this);
+        }
+        atomic::fence(Ordering::Acquire);
+
+        let this = ManuallyDrop::new(this);
+        let value = unsafe { ptr::read(&*this.inner().value) };
+        unsafe { ArcInner::release_weak(this.ptr) };
+        Ok(value)
+    }
+
+    /// gives exclusive access to the value
+    /// - `None` if another `Arc` exists, the value is then only borrowed to clone it
+    /// - `Some(Err)` if only `Weak` pointers are left and the value could not be moved out of their reach
+    fn make_unique(this: &mut Self) -> Option<Result<(), ()>> {
+        let inner = this.inner();
+        //  the strong count is 0 meanwhile, so `upgrade` fails
+        if inner.strong.compare_exchange(1, 0, Ordering::Acquire, Ordering::Relaxed).is_err() {
+            return None;
+        }
+
+        if inner.weak.load(Ordering::Relaxed) == 1 {
+            inner.strong.store(1, Ordering::Release);
+            return Some(Ok(()));
+        }
+
+        //  `Weak` pointers are left, move the value to a new allocation
+        let value = unsafe { ptr::read(&*inner.value) };
+        match ArcInner::try_allocate(value) {
+            Ok(fresh) => {
+                let old = core::mem::replace(&mut this.ptr, fresh);
+                unsafe { ArcInner::release_weak(old) };
+                Some(Ok(()))
+            }
+            Err(value) => {
+                core::mem::forget(value);
+                inner.strong.store(1, Ordering::Release);
+                Some(Err(()))
+            }
+        }
+    }
+}
+
+impl<T: Clone> Arc<T> {
+
+    /// returns mutable reference, the value is cloned first if it is shared
+    /// - `Weak` pointers lose the value if this is the only `Arc`
+    /// - panics if allocation fails
+    pub fn make_mut(this: &mut Self) -> &mut T {
+        match Self::make_unique(this) {
+            Some(Ok(())) => {}
+            None => *this = Self::new((**this).clone()),
+            Some(Err(())) => panic!("failed to allocate memory for Arc"),
+        }
+        unsafe { &mut *this.value_ptr() }
+    }
+}
+
+impl<T: TryClone> Arc<T>
+where T::Error: Default {
+
+    /// same as [`Arc::make_mut`], but fails instead of panicking
+    /// - returns `Err` if cloning or allocation fails
+    pub fn try_make_mut(this: &mut Self) -> Result<&mut T, T::Error> {
+        match Self::make_unique(this) {
+            Some(Ok(())) => {}
+            None => {
+                let value = (**this).try_clone()?;
+                *this = Self::try_new(value).map_err(|_| T::Error::default())?;
+            }
+            Some(Err(())) => return Err(T::Error::default()),
+        }
+        Ok(unsafe { &mut *this.value_ptr() })
+    }
+}
+
+impl<T> Arc<Mutex<T>> {
+
+    /// Allocates `value` behind a lock
+    /// - panics if allocation fails
+    #[inline]
+    pub fn new_mutex(value: T) -> Self {
+        Self::new(Mutex::new(value))
+    }
+
+    /// Tries to allocate `value` behind a lock
+    /// - returns `Err` if allocation fails
+    #[inline]
+    pub fn try_new_mutex(value: T) -> Result<Self, ()> {
+        Self::try_new(Mutex::new(value))
+    }
+
+    /// runs `f` with the value locked and interrupts disabled
+    /// - use it if an interrupt handler takes the lock too (see [`Arc::try_with`])
+    pub fn with<R, F: FnOnce(&mut T) -> R>(&self, f: F) -> R {
+        crate::io::int::without_interrupts(|| f(&mut self.lock()))
+    }
+
+    /// runs `f` if the lock is free
+    /// - meant for interrupt handlers, which must not wait for the code they interrupted
+    /// - returns `None` if the lock is held
+    pub fn try_with<R, F: FnOnce(&mut T) -> R>(&self, f: F) -> Option<R> {
+        self.try_lock().map(|mut guard| f(&mut guard))
+    }
+}
+
+impl<T> Drop for Arc<T> {
+    fn drop(&mut self) {
+        if self.inner().strong.fetch_sub(1, Ordering::Release) != 1 {
+            return;
+        }
+        //  all uses of the value by other `Arc`s happen before it is dropped
+        atomic::fence(Ordering::Acquire);
+        unsafe {
+            ptr::drop_in_place(self.value_ptr());
+            ArcInner::release_weak(self.ptr);
+        }
+    }
+}
+
+impl<T> Clone for Arc<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        let old = self.inner().strong.fetch_add(1, Ordering::Relaxed);
+        assert!(old <= MAX_REFCOUNT, "Arc strong count overflow");
+        Self { ptr: self.ptr }
+    }
+}
+
+impl<T> TryClone for Arc<T> {
+    type Error = Nothing;
+
+    /// never fails, only the reference count changes
+    #[inline]
+    fn try_clone(&self) -> Result<Self, Self::Error> {
+        Ok(self.clone())
+    }
+}
+
+impl<T> Deref for Arc<T> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        &self.inner().value
+    }
+}
+
+impl<T> AsRef<T> for Arc<T> {
+    fn as_ref(&self) -> &T {
+        &self.inner().value
+    }
+}
+
+impl<T: Display> Display for Arc<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(&**self, f)
+    }
+}
+
+impl<T: Debug> Debug for Arc<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+
+/// `Weak` points to the value of an [`Arc`] without keeping it alive
+/// - use [`Weak::upgrade`] to access the value
+pub struct Weak<T> {
+    /// `None` for [`Weak::new`]
+    ptr: Option<NonNull<ArcInner<T>>>,
+}
+
+unsafe impl<T: Send + Sync> Send for Weak<T> {}
+unsafe impl<T: Send + Sync> Sync for Weak<T> {}
+
+impl<T> Weak<T> {
+
+    /// creates `Weak` pointer without value (nothing is allocated)
+    pub const fn new() -> Self {
+        Self { ptr: None }
+    }
+
+    #[inline(always)]
+    fn inner(&self) -> Option<&ArcInner<T>> {
+        self.ptr.map(|ptr| unsafe { &*ptr.as_ptr() })
+    }
+
+    /// returns `Arc` to the value
+    /// - returns `None` if the value was dropped already
+    pub fn upgrade(&self) -> Option<Arc<T>> {
+        let strong = &self.inner()?.strong;
+        let mut current = strong.load(Ordering::Relaxed);
+        loop {
+            if current == 0 {
+                return None;
+            }
+            assert!(current <= MAX_REFCOUNT, "Arc strong count overflow");
+            match strong.compare_exchange_weak(current, current + 1, Ordering::Acquire, Ordering::Relaxed) {
+                Ok(_) => return self.ptr.map(|ptr| Arc { ptr }),
+                Err(old) => current = old,
+            }
+        }
+    }
+
+    /// returns the number of `Arc` pointers to the value
+    #[inline]
+    pub fn strong_count(&self) -> usize {
+        self.inner().map_or(0, |i| i.strong.load(Ordering::Acquire))
+    }
+
+    /// returns the number of `Weak` pointers to the value (0 once the value is dropped)
+    pub fn weak_count(&self) -> usize {
+        let Some(inner) = self.inner() else { return 0 };
+        let weak = inner.weak.load(Ordering::Acquire);
+        if inner.strong.load(Ordering::Acquire) == 0 {
+            0
+        } else {
+            //  `is_unique` can not lock while this `Weak` exists
+            weak - 1
+        }
+    }
+
+    /// checks if both point to the same allocation (or both are from [`Weak::new`])
+    #[inline]
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        self.ptr == other.ptr
+    }
+}
+
+impl<T> Drop for Weak<T> {
+    fn drop(&mut self) {
+        if let Some(ptr) = self.ptr {
+            unsafe { ArcInner::release_weak(ptr) };
+        }
+    }
+}
+
+impl<T> Clone for Weak<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        if let Some(inner) = self.inner() {
+            let old = inner.weak.fetch_add(1, Ordering::Relaxed);
+            assert!(old <= MAX_REFCOUNT, "Arc weak count overflow");
+        }
+        Self { ptr: self.ptr }
+    }
+}
+
+impl<T> Default for Weak<T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Debug for Weak<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "(Weak)")
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mem::test_util::Counted;
+    use std::sync::Barrier;
+    use std::thread;
+    use std::vec::Vec;
+
+    const THREADS: usize = 16;
+    const ROUNDS: usize = 10_000;
+
+    /// runs `f(thread index)` on [`THREADS`] threads started at the same time
+    fn hammer<F: Fn(usize) + Sync>(f: F) {
+        let barrier = Barrier::new(THREADS);
+        thread::scope(|s| {
+            for i in 0..THREADS {
+                let (f, barrier) = (&f, &barrier);
+                s.spawn(move || {
+                    barrier.wait();
+                    f(i);
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn clone_and_drop() {
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+        let arc = Arc::new(Counted(&DROPS));
+        hammer(|_| {
+            for _ in 0..ROUNDS {
+                let clones: Vec<Arc<Counted>> = (0..4).map(|_| arc.clone()).collect();
+                assert!(Arc::strong_count(&arc) >= 5);
+                drop(clones);
+            }
+        });
+        assert_eq!(Arc::strong_count(&arc), 1);
+        assert_eq!(DROPS.load(Ordering::SeqCst), 0);
+        drop(arc);
+        assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn last_drop_from_other_threads() {
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+        for _ in 0..100 {
+            let arc = Arc::new(Counted(&DROPS));
+            let clones: Vec<Arc<Counted>> = (0..THREADS).map(|_| arc.clone()).collect();
+            drop(arc);
+            let clones = std::sync::Mutex::new(clones);
+            hammer(|_| drop(clones.lock().unwrap().pop()));
+        }
+        assert_eq!(DROPS.load(Ordering::SeqCst), 100);
+    }
+
+    #[test]
+    fn upgrade_while_dropping() {
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+        for _ in 0..200 {
+            let arc = Arc::new(Counted(&DROPS));
+            let weak = Arc::downgrade(&arc);
+            let arc = std::sync::Mutex::new(Some(arc));
+            hammer(|i| {
+                if i == 0 {
+                    drop(arc.lock().unwrap().take());
+                    return;
+                }
+                for _ in 0..100 {
+                    match weak.upgrade() {
+                        Some(strong) => assert!(Arc::strong_count(&strong) >= 1),
+                        None => assert_eq!(weak.strong_count(), 0),
+                    }
+                    drop(weak.clone());
+                }
+            });
+            assert!(weak.upgrade().is_none());
+            assert_eq!(weak.weak_count(), 0);
+        }
+        assert_eq!(DROPS.load(Ordering::SeqCst), 200);
+    }
+
+    #[test]
+    fn downgrade_races_get_mut() {
+        let mut arc = Arc::new(0usize);
+        let shared = arc.clone();
+        hammer(|_| {
+            for _ in 0..ROUNDS / 10 {
+                let weak = Arc::downgrade(&shared);
+                assert!(weak.upgrade().is_some());
+            }
+        });
+        assert_eq!(Arc::weak_count(&arc), 0);
+        assert!(Arc::get_mut(&mut arc).is_none());
+        drop(shared);
+        *Arc::get_mut(&mut arc).unwrap() = 7;
+        let weak = Arc::downgrade(&arc);
+        assert!(Arc::get_mut(&mut arc).is_none());
+        drop(weak);
+        assert_eq!(*Arc::get_mut(&mut arc).unwrap(), 7);
+    }
+
+    #[test]
+    fn mutex_counter() {
+        let counter = Arc::new_mutex(0usize);
+        hammer(|_| {
+            let counter = counter.clone();
+            for _ in 0..ROUNDS {
+                *counter.lock() += 1;
+                while counter.try_with(|c| *c += 1).is_none() {}
+            }
+        });
+        assert_eq!(*counter.lock(), THREADS * ROUNDS * 2);
+        assert_eq!(Arc::try_unwrap(counter).ok().map(|m| m.into_inner()), Some(THREADS * ROUNDS * 2));
+    }
+
+    #[test]
+    fn make_mut_and_try_unwrap() {
+        let mut a = Arc::new(1);
+        let b = a.clone();
+        *Arc::make_mut(&mut a) += 1;
+        assert_eq!((*a, *b), (2, 1));
+        assert!(!Arc::ptr_eq(&a, &b));
+
+        let weak = Arc::downgrade(&a);
+        *Arc::make_mut(&mut a) += 1;
+        assert_eq!(*a, 3);
+        assert!(weak.upgrade().is_none());
+
+        let c = b.clone();
+        let b = Arc::try_unwrap(b).unwrap_err();
+        drop(c);
+        assert_eq!(Arc::try_unwrap(b).ok(), Some(1));
+    }
+
+    #[test]
+    fn try_unwrap_races() {
+        for _ in 0..200 {
+            let unwrapped = AtomicUsize::new(0);
+            let arc = Arc::new(5);
+            let clones = std::sync::Mutex::new((0..THREADS).map(|_| arc.clone()).collect::<Vec<_>>());
+            drop(arc);
+            hammer(|_| {
+                let mine = clones.lock().unwrap().pop().unwrap();
+                if Arc::try_unwrap(mine).is_ok() {
+                    unwrapped.fetch_add(1, Ordering::SeqCst);
+                }
+            });
+            //  losers drop their `Arc`, so the value may be dropped without a winner, but never two win
+            assert!(unwrapped.load(Ordering::SeqCst) <= 1);
+        }
+    }
+}
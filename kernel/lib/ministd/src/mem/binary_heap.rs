@@ -0,0 +1,426 @@
+//	mem/binary_heap.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  priority queue implemented as a binary max-heap
+//  - elements live in one `DynamicBuffer` which grows geometrically like `Vec`
+//  - the largest element is at index 0, children of `i` are at `2i + 1` and `2i + 2`
+//  - wrap elements in [`core::cmp::Reverse`] to get a min-heap (e.g. for timer deadlines)
+//  - `try_*` functions return `Err` instead of panicking when the allocator runs out of memory
+
+use core::fmt::Debug;
+use core::iter::FusedIterator;
+use core::ptr::drop_in_place;
+use core::slice::{self, from_raw_parts, from_raw_parts_mut};
+
+use crate::mem::DynamicBuffer;
+use crate::TryClone;
+
+
+/// Priority queue, [`BinaryHeap::pop`] returns the largest element
+pub struct BinaryHeap<T: Sized> {
+    /// `data.size` is the number of elements
+    data: DynamicBuffer<T, 0>,
+}
+
+impl<T: Sized> BinaryHeap<T> {
+
+    /// Constructs new empty `BinaryHeap` (nothing is allocated)
+    pub const fn new() -> Self {
+        Self {
+            data: DynamicBuffer::empty(),
+        }
+    }
+
+    /// Constructs new empty `BinaryHeap` with at least the specified capacity allocated
+    /// - **panics** if allocation fails
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::try_with_capacity(capacity).expect("failed to allocate memory for BinaryHeap")
+    }
+
+    /// Tries to construct new empty `BinaryHeap` with at least the specified capacity allocated
+    /// - returns `Err` if allocation fails
+    pub fn try_with_capacity(capacity: usize) -> Result<Self, ()> {
+        let mut heap = Self::new();
+        heap.try_reserve(capacity)?;
+        Ok(heap)
+    }
+
+    #[inline(always)] pub const fn len(&self) -> usize { self.data.size as usize }
+    #[inline(always)] pub const fn is_empty(&self) -> bool { self.data.size == 0 }
+    #[inline(always)] pub const fn capacity(&self) -> usize { self.data.capacity() }
+
+    /// Reserves capacity for at least `additional` more elements
+    /// - **panics** if allocation fails
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.try_reserve(additional).expect("failed to allocate memory for BinaryHeap")
+    }
+
+    /// Tries to reserve capacity for at least `additional` more elements
+    /// - returns `Err` if allocation fails
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), ()> {
+        let needed = self.len().checked_add(additional).ok_or(())?;
+        if needed > self.capacity() {
+            self.data.try_resize(needed)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Shrinks the capacity of the heap as much as possible
+    /// - returns `Err` if allocation fails
+    pub fn try_shrink_to_fit(&mut self) -> Result<(), ()> {
+        if self.is_empty() {
+            self.data = DynamicBuffer::empty();
+            Ok(())
+        } else {
+            self.data.try_resize_exact(self.len())
+        }
+    }
+
+    /// returns the elements in heap order (not sorted)
+    pub fn as_slice(&self) -> &[T] {
+        if self.is_empty() {
+            &[]
+        } else {
+            unsafe { from_raw_parts(self.data.as_ptr(), self.len()) }
+        }
+    }
+
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        if self.is_empty() {
+            &mut []
+        } else {
+            unsafe { from_raw_parts_mut(self.data.as_ptr(), self.len()) }
+        }
+    }
+
+    /// returns the largest element
+    #[inline]
+    pub fn peek(&self) -> Option<&T> {
+        self.as_slice().first()
+    }
+
+    /// iterates over the elements in heap order (not sorted)
+    #[inline]
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.as_slice().iter()
+    }
+
+    /// Drops all elements
+    /// - does not affect `capacity`
+    pub fn clear(&mut self) {
+        let all: *mut [T] = self.as_mut_slice();
+        self.data.size = 0;
+        unsafe { drop_in_place(all) };
+    }
+
+    /// removes the last element of the array, which is a leaf of the heap
+    #[inline]
+    fn pop_leaf(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        self.data.size -= 1;
+        Some(unsafe { self.data.as_ptr().add(self.len()).read() })
+    }
+}
+
+impl<T: Sized + Ord> BinaryHeap<T> {
+
+    /// Adds element to the heap
+    /// - this is `O(log n)` operation
+    /// - **panics** if allocation fails
+    pub fn push(&mut self, val: T) {
+        if self.try_push(val).is_err() {
+            panic!("failed to allocate memory for BinaryHeap");
+        }
+    }
+
+    /// Tries to add element to the heap
+    /// - this is `O(log n)` operation
+    /// - returns `Err` if allocation fails
+    ///     - in this case returns the ownership of `val`
+    pub fn try_push(&mut self, val: T) -> Result<(), T> {
+        if self.len() == self.capacity() && self.data.try_expand().is_err() {
+            return Err(val);
+        }
+        unsafe { self.data.as_ptr().add(self.len()).write(val) };
+        self.data.size += 1;
+        self.sift_up(self.len() - 1);
+        Ok(())
+    }
+
+    /// Removes and returns the largest element
+    /// - this is `O(log n)` operation
+    pub fn pop(&mut self) -> Option<T> {
+        let len = self.len();
+        if len == 0 {
+            return None;
+        }
+        self.as_mut_slice().swap(0, len - 1);
+        let top = self.pop_leaf();
+        self.sift_down(0);
+        top
+    }
+
+    /// moves element `i` up until its parent is not smaller
+    fn sift_up(&mut self, mut i: usize) {
+        let heap = self.as_mut_slice();
+        while i > 0 {
+            let parent = (i - 1) / 2;
+            if heap[i] <= heap[parent] {
+                break;
+            }
+            heap.swap(i, parent);
+            i = parent;
+        }
+    }
+
+    /// moves element `i` down until no child is larger
+    fn sift_down(&mut self, mut i: usize) {
+        let heap = self.as_mut_slice();
+        loop {
+            let left = 2 * i + 1;
+            if left >= heap.len() {
+                break;
+            }
+            let right = left + 1;
+            let child = if right < heap.len() && heap[right] > heap[left] { right } else { left };
+            if heap[child] <= heap[i] {
+                break;
+            }
+            heap.swap(i, child);
+            i = child;
+        }
+    }
+}
+
+impl<T: Sized> Drop for BinaryHeap<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T: Sized + Clone> Clone for BinaryHeap<T> {
+    /// **panics** if allocation fails
+    fn clone(&self) -> Self {
+        let mut heap = Self::with_capacity(self.len());
+        for val in self.iter() {
+            //  the order is copied, no sifting needed
+            unsafe { heap.data.as_ptr().add(heap.len()).write(val.clone()) };
+            heap.data.size += 1;
+        }
+        heap
+    }
+}
+
+impl<T: Sized + TryClone> TryClone for BinaryHeap<T> {
+    type Error = ();
+
+    fn try_clone(&self) -> Result<Self, Self::Error>
+    where Self: Sized, Self::Error: Default {
+        let mut heap = Self::try_with_capacity(self.len())?;
+        for val in self.iter() {
+            //  on error `heap` drops the elements cloned so far
+            let val = val.try_clone().map_err(|_| ())?;
+            unsafe { heap.data.as_ptr().add(heap.len()).write(val) };
+            heap.data.size += 1;
+        }
+        Ok(heap)
+    }
+}
+
+impl<T: Sized> Default for BinaryHeap<T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Sized + Debug> Debug for BinaryHeap<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Sized + Ord> FromIterator<T> for BinaryHeap<T> {
+    /// **panics** if allocation fails
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut heap = Self::new();
+        heap.extend(iter);
+        heap
+    }
+}
+
+impl<T: Sized + Ord> Extend<T> for BinaryHeap<T> {
+    /// **panics** if allocation fails
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for val in iter {
+            self.push(val);
+        }
+    }
+}
+
+impl<'l, T: Sized> IntoIterator for &'l BinaryHeap<T> {
+    type Item = &'l T;
+    type IntoIter = slice::Iter<'l, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: Sized> IntoIterator for BinaryHeap<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+
+/// Owning iterator over `T` in no particular order
+/// - use [`BinaryHeap::pop`] to take the elements sorted
+/// - the elements that are not taken are dropped with the iterator
+pub struct IntoIter<T: Sized>(BinaryHeap<T>);
+
+impl<T: Sized> Iterator for IntoIter<T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop_leaf()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len(), Some(self.0.len()))
+    }
+}
+
+impl<T: Sized> ExactSizeIterator for IntoIter<T> {}
+impl<T: Sized> FusedIterator for IntoIter<T> {}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mem::test_util::{Rng, Counted};
+    use core::cmp::Reverse;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use std::collections::BinaryHeap as StdHeap;
+    use std::vec::Vec;
+
+    /// counts drops, compares by the rank only
+    struct Ranked<'a> {
+        rank: u32,
+        _counted: Counted<'a>,
+    }
+
+    impl<'a> Ranked<'a> {
+        fn new(rank: u32, drops: &'a AtomicUsize) -> Self {
+            Self { rank, _counted: Counted(drops) }
+        }
+    }
+
+    impl PartialEq for Ranked<'_> {
+        fn eq(&self, other: &Self) -> bool {
+            self.rank == other.rank
+        }
+    }
+    impl Eq for Ranked<'_> {}
+    impl PartialOrd for Ranked<'_> {
+        fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+    impl Ord for Ranked<'_> {
+        fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+            self.rank.cmp(&other.rank)
+        }
+    }
+
+    #[test]
+    fn push_and_pop() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        let mut heap = BinaryHeap::new();
+        let mut std = StdHeap::new();
+
+        for _ in 0..50_000 {
+            if rng.next(3) == 0 {
+                assert_eq!(heap.pop(), std.pop());
+            } else {
+                let v = rng.next(1000);
+                heap.push(v);
+                std.push(v);
+            }
+            assert_eq!(heap.peek(), std.peek());
+            assert_eq!(heap.len(), std.len());
+        }
+
+        let mut sorted = Vec::new();
+        while let Some(v) = heap.pop() {
+            sorted.push(v);
+        }
+        assert!(sorted.into_iter().eq(std.into_sorted_vec().into_iter().rev()));
+        assert!(heap.is_empty());
+        assert_eq!(heap.pop(), None);
+    }
+
+    #[test]
+    fn min_heap() {
+        let heap: BinaryHeap<Reverse<u32>> = [5, 1, 8, 3, 9, 2].into_iter().map(Reverse).collect();
+        let mut heap = heap.clone();
+        assert_eq!(heap.peek(), Some(&Reverse(1)));
+        let order: Vec<u32> = core::iter::from_fn(|| heap.pop()).map(|Reverse(v)| v).collect();
+        assert_eq!(order, [1, 2, 3, 5, 8, 9]);
+    }
+
+    #[test]
+    fn capacity() {
+        let mut heap: BinaryHeap<u32> = BinaryHeap::with_capacity(10);
+        let capacity = heap.capacity();
+        assert!(capacity >= 10);
+        heap.extend(0..10);
+        assert_eq!(heap.capacity(), capacity);
+        assert_eq!(heap.as_slice().len(), 10);
+
+        heap.reserve(100);
+        assert!(heap.capacity() >= 110);
+        heap.try_shrink_to_fit().unwrap();
+        assert_eq!(heap.capacity(), 10);
+        assert_eq!(heap.peek(), Some(&9));
+
+        heap.clear();
+        heap.try_shrink_to_fit().unwrap();
+        assert_eq!(heap.capacity(), 0);
+    }
+
+    #[test]
+    fn drops() {
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+        let mut heap = BinaryHeap::new();
+        for v in 0..100 {
+            heap.push(Ranked::new(v * 7 % 100, &DROPS));
+        }
+        assert_eq!(heap.pop().map(|c| c.rank), Some(99));
+        assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+
+        let mut iter = heap.into_iter();
+        let taken: Vec<_> = iter.by_ref().take(9).collect();
+        drop(iter);
+        assert_eq!(DROPS.load(Ordering::SeqCst), 1 + 90);
+        drop(taken);
+        assert_eq!(DROPS.load(Ordering::SeqCst), 100);
+
+        let mut heap = BinaryHeap::new();
+        heap.extend((0..10).map(|v| Ranked::new(v, &DROPS)));
+        drop(heap);
+        assert_eq!(DROPS.load(Ordering::SeqCst), 110);
+    }
+}
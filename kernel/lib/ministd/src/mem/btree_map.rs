@@ -0,0 +1,1401 @@
+//	mem/btree_map.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  ordered map implemented as a B-tree
+//  - every node holds up to [`CAPACITY`] sorted entries, internal nodes one more edge
+//  - nodes come from [`ALLOCATOR`], one allocation per node
+//  - insertion splits full nodes on the way down, so a failed allocation leaves a valid tree behind
+//  - removal refills small nodes on the way down and never allocates
+//  - iterators keep the path from the root on a fixed size stack, nodes have no parent pointers
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt::Debug;
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+use core::mem::{self, ManuallyDrop, MaybeUninit};
+use core::ops::{Bound, Index, RangeBounds};
+use core::ptr::{self, NonNull};
+
+use crate::mem::alloc::ALLOCATOR;
+use crate::TryClone;
+
+
+/// minimum degree, every node except the root holds at least `B - 1` entries
+const B: usize = 6;
+/// maximum number of entries in one node
+const CAPACITY: usize = 2 * B - 1;
+/// deepest possible tree, nodes have at least `B` children and the address space is finite
+const MAX_HEIGHT: usize = 24;
+
+
+struct Node<K, V> {
+    len: u16,
+    leaf: bool,
+    keys: [MaybeUninit<K>; CAPACITY],
+    vals: [MaybeUninit<V>; CAPACITY],
+    /// only used in internal nodes, `len + 1` are valid
+    edges: [MaybeUninit<NonNull<Node<K, V>>>; CAPACITY + 1],
+}
+
+/// position of an entry
+type Handle<K, V> = (NonNull<Node<K, V>>, usize);
+
+impl<K, V> Node<K, V> {
+
+    /// allocates node without entries
+    /// - returns `Err` if allocation fails
+    fn try_allocate(leaf: bool) -> Result<NonNull<Self>, ()> {
+        let node = unsafe { ALLOCATOR.allocate_uninit::<Self>() }?.cast::<Self>();
+        unsafe {
+            (&raw mut (*node.as_ptr()).len).write(0);
+            (&raw mut (*node.as_ptr()).leaf).write(leaf);
+        }
+        Ok(node)
+    }
+
+    /// frees the memory of `node`, the entries must have been moved out
+    unsafe fn deallocate(node: NonNull<Self>) {
+        unsafe { ALLOCATOR.dealloc(node.as_ptr() as *mut u8, Layout::new::<Self>()) };
+    }
+
+    /// drops all entries of `node` and its subtree and frees the nodes
+    unsafe fn free_tree(node: NonNull<Self>) {
+        let n = unsafe { &mut *node.as_ptr() };
+        for i in 0..n.len() {
+            unsafe {
+                n.keys[i].assume_init_drop();
+                n.vals[i].assume_init_drop();
+            }
+        }
+        if !n.leaf {
+            for i in 0..=n.len() {
+                unsafe { Self::free_tree(n.edge(i)) };
+            }
+        }
+        unsafe { Self::deallocate(node) };
+    }
+
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    #[inline(always)]
+    unsafe fn key(&self, i: usize) -> &K {
+        unsafe { self.keys[i].assume_init_ref() }
+    }
+
+    #[inline(always)]
+    unsafe fn val(&self, i: usize) -> &V {
+        unsafe { self.vals[i].assume_init_ref() }
+    }
+
+    #[inline(always)]
+    unsafe fn val_mut(&mut self, i: usize) -> &mut V {
+        unsafe { self.vals[i].assume_init_mut() }
+    }
+
+    #[inline(always)]
+    unsafe fn edge(&self, i: usize) -> NonNull<Self> {
+        unsafe { self.edges[i].assume_init() }
+    }
+
+    /// returns `Ok` with index of `key` or `Err` with index of the edge it would be under
+    fn search<Q>(&self, key: &Q) -> Result<usize, usize>
+    where K: Borrow<Q>, Q: Ord + ?Sized {
+        for i in 0..self.len() {
+            match key.cmp(unsafe { self.key(i) }.borrow()) {
+                Ordering::Less => return Err(i),
+                Ordering::Equal => return Ok(i),
+                Ordering::Greater => {}
+            }
+        }
+        Err(self.len())
+    }
+
+    /// replaces entry `i`, returns the old one
+    #[inline]
+    unsafe fn replace(&mut self, i: usize, key: K, val: V) -> (K, V) {
+        unsafe {
+            (
+                mem::replace(self.keys[i].assume_init_mut(), key),
+                mem::replace(self.vals[i].assume_init_mut(), val),
+            )
+        }
+    }
+
+    /// inserts entry at `i` and `edge` right of it (internal nodes only)
+    /// - the node must not be full
+    unsafe fn insert_at(&mut self, i: usize, key: K, val: V, edge: Option<NonNull<Self>>) {
+        let len = self.len();
+        unsafe {
+            let keys = self.keys.as_mut_ptr();
+            let vals = self.vals.as_mut_ptr();
+            ptr::copy(keys.add(i), keys.add(i + 1), len - i);
+            ptr::copy(vals.add(i), vals.add(i + 1), len - i);
+            if let Some(edge) = edge {
+                let edges = self.edges.as_mut_ptr();
+                ptr::copy(edges.add(i + 1), edges.add(i + 2), len - i);
+                self.edges[i + 1].write(edge);
+            }
+        }
+        self.keys[i].write(key);
+        self.vals[i].write(val);
+        self.len += 1;
+    }
+
+    /// removes entry at `i` and the edge right of it
+    unsafe fn remove_at(&mut self, i: usize) -> (K, V, Option<NonNull<Self>>) {
+        let len = self.len();
+        unsafe {
+            let key = self.keys[i].assume_init_read();
+            let val = self.vals[i].assume_init_read();
+            let keys = self.keys.as_mut_ptr();
+            let vals = self.vals.as_mut_ptr();
+            ptr::copy(keys.add(i + 1), keys.add(i), len - i - 1);
+            ptr::copy(vals.add(i + 1), vals.add(i), len - i - 1);
+            let edge = if self.leaf {
+                None
+            } else {
+                let edge = self.edge(i + 1);
+                let edges = self.edges.as_mut_ptr();
+                ptr::copy(edges.add(i + 2), edges.add(i + 1), len - i - 1);
+                Some(edge)
+            };
+            self.len -= 1;
+            (key, val, edge)
+        }
+    }
+
+    /// inserts entry and `edge` (internal nodes only) before all others
+    unsafe fn push_front(&mut self, key: K, val: V, edge: Option<NonNull<Self>>) {
+        let len = self.len();
+        unsafe {
+            let keys = self.keys.as_mut_ptr();
+            let vals = self.vals.as_mut_ptr();
+            ptr::copy(keys, keys.add(1), len);
+            ptr::copy(vals, vals.add(1), len);
+            if let Some(edge) = edge {
+                let edges = self.edges.as_mut_ptr();
+                ptr::copy(edges, edges.add(1), len + 1);
+                self.edges[0].write(edge);
+            }
+        }
+        self.keys[0].write(key);
+        self.vals[0].write(val);
+        self.len += 1;
+    }
+
+    /// removes the first entry and edge
+    unsafe fn pop_front(&mut self) -> (K, V, Option<NonNull<Self>>) {
+        let len = self.len();
+        unsafe {
+            let key = self.keys[0].assume_init_read();
+            let val = self.vals[0].assume_init_read();
+            let keys = self.keys.as_mut_ptr();
+            let vals = self.vals.as_mut_ptr();
+            ptr::copy(keys.add(1), keys, len - 1);
+            ptr::copy(vals.add(1), vals, len - 1);
+            let edge = if self.leaf {
+                None
+            } else {
+                let edge = self.edge(0);
+                let edges = self.edges.as_mut_ptr();
+                ptr::copy(edges.add(1), edges, len);
+                Some(edge)
+            };
+            self.len -= 1;
+            (key, val, edge)
+        }
+    }
+
+    /// inserts entry and `edge` (internal nodes only) after all others
+    unsafe fn push_back(&mut self, key: K, val: V, edge: Option<NonNull<Self>>) {
+        let len = self.len();
+        self.keys[len].write(key);
+        self.vals[len].write(val);
+        if let Some(edge) = edge {
+            self.edges[len + 1].write(edge);
+        }
+        self.len += 1;
+    }
+
+    /// removes the last entry and edge
+    unsafe fn pop_back(&mut self) -> (K, V, Option<NonNull<Self>>) {
+        self.len -= 1;
+        let len = self.len();
+        unsafe {
+            let edge = if self.leaf { None } else { Some(self.edge(len + 1)) };
+            (self.keys[len].assume_init_read(), self.vals[len].assume_init_read(), edge)
+        }
+    }
+
+    /// splits full child `i` in two, its middle entry moves into `self`
+    /// - `self` must not be full
+    /// - returns `Err` if allocation fails, nothing is changed then
+    unsafe fn split_child(&mut self, i: usize) -> Result<(), ()> {
+        let left = unsafe { &mut *self.edge(i).as_ptr() };
+        let right_ptr = Self::try_allocate(left.leaf)?;
+        let right = unsafe { &mut *right_ptr.as_ptr() };
+
+        unsafe {
+            ptr::copy_nonoverlapping(left.keys.as_ptr().add(B), right.keys.as_mut_ptr(), B - 1);
+            ptr::copy_nonoverlapping(left.vals.as_ptr().add(B), right.vals.as_mut_ptr(), B - 1);
+            if !left.leaf {
+                ptr::copy_nonoverlapping(left.edges.as_ptr().add(B), right.edges.as_mut_ptr(), B);
+            }
+        }
+        right.len = (B - 1) as u16;
+        left.len = (B - 1) as u16;
+
+        let (key, val) = unsafe { (left.keys[B - 1].assume_init_read(), left.vals[B - 1].assume_init_read()) };
+        unsafe { self.insert_at(i, key, val, Some(right_ptr)) };
+        Ok(())
+    }
+
+    /// merges child `i + 1` and entry `i` into child `i`
+    unsafe fn merge_children(&mut self, i: usize) {
+        let (key, val, right_ptr) = unsafe { self.remove_at(i) };
+        let Some(right_ptr) = right_ptr else { return };
+        let left = unsafe { &mut *self.edge(i).as_ptr() };
+        let right = unsafe { &mut *right_ptr.as_ptr() };
+        let (llen, rlen) = (left.len(), right.len());
+
+        left.keys[llen].write(key);
+        left.vals[llen].write(val);
+        unsafe {
+            ptr::copy_nonoverlapping(right.keys.as_ptr(), left.keys.as_mut_ptr().add(llen + 1), rlen);
+            ptr::copy_nonoverlapping(right.vals.as_ptr(), left.vals.as_mut_ptr().add(llen + 1), rlen);
+            if !left.leaf {
+                ptr::copy_nonoverlapping(right.edges.as_ptr(), left.edges.as_mut_ptr().add(llen + 1), rlen + 1);
+            }
+            Self::deallocate(right_ptr);
+        }
+        left.len = (llen + 1 + rlen) as u16;
+    }
+
+    /// makes sure child `i` has more than the minimum of entries before descending into it
+    /// - returns the child to descend into (the children may have been merged)
+    unsafe fn fill_child(&mut self, i: usize) -> NonNull<Self> {
+        unsafe {
+            let child = self.edge(i);
+            if child.as_ref().len() >= B {
+                return child;
+            }
+            if i > 0 && self.edge(i - 1).as_ref().len() >= B {
+                let (key, val, edge) = (*self.edge(i - 1).as_ptr()).pop_back();
+                let (key, val) = self.replace(i - 1, key, val);
+                (*child.as_ptr()).push_front(key, val, edge);
+                return child;
+            }
+            if i < self.len() && self.edge(i + 1).as_ref().len() >= B {
+                let (key, val, edge) = (*self.edge(i + 1).as_ptr()).pop_front();
+                let (key, val) = self.replace(i, key, val);
+                (*child.as_ptr()).push_back(key, val, edge);
+                return child;
+            }
+            if i < self.len() {
+                self.merge_children(i);
+                child
+            } else {
+                self.merge_children(i - 1);
+                self.edge(i - 1)
+            }
+        }
+    }
+}
+
+
+/// entry to remove
+enum Target<'q, Q: ?Sized> {
+    Key(&'q Q),
+    First,
+    Last,
+}
+
+impl<Q: ?Sized> Clone for Target<'_, Q> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<Q: ?Sized> Copy for Target<'_, Q> {}
+
+
+/// Ordered map implemented as a B-tree
+///
+/// ### Generic parameters
+/// 1. `K`: key type, must implement `Ord`
+/// 2. `V`: value type
+pub struct BTreeMap<K, V> {
+    root: Option<NonNull<Node<K, V>>>,
+    len: usize,
+    _marker: PhantomData<(K, V)>,
+}
+
+//  the map owns its nodes
+unsafe impl<K: Send, V: Send> Send for BTreeMap<K, V> {}
+unsafe impl<K: Sync, V: Sync> Sync for BTreeMap<K, V> {}
+
+impl<K, V> BTreeMap<K, V> {
+
+    /// Constructs new empty `BTreeMap` (nothing is allocated)
+    pub const fn new() -> Self {
+        Self {
+            root: None,
+            len: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    #[inline(always)] pub fn len(&self) -> usize { self.len }
+    #[inline(always)] pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// removes all entries and frees the nodes
+    pub fn clear(&mut self) {
+        drop(mem::take(self));
+    }
+
+    /// returns the entry with the smallest key
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        let mut node = unsafe { self.root?.as_ref() };
+        while !node.leaf {
+            node = unsafe { node.edge(0).as_ref() };
+        }
+        unsafe { Some((node.key(0), node.val(0))) }
+    }
+
+    /// returns the entry with the largest key
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        let mut node = unsafe { self.root?.as_ref() };
+        while !node.leaf {
+            node = unsafe { node.edge(node.len()).as_ref() };
+        }
+        let last = node.len() - 1;
+        unsafe { Some((node.key(last), node.val(last))) }
+    }
+
+    /// iterates over the entries in key order
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            cursor: Cursor::seek(self.root, |_| false),
+            left: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    /// iterates over the entries in key order, values are mutable
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            cursor: Cursor::seek(self.root, |_| false),
+            left: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys(self.iter())
+    }
+
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values(self.iter())
+    }
+
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut(self.iter_mut())
+    }
+}
+
+impl<K: Ord, V> BTreeMap<K, V> {
+
+    /// returns position of `key`
+    fn find<Q>(&self, key: &Q) -> Option<Handle<K, V>>
+    where K: Borrow<Q>, Q: Ord + ?Sized {
+        let mut node = self.root?;
+        loop {
+            let n = unsafe { node.as_ref() };
+            match n.search(key) {
+                Ok(i) => return Some((node, i)),
+                Err(_) if n.leaf => return None,
+                Err(i) => node = unsafe { n.edge(i) },
+            }
+        }
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where K: Borrow<Q>, Q: Ord + ?Sized {
+        self.get_key_value(key).map(|(_, v)| v)
+    }
+
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where K: Borrow<Q>, Q: Ord + ?Sized {
+        let (node, i) = self.find(key)?;
+        let node = unsafe { node.as_ref() };
+        unsafe { Some((node.key(i), node.val(i))) }
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where K: Borrow<Q>, Q: Ord + ?Sized {
+        let (node, i) = self.find(key)?;
+        unsafe { Some((*node.as_ptr()).val_mut(i)) }
+    }
+
+    #[inline]
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where K: Borrow<Q>, Q: Ord + ?Sized {
+        self.find(key).is_some()
+    }
+
+    /// Inserts entry, returns the old value if `key` was present
+    /// - the stored key is kept in that case
+    /// - **panics** if allocation fails
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.try_insert(key, value) {
+            Ok(old) => old,
+            Err(_) => panic!("failed to allocate memory for BTreeMap"),
+        }
+    }
+
+    /// Tries to insert entry, returns the old value if `key` was present
+    /// - the stored key is kept in that case
+    /// - returns `Err` if allocation fails
+    ///     - in this case returns the ownership of `key` and `value`
+    pub fn try_insert(&mut self, key: K, value: V) -> Result<Option<V>, (K, V)> {
+        if let Some((node, i)) = self.find(&key) {
+            let old = mem::replace(unsafe { (*node.as_ptr()).val_mut(i) }, value);
+            return Ok(Some(old));
+        }
+        self.insert_new(key, value).map(|_| None)
+    }
+
+    /// inserts entry with key that is not in the map, returns pointer to its value
+    /// - returns `Err` if allocation fails, the tree may have been split but stays valid
+    fn insert_new(&mut self, key: K, value: V) -> Result<NonNull<V>, (K, V)> {
+        let root = match self.root {
+            Some(root) => root,
+            None => match Node::try_allocate(true) {
+                Ok(root) => *self.root.insert(root),
+                Err(_) => return Err((key, value)),
+            },
+        };
+
+        //  the root is split by putting a new root above it
+        let mut node = root;
+        if unsafe { root.as_ref() }.len() == CAPACITY {
+            let Ok(new) = Node::<K, V>::try_allocate(false) else { return Err((key, value)) };
+            unsafe {
+                (*new.as_ptr()).edges[0].write(root);
+                if (*new.as_ptr()).split_child(0).is_err() {
+                    Node::deallocate(new);
+                    return Err((key, value));
+                }
+            }
+            self.root = Some(new);
+            node = new;
+        }
+
+        loop {
+            let n = unsafe { &mut *node.as_ptr() };
+            let (Ok(mut i) | Err(mut i)) = n.search(&key);
+            if n.leaf {
+                unsafe { n.insert_at(i, key, value, None) };
+                self.len += 1;
+                return Ok(NonNull::from(unsafe { n.val_mut(i) }));
+            }
+            if unsafe { n.edge(i).as_ref() }.len() == CAPACITY {
+                if unsafe { n.split_child(i) }.is_err() {
+                    return Err((key, value));
+                }
+                if key > *unsafe { n.key(i) } {
+                    i += 1;
+                }
+            }
+            node = unsafe { n.edge(i) };
+        }
+    }
+
+    /// removes `target` from the subtree of `node`
+    /// - `node` must be the root or have more than the minimum of entries
+    unsafe fn remove_in<Q>(mut node: NonNull<Node<K, V>>, target: Target<'_, Q>) -> Option<(K, V)>
+    where K: Borrow<Q>, Q: Ord + ?Sized {
+        loop {
+            let n = unsafe { &mut *node.as_ptr() };
+            let position = match target {
+                Target::Key(key) => n.search(key),
+                Target::First if n.leaf => Ok(0),
+                Target::First => Err(0),
+                Target::Last if n.leaf => Ok(n.len() - 1),
+                Target::Last => Err(n.len()),
+            };
+
+            match position {
+                Ok(i) if n.leaf => {
+                    let (key, val, _) = unsafe { n.remove_at(i) };
+                    return Some((key, val));
+                }
+                Ok(i) => unsafe {
+                    //  the entry is replaced by its neighbour from a child that can spare one
+                    if n.edge(i).as_ref().len() >= B {
+                        let (key, val) = Self::remove_in(n.edge(i), Target::<Q>::Last)?;
+                        return Some(n.replace(i, key, val));
+                    }
+                    if n.edge(i + 1).as_ref().len() >= B {
+                        let (key, val) = Self::remove_in(n.edge(i + 1), Target::<Q>::First)?;
+                        return Some(n.replace(i, key, val));
+                    }
+                    n.merge_children(i);
+                    node = n.edge(i);
+                },
+                Err(_) if n.leaf => return None,
+                Err(i) => node = unsafe { n.fill_child(i) },
+            }
+        }
+    }
+
+    fn remove_target<Q>(&mut self, target: Target<'_, Q>) -> Option<(K, V)>
+    where K: Borrow<Q>, Q: Ord + ?Sized {
+        let root = self.root?;
+        let entry = unsafe { Self::remove_in(root, target) };
+        if entry.is_some() {
+            self.len -= 1;
+        }
+
+        //  merging the last two children of the root leaves it empty
+        let r = unsafe { root.as_ref() };
+        if r.len() == 0 {
+            self.root = if r.leaf { None } else { Some(unsafe { r.edge(0) }) };
+            unsafe { Node::deallocate(root) };
+        }
+        entry
+    }
+
+    /// removes `key` and returns its value
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where K: Borrow<Q>, Q: Ord + ?Sized {
+        self.remove_entry(key).map(|(_, v)| v)
+    }
+
+    /// removes `key` and returns the stored key and value
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where K: Borrow<Q>, Q: Ord + ?Sized {
+        self.remove_target(Target::Key(key))
+    }
+
+    /// removes the entry with the smallest key
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        self.remove_target(Target::<K>::First)
+    }
+
+    /// removes the entry with the largest key
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        self.remove_target(Target::<K>::Last)
+    }
+
+    /// returns entry of `key` for in-place manipulation
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        match self.find(&key) {
+            Some((node, index)) => Entry::Occupied(OccupiedEntry { map: self, node, index }),
+            None => Entry::Vacant(VacantEntry { map: self, key }),
+        }
+    }
+
+    /// returns cursor over the entries in `range`
+    /// - **panics** if the range starts after it ends
+    fn cursor<Q, R>(&self, range: &R) -> Cursor<K, V>
+    where K: Borrow<Q>, Q: Ord + ?Sized, R: RangeBounds<Q> {
+        use Bound::*;
+        match (range.start_bound(), range.end_bound()) {
+            (Excluded(s), Excluded(e)) if s == e => panic!("range start and end are equal and excluded in BTreeMap"),
+            (Included(s) | Excluded(s), Included(e) | Excluded(e)) if s > e => panic!("range start is greater than range end in BTreeMap"),
+            _ => {}
+        }
+
+        let mut cursor = match range.start_bound() {
+            Included(s) => Cursor::seek(self.root, |k| k.borrow() < s),
+            Excluded(s) => Cursor::seek(self.root, |k| k.borrow() <= s),
+            Unbounded => Cursor::seek(self.root, |_| false),
+        };
+        //  the first entry after the range ends the iteration
+        cursor.end = match range.end_bound() {
+            Included(e) => Cursor::seek(self.root, |k| k.borrow() <= e).peek(),
+            Excluded(e) => Cursor::seek(self.root, |k| k.borrow() < e).peek(),
+            Unbounded => None,
+        };
+        cursor
+    }
+
+    /// iterates over the entries with keys in `range` in key order
+    /// - **panics** if the range starts after it ends
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V>
+    where K: Borrow<Q>, Q: Ord + ?Sized, R: RangeBounds<Q> {
+        Range {
+            cursor: self.cursor(&range),
+            _marker: PhantomData,
+        }
+    }
+
+    /// iterates over the entries with keys in `range` in key order, values are mutable
+    /// - **panics** if the range starts after it ends
+    pub fn range_mut<Q, R>(&mut self, range: R) -> RangeMut<'_, K, V>
+    where K: Borrow<Q>, Q: Ord + ?Sized, R: RangeBounds<Q> {
+        RangeMut {
+            cursor: self.cursor(&range),
+            _marker: PhantomData,
+        }
+    }
+
+    /// copies the map entry by entry
+    /// - returns `Err` if allocation or `clone` fails
+    pub(crate) fn clone_with<F>(&self, mut clone: F) -> Result<Self, ()>
+    where F: FnMut(&K, &V) -> Result<(K, V), ()> {
+        let mut map = Self::new();
+        for (k, v) in self.iter() {
+            //  on error `map` drops the entries cloned so far
+            let (key, value) = clone(k, v)?;
+            map.insert_new(key, value).map_err(|_| ())?;
+        }
+        Ok(map)
+    }
+}
+
+impl<K, V> Drop for BTreeMap<K, V> {
+    fn drop(&mut self) {
+        if let Some(root) = self.root.take() {
+            unsafe { Node::free_tree(root) };
+        }
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> Clone for BTreeMap<K, V> {
+    /// **panics** if allocation fails
+    fn clone(&self) -> Self {
+        self.clone_with(|k, v| Ok((k.clone(), v.clone())))
+            .expect("failed to allocate memory for BTreeMap")
+    }
+}
+
+impl<K: Ord + TryClone, V: TryClone> TryClone for BTreeMap<K, V> {
+    type Error = ();
+
+    fn try_clone(&self) -> Result<Self, Self::Error>
+    where Self: Sized, Self::Error: Default {
+        self.clone_with(|k, v| match (k.try_clone(), v.try_clone()) {
+            (Ok(k), Ok(v)) => Ok((k, v)),
+            _ => Err(()),
+        })
+    }
+}
+
+impl<K, V> Default for BTreeMap<K, V> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for BTreeMap<K, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: PartialEq, V: PartialEq> PartialEq for BTreeMap<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<K: Eq, V: Eq> Eq for BTreeMap<K, V> {}
+
+impl<K, Q, V> Index<&Q> for BTreeMap<K, V>
+where K: Ord + Borrow<Q>, Q: Ord + ?Sized {
+    type Output = V;
+
+    /// **panics** if `key` is missing
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("key is not in BTreeMap")
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for BTreeMap<K, V> {
+    /// **panics** if allocation fails
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for BTreeMap<K, V> {
+    /// **panics** if allocation fails
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<'l, K, V> IntoIterator for &'l BTreeMap<K, V> {
+    type Item = (&'l K, &'l V);
+    type IntoIter = Iter<'l, K, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'l, K, V> IntoIterator for &'l mut BTreeMap<K, V> {
+    type Item = (&'l K, &'l mut V);
+    type IntoIter = IterMut<'l, K, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K: Ord, V> IntoIterator for BTreeMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+
+/// Entry of [`BTreeMap`], returned by [`BTreeMap::entry`]
+pub enum Entry<'l, K: Ord, V> {
+    Vacant(VacantEntry<'l, K, V>),
+    Occupied(OccupiedEntry<'l, K, V>),
+}
+
+impl<'l, K: Ord, V> Entry<'l, K, V> {
+
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Vacant(e) => e.key(),
+            Entry::Occupied(e) => e.key(),
+        }
+    }
+
+    /// returns the value, `default` is inserted first if the entry is vacant
+    /// - **panics** if allocation fails
+    pub fn or_insert(self, default: V) -> &'l mut V {
+        self.or_insert_with(|| default)
+    }
+
+    /// returns the value, the result of `f` is inserted first if the entry is vacant
+    /// - **panics** if allocation fails
+    pub fn or_insert_with<F: FnOnce() -> V>(self, f: F) -> &'l mut V {
+        match self {
+            Entry::Vacant(e) => e.insert(f()),
+            Entry::Occupied(e) => e.into_mut(),
+        }
+    }
+
+    /// calls `f` on the value if the entry is occupied
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(e) = &mut self {
+            f(e.get_mut());
+        }
+        self
+    }
+}
+
+impl<'l, K: Ord, V: Default> Entry<'l, K, V> {
+
+    /// returns the value, `V::default()` is inserted first if the entry is vacant
+    /// - **panics** if allocation fails
+    pub fn or_default(self) -> &'l mut V {
+        self.or_insert_with(V::default)
+    }
+}
+
+
+/// Vacant entry of [`BTreeMap`]
+pub struct VacantEntry<'l, K: Ord, V> {
+    map: &'l mut BTreeMap<K, V>,
+    key: K,
+}
+
+impl<'l, K: Ord, V> VacantEntry<'l, K, V> {
+
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// inserts `value` and returns reference to it
+    /// - **panics** if allocation fails
+    pub fn insert(self, value: V) -> &'l mut V {
+        match self.try_insert(value) {
+            Ok(value) => value,
+            Err(_) => panic!("failed to allocate memory for BTreeMap"),
+        }
+    }
+
+    /// tries to insert `value` and returns reference to it
+    /// - returns `Err` if allocation fails
+    ///     - in this case returns the ownership of the key and `value`
+    pub fn try_insert(self, value: V) -> Result<&'l mut V, (K, V)> {
+        self.map.insert_new(self.key, value).map(|mut v| unsafe { v.as_mut() })
+    }
+}
+
+
+/// Occupied entry of [`BTreeMap`]
+pub struct OccupiedEntry<'l, K: Ord, V> {
+    map: &'l mut BTreeMap<K, V>,
+    node: NonNull<Node<K, V>>,
+    index: usize,
+}
+
+impl<'l, K: Ord, V> OccupiedEntry<'l, K, V> {
+
+    pub fn key(&self) -> &K {
+        unsafe { self.node.as_ref().key(self.index) }
+    }
+
+    pub fn get(&self) -> &V {
+        unsafe { self.node.as_ref().val(self.index) }
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        unsafe { (*self.node.as_ptr()).val_mut(self.index) }
+    }
+
+    pub fn into_mut(self) -> &'l mut V {
+        unsafe { (*self.node.as_ptr()).val_mut(self.index) }
+    }
+
+    /// replaces the value, returns the old one
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    /// removes the entry and returns its value
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+
+    /// removes the entry and returns the stored key and value
+    pub fn remove_entry(self) -> (K, V) {
+        //  the stored key moves while the tree is rebalanced, search with a bitwise copy
+        //  the copy is only compared and never dropped
+        let key = ManuallyDrop::new(unsafe { ptr::read(self.key()) });
+        self.map.remove_entry(&*key).expect("occupied entry is not in BTreeMap")
+    }
+}
+
+
+/// path from the root to the next entry
+/// - every level holds a node and the index of its next entry
+/// - levels whose index is past the last entry are finished and get popped
+struct Cursor<K, V> {
+    stack: [(*mut Node<K, V>, usize); MAX_HEIGHT],
+    depth: usize,
+    /// first entry not to yield, `None` to run to the end
+    end: Option<Handle<K, V>>,
+}
+
+impl<K, V> Clone for Cursor<K, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K, V> Copy for Cursor<K, V> {}
+
+impl<K, V> Cursor<K, V> {
+
+    /// descends from `root`, on every level skips the entries `before` returns `true` for
+    /// - `before` must be `true` for a prefix of the entries in key order
+    fn seek<F: FnMut(&K) -> bool>(root: Option<NonNull<Node<K, V>>>, mut before: F) -> Self {
+        let mut cursor = Self {
+            stack: [(ptr::null_mut(), 0); MAX_HEIGHT],
+            depth: 0,
+            end: None,
+        };
+        let mut node = root;
+        while let Some(ptr) = node {
+            let n = unsafe { ptr.as_ref() };
+            let mut i = 0;
+            while i < n.len() && before(unsafe { n.key(i) }) {
+                i += 1;
+            }
+            cursor.push(ptr, i);
+            node = if n.leaf { None } else { Some(unsafe { n.edge(i) }) };
+        }
+        cursor
+    }
+
+    #[inline(always)]
+    fn push(&mut self, node: NonNull<Node<K, V>>, index: usize) {
+        self.stack[self.depth] = (node.as_ptr(), index);
+        self.depth += 1;
+    }
+
+    /// returns position of the next entry without moving
+    fn peek(&self) -> Option<Handle<K, V>> {
+        self.stack[..self.depth].iter().rev()
+            .find(|&&(node, i)| i < unsafe { (*node).len() })
+            .map(|&(node, i)| (unsafe { NonNull::new_unchecked(node) }, i))
+    }
+
+    /// returns position of the next entry and moves past it
+    fn next(&mut self) -> Option<Handle<K, V>> {
+        let next = self.peek()?;
+        if Some(next) == self.end {
+            return None;
+        }
+        while self.depth > 0 {
+            let (node, i) = self.stack[self.depth - 1];
+            let n = unsafe { &*node };
+            if i < n.len() {
+                self.stack[self.depth - 1].1 += 1;
+                if !n.leaf {
+                    //  the entries between this one and the next are in the leftmost path of the next edge
+                    let mut child = unsafe { n.edge(i + 1) };
+                    loop {
+                        self.push(child, 0);
+                        let c = unsafe { child.as_ref() };
+                        if c.leaf {
+                            break;
+                        }
+                        child = unsafe { c.edge(0) };
+                    }
+                }
+                break;
+            }
+            self.depth -= 1;
+        }
+        Some(next)
+    }
+}
+
+
+/// Iterator over `(&K, &V)` in key order
+pub struct Iter<'l, K, V> {
+    cursor: Cursor<K, V>,
+    left: usize,
+    _marker: PhantomData<&'l BTreeMap<K, V>>,
+}
+
+impl<'l, K, V> Iterator for Iter<'l, K, V> {
+    type Item = (&'l K, &'l V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node, i) = self.cursor.next()?;
+        self.left -= 1;
+        let node = unsafe { &*node.as_ptr() };
+        unsafe { Some((node.key(i), node.val(i))) }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.left, Some(self.left))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+impl<K, V> FusedIterator for Iter<'_, K, V> {}
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self { cursor: self.cursor, left: self.left, _marker: PhantomData }
+    }
+}
+
+
+/// Iterator over `(&K, &mut V)` in key order
+pub struct IterMut<'l, K, V> {
+    cursor: Cursor<K, V>,
+    left: usize,
+    _marker: PhantomData<&'l mut BTreeMap<K, V>>,
+}
+
+impl<'l, K, V> Iterator for IterMut<'l, K, V> {
+    type Item = (&'l K, &'l mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node, i) = self.cursor.next()?;
+        self.left -= 1;
+        let node = unsafe { &mut *node.as_ptr() };
+        unsafe { Some((node.keys[i].assume_init_ref(), node.vals[i].assume_init_mut())) }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.left, Some(self.left))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+impl<K, V> FusedIterator for IterMut<'_, K, V> {}
+
+
+/// Iterator over `(&K, &V)` of a key range, returned by [`BTreeMap::range`]
+pub struct Range<'l, K, V> {
+    cursor: Cursor<K, V>,
+    _marker: PhantomData<&'l BTreeMap<K, V>>,
+}
+
+impl<'l, K, V> Iterator for Range<'l, K, V> {
+    type Item = (&'l K, &'l V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node, i) = self.cursor.next()?;
+        let node = unsafe { &*node.as_ptr() };
+        unsafe { Some((node.key(i), node.val(i))) }
+    }
+}
+
+impl<K, V> FusedIterator for Range<'_, K, V> {}
+
+impl<K, V> Clone for Range<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self { cursor: self.cursor, _marker: PhantomData }
+    }
+}
+
+
+/// Iterator over `(&K, &mut V)` of a key range, returned by [`BTreeMap::range_mut`]
+pub struct RangeMut<'l, K, V> {
+    cursor: Cursor<K, V>,
+    _marker: PhantomData<&'l mut BTreeMap<K, V>>,
+}
+
+impl<'l, K, V> Iterator for RangeMut<'l, K, V> {
+    type Item = (&'l K, &'l mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node, i) = self.cursor.next()?;
+        let node = unsafe { &mut *node.as_ptr() };
+        unsafe { Some((node.keys[i].assume_init_ref(), node.vals[i].assume_init_mut())) }
+    }
+}
+
+impl<K, V> FusedIterator for RangeMut<'_, K, V> {}
+
+
+pub struct Keys<'l, K, V>(Iter<'l, K, V>);
+
+impl<'l, K, V> Iterator for Keys<'l, K, V> {
+    type Item = &'l K;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(k, _)| k)
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Keys<'_, K, V> {}
+impl<K, V> FusedIterator for Keys<'_, K, V> {}
+
+
+pub struct Values<'l, K, V>(Iter<'l, K, V>);
+
+impl<'l, K, V> Iterator for Values<'l, K, V> {
+    type Item = &'l V;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(_, v)| v)
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Values<'_, K, V> {}
+impl<K, V> FusedIterator for Values<'_, K, V> {}
+
+
+pub struct ValuesMut<'l, K, V>(IterMut<'l, K, V>);
+
+impl<'l, K, V> Iterator for ValuesMut<'l, K, V> {
+    type Item = &'l mut V;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(_, v)| v)
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for ValuesMut<'_, K, V> {}
+impl<K, V> FusedIterator for ValuesMut<'_, K, V> {}
+
+
+/// Owning iterator over `(K, V)` in key order
+/// - the entries that are not taken are dropped with the iterator
+pub struct IntoIter<K: Ord, V>(BTreeMap<K, V>);
+
+impl<K: Ord, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop_first()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len(), Some(self.0.len()))
+    }
+}
+
+impl<K: Ord, V> DoubleEndedIterator for IntoIter<K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.pop_last()
+    }
+}
+
+impl<K: Ord, V> ExactSizeIterator for IntoIter<K, V> {}
+impl<K: Ord, V> FusedIterator for IntoIter<K, V> {}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mem::test_util::{Rng, Counted};
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use std::collections::BTreeMap as StdMap;
+    use std::vec::Vec;
+
+    fn assert_same(map: &BTreeMap<u32, u32>, std: &StdMap<u32, u32>) {
+        assert_eq!(map.len(), std.len());
+        assert!(map.iter().eq(std.iter()));
+        assert_eq!(map.first_key_value(), std.first_key_value());
+        assert_eq!(map.last_key_value(), std.last_key_value());
+    }
+
+    #[test]
+    fn insert_and_remove() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        let mut map = BTreeMap::new();
+        let mut std = StdMap::new();
+
+        for round in 0..50_000 {
+            let key = rng.next(2000);
+            match rng.next(4) {
+                0 | 1 => assert_eq!(map.insert(key, round), std.insert(key, round)),
+                2 => assert_eq!(map.remove(&key), std.remove(&key)),
+                _ => assert_eq!(map.remove_entry(&key), std.remove_entry(&key)),
+            }
+            assert_eq!(map.get(&key), std.get(&key));
+            assert_eq!(map.contains_key(&key), std.contains_key(&key));
+            if round % 1000 == 0 {
+                assert_same(&map, &std);
+            }
+        }
+        assert_same(&map, &std);
+
+        for v in map.values_mut() {
+            *v += 1;
+        }
+        assert!(map.values().zip(std.values()).all(|(a, b)| *a == *b + 1));
+    }
+
+    #[test]
+    fn pop_first_and_last() {
+        let mut map: BTreeMap<u32, u32> = (0..1000).map(|k| (k, k * 2)).collect();
+        for i in 0..500 {
+            assert_eq!(map.pop_first(), Some((i, i * 2)));
+            assert_eq!(map.pop_last(), Some((999 - i, (999 - i) * 2)));
+            assert_eq!(map.len(), 1000 - 2 * (i as usize + 1));
+        }
+        assert!(map.is_empty());
+        assert!(map.root.is_none());
+        assert_eq!(map.pop_first(), None);
+        assert_eq!(map.pop_last(), None);
+        assert_eq!(map.first_key_value(), None);
+
+        //  empty again, the tree is rebuilt
+        map.insert(7, 7);
+        assert_eq!(map.pop_last(), Some((7, 7)));
+    }
+
+    #[test]
+    fn range_bounds() {
+        use Bound::*;
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        //  even keys, so the bounds hit both present and missing keys
+        let std: StdMap<u32, u32> = (0..600).map(|k| (k * 2, k)).collect();
+        let mut map: BTreeMap<u32, u32> = std.iter().map(|(k, v)| (*k, *v)).collect();
+
+        let bound = |rng: &mut Rng| {
+            let key = rng.next(1300);
+            match rng.next(3) {
+                0 => Included(key),
+                1 => Excluded(key),
+                _ => Unbounded,
+            }
+        };
+
+        for _ in 0..5000 {
+            let (start, end) = (bound(&mut rng), bound(&mut rng));
+            let invalid = match (start, end) {
+                (Excluded(s), Excluded(e)) => s >= e,
+                (Included(s) | Excluded(s), Included(e) | Excluded(e)) => s > e,
+                _ => false,
+            };
+            if invalid {
+                continue;
+            }
+            assert!(map.range((start, end)).eq(std.range((start, end))), "{:?}..{:?}", start, end);
+        }
+
+        //  every combination at the edges of the map
+        let edges = [Included(0), Excluded(0), Included(1198), Excluded(1198), Unbounded];
+        for start in edges {
+            for end in edges {
+                if matches!((start, end), (Excluded(s), Excluded(e)) if s == e)
+                    || matches!((start, end), (Included(s) | Excluded(s), Included(e) | Excluded(e)) if s > e) {
+                    continue;
+                }
+                assert!(map.range((start, end)).eq(std.range((start, end))), "{:?}..{:?}", start, end);
+            }
+        }
+
+        //  equal included bounds select one key
+        assert!(map.range(10..=10).eq([(&10, &5)]));
+        assert_eq!(map.range(10..10).count(), 0);
+        assert_eq!(map.range(11..=11).count(), 0);
+
+        for (_, v) in map.range_mut(100..200) {
+            *v = 0;
+        }
+        assert!(map.range(100..200).all(|(_, v)| *v == 0));
+        assert_eq!(map.get(&98), Some(&49));
+        assert_eq!(map.get(&200), Some(&100));
+
+        let empty: BTreeMap<u32, u32> = BTreeMap::new();
+        assert_eq!(empty.range(..).count(), 0);
+        assert_eq!(empty.range(3..5).count(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "range start is greater than range end")]
+    fn range_start_after_end() {
+        let map: BTreeMap<u32, u32> = (0..10).map(|k| (k, k)).collect();
+        let (start, end) = (5, 4);
+        let _ = map.range(start..end);
+    }
+
+    #[test]
+    #[should_panic(expected = "range start and end are equal and excluded")]
+    fn range_equal_excluded() {
+        let map: BTreeMap<u32, u32> = (0..10).map(|k| (k, k)).collect();
+        let _ = map.range((Bound::Excluded(5), Bound::Excluded(5)));
+    }
+
+    #[test]
+    fn entry() {
+        let mut map: BTreeMap<u32, u32> = BTreeMap::new();
+
+        *map.entry(1).or_insert(10) += 1;
+        *map.entry(1).or_insert(100) += 1;
+        assert_eq!(map.get(&1), Some(&12));
+
+        assert_eq!(*map.entry(2).or_insert_with(|| 20), 20);
+        assert_eq!(*map.entry(3).or_default(), 0);
+        map.entry(3).and_modify(|v| *v = 30);
+        map.entry(4).and_modify(|v| *v = 40);
+        assert_eq!(map.get(&3), Some(&30));
+        assert!(!map.contains_key(&4));
+
+        match map.entry(5) {
+            Entry::Vacant(e) => {
+                assert_eq!(*e.key(), 5);
+                assert_eq!(*e.insert(50), 50);
+            }
+            Entry::Occupied(_) => panic!("5 is not in the map"),
+        }
+        match map.entry(6) {
+            Entry::Vacant(e) => assert_eq!(e.into_key(), 6),
+            Entry::Occupied(_) => panic!("6 is not in the map"),
+        }
+        assert!(!map.contains_key(&6));
+
+        match map.entry(5) {
+            Entry::Occupied(mut e) => {
+                assert_eq!((*e.key(), *e.get()), (5, 50));
+                assert_eq!(e.insert(55), 50);
+                *e.get_mut() += 1;
+                assert_eq!(e.remove_entry(), (5, 56));
+            }
+            Entry::Vacant(_) => panic!("5 is in the map"),
+        }
+        assert!(!map.contains_key(&5));
+
+        //  removal through entries across node splits and merges
+        let map_value = |k: u32| match k { 1 => 12, 2 => 20, 3 => 30, k => k };
+        for k in 0..1000 {
+            map.entry(k).or_insert(k);
+        }
+        for k in (0..1000).step_by(3) {
+            match map.entry(k) {
+                Entry::Occupied(e) => assert_eq!(e.remove(), map_value(k)),
+                Entry::Vacant(_) => panic!("{} is in the map", k),
+            }
+        }
+        assert_eq!(map.len(), 1000 - 334);
+        assert!(map.keys().all(|k| k % 3 != 0));
+    }
+
+    #[test]
+    fn drops() {
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+        let mut map = BTreeMap::new();
+        for k in 0..1000 {
+            map.insert(k, Counted(&DROPS));
+        }
+
+        //  replaced values are returned, not dropped by the map
+        drop(map.insert(10, Counted(&DROPS)));
+        assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+        drop(map.remove(&20));
+        drop(map.pop_first());
+        drop(map.pop_last());
+        assert_eq!(DROPS.load(Ordering::SeqCst), 4);
+
+        let mut iter = map.into_iter();
+        let taken: Vec<_> = iter.by_ref().take(100).collect();
+        drop(iter);
+        assert_eq!(DROPS.load(Ordering::SeqCst), 4 + 897);
+        drop(taken);
+        assert_eq!(DROPS.load(Ordering::SeqCst), 1001);
+
+        let mut map = BTreeMap::new();
+        for k in 0..100 {
+            map.insert(k, Counted(&DROPS));
+        }
+        map.clear();
+        assert_eq!(DROPS.load(Ordering::SeqCst), 1101);
+    }
+
+    #[test]
+    fn clone_and_compare() {
+        let map: BTreeMap<u32, u32> = (0..500).map(|k| (k * 7 % 500, k)).collect();
+        let copy = map.clone();
+        assert!(copy == map);
+        assert!(copy.iter().eq(map.iter()));
+
+        let mut other = copy.clone();
+        other.insert(0, 1);
+        assert!(other != map);
+        assert_eq!(map[&7], 1);
+        assert!(map.into_iter().rev().map(|(k, _)| k).eq((0..500).rev()));
+    }
+}
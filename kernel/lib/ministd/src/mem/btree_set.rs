@@ -0,0 +1,260 @@
+//	mem/btree_set.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  ordered set, a [`BTreeMap`] without values
+//  - see [`crate::mem::btree_map`] for how the tree works
+
+use core::borrow::Borrow;
+use core::fmt::Debug;
+use core::iter::FusedIterator;
+use core::ops::RangeBounds;
+
+use crate::mem::btree_map::{self, BTreeMap};
+use crate::TryClone;
+
+
+/// Ordered set implemented as a B-tree
+///
+/// ### Generic parameters
+/// 1. `T`: element type, must implement `Ord`
+pub struct BTreeSet<T> {
+    map: BTreeMap<T, ()>,
+}
+
+impl<T> BTreeSet<T> {
+
+    /// Constructs new empty `BTreeSet` (nothing is allocated)
+    pub const fn new() -> Self {
+        Self { map: BTreeMap::new() }
+    }
+
+    #[inline(always)] pub fn len(&self) -> usize { self.map.len() }
+    #[inline(always)] pub fn is_empty(&self) -> bool { self.map.is_empty() }
+
+    /// removes all elements and frees the nodes
+    #[inline]
+    pub fn clear(&mut self) {
+        self.map.clear()
+    }
+
+    /// returns the smallest element
+    pub fn first(&self) -> Option<&T> {
+        self.map.first_key_value().map(|(k, _)| k)
+    }
+
+    /// returns the largest element
+    pub fn last(&self) -> Option<&T> {
+        self.map.last_key_value().map(|(k, _)| k)
+    }
+
+    /// iterates over the elements in order
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter(self.map.keys())
+    }
+}
+
+impl<T: Ord> BTreeSet<T> {
+
+    /// Inserts `value`, returns `false` if it was present already
+    /// - the stored element is kept in that case
+    /// - **panics** if allocation fails
+    pub fn insert(&mut self, value: T) -> bool {
+        match self.try_insert(value) {
+            Ok(new) => new,
+            Err(_) => panic!("failed to allocate memory for BTreeSet"),
+        }
+    }
+
+    /// Tries to insert `value`, returns `false` if it was present already
+    /// - the stored element is kept in that case
+    /// - returns `Err` if allocation fails
+    ///     - in this case returns the ownership of `value`
+    pub fn try_insert(&mut self, value: T) -> Result<bool, T> {
+        self.map.try_insert(value, ())
+            .map(|old| old.is_none())
+            .map_err(|(value, _)| value)
+    }
+
+    #[inline]
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where T: Borrow<Q>, Q: Ord + ?Sized {
+        self.map.contains_key(value)
+    }
+
+    /// returns the stored element equal to `value`
+    pub fn get<Q>(&self, value: &Q) -> Option<&T>
+    where T: Borrow<Q>, Q: Ord + ?Sized {
+        self.map.get_key_value(value).map(|(k, _)| k)
+    }
+
+    /// removes `value`, returns `true` if it was present
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where T: Borrow<Q>, Q: Ord + ?Sized {
+        self.map.remove(value).is_some()
+    }
+
+    /// removes `value` and returns the stored element
+    pub fn take<Q>(&mut self, value: &Q) -> Option<T>
+    where T: Borrow<Q>, Q: Ord + ?Sized {
+        self.map.remove_entry(value).map(|(k, _)| k)
+    }
+
+    /// removes the smallest element
+    pub fn pop_first(&mut self) -> Option<T> {
+        self.map.pop_first().map(|(k, _)| k)
+    }
+
+    /// removes the largest element
+    pub fn pop_last(&mut self) -> Option<T> {
+        self.map.pop_last().map(|(k, _)| k)
+    }
+
+    /// iterates over the elements in `range` in order
+    /// - **panics** if the range starts after it ends
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, T>
+    where T: Borrow<Q>, Q: Ord + ?Sized, R: RangeBounds<Q> {
+        Range(self.map.range(range))
+    }
+
+    /// checks if `self` and `other` have no element in common
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.iter().all(|v| !other.contains(v))
+    }
+
+    /// checks if every element of `self` is in `other`
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.len() <= other.len() && self.iter().all(|v| other.contains(v))
+    }
+}
+
+impl<T: Ord + Clone> Clone for BTreeSet<T> {
+    /// **panics** if allocation fails
+    fn clone(&self) -> Self {
+        Self { map: self.map.clone() }
+    }
+}
+
+impl<T: Ord + TryClone> TryClone for BTreeSet<T> {
+    type Error = ();
+
+    fn try_clone(&self) -> Result<Self, Self::Error>
+    where Self: Sized, Self::Error: Default {
+        Ok(Self { map: self.map.clone_with(|k, _| Ok((k.try_clone().map_err(|_| ())?, ())))? })
+    }
+}
+
+impl<T> Default for BTreeSet<T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Debug> Debug for BTreeSet<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for BTreeSet<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.map == other.map
+    }
+}
+
+impl<T: Eq> Eq for BTreeSet<T> {}
+
+impl<T: Ord> FromIterator<T> for BTreeSet<T> {
+    /// **panics** if allocation fails
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<T: Ord> Extend<T> for BTreeSet<T> {
+    /// **panics** if allocation fails
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for v in iter {
+            self.insert(v);
+        }
+    }
+}
+
+impl<'l, T> IntoIterator for &'l BTreeSet<T> {
+    type Item = &'l T;
+    type IntoIter = Iter<'l, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: Ord> IntoIterator for BTreeSet<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self.map.into_iter())
+    }
+}
+
+
+/// Iterator over `&T` in order
+pub struct Iter<'l, T>(btree_map::Keys<'l, T, ()>);
+
+impl<'l, T> Iterator for Iter<'l, T> {
+    type Item = &'l T;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+impl<T> FusedIterator for Iter<'_, T> {}
+
+
+/// Iterator over `&T` of a range, returned by [`BTreeSet::range`]
+pub struct Range<'l, T>(btree_map::Range<'l, T, ()>);
+
+impl<'l, T> Iterator for Range<'l, T> {
+    type Item = &'l T;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(k, _)| k)
+    }
+}
+
+impl<T> FusedIterator for Range<'_, T> {}
+
+
+/// Owning iterator over `T` in order
+/// - the elements that are not taken are dropped with the iterator
+pub struct IntoIter<T: Ord>(btree_map::IntoIter<T, ()>);
+
+impl<T: Ord> Iterator for IntoIter<T> {
+    type Item = T;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(k, _)| k)
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<T: Ord> DoubleEndedIterator for IntoIter<T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(k, _)| k)
+    }
+}
+
+impl<T: Ord> ExactSizeIterator for IntoIter<T> {}
+impl<T: Ord> FusedIterator for IntoIter<T> {}
@@ -40,6 +40,10 @@ pub struct DynamicBuffer<T: Sized, const STEP: usize = 4> {
     _marker: PhantomData<T>,
 }
 
+//  the buffer owns its memory
+unsafe impl<T: Send, const STEP: usize> Send for DynamicBuffer<T, STEP> {}
+unsafe impl<T: Sync, const STEP: usize> Sync for DynamicBuffer<T, STEP> {}
+
 impl<T: Sized, const STEP: usize> DynamicBuffer<T, STEP> {
 
     /// returns `Layout` describing memory layout for `self`
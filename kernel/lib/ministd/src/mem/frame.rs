@@ -0,0 +1,392 @@
+//	mem/frame.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  physical frame allocator
+//  - manages every USABLE entry of the bootloader memory map
+//  - buddy allocator, the free lists are stored in the free frames themselves,
+//    so it never allocates from the heap (the heap grows using it)
+//  - addresses are physical, use [`crate::mem::hhdm_offset()`] to access the memory
+
+
+use spin::Mutex;
+use bootloader::MEMMAP;
+use limine_rs::memory_map::EntryType;
+
+use crate::mem::{KB, Region};
+use crate::mem::alloc::ALLOCATOR;
+
+
+/// size of one physical frame
+pub const FRAME_SIZE: usize = 4 * KB;
+
+/// buddy order (largest block is 2^(ORDER - 1) frames)
+const ORDER: usize = 33;
+
+/// end of a free list
+const NONE: usize = usize::MAX;
+
+
+/// frame counts of the [`FRAMES`] allocator
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct FrameStats {
+    pub total: usize,
+    pub used: usize,
+}
+
+impl FrameStats {
+    #[inline(always)]
+    pub fn free(&self) -> usize {
+        self.total - self.used
+    }
+}
+
+
+/// # Buddy frame allocator
+/// - a free block of `2^n` frames is aligned to its size and is kept in list `n`
+/// - the first frame of a free block holds the physical address of the next block in its list
+pub struct FrameAllocator {
+    /// first block of each free list
+    free: [usize; ORDER],
+    /// virtual address of physical address 0
+    offset: usize,
+    total: usize,
+    used: usize,
+}
+
+impl FrameAllocator {
+    pub const fn new() -> Self {
+        Self {
+            free: [NONE; ORDER],
+            offset: 0,
+            total: 0,
+            used: 0,
+        }
+    }
+
+    /// sets where the physical memory is mapped (it is identity mapped by default)
+    /// # Safety
+    /// - must be called before any memory is added
+    pub unsafe fn set_offset(&mut self, offset: usize) {
+        self.offset = offset;
+    }
+
+    /// adds physical memory `[start, end)` to the allocator
+    /// - unaligned edges are cut off
+    /// # Safety
+    /// - the memory must be unused, writable at `start + offset` and must not be added twice
+    pub unsafe fn add_region(&mut self, start: usize, end: usize) {
+        let mut frame = start.div_ceil(FRAME_SIZE);
+        let last = end / FRAME_SIZE;
+
+        while frame < last {
+            //  largest block aligned to its size that fits
+            let align = match frame {
+                0 => ORDER - 1,
+                _ => frame.trailing_zeros() as usize,
+            };
+            let order = align.min((last - frame).ilog2() as usize).min(ORDER - 1);
+
+            unsafe { self.insert(frame * FRAME_SIZE, order) };
+            frame += 1 << order;
+            self.total += 1 << order;
+        }
+    }
+
+    /// allocates `count` contiguous frames
+    /// - returns physical address of the first frame
+    /// - the block is aligned to `count` rounded up to power of 2 frames
+    pub fn alloc(&mut self, count: usize) -> Result<usize, ()> {
+        if count == 0 {
+            return Err(());
+        }
+        let order = Self::order(count)?;
+
+        let mut current = (order..ORDER).find(|&o| self.free[o] != NONE).ok_or(())?;
+        let block = unsafe { self.pop(current) };
+
+        //  upper halves of the larger block are returned
+        while current > order {
+            current -= 1;
+            unsafe { self.push(block + (FRAME_SIZE << current), current) };
+        }
+
+        self.used += 1 << order;
+        Ok(block)
+    }
+
+    /// frees frames allocated by [`FrameAllocator::alloc`]
+    /// # Safety
+    /// - `address` must be returned by [`FrameAllocator::alloc`] and `count` must be the same as at the allocation
+    /// - the frames must not be used anymore
+    pub unsafe fn free(&mut self, address: usize, count: usize) {
+        if count == 0 {
+            return;
+        }
+        let Ok(order) = Self::order(count) else {
+            return;
+        };
+        unsafe { self.insert(address, order) };
+        self.used -= 1 << order;
+    }
+
+    #[inline]
+    pub fn stats(&self) -> FrameStats {
+        FrameStats {
+            total: self.total,
+            used: self.used,
+        }
+    }
+
+
+    /// returns order of the block that holds `count` frames
+    fn order(count: usize) -> Result<usize, ()> {
+        let order = count.checked_next_power_of_two().ok_or(())?.trailing_zeros() as usize;
+        if order < ORDER {
+            Ok(order)
+        } else {
+            Err(())
+        }
+    }
+
+    /// adds free block of order `order`, merged with its free buddies
+    unsafe fn insert(&mut self, mut address: usize, mut order: usize) {
+        while order < ORDER - 1 {
+            let buddy = address ^ (FRAME_SIZE << order);
+            if !unsafe { self.remove(buddy, order) } {
+                break;
+            }
+            address = address.min(buddy);
+            order += 1;
+        }
+        unsafe { self.push(address, order) };
+    }
+
+    /// pointer to the list link stored in the free block at `address`
+    #[inline]
+    fn link(&self, address: usize) -> *mut usize {
+        (address + self.offset) as *mut usize
+    }
+
+    unsafe fn push(&mut self, address: usize, order: usize) {
+        unsafe { self.link(address).write(self.free[order]) };
+        self.free[order] = address;
+    }
+
+    /// the list `order` must not be empty
+    unsafe fn pop(&mut self, order: usize) -> usize {
+        let block = self.free[order];
+        self.free[order] = unsafe { self.link(block).read() };
+        block
+    }
+
+    /// removes block `address` from list `order`
+    /// - returns `false` if the block is not there
+    unsafe fn remove(&mut self, address: usize, order: usize) -> bool {
+        let mut prev: Option<usize> = None;
+        let mut current = self.free[order];
+
+        while current != NONE {
+            let next = unsafe { self.link(current).read() };
+            if current == address {
+                match prev {
+                    Some(p) => unsafe { self.link(p).write(next) },
+                    None => self.free[order] = next,
+                }
+                return true;
+            }
+            prev = Some(current);
+            current = next;
+        }
+        false
+    }
+}
+
+impl Default for FrameAllocator {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// global physical frame allocator
+pub static FRAMES: Mutex<FrameAllocator> = Mutex::new(FrameAllocator::new());
+
+
+/// allocates `count` contiguous physical frames
+/// - returns physical address of the first one
+#[inline]
+pub fn alloc_frames(count: usize) -> Result<usize, ()> {
+    FRAMES.lock().alloc(count)
+}
+
+/// same as [`alloc_frames`] but returns `Err` instead of waiting for the allocator lock
+/// - used when growing the heap (the heap may run out while the allocator is locked)
+#[inline]
+pub fn try_alloc_frames(count: usize) -> Result<usize, ()> {
+    FRAMES.try_lock().ok_or(())?.alloc(count)
+}
+
+/// allocates one physical frame
+#[inline]
+pub fn alloc_frame() -> Result<usize, ()> {
+    alloc_frames(1)
+}
+
+/// returns frames obtained from [`alloc_frames`]
+/// # Safety
+/// - `address` must come from [`alloc_frames`] with the same `count`
+/// - the frames must not be freed twice or used afterwards
+#[inline]
+pub unsafe fn free_frames(address: usize, count: usize) {
+    unsafe { FRAMES.lock().free(address, count) }
+}
+
+/// returns frame obtained from [`alloc_frame`]
+/// # Safety
+/// - same as [`free_frames`] with `count` 1
+#[inline]
+pub unsafe fn free_frame(address: usize) {
+    unsafe { free_frames(address, 1) }
+}
+
+/// returns the number of total and used frames
+#[inline]
+pub fn stats() -> FrameStats {
+    FRAMES.lock().stats()
+}
+
+
+/// adds physical `[start, end)` without the parts covered by `heap` (virtual regions)
+fn add_without(frames: &mut FrameAllocator, start: usize, end: usize, heap: &[Region], hhdm: usize) {
+    let Some((region, rest)) = heap.split_first() else {
+        unsafe { frames.add_region(start, end) };
+        return;
+    };
+
+    let heap_start = region.start().wrapping_sub(hhdm);
+    let heap_end = heap_start + region.size();
+
+    if heap_end <= start || heap_start >= end {
+        add_without(frames, start, end, rest, hhdm);
+    } else {
+        if start < heap_start {
+            add_without(frames, start, heap_start, rest, hhdm);
+        }
+        if heap_end < end {
+            add_without(frames, heap_end, end, rest, hhdm);
+        }
+    }
+}
+
+/// seeds [`FRAMES`] with every USABLE memory map entry
+/// - memory of the heap is left out
+/// - must be called after the heap is initialized
+pub(crate) fn init() -> Result<(), ()> {
+    let hhdm = crate::mem::hhdm_offset().ok_or(())?;
+    let entries = MEMMAP.get_response().ok_or(())?.entries();
+
+    //  copy, the heap may grow while frames are added
+    let heap = ALLOCATOR.regions();
+
+    let mut frames = FRAMES.lock();
+    unsafe { frames.set_offset(hhdm) };
+
+    for entry in entries {
+        if entry.entry_type != EntryType::USABLE {
+            continue;
+        }
+        let start = entry.base as usize;
+        add_without(&mut frames, start, start + entry.length as usize, heap.as_slice(), hhdm);
+    }
+
+    if frames.stats().total == 0 {
+        Err(())
+    } else {
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// frames of the fake physical memory
+    const FRAMES_IN: usize = 64;
+
+    /// allocator over leaked memory at physical address 0
+    fn allocator() -> FrameAllocator {
+        let memory = std::vec![0u64; FRAMES_IN * FRAME_SIZE / 8].leak();
+        let mut frames = FrameAllocator::new();
+        unsafe { frames.set_offset(memory.as_mut_ptr() as usize) };
+        frames
+    }
+
+    #[test]
+    fn alloc_and_free() {
+        let mut frames = allocator();
+        unsafe { frames.add_region(0, FRAMES_IN * FRAME_SIZE) };
+        assert_eq!(frames.stats(), FrameStats { total: FRAMES_IN, used: 0 });
+
+        let one = frames.alloc(1).unwrap();
+        let three = frames.alloc(3).unwrap();
+        let sixteen = frames.alloc(16).unwrap();
+        assert_eq!(three % (4 * FRAME_SIZE), 0);
+        assert_eq!(sixteen % (16 * FRAME_SIZE), 0);
+        assert_eq!(frames.stats().used, 1 + 4 + 16);
+        assert!(frames.alloc(FRAMES_IN).is_err());
+
+        unsafe {
+            frames.free(three, 3);
+            frames.free(one, 1);
+            frames.free(sixteen, 16);
+        }
+        assert_eq!(frames.stats().used, 0);
+
+        //  everything merged back into one block
+        assert_eq!(frames.alloc(FRAMES_IN), Ok(0));
+    }
+
+    #[test]
+    fn unaligned_region() {
+        let mut frames = allocator();
+        //  frames 1..10 are fully inside
+        unsafe { frames.add_region(100, 10 * FRAME_SIZE + 5) };
+        assert_eq!(frames.stats().total, 9);
+
+        //  blocks are 1, 2..4, 4..8 and 8..10
+        assert!(frames.alloc(8).is_err());
+        assert_eq!(frames.alloc(4), Ok(4 * FRAME_SIZE));
+        assert_eq!(frames.alloc(2), Ok(8 * FRAME_SIZE));
+        assert_eq!(frames.alloc(2), Ok(2 * FRAME_SIZE));
+        assert_eq!(frames.alloc(1), Ok(FRAME_SIZE));
+        assert!(frames.alloc(1).is_err());
+    }
+
+    #[test]
+    fn exhaustion() {
+        let mut frames = allocator();
+        unsafe {
+            frames.add_region(0, 32 * FRAME_SIZE);
+            frames.add_region(40 * FRAME_SIZE, FRAMES_IN * FRAME_SIZE);
+        }
+        assert!(frames.alloc(0).is_err());
+
+        let mut taken = std::vec::Vec::new();
+        while let Ok(frame) = frames.alloc(1) {
+            assert!(!taken.contains(&frame));
+            taken.push(frame);
+        }
+        assert_eq!(taken.len(), 56);
+        assert_eq!(frames.stats().free(), 0);
+
+        for frame in taken {
+            unsafe { frames.free(frame, 1) };
+        }
+        assert_eq!(frames.alloc(32), Ok(0));
+        assert_eq!(frames.alloc(16), Ok(48 * FRAME_SIZE));
+        assert_eq!(frames.alloc(8), Ok(40 * FRAME_SIZE));
+        assert!(frames.alloc(1).is_err());
+    }
+}
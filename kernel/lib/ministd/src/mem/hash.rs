@@ -0,0 +1,68 @@
+//	mem/hash.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  hashers for [`HashMap`](crate::HashMap) and [`HashSet`](crate::HashSet)
+//  - any `BuildHasher` can be plugged in through the `S` parameter of the collections
+//  - the default is FNV-1a with a final mix, it is fast but not resistant against chosen keys
+//    (there is no source of randomness this early, and kernel keys are not chosen by attackers)
+
+use core::hash::{BuildHasher, Hasher};
+
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+
+/// FNV-1a hasher
+#[derive(Copy, Clone, Debug)]
+pub struct FnvHasher(u64);
+
+impl FnvHasher {
+    pub const fn new() -> Self {
+        Self(FNV_OFFSET)
+    }
+}
+
+impl Default for FnvHasher {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hasher for FnvHasher {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 = (self.0 ^ b as u64).wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    /// spreads the high bits into the low ones, the tables index with the low bits
+    #[inline]
+    fn finish(&self) -> u64 {
+        let mut h = self.0;
+        h ^= h >> 33;
+        h = h.wrapping_mul(0xff51_afd7_ed55_8ccd);
+        h ^= h >> 33;
+        h
+    }
+}
+
+
+/// Creates [`FnvHasher`]s
+#[derive(Copy, Clone, Default, Debug)]
+pub struct BuildFnvHasher;
+
+impl BuildHasher for BuildFnvHasher {
+    type Hasher = FnvHasher;
+
+    #[inline(always)]
+    fn build_hasher(&self) -> FnvHasher {
+        FnvHasher::new()
+    }
+}
+
+/// hasher used when none is given
+pub type DefaultHashBuilder = BuildFnvHasher;
@@ -0,0 +1,914 @@
+//	mem/hash_map.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  hash map with open addressing
+//  - buckets live in one `DynamicBuffer` with a power of two capacity, colliding keys take the next free bucket
+//  - every bucket stores the full hash of its key, 0 marks a free bucket (hashes get their top bit set)
+//  - removal shifts the following entries back instead of leaving tombstones
+//  - the table grows once it is 3/4 full, it never shrinks on its own
+//  - `try_*` functions return `Err` instead of panicking when the allocator runs out of memory
+
+use core::borrow::Borrow;
+use core::fmt::Debug;
+use core::hash::{BuildHasher, Hash};
+use core::iter::FusedIterator;
+use core::mem::{self, MaybeUninit};
+use core::ops::Index;
+
+use crate::mem::DynamicBuffer;
+use crate::mem::hash::DefaultHashBuilder;
+use crate::TryClone;
+
+
+/// hash of a free bucket
+const EMPTY: u64 = 0;
+/// set in every stored hash, so no key hashes to [`EMPTY`]
+const OCCUPIED: u64 = 1 << 63;
+/// smallest allocated table
+const MIN_BUCKETS: usize = 8;
+
+
+struct Bucket<K, V> {
+    hash: u64,
+    key: MaybeUninit<K>,
+    value: MaybeUninit<V>,
+}
+
+impl<K, V> Bucket<K, V> {
+    #[inline(always)]
+    fn is_empty(&self) -> bool {
+        self.hash == EMPTY
+    }
+
+    /// `self` must be occupied
+    #[inline(always)]
+    unsafe fn key(&self) -> &K {
+        unsafe { self.key.assume_init_ref() }
+    }
+
+    /// moves the entry out and marks the bucket free
+    /// - `self` must be occupied
+    #[inline(always)]
+    unsafe fn take(&mut self) -> (K, V) {
+        self.hash = EMPTY;
+        unsafe { (self.key.assume_init_read(), self.value.assume_init_read()) }
+    }
+}
+
+
+/// number of entries a table of `buckets` holds before it grows
+#[inline(always)]
+const fn max_load(buckets: usize) -> usize {
+    buckets - buckets / 4
+}
+
+/// smallest table that holds `len` entries
+fn buckets_for(len: usize) -> Result<usize, ()> {
+    let buckets = len.checked_mul(4).ok_or(())?.div_ceil(3).max(MIN_BUCKETS);
+    buckets.checked_next_power_of_two().filter(|&b| b <= u32::MAX as usize).ok_or(())
+}
+
+
+/// Hash map with open addressing
+///
+/// ### Generic parameters
+/// 1. `K`: key type, must implement `Hash` and `Eq`
+/// 2. `V`: value type
+/// 3. `S`: builds the hashers (see [`crate::mem::hash`])
+pub struct HashMap<K, V, S = DefaultHashBuilder> {
+    buckets: DynamicBuffer<Bucket<K, V>, 0>,
+    len: usize,
+    hasher: S,
+}
+
+impl<K, V> HashMap<K, V, DefaultHashBuilder> {
+
+    /// Constructs new empty `HashMap` (nothing is allocated)
+    pub const fn new() -> Self {
+        Self::with_hasher(crate::mem::hash::BuildFnvHasher)
+    }
+}
+
+impl<K: Hash + Eq, V> HashMap<K, V, DefaultHashBuilder> {
+
+    /// Constructs new empty `HashMap` that holds at least `capacity` entries without growing
+    /// - **panics** if allocation fails
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::try_with_capacity(capacity).expect("failed to allocate memory for HashMap")
+    }
+
+    /// Tries to construct new empty `HashMap` that holds at least `capacity` entries without growing
+    /// - returns `Err` if allocation fails
+    pub fn try_with_capacity(capacity: usize) -> Result<Self, ()> {
+        let mut map = Self::new();
+        map.try_reserve(capacity)?;
+        Ok(map)
+    }
+}
+
+impl<K, V, S> HashMap<K, V, S> {
+
+    /// Constructs new empty `HashMap` which uses `hasher` (nothing is allocated)
+    pub const fn with_hasher(hasher: S) -> Self {
+        Self {
+            buckets: DynamicBuffer::empty(),
+            len: 0,
+            hasher,
+        }
+    }
+
+    #[inline(always)] pub fn len(&self) -> usize { self.len }
+    #[inline(always)] pub fn is_empty(&self) -> bool { self.len == 0 }
+    #[inline(always)] pub fn hasher(&self) -> &S { &self.hasher }
+
+    /// number of entries the map holds before it grows
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        if self.buckets.has_data() { max_load(self.buckets.capacity()) } else { 0 }
+    }
+
+    #[inline(always)]
+    fn mask(&self) -> usize {
+        self.buckets.capacity() - 1
+    }
+
+    #[inline(always)]
+    fn slots(&self) -> &[Bucket<K, V>] {
+        if self.buckets.has_data() {
+            unsafe { core::slice::from_raw_parts(self.buckets.as_ptr(), self.buckets.capacity()) }
+        } else {
+            &[]
+        }
+    }
+
+    #[inline(always)]
+    fn slots_mut(&mut self) -> &mut [Bucket<K, V>] {
+        if self.buckets.has_data() {
+            unsafe { core::slice::from_raw_parts_mut(self.buckets.as_ptr(), self.buckets.capacity()) }
+        } else {
+            &mut []
+        }
+    }
+
+    /// removes all entries, the memory is kept
+    pub fn clear(&mut self) {
+        for bucket in self.slots_mut() {
+            if !bucket.is_empty() {
+                drop(unsafe { bucket.take() });
+            }
+        }
+        self.len = 0;
+    }
+
+    /// keeps only the entries `f` returns `true` for
+    pub fn retain<F: FnMut(&K, &mut V) -> bool>(&mut self, mut f: F) {
+        let Some(start) = self.slots().iter().position(Bucket::is_empty) else { return };
+        let mask = self.mask();
+
+        //  removal only moves entries of the same run back, starting after a free bucket
+        //  none of them moves before the current position
+        let mut i = (start + 1) & mask;
+        while i != start {
+            let bucket = &mut self.slots_mut()[i];
+            if !bucket.is_empty() {
+                let (key, value) = unsafe { (bucket.key.assume_init_ref(), bucket.value.assume_init_mut()) };
+                let keep = f(key, value);
+                if !keep {
+                    drop(self.remove_at(i));
+                    continue;
+                }
+            }
+            i = (i + 1) & mask;
+        }
+    }
+
+    /// `index` must be a free bucket
+    #[inline]
+    fn write_at(&mut self, index: usize, hash: u64, key: K, value: V) -> &mut V {
+        self.len += 1;
+        let bucket = &mut self.slots_mut()[index];
+        bucket.hash = hash;
+        bucket.key.write(key);
+        bucket.value.write(value)
+    }
+
+    /// removes entry at `index` and moves the entries behind it back
+    fn remove_at(&mut self, mut index: usize) -> (K, V) {
+        let mask = self.mask();
+        let slots = self.slots_mut();
+        let entry = unsafe { slots[index].take() };
+
+        let mut next = index;
+        loop {
+            next = (next + 1) & mask;
+            if slots[next].is_empty() {
+                break;
+            }
+            let ideal = slots[next].hash as usize & mask;
+            //  the entry may fill the hole if its ideal bucket is not between the hole and itself
+            if (next.wrapping_sub(ideal) & mask) >= (next.wrapping_sub(index) & mask) {
+                slots.swap(index, next);
+                index = next;
+            }
+        }
+        self.len -= 1;
+        entry
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            slots: self.slots().iter(),
+            left: self.len,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        let left = self.len;
+        IterMut {
+            slots: self.slots_mut().iter_mut(),
+            left,
+        }
+    }
+
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys(self.iter())
+    }
+
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values(self.iter())
+    }
+
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut(self.iter_mut())
+    }
+
+    /// removes all entries and returns them
+    /// - entries that are not taken from the iterator are dropped
+    pub fn drain(&mut self) -> IntoIter<K, V> {
+        let buckets = mem::replace(&mut self.buckets, DynamicBuffer::empty());
+        let len = mem::take(&mut self.len);
+        IntoIter {
+            buckets,
+            index: 0,
+            left: len,
+        }
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> HashMap<K, V, S> {
+
+    #[inline]
+    fn make_hash<Q: Hash + ?Sized>(&self, key: &Q) -> u64 {
+        self.hasher.hash_one(key) | OCCUPIED
+    }
+
+    /// returns index of the bucket holding `key`
+    fn find<Q>(&self, hash: u64, key: &Q) -> Option<usize>
+    where K: Borrow<Q>, Q: Eq + ?Sized {
+        let slots = self.slots();
+        if self.len == 0 {
+            return None;
+        }
+        let mask = self.mask();
+        let mut i = hash as usize & mask;
+        //  the table is never full, so there is a free bucket
+        loop {
+            let bucket = &slots[i];
+            if bucket.is_empty() {
+                return None;
+            }
+            if bucket.hash == hash && unsafe { bucket.key() }.borrow() == key {
+                return Some(i);
+            }
+            i = (i + 1) & mask;
+        }
+    }
+
+    /// returns index of the bucket holding `key` or of the free bucket where it belongs
+    /// - the table must have a free bucket
+    fn find_or_free(&self, hash: u64, key: &K) -> usize {
+        let slots = self.slots();
+        let mask = self.mask();
+        let mut i = hash as usize & mask;
+        loop {
+            let bucket = &slots[i];
+            if bucket.is_empty() || (bucket.hash == hash && unsafe { bucket.key() } == key) {
+                return i;
+            }
+            i = (i + 1) & mask;
+        }
+    }
+
+    /// moves all entries into a table of `buckets`
+    /// - returns `Err` if allocation fails, the map is unchanged then
+    fn try_rehash(&mut self, buckets: usize) -> Result<(), ()> {
+        let new = DynamicBuffer::<Bucket<K, V>, 0>::try_with_capacity(buckets)?;
+        let new_slots = unsafe { core::slice::from_raw_parts_mut(new.as_ptr(), new.capacity()) };
+        for bucket in new_slots.iter_mut() {
+            bucket.hash = EMPTY;
+        }
+
+        let mask = new.capacity() - 1;
+        for bucket in self.slots_mut() {
+            if bucket.is_empty() {
+                continue;
+            }
+            let mut i = bucket.hash as usize & mask;
+            while !new_slots[i].is_empty() {
+                i = (i + 1) & mask;
+            }
+            new_slots[i].hash = bucket.hash;
+            unsafe {
+                new_slots[i].key.write(bucket.key.assume_init_read());
+                new_slots[i].value.write(bucket.value.assume_init_read());
+            }
+            bucket.hash = EMPTY;
+        }
+
+        //  the old buffer only frees its memory, the entries were moved
+        self.buckets = new;
+        Ok(())
+    }
+
+    /// Reserves space for at least `additional` more entries
+    /// - **panics** if allocation fails
+    pub fn reserve(&mut self, additional: usize) {
+        self.try_reserve(additional).expect("failed to allocate memory for HashMap")
+    }
+
+    /// Tries to reserve space for at least `additional` more entries
+    /// - returns `Err` if allocation fails
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), ()> {
+        let needed = self.len.checked_add(additional).ok_or(())?;
+        if needed <= self.capacity() && self.buckets.has_data() {
+            return Ok(());
+        }
+        self.try_rehash(buckets_for(needed)?)
+    }
+
+    /// shrinks the table as much as possible
+    /// - returns `Err` if allocation fails
+    pub fn try_shrink_to_fit(&mut self) -> Result<(), ()> {
+        if self.len == 0 {
+            self.buckets = DynamicBuffer::empty();
+            return Ok(());
+        }
+        let buckets = buckets_for(self.len)?;
+        if buckets < self.buckets.capacity() {
+            self.try_rehash(buckets)?;
+        }
+        Ok(())
+    }
+
+    /// Inserts entry, returns the old value if `key` was present
+    /// - **panics** if allocation fails
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.try_insert(key, value) {
+            Ok(old) => old,
+            Err(_) => panic!("failed to allocate memory for HashMap"),
+        }
+    }
+
+    /// Tries to insert entry, returns the old value if `key` was present
+    /// - returns `Err` if allocation fails
+    ///     - in this case returns the ownership of `key` and `value`
+    pub fn try_insert(&mut self, key: K, value: V) -> Result<Option<V>, (K, V)> {
+        let hash = self.make_hash(&key);
+        if let Some(i) = self.find(hash, &key) {
+            let old = mem::replace(unsafe { self.slots_mut()[i].value.assume_init_mut() }, value);
+            return Ok(Some(old));
+        }
+        if self.try_reserve(1).is_err() {
+            return Err((key, value));
+        }
+        let i = self.find_or_free(hash, &key);
+        self.write_at(i, hash, key, value);
+        Ok(None)
+    }
+
+    /// returns value of `key`, it is inserted from `f` if it is missing
+    /// - **panics** if allocation fails
+    pub fn get_or_insert_with<F: FnOnce() -> V>(&mut self, key: K, f: F) -> &mut V {
+        match self.try_get_or_insert_with(key, f) {
+            Ok(value) => value,
+            Err(_) => panic!("failed to allocate memory for HashMap"),
+        }
+    }
+
+    /// returns value of `key`, it is inserted from `f` if it is missing
+    /// - returns `Err` if allocation fails (`f` is not called then)
+    ///     - in this case returns the ownership of `key`
+    pub fn try_get_or_insert_with<F: FnOnce() -> V>(&mut self, key: K, f: F) -> Result<&mut V, K> {
+        let hash = self.make_hash(&key);
+        let i = match self.find(hash, &key) {
+            Some(i) => i,
+            None => {
+                if self.try_reserve(1).is_err() {
+                    return Err(key);
+                }
+                let i = self.find_or_free(hash, &key);
+                self.write_at(i, hash, key, f());
+                i
+            }
+        };
+        Ok(unsafe { self.slots_mut()[i].value.assume_init_mut() })
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where K: Borrow<Q>, Q: Hash + Eq + ?Sized {
+        self.get_key_value(key).map(|(_, v)| v)
+    }
+
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where K: Borrow<Q>, Q: Hash + Eq + ?Sized {
+        let i = self.find(self.make_hash(key), key)?;
+        let bucket = &self.slots()[i];
+        Some(unsafe { (bucket.key(), bucket.value.assume_init_ref()) })
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where K: Borrow<Q>, Q: Hash + Eq + ?Sized {
+        let i = self.find(self.make_hash(key), key)?;
+        Some(unsafe { self.slots_mut()[i].value.assume_init_mut() })
+    }
+
+    #[inline]
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where K: Borrow<Q>, Q: Hash + Eq + ?Sized {
+        self.find(self.make_hash(key), key).is_some()
+    }
+
+    /// removes `key` and returns its value
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where K: Borrow<Q>, Q: Hash + Eq + ?Sized {
+        self.remove_entry(key).map(|(_, v)| v)
+    }
+
+    /// removes `key` and returns the stored key and value
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where K: Borrow<Q>, Q: Hash + Eq + ?Sized {
+        let i = self.find(self.make_hash(key), key)?;
+        Some(self.remove_at(i))
+    }
+}
+
+impl<K, V, S: Clone> HashMap<K, V, S> {
+
+    /// copies the table, entries keep their buckets
+    /// - `clone` is called for every entry and stops at the first error
+    /// - returns `Err` if allocation or `clone` fails
+    pub(crate) fn clone_with<F>(&self, mut clone: F) -> Result<Self, ()>
+    where F: FnMut(&K, &V) -> Result<(K, V), ()> {
+        let mut map = Self::with_hasher(self.hasher.clone());
+        if !self.buckets.has_data() {
+            return Ok(map);
+        }
+        map.buckets = DynamicBuffer::try_with_capacity(self.buckets.capacity())?;
+        for bucket in map.slots_mut() {
+            bucket.hash = EMPTY;
+        }
+
+        for (i, bucket) in self.slots().iter().enumerate() {
+            if bucket.is_empty() {
+                continue;
+            }
+            //  on error `map` drops the entries cloned so far
+            let (key, value) = clone(unsafe { bucket.key() }, unsafe { bucket.value.assume_init_ref() })?;
+            map.write_at(i, bucket.hash, key, value);
+        }
+        Ok(map)
+    }
+}
+
+impl<K, V, S> Drop for HashMap<K, V, S> {
+    fn drop(&mut self) {
+        if mem::needs_drop::<(K, V)>() {
+            self.clear();
+        }
+    }
+}
+
+impl<K: Clone, V: Clone, S: Clone> Clone for HashMap<K, V, S> {
+    /// **panics** if allocation fails
+    fn clone(&self) -> Self {
+        self.clone_with(|k, v| Ok((k.clone(), v.clone())))
+            .expect("failed to allocate memory for HashMap")
+    }
+}
+
+impl<K: TryClone, V: TryClone, S: Clone> TryClone for HashMap<K, V, S> {
+    type Error = ();
+
+    fn try_clone(&self) -> Result<Self, Self::Error>
+    where Self: Sized, Self::Error: Default {
+        self.clone_with(|k, v| match (k.try_clone(), v.try_clone()) {
+            (Ok(k), Ok(v)) => Ok((k, v)),
+            _ => Err(()),
+        })
+    }
+}
+
+impl<K, V, S: Default> Default for HashMap<K, V, S> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K: Debug, V: Debug, S> Debug for HashMap<K, V, S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Hash + Eq, V: PartialEq, S: BuildHasher> PartialEq for HashMap<K, V, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+
+impl<K: Hash + Eq, V: Eq, S: BuildHasher> Eq for HashMap<K, V, S> {}
+
+impl<K, Q, V, S> Index<&Q> for HashMap<K, V, S>
+where K: Hash + Eq + Borrow<Q>, Q: Hash + Eq + ?Sized, S: BuildHasher {
+    type Output = V;
+
+    /// **panics** if `key` is missing
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("key is not in HashMap")
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Default> FromIterator<(K, V)> for HashMap<K, V, S> {
+    /// **panics** if allocation fails
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> Extend<(K, V)> for HashMap<K, V, S> {
+    /// **panics** if allocation fails
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<'l, K, V, S> IntoIterator for &'l HashMap<K, V, S> {
+    type Item = (&'l K, &'l V);
+    type IntoIter = Iter<'l, K, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'l, K, V, S> IntoIterator for &'l mut HashMap<K, V, S> {
+    type Item = (&'l K, &'l mut V);
+    type IntoIter = IterMut<'l, K, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K, V, S> IntoIterator for HashMap<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+    fn into_iter(mut self) -> Self::IntoIter {
+        self.drain()
+    }
+}
+
+
+/// Iterator over `(&K, &V)` in bucket order
+pub struct Iter<'l, K, V> {
+    slots: core::slice::Iter<'l, Bucket<K, V>>,
+    left: usize,
+}
+
+impl<'l, K, V> Iterator for Iter<'l, K, V> {
+    type Item = (&'l K, &'l V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bucket = self.slots.find(|b| !b.is_empty())?;
+        self.left -= 1;
+        Some(unsafe { (bucket.key(), bucket.value.assume_init_ref()) })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.left, Some(self.left))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+impl<K, V> FusedIterator for Iter<'_, K, V> {}
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self { slots: self.slots.clone(), left: self.left }
+    }
+}
+
+
+/// Iterator over `(&K, &mut V)` in bucket order
+pub struct IterMut<'l, K, V> {
+    slots: core::slice::IterMut<'l, Bucket<K, V>>,
+    left: usize,
+}
+
+impl<'l, K, V> Iterator for IterMut<'l, K, V> {
+    type Item = (&'l K, &'l mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bucket = self.slots.find(|b| !b.is_empty())?;
+        self.left -= 1;
+        Some(unsafe { (bucket.key.assume_init_ref(), bucket.value.assume_init_mut()) })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.left, Some(self.left))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+impl<K, V> FusedIterator for IterMut<'_, K, V> {}
+
+
+pub struct Keys<'l, K, V>(Iter<'l, K, V>);
+
+impl<'l, K, V> Iterator for Keys<'l, K, V> {
+    type Item = &'l K;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(k, _)| k)
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Keys<'_, K, V> {}
+impl<K, V> FusedIterator for Keys<'_, K, V> {}
+
+
+pub struct Values<'l, K, V>(Iter<'l, K, V>);
+
+impl<'l, K, V> Iterator for Values<'l, K, V> {
+    type Item = &'l V;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(_, v)| v)
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Values<'_, K, V> {}
+impl<K, V> FusedIterator for Values<'_, K, V> {}
+
+
+pub struct ValuesMut<'l, K, V>(IterMut<'l, K, V>);
+
+impl<'l, K, V> Iterator for ValuesMut<'l, K, V> {
+    type Item = &'l mut V;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(_, v)| v)
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for ValuesMut<'_, K, V> {}
+impl<K, V> FusedIterator for ValuesMut<'_, K, V> {}
+
+
+/// Owning iterator over `(K, V)`, returned by [`HashMap::into_iter`] and [`HashMap::drain`]
+/// - the entries that are not taken are dropped with the iterator
+pub struct IntoIter<K, V> {
+    buckets: DynamicBuffer<Bucket<K, V>, 0>,
+    index: usize,
+    left: usize,
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.left > 0 {
+            let bucket = unsafe { &mut *self.buckets.as_ptr().add(self.index) };
+            self.index += 1;
+            if !bucket.is_empty() {
+                self.left -= 1;
+                return Some(unsafe { bucket.take() });
+            }
+        }
+        None
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.left, Some(self.left))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {}
+impl<K, V> FusedIterator for IntoIter<K, V> {}
+
+impl<K, V> Drop for IntoIter<K, V> {
+    fn drop(&mut self) {
+        self.for_each(drop);
+    }
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mem::test_util::{Rng, Counted};
+    use core::hash::Hasher;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use std::collections::HashMap as StdMap;
+    use std::vec::Vec;
+
+    /// puts every key into one of 4 hashes, so probe chains get long and wrap around
+    #[derive(Clone, Default)]
+    struct Colliding;
+
+    struct CollidingHasher(u64);
+
+    impl Hasher for CollidingHasher {
+        fn write(&mut self, bytes: &[u8]) {
+            for b in bytes {
+                self.0 = self.0.wrapping_add(*b as u64);
+            }
+        }
+        fn finish(&self) -> u64 {
+            //  three of the chains start near the end of the table and wrap around
+            self.0 % 4 * 0x1fff_ffff_ffff_fffd
+        }
+    }
+
+    impl BuildHasher for Colliding {
+        type Hasher = CollidingHasher;
+        fn build_hasher(&self) -> CollidingHasher {
+            CollidingHasher(0)
+        }
+    }
+
+    fn assert_same<S: BuildHasher>(map: &HashMap<u32, u32, S>, std: &StdMap<u32, u32>) {
+        assert_eq!(map.len(), std.len());
+        assert!(map.len() <= map.capacity());
+        assert_eq!(map.iter().count(), std.len());
+        for (k, v) in map.iter() {
+            assert_eq!(std.get(k), Some(v));
+        }
+    }
+
+    fn against_std<S: BuildHasher>(mut map: HashMap<u32, u32, S>, rounds: u32, keys: u32) {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        let mut std = StdMap::new();
+
+        for round in 0..rounds {
+            let key = rng.next(keys);
+            match rng.next(4) {
+                0 | 1 => assert_eq!(map.insert(key, round), std.insert(key, round)),
+                2 => assert_eq!(map.remove(&key), std.remove(&key)),
+                _ => assert_eq!(map.remove_entry(&key), std.remove_entry(&key)),
+            }
+            assert_eq!(map.get(&key), std.get(&key));
+            assert_eq!(map.contains_key(&key), std.contains_key(&key));
+            if round % 500 == 0 {
+                assert_same(&map, &std);
+            }
+        }
+        assert_same(&map, &std);
+
+        map.retain(|k, v| {
+            *v = *k;
+            k % 3 != 0
+        });
+        std.retain(|k, _| k % 3 != 0);
+        assert_eq!(map.len(), std.len());
+        assert!(map.iter().all(|(k, v)| k == v && std.contains_key(k)));
+    }
+
+    #[test]
+    fn insert_and_remove() {
+        against_std(HashMap::new(), 50_000, 3000);
+    }
+
+    #[test]
+    fn insert_and_remove_colliding() {
+        //  removal has to shift back entries of other chains too
+        against_std(HashMap::with_hasher(Colliding), 5000, 300);
+    }
+
+    #[test]
+    fn capacity() {
+        let mut map: HashMap<u32, u32> = HashMap::new();
+        assert_eq!(map.capacity(), 0);
+        assert_eq!(map.get(&1), None);
+        assert_eq!(map.remove(&1), None);
+
+        map.reserve(100);
+        let capacity = map.capacity();
+        assert!(capacity >= 100);
+        for k in 0..capacity as u32 {
+            map.insert(k, k);
+        }
+        assert_eq!(map.capacity(), capacity);
+        map.insert(capacity as u32, 0);
+        assert!(map.capacity() > capacity);
+
+        map.retain(|k, _| *k < 10);
+        map.try_shrink_to_fit().unwrap();
+        assert!(map.capacity() >= 10 && map.capacity() < capacity);
+        assert!((0..10).all(|k| map.get(&k) == Some(&k)));
+
+        map.clear();
+        assert!(map.is_empty());
+        map.try_shrink_to_fit().unwrap();
+        assert_eq!(map.capacity(), 0);
+    }
+
+    #[test]
+    fn get_or_insert_and_index() {
+        let mut map: HashMap<&str, u32> = HashMap::new();
+        *map.get_or_insert_with("a", || 1) += 1;
+        *map.get_or_insert_with("a", || 10) += 1;
+        assert_eq!(map["a"], 3);
+        *map.get_mut("a").unwrap() = 5;
+        assert_eq!(map.get_key_value("a"), Some((&"a", &5)));
+        assert_eq!(map.try_insert("b", 2), Ok(None));
+        assert_eq!(map.try_insert("b", 3), Ok(Some(2)));
+        for v in map.values_mut() {
+            *v *= 10;
+        }
+        let mut values: Vec<u32> = map.values().copied().collect();
+        values.sort();
+        assert_eq!(values, [30, 50]);
+    }
+
+    #[test]
+    fn drain_and_into_iter() {
+        let mut map: HashMap<u32, u32> = (0..100).map(|k| (k, k + 1)).collect();
+        let mut drained: Vec<(u32, u32)> = map.drain().collect();
+        drained.sort();
+        assert!(drained.into_iter().eq((0..100).map(|k| (k, k + 1))));
+        assert!(map.is_empty());
+        assert_eq!(map.get(&5), None);
+
+        map.extend((0..50).map(|k| (k, k)));
+        let copy = map.clone();
+        assert!(copy == map);
+        let mut keys: Vec<u32> = copy.into_iter().map(|(k, _)| k).collect();
+        keys.sort();
+        assert!(keys.into_iter().eq(0..50));
+    }
+
+    #[test]
+    fn drops() {
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+        let mut map = HashMap::new();
+        for k in 0..1000 {
+            map.insert(k, Counted(&DROPS));
+        }
+
+        //  replaced values are returned, not dropped by the map
+        drop(map.insert(10, Counted(&DROPS)));
+        drop(map.remove(&20));
+        assert_eq!(DROPS.load(Ordering::SeqCst), 2);
+
+        map.retain(|k, _| *k >= 100);
+        assert_eq!(DROPS.load(Ordering::SeqCst), 2 + 99);
+
+        let mut iter = map.drain();
+        let taken: Vec<_> = iter.by_ref().take(100).collect();
+        drop(iter);
+        assert!(map.is_empty());
+        assert_eq!(DROPS.load(Ordering::SeqCst), 101 + 800);
+        drop(taken);
+        assert_eq!(DROPS.load(Ordering::SeqCst), 1001);
+
+        for k in 0..100 {
+            map.insert(k, Counted(&DROPS));
+        }
+        drop(map);
+        assert_eq!(DROPS.load(Ordering::SeqCst), 1101);
+    }
+}
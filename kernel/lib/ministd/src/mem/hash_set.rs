@@ -0,0 +1,312 @@
+//	mem/hash_set.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  hash set, a [`HashMap`] without values
+//  - see [`crate::mem::hash_map`] for how the table works
+
+use core::borrow::Borrow;
+use core::fmt::Debug;
+use core::hash::{BuildHasher, Hash};
+use core::iter::FusedIterator;
+
+use crate::mem::hash::DefaultHashBuilder;
+use crate::mem::hash_map::{self, HashMap};
+use crate::TryClone;
+
+
+/// Hash set with open addressing
+///
+/// ### Generic parameters
+/// 1. `T`: element type, must implement `Hash` and `Eq`
+/// 2. `S`: builds the hashers (see [`crate::mem::hash`])
+pub struct HashSet<T, S = DefaultHashBuilder> {
+    map: HashMap<T, (), S>,
+}
+
+impl<T> HashSet<T, DefaultHashBuilder> {
+
+    /// Constructs new empty `HashSet` (nothing is allocated)
+    pub const fn new() -> Self {
+        Self { map: HashMap::new() }
+    }
+}
+
+impl<T: Hash + Eq> HashSet<T, DefaultHashBuilder> {
+
+    /// Constructs new empty `HashSet` that holds at least `capacity` elements without growing
+    /// - **panics** if allocation fails
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self { map: HashMap::with_capacity(capacity) }
+    }
+
+    /// Tries to construct new empty `HashSet` that holds at least `capacity` elements without growing
+    /// - returns `Err` if allocation fails
+    pub fn try_with_capacity(capacity: usize) -> Result<Self, ()> {
+        Ok(Self { map: HashMap::try_with_capacity(capacity)? })
+    }
+}
+
+impl<T, S> HashSet<T, S> {
+
+    /// Constructs new empty `HashSet` which uses `hasher` (nothing is allocated)
+    pub const fn with_hasher(hasher: S) -> Self {
+        Self { map: HashMap::with_hasher(hasher) }
+    }
+
+    #[inline(always)] pub fn len(&self) -> usize { self.map.len() }
+    #[inline(always)] pub fn is_empty(&self) -> bool { self.map.is_empty() }
+    #[inline(always)] pub fn hasher(&self) -> &S { self.map.hasher() }
+
+    /// number of elements the set holds before it grows
+    #[inline(always)]
+    pub fn capacity(&self) -> usize {
+        self.map.capacity()
+    }
+
+    /// removes all elements, the memory is kept
+    #[inline]
+    pub fn clear(&mut self) {
+        self.map.clear()
+    }
+
+    /// keeps only the elements `f` returns `true` for
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        self.map.retain(|k, _| f(k))
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter(self.map.keys())
+    }
+
+    /// removes all elements and returns them
+    /// - elements that are not taken from the iterator are dropped
+    pub fn drain(&mut self) -> IntoIter<T> {
+        IntoIter(self.map.drain())
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher> HashSet<T, S> {
+
+    /// Reserves space for at least `additional` more elements
+    /// - **panics** if allocation fails
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.map.reserve(additional)
+    }
+
+    /// Tries to reserve space for at least `additional` more elements
+    /// - returns `Err` if allocation fails
+    #[inline]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), ()> {
+        self.map.try_reserve(additional)
+    }
+
+    /// shrinks the table as much as possible
+    /// - returns `Err` if allocation fails
+    #[inline]
+    pub fn try_shrink_to_fit(&mut self) -> Result<(), ()> {
+        self.map.try_shrink_to_fit()
+    }
+
+    /// Inserts `value`, returns `false` if it was present already
+    /// - the stored element is kept in that case
+    /// - **panics** if allocation fails
+    pub fn insert(&mut self, value: T) -> bool {
+        match self.try_insert(value) {
+            Ok(new) => new,
+            Err(_) => panic!("failed to allocate memory for HashSet"),
+        }
+    }
+
+    /// Tries to insert `value`, returns `false` if it was present already
+    /// - the stored element is kept in that case
+    /// - returns `Err` if allocation fails
+    ///     - in this case returns the ownership of `value`
+    pub fn try_insert(&mut self, value: T) -> Result<bool, T> {
+        if self.map.contains_key(&value) {
+            return Ok(false);
+        }
+        self.map.try_insert(value, ())
+            .map(|_| true)
+            .map_err(|(value, _)| value)
+    }
+
+    #[inline]
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where T: Borrow<Q>, Q: Hash + Eq + ?Sized {
+        self.map.contains_key(value)
+    }
+
+    /// returns the stored element equal to `value`
+    pub fn get<Q>(&self, value: &Q) -> Option<&T>
+    where T: Borrow<Q>, Q: Hash + Eq + ?Sized {
+        self.map.get_key_value(value).map(|(k, _)| k)
+    }
+
+    /// removes `value`, returns `true` if it was present
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where T: Borrow<Q>, Q: Hash + Eq + ?Sized {
+        self.map.remove(value).is_some()
+    }
+
+    /// removes `value` and returns the stored element
+    pub fn take<Q>(&mut self, value: &Q) -> Option<T>
+    where T: Borrow<Q>, Q: Hash + Eq + ?Sized {
+        self.map.remove_entry(value).map(|(k, _)| k)
+    }
+
+    /// checks if `self` and `other` have no element in common
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.iter().all(|v| !other.contains(v))
+    }
+
+    /// checks if every element of `self` is in `other`
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.len() <= other.len() && self.iter().all(|v| other.contains(v))
+    }
+}
+
+impl<T: Clone, S: Clone> Clone for HashSet<T, S> {
+    /// **panics** if allocation fails
+    fn clone(&self) -> Self {
+        Self { map: self.map.clone() }
+    }
+}
+
+impl<T: TryClone, S: Clone> TryClone for HashSet<T, S> {
+    type Error = ();
+
+    fn try_clone(&self) -> Result<Self, Self::Error>
+    where Self: Sized, Self::Error: Default {
+        Ok(Self { map: self.map.clone_with(|k, _| Ok((k.try_clone().map_err(|_| ())?, ())))? })
+    }
+}
+
+impl<T, S: Default> Default for HashSet<T, S> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<T: Debug, S> Debug for HashSet<T, S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher> PartialEq for HashSet<T, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.is_subset(other)
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher> Eq for HashSet<T, S> {}
+
+impl<T: Hash + Eq, S: BuildHasher + Default> FromIterator<T> for HashSet<T, S> {
+    /// **panics** if allocation fails
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = Self::default();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher> Extend<T> for HashSet<T, S> {
+    /// **panics** if allocation fails
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.map.extend(iter.into_iter().map(|v| (v, ())))
+    }
+}
+
+impl<'l, T, S> IntoIterator for &'l HashSet<T, S> {
+    type Item = &'l T;
+    type IntoIter = Iter<'l, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T, S> IntoIterator for HashSet<T, S> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self.map.into_iter())
+    }
+}
+
+
+/// Iterator over `&T` in bucket order
+pub struct Iter<'l, T>(hash_map::Keys<'l, T, ()>);
+
+impl<'l, T> Iterator for Iter<'l, T> {
+    type Item = &'l T;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+impl<T> FusedIterator for Iter<'_, T> {}
+
+
+/// Owning iterator over `T`
+/// - the elements that are not taken are dropped with the iterator
+pub struct IntoIter<T>(hash_map::IntoIter<T, ()>);
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(k, _)| k)
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+impl<T> FusedIterator for IntoIter<T> {}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn set_operations() {
+        let mut even: HashSet<u32> = (0..100).map(|v| v * 2).collect();
+        let odd: HashSet<u32> = (0..100).map(|v| v * 2 + 1).collect();
+        assert_eq!(even.len(), 100);
+        assert!(even.is_disjoint(&odd));
+
+        assert!(!even.insert(4));
+        assert_eq!(even.try_insert(5), Ok(true));
+        assert!(!even.is_disjoint(&odd));
+        assert!(even.contains(&5));
+        assert_eq!(even.get(&5), Some(&5));
+        assert_eq!(even.take(&5), Some(5));
+        assert!(!even.remove(&5));
+
+        let small: HashSet<u32> = [2, 4, 6].into_iter().collect();
+        assert!(small.is_subset(&even));
+        assert!(!even.is_subset(&small));
+        assert!(small != even);
+        assert!(small == [6, 4, 2].into_iter().collect());
+
+        even.retain(|v| *v < 10);
+        let mut left: Vec<u32> = even.drain().collect();
+        left.sort();
+        assert_eq!(left, [0, 2, 4, 6, 8]);
+        assert!(even.is_empty());
+    }
+}
@@ -16,16 +16,38 @@ pub use core::mem::needs_drop;
 pub mod readonly;
 pub use readonly::ReadOnly;
 pub mod alloc;
+pub mod frame;
+pub mod paging;
 pub mod boxed;
 pub mod array;
 pub mod dynamic_buffer;
+pub mod rc;
+pub mod arc;
+pub mod hash;
+pub mod hash_map;
+pub mod hash_set;
+pub mod btree_map;
+pub mod btree_set;
 
 pub mod string;
 pub mod vec;
+pub mod vec_deque;
+pub mod binary_heap;
+
+#[cfg(test)]
+mod test_util;
 
 pub use dynamic_buffer::DynamicBuffer;
 
 
+/// returns offset of the higher half direct map
+/// - physical address `p` is mapped at `p + hhdm_offset()`
+#[inline]
+pub fn hhdm_offset() -> Option<usize> {
+    bootloader::HHDM.get_response().map(|r| r.offset() as usize)
+}
+
+
 
 #[derive(Copy, Clone)]
 pub struct Region {
@@ -78,5 +100,16 @@ impl Region {
     pub fn size(&self) -> usize {
         self.size
     }
+    /// returns the first address after the region
+    #[inline]
+    pub fn end(&self) -> usize {
+        self.start + self.size
+    }
+
+    /// checks if the two regions share any address
+    #[inline]
+    pub fn overlaps(&self, other: &Region) -> bool {
+        self.start < other.end() && other.start < self.end()
+    }
 
 }
\ No newline at end of file
@@ -0,0 +1,382 @@
+//	mem/paging.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  page table manager
+//  - edits the page tables the bootloader left active (4- or 5-level)
+//  - tables are accessed through the HHDM
+//  - intermediate tables are allocated from a [`FrameSource`] (the frame allocator by default)
+
+
+use core::ops::{BitAnd, BitOr, BitOrAssign, Not};
+use spin::Mutex;
+use limine_rs::paging::Mode;
+
+use crate::arch;
+use crate::mem::frame::{self, FRAME_SIZE};
+
+
+pub const PAGE_SIZE: usize = FRAME_SIZE;
+
+/// number of entries in one page table
+pub const ENTRY_COUNT: usize = 512;
+
+/// bits 12..52 of an entry
+const ADDRESS_MASK: u64 = 0x000f_ffff_ffff_f000;
+const CR4_LA57: u64 = 1 << 12;
+const IA32_EFER: u32 = 0xc000_0080;
+const EFER_NXE: u64 = 1 << 11;
+
+
+/// flags of page table entries
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct PageFlags(u64);
+
+impl PageFlags {
+    pub const EMPTY: Self = Self(0);
+    pub const PRESENT: Self = Self(1 << 0);
+    pub const WRITABLE: Self = Self(1 << 1);
+    pub const USER: Self = Self(1 << 2);
+    pub const WRITE_THROUGH: Self = Self(1 << 3);
+    pub const NO_CACHE: Self = Self(1 << 4);
+    pub const ACCESSED: Self = Self(1 << 5);
+    pub const DIRTY: Self = Self(1 << 6);
+    /// 2 MiB / 1 GiB page (only in level 2 / level 3 entries)
+    pub const HUGE: Self = Self(1 << 7);
+    pub const GLOBAL: Self = Self(1 << 8);
+    pub const NO_EXECUTE: Self = Self(1 << 63);
+
+    /// every bit that is not part of the address
+    const ALL: Self = Self(!ADDRESS_MASK);
+
+    #[inline(always)]
+    pub const fn from_bits(bits: u64) -> Self {
+        Self(bits & Self::ALL.0)
+    }
+    #[inline(always)]
+    pub const fn bits(&self) -> u64 {
+        self.0
+    }
+    #[inline(always)]
+    pub const fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for PageFlags {
+    type Output = Self;
+    #[inline(always)]
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for PageFlags {
+    #[inline(always)]
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl BitAnd for PageFlags {
+    type Output = Self;
+    #[inline(always)]
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl Not for PageFlags {
+    type Output = Self;
+    #[inline(always)]
+    fn not(self) -> Self {
+        Self(!self.0 & Self::ALL.0)
+    }
+}
+
+
+/// Source of physical frames for page tables
+pub trait FrameSource {
+    /// returns physical address of an unused frame
+    fn alloc_frame(&mut self) -> Result<usize, ()>;
+}
+
+/// uses the global frame allocator ([`crate::mem::frame`])
+pub struct GlobalFrames;
+
+impl FrameSource for GlobalFrames {
+    #[inline]
+    fn alloc_frame(&mut self) -> Result<usize, ()> {
+        frame::alloc_frame()
+    }
+}
+
+
+#[repr(C, align(4096))]
+pub struct PageTable {
+    pub entries: [u64; ENTRY_COUNT],
+}
+
+
+/// result of [`Mapper::translate`]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Translation {
+    pub phys: usize,
+    pub flags: PageFlags,
+    /// size of the page that maps the address
+    pub page_size: usize,
+}
+
+
+pub struct Mapper {
+    /// physical address of the root table
+    root: usize,
+    levels: usize,
+    hhdm: usize,
+    /// mask of flags supported by the CPU
+    supported: PageFlags,
+    initialized: bool,
+}
+
+impl Mapper {
+    pub const fn new() -> Self {
+        Self {
+            root: 0,
+            levels: 4,
+            hhdm: 0,
+            supported: PageFlags::EMPTY,
+            initialized: false,
+        }
+    }
+
+    /// uses the page tables that are active on this processor
+    fn init(&mut self, hhdm: usize, mode: Option<Mode>) {
+        let five_level = match mode {
+            Some(m) => m == Mode::FIVE_LEVEL,
+            None => arch::cr4() & CR4_LA57 != 0,
+        };
+
+        self.root = (arch::cr3() & ADDRESS_MASK) as usize;
+        self.levels = if five_level { 5 } else { 4 };
+        self.hhdm = hhdm;
+        self.supported = !PageFlags::EMPTY;
+        if unsafe { arch::rdmsr(IA32_EFER) } & EFER_NXE == 0 {
+            self.supported = !PageFlags::NO_EXECUTE;
+        }
+        self.initialized = true;
+    }
+
+    #[inline(always)] pub fn levels(&self) -> usize { self.levels }
+    #[inline(always)] pub fn root(&self) -> usize { self.root }
+    #[inline(always)] pub fn is_initialized(&self) -> bool { self.initialized }
+
+    /// returns the number of usable virtual address bits
+    #[inline(always)]
+    pub fn address_bits(&self) -> usize {
+        12 + 9 * self.levels
+    }
+
+    /// checks if `virt` is canonical (sign extended)
+    pub fn is_canonical(&self, virt: usize) -> bool {
+        let shift = self.address_bits() - 1;
+        let top = virt >> shift;
+        top == 0 || top == usize::MAX >> shift
+    }
+
+    #[inline(always)]
+    #[allow(clippy::mut_from_ref)]
+    fn table(&self, phys: usize) -> &mut PageTable {
+        unsafe { &mut *((phys + self.hhdm) as *mut PageTable) }
+    }
+
+    #[inline(always)]
+    fn index(virt: usize, level: usize) -> usize {
+        (virt >> (12 + 9 * (level - 1))) & (ENTRY_COUNT - 1)
+    }
+
+    /// returns the level 1 entry for `virt`
+    /// - creates missing tables if `source` is given
+    /// - returns `Err` if `virt` is mapped by a huge page
+    fn walk(&mut self, virt: usize, user: bool, mut source: Option<&mut dyn FrameSource>) -> Result<&mut u64, ()> {
+        if !self.initialized || !self.is_canonical(virt) {
+            return Err(());
+        }
+
+        let mut table = self.table(self.root);
+        for level in (2..=self.levels).rev() {
+            let entry = &mut table.entries[Self::index(virt, level)];
+
+            if *entry & PageFlags::PRESENT.0 == 0 {
+                let source = source.as_mut().ok_or(())?;
+                let new = source.alloc_frame()?;
+                unsafe { core::ptr::write_bytes((new + self.hhdm) as *mut u8, 0, PAGE_SIZE) };
+
+                //  leaf entries decide the actual permissions
+                *entry = new as u64 | (PageFlags::PRESENT | PageFlags::WRITABLE).0;
+            } else if *entry & PageFlags::HUGE.0 != 0 {
+                return Err(());
+            }
+            if user {
+                *entry |= PageFlags::USER.0;
+            }
+
+            table = self.table((*entry & ADDRESS_MASK) as usize);
+        }
+
+        Ok(&mut table.entries[Self::index(virt, 1)])
+    }
+
+    /// maps 4 KiB page at `virt` to `phys`
+    /// - `PRESENT` is added to `flags`
+    /// - returns `Err` if `virt` is already mapped or addresses are not aligned
+    pub fn map_with(&mut self, virt: usize, phys: usize, flags: PageFlags, source: &mut dyn FrameSource) -> Result<(), ()> {
+        if !virt.is_multiple_of(PAGE_SIZE) || !phys.is_multiple_of(PAGE_SIZE) || phys as u64 & !ADDRESS_MASK != 0 {
+            return Err(());
+        }
+        let flags = (flags | PageFlags::PRESENT) & self.supported;
+
+        let entry = self.walk(virt, flags.contains(PageFlags::USER), Some(source))?;
+        if *entry & PageFlags::PRESENT.0 != 0 {
+            return Err(());
+        }
+        *entry = phys as u64 | flags.0;
+        arch::invlpg(virt);
+        Ok(())
+    }
+
+    /// maps 4 KiB page using the global frame allocator for page tables
+    #[inline]
+    pub fn map(&mut self, virt: usize, phys: usize, flags: PageFlags) -> Result<(), ()> {
+        self.map_with(virt, phys, flags, &mut GlobalFrames)
+    }
+
+    /// unmaps 4 KiB page at `virt`
+    /// - returns physical address it was mapped to
+    /// - page tables are not freed
+    pub fn unmap(&mut self, virt: usize) -> Result<usize, ()> {
+        if !virt.is_multiple_of(PAGE_SIZE) {
+            return Err(());
+        }
+        let entry = self.walk(virt, false, None)?;
+        if *entry & PageFlags::PRESENT.0 == 0 {
+            return Err(());
+        }
+        let phys = (*entry & ADDRESS_MASK) as usize;
+        *entry = 0;
+        arch::invlpg(virt);
+        Ok(phys)
+    }
+
+    /// changes flags of 4 KiB page at `virt`
+    /// - `PRESENT` is added to `flags`
+    pub fn protect(&mut self, virt: usize, flags: PageFlags) -> Result<(), ()> {
+        if !virt.is_multiple_of(PAGE_SIZE) {
+            return Err(());
+        }
+        let flags = (flags | PageFlags::PRESENT) & self.supported;
+
+        let entry = self.walk(virt, flags.contains(PageFlags::USER), None)?;
+        if *entry & PageFlags::PRESENT.0 == 0 {
+            return Err(());
+        }
+        *entry = (*entry & ADDRESS_MASK) | flags.0;
+        arch::invlpg(virt);
+        Ok(())
+    }
+
+    /// returns physical address `virt` is mapped to
+    /// - huge pages are handled
+    pub fn translate(&self, virt: usize) -> Option<Translation> {
+        if !self.initialized || !self.is_canonical(virt) {
+            return None;
+        }
+
+        let mut table = self.table(self.root);
+        for level in (1..=self.levels).rev() {
+            let entry = table.entries[Self::index(virt, level)];
+            if entry & PageFlags::PRESENT.0 == 0 {
+                return None;
+            }
+
+            if level == 1 || (level <= 3 && entry & PageFlags::HUGE.0 != 0) {
+                let page_size = PAGE_SIZE << (9 * (level - 1));
+                let base = (entry & ADDRESS_MASK) as usize & !(page_size - 1);
+                return Some(Translation {
+                    phys: base + (virt & (page_size - 1)),
+                    flags: PageFlags::from_bits(entry),
+                    page_size,
+                });
+            }
+
+            table = self.table((entry & ADDRESS_MASK) as usize);
+        }
+        None
+    }
+
+    /// flushes whole TLB (except global pages)
+    #[inline]
+    pub fn flush_all(&self) {
+        unsafe { arch::set_cr3(arch::cr3()) };
+    }
+}
+
+impl Default for Mapper {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// manager of the active page tables
+pub static MAPPER: Mutex<Mapper> = Mutex::new(Mapper::new());
+
+
+/// maps 4 KiB page at `virt` to `phys` (see [`Mapper::map`])
+#[inline]
+pub fn map(virt: usize, phys: usize, flags: PageFlags) -> Result<(), ()> {
+    MAPPER.lock().map(virt, phys, flags)
+}
+
+/// unmaps 4 KiB page at `virt` (see [`Mapper::unmap`])
+#[inline]
+pub fn unmap(virt: usize) -> Result<usize, ()> {
+    MAPPER.lock().unmap(virt)
+}
+
+/// returns physical address `virt` is mapped to
+#[inline]
+pub fn translate(virt: usize) -> Option<usize> {
+    MAPPER.lock().translate(virt).map(|t| t.phys)
+}
+
+/// changes flags of 4 KiB page at `virt` (see [`Mapper::protect`])
+#[inline]
+pub fn protect(virt: usize, flags: PageFlags) -> Result<(), ()> {
+    MAPPER.lock().protect(virt, flags)
+}
+
+/// makes physical `[phys, phys + size)` accessible through the HHDM
+/// - pages that are not mapped yet are mapped uncached and non-executable
+/// - returns virtual address of `phys`
+pub fn map_mmio(phys: usize, size: usize) -> Result<usize, ()> {
+    let hhdm = crate::mem::hhdm_offset().ok_or(())?;
+    let flags = PageFlags::WRITABLE | PageFlags::NO_CACHE | PageFlags::NO_EXECUTE;
+
+    let mut mapper = MAPPER.lock();
+    let start = phys & !(PAGE_SIZE - 1);
+    for page in (start..phys + size.max(1)).step_by(PAGE_SIZE) {
+        if mapper.translate(page + hhdm).is_none() {
+            mapper.map(page + hhdm, page, flags)?;
+        }
+    }
+    Ok(phys + hhdm)
+}
+
+
+pub(crate) fn init() -> Result<(), ()> {
+    let hhdm = crate::mem::hhdm_offset().ok_or(())?;
+    let mode = bootloader::PAGING_MODE.get_response().map(|r| r.mode());
+    MAPPER.lock().init(hhdm, mode);
+    Ok(())
+}
@@ -0,0 +1,491 @@
+//	mem/rc.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  single-threaded reference counting pointers `Rc<T>` and `Weak<T>`
+//  - the value and both counters share one allocation from [`ALLOCATOR`]
+//  - all strong pointers together hold one weak reference, the allocation is freed once the weak count drops to 0
+//  - the value is dropped when the last `Rc` is dropped, `Weak` pointers can not upgrade afterwards
+//  - counters are not atomic, `Rc` is neither `Send` nor `Sync`
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::cell::Cell;
+use core::fmt::{Debug, Display};
+use core::mem::{ManuallyDrop, MaybeUninit};
+use core::ops::Deref;
+use core::ptr::{self, NonNull};
+
+use crate::{TryClone, Nothing};
+use crate::mem::alloc::ALLOCATOR;
+
+
+struct RcBox<T> {
+    strong: Cell<usize>,
+    /// number of `Weak` pointers + 1 while there is any `Rc`
+    weak: Cell<usize>,
+    value: ManuallyDrop<T>,
+}
+
+impl<T> RcBox<T> {
+    const fn layout() -> Layout {
+        Layout::new::<RcBox<T>>()
+    }
+
+    /// allocates box with strong and weak count 1
+    /// - gives `value` back if allocation fails
+    fn try_allocate(value: T) -> Result<NonNull<RcBox<T>>, T> {
+        let data: NonNull<MaybeUninit<RcBox<T>>> = match unsafe { ALLOCATOR.allocate_uninit() } {
+            Ok(data) => data,
+            Err(_) => return Err(value),
+        };
+        let data = data.cast::<RcBox<T>>();
+        unsafe {
+            data.as_ptr().write(RcBox {
+                strong: Cell::new(1),
+                weak: Cell::new(1),
+                value: ManuallyDrop::new(value),
+            });
+        }
+        Ok(data)
+    }
+
+    #[inline(always)]
+    fn inc_strong(&self) {
+        self.strong.set(self.strong.get() + 1);
+    }
+
+    #[inline(always)]
+    fn inc_weak(&self) {
+        self.weak.set(self.weak.get() + 1);
+    }
+
+    /// decrements the weak count and frees the box if it was the last reference
+    /// - `this` must not be used afterwards
+    unsafe fn release_weak(this: NonNull<RcBox<T>>) {
+        let weak = unsafe { this.as_ref() }.weak.get() - 1;
+        unsafe { this.as_ref() }.weak.set(weak);
+        if weak == 0 {
+            unsafe { ALLOCATOR.dealloc(this.as_ptr() as *mut u8, Self::layout()) };
+        }
+    }
+}
+
+
+/// `Rc` shares ownership of a heap allocated value on a single thread
+/// - cloning only increments the reference count
+/// - the value can not be mutated unless it is unique (see [`Rc::get_mut`] and [`Rc::make_mut`])
+pub struct Rc<T> {
+    ptr: NonNull<RcBox<T>>,
+}
+
+impl<T> Rc<T> {
+
+    /// Allocates `value` on heap
+    /// - panics if allocation fails
+    pub fn new(value: T) -> Self {
+        match Self::try_new(value) {
+            Ok(rc) => rc,
+            Err(_) => panic!("failed to allocate memory for Rc"),
+        }
+    }
+
+    /// Tries to allocate `value` on heap
+    /// - returns `Err` if allocation fails
+    pub fn try_new(value: T) -> Result<Self, ()> {
+        RcBox::try_allocate(value)
+            .map(|ptr| Self { ptr })
+            .map_err(|_| ())
+    }
+
+    #[inline(always)]
+    fn inner(&self) -> &RcBox<T> {
+        unsafe { self.ptr.as_ref() }
+    }
+
+    /// returns the number of `Rc` pointers to the value
+    #[inline]
+    pub fn strong_count(this: &Self) -> usize {
+        this.inner().strong.get()
+    }
+
+    /// returns the number of `Weak` pointers to the value
+    #[inline]
+    pub fn weak_count(this: &Self) -> usize {
+        this.inner().weak.get() - 1
+    }
+
+    /// checks if both point to the same allocation
+    #[inline]
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        this.ptr == other.ptr
+    }
+
+    /// returns pointer to the value
+    #[inline]
+    pub fn as_ptr(this: &Self) -> *const T {
+        &*this.inner().value as *const T
+    }
+
+    /// creates `Weak` pointer to the value
+    pub fn downgrade(this: &Self) -> Weak<T> {
+        this.inner().inc_weak();
+        Weak { ptr: Some(this.ptr) }
+    }
+
+    /// returns mutable reference if there is no other `Rc` or `Weak` pointer
+    pub fn get_mut(this: &mut Self) -> Option<&mut T> {
+        if Self::is_unique(this) {
+            Some(unsafe { &mut this.ptr.as_mut().value })
+        } else {
+            None
+        }
+    }
+
+    #[inline(always)]
+    fn is_unique(this: &Self) -> bool {
+        Self::strong_count(this) == 1 && Self::weak_count(this) == 0
+    }
+
+    /// returns the value if this is the only `Rc`, otherwise gives the `Rc` back
+    /// - `Weak` pointers can not upgrade afterwards
+    pub fn try_unwrap(this: Self) -> Result<T, Self> {
+        if Self::strong_count(&this) != 1 {
+            return Err(this);
+        }
+        let this = ManuallyDrop::new(this);
+        let inner = this.inner();
+        let value = unsafe { ptr::read(&*inner.value) };
+        inner.strong.set(0);
+        unsafe { RcBox::release_weak(this.ptr) };
+        Ok(value)
+    }
+
+    /// moves the value into a new allocation if `Weak` pointers are left
+    /// - `this` must be the only `Rc`
+    /// - returns `Err` if allocation fails
+    fn disassociate(this: &mut Self) -> Result<(), ()> {
+        let fresh = match unsafe { ALLOCATOR.allocate_uninit::<RcBox<T>>() } {
+            Ok(data) => data.cast::<RcBox<T>>(),
+            Err(_) => return Err(()),
+        };
+        let old = this.ptr;
+        unsafe {
+            fresh.as_ptr().write(RcBox {
+                strong: Cell::new(1),
+                weak: Cell::new(1),
+                value: ManuallyDrop::new(ptr::read(&*old.as_ref().value)),
+            });
+            old.as_ref().strong.set(0);
+            RcBox::release_weak(old);
+        }
+        this.ptr = fresh;
+        Ok(())
+    }
+}
+
+impl<T: Clone> Rc<T> {
+
+    /// returns mutable reference, the value is cloned first if it is shared
+    /// - `Weak` pointers lose the value if this is the only `Rc`
+    /// - panics if allocation fails
+    pub fn make_mut(this: &mut Self) -> &mut T {
+        if Self::strong_count(this) != 1 {
+            *this = Self::new((**this).clone());
+        } else if Self::weak_count(this) != 0 && Self::disassociate(this).is_err() {
+            panic!("failed to allocate memory for Rc");
+        }
+        unsafe { &mut this.ptr.as_mut().value }
+    }
+}
+
+impl<T: TryClone> Rc<T>
+where T::Error: Default {
+
+    /// same as [`Rc::make_mut`], but fails instead of panicking
+    /// - returns `Err` if cloning or allocation fails
+    pub fn try_make_mut(this: &mut Self) -> Result<&mut T, T::Error> {
+        if Self::strong_count(this) != 1 {
+            let value = (**this).try_clone()?;
+            *this = Self::try_new(value).map_err(|_| T::Error::default())?;
+        } else if Self::weak_count(this) != 0 {
+            Self::disassociate(this).map_err(|_| T::Error::default())?;
+        }
+        Ok(unsafe { &mut this.ptr.as_mut().value })
+    }
+}
+
+impl<T> Drop for Rc<T> {
+    fn drop(&mut self) {
+        let inner = self.inner();
+        let strong = inner.strong.get() - 1;
+        inner.strong.set(strong);
+        if strong == 0 {
+            unsafe {
+                ManuallyDrop::drop(&mut self.ptr.as_mut().value);
+                RcBox::release_weak(self.ptr);
+            }
+        }
+    }
+}
+
+impl<T> Clone for Rc<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        self.inner().inc_strong();
+        Self { ptr: self.ptr }
+    }
+}
+
+impl<T> TryClone for Rc<T> {
+    type Error = Nothing;
+
+    /// never fails, only the reference count changes
+    #[inline]
+    fn try_clone(&self) -> Result<Self, Self::Error> {
+        Ok(self.clone())
+    }
+}
+
+impl<T> Deref for Rc<T> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        &self.inner().value
+    }
+}
+
+impl<T> AsRef<T> for Rc<T> {
+    fn as_ref(&self) -> &T {
+        &self.inner().value
+    }
+}
+
+impl<T: Display> Display for Rc<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(&**self, f)
+    }
+}
+
+impl<T: Debug> Debug for Rc<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+
+/// `Weak` points to the value of an [`Rc`] without keeping it alive
+/// - use [`Weak::upgrade`] to access the value
+pub struct Weak<T> {
+    /// `None` for [`Weak::new`]
+    ptr: Option<NonNull<RcBox<T>>>,
+}
+
+impl<T> Weak<T> {
+
+    /// creates `Weak` pointer without value (nothing is allocated)
+    pub const fn new() -> Self {
+        Self { ptr: None }
+    }
+
+    #[inline(always)]
+    fn inner(&self) -> Option<&RcBox<T>> {
+        self.ptr.map(|ptr| unsafe { &*ptr.as_ptr() })
+    }
+
+    /// returns `Rc` to the value
+    /// - returns `None` if the value was dropped already
+    pub fn upgrade(&self) -> Option<Rc<T>> {
+        let inner = self.inner()?;
+        if inner.strong.get() == 0 {
+            return None;
+        }
+        inner.inc_strong();
+        self.ptr.map(|ptr| Rc { ptr })
+    }
+
+    /// returns the number of `Rc` pointers to the value
+    #[inline]
+    pub fn strong_count(&self) -> usize {
+        self.inner().map_or(0, |i| i.strong.get())
+    }
+
+    /// returns the number of `Weak` pointers to the value (0 once the value is dropped)
+    #[inline]
+    pub fn weak_count(&self) -> usize {
+        match self.inner() {
+            Some(i) if i.strong.get() > 0 => i.weak.get() - 1,
+            _ => 0,
+        }
+    }
+
+    /// checks if both point to the same allocation (or both are from [`Weak::new`])
+    #[inline]
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        self.ptr == other.ptr
+    }
+}
+
+impl<T> Drop for Weak<T> {
+    fn drop(&mut self) {
+        if let Some(ptr) = self.ptr {
+            unsafe { RcBox::release_weak(ptr) };
+        }
+    }
+}
+
+impl<T> Clone for Weak<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        if let Some(inner) = self.inner() {
+            inner.inc_weak();
+        }
+        Self { ptr: self.ptr }
+    }
+}
+
+impl<T> Default for Weak<T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Debug for Weak<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "(Weak)")
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mem::test_util::{Counted, isolated};
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn make_mut_clones_shared() {
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+        let mut a = Rc::new((1, Counted(&DROPS)));
+        let b = a.clone();
+        Rc::make_mut(&mut a).0 = 2;
+        assert!(!Rc::ptr_eq(&a, &b));
+        assert_eq!((a.0, b.0), (2, 1));
+        assert_eq!((Rc::strong_count(&a), Rc::strong_count(&b)), (1, 1));
+        assert_eq!(DROPS.load(Ordering::SeqCst), 0);
+
+        drop(a);
+        assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+        drop(b);
+        assert_eq!(DROPS.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn make_mut_unique() {
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+        let mut a = Rc::new((1, Counted(&DROPS)));
+        let ptr = Rc::as_ptr(&a);
+        Rc::make_mut(&mut a).0 = 2;
+        assert_eq!(Rc::as_ptr(&a), ptr);
+        assert_eq!(a.0, 2);
+
+        //  the value moves away from the `Weak`, nothing is cloned or dropped
+        let weak = Rc::downgrade(&a);
+        Rc::make_mut(&mut a).0 = 3;
+        assert!(weak.upgrade().is_none());
+        assert_eq!(Rc::weak_count(&a), 0);
+        assert_eq!(a.0, 3);
+        assert_eq!(DROPS.load(Ordering::SeqCst), 0);
+
+        drop(weak);
+        drop(a);
+        assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn try_unwrap() {
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+        let a = Rc::new((1, Counted(&DROPS)));
+        let b = a.clone();
+        let weak = Rc::downgrade(&a);
+        let a = Rc::try_unwrap(a).unwrap_err();
+        assert_eq!(Rc::strong_count(&a), 2);
+
+        drop(b);
+        let value = Rc::try_unwrap(a).unwrap();
+        assert_eq!(value.0, 1);
+        assert!(weak.upgrade().is_none());
+        assert_eq!(DROPS.load(Ordering::SeqCst), 0);
+
+        drop(value);
+        drop(weak);
+        assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn get_mut() {
+        let mut a = Rc::new(1);
+        *Rc::get_mut(&mut a).unwrap() = 2;
+
+        let b = a.clone();
+        assert!(Rc::get_mut(&mut a).is_none());
+        drop(b);
+
+        let weak = Rc::downgrade(&a);
+        assert!(Rc::get_mut(&mut a).is_none());
+        drop(weak);
+
+        assert_eq!(Rc::get_mut(&mut a), Some(&mut 2));
+    }
+
+    #[test]
+    fn weak_after_last_strong() {
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+        let a = Rc::new(Counted(&DROPS));
+        let weak = Rc::downgrade(&a);
+        let other = weak.clone();
+        assert_eq!((weak.strong_count(), weak.weak_count()), (1, 2));
+        assert!(weak.upgrade().is_some());
+
+        drop(a);
+        assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+        assert!(weak.upgrade().is_none());
+        assert_eq!((weak.strong_count(), weak.weak_count()), (0, 0));
+
+        drop(weak);
+        drop(other);
+        assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+        assert!(Weak::<u32>::new().upgrade().is_none());
+    }
+
+    #[test]
+    fn frees_allocation() {
+        isolated("mem::rc::tests::frees_allocation", || {
+            static DROPS: AtomicUsize = AtomicUsize::new(0);
+            let before = ALLOCATOR.allocated_bytes();
+
+            //  the last `Weak` frees the allocation
+            let a = Rc::new(Counted(&DROPS));
+            let weak = Rc::downgrade(&a);
+            drop(a);
+            assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+            assert!(ALLOCATOR.allocated_bytes() > before);
+            drop(weak);
+            assert_eq!(ALLOCATOR.allocated_bytes(), before);
+
+            //  both the clone and the moved value are freed
+            let mut a = Rc::new((1, Counted(&DROPS)));
+            let b = a.clone();
+            Rc::make_mut(&mut a);
+            let weak = Rc::downgrade(&a);
+            Rc::make_mut(&mut a);
+            drop((a, b, weak));
+            assert_eq!(DROPS.load(Ordering::SeqCst), 3);
+            assert_eq!(ALLOCATOR.allocated_bytes(), before);
+
+            let value = Rc::try_unwrap(Rc::new(Counted(&DROPS))).unwrap();
+            assert_eq!(ALLOCATOR.allocated_bytes(), before);
+            drop(value);
+            assert_eq!(DROPS.load(Ordering::SeqCst), 4);
+        });
+    }
+}
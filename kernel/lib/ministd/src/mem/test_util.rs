@@ -0,0 +1,62 @@
+//	mem/test_util.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  helpers shared by the host tests of the containers
+
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use std::process::Command;
+
+
+/// deterministic xorshift
+pub struct Rng(pub u64);
+
+impl Rng {
+    /// returns number in `0..bound`
+    pub fn next(&mut self, bound: u32) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % bound as u64) as u32
+    }
+}
+
+/// counts drops of the values it is stored in
+/// - clones count to the same counter
+#[derive(Debug)]
+pub struct Counted<'a>(pub &'a AtomicUsize);
+
+impl Clone for Counted<'_> {
+    fn clone(&self) -> Self {
+        Self(self.0)
+    }
+}
+
+impl Drop for Counted<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// set in the process started by [`isolated`]
+const ISOLATED: &str = "MINISTD_ISOLATED_TEST";
+
+/// runs `body` in a new process that runs test `name` alone
+/// - other tests share the heap, so its statistics are only exact without them
+/// - `name` is the path of the test inside the crate (e.g. `mem::rc::tests::frees`)
+/// - **panics** if the test fails in the new process
+pub fn isolated(name: &str, body: impl FnOnce()) {
+    if std::env::var_os(ISOLATED).is_some() {
+        body();
+        return;
+    }
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args([name, "--exact", "--test-threads=1", "--nocapture"])
+        .env(ISOLATED, "1")
+        .output()
+        .unwrap();
+    let stdout = std::string::String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}{}", stdout, std::string::String::from_utf8_lossy(&output.stderr));
+    assert!(stdout.contains("1 passed"), "test {} not found", name);
+}
@@ -0,0 +1,713 @@
+//	mem/vec_deque.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  double-ended queue implemented as a growable ring buffer
+//  - elements live in one `DynamicBuffer`, starting at `head` and wrapping around its end
+//  - pushing and popping at both ends is `O(1)`, the buffer grows by `STEP` like `Vec`
+//  - growing copies the elements to the start of the new buffer in order
+//  - `try_*` functions return `Err` instead of panicking when the allocator runs out of memory
+
+use core::fmt::Debug;
+use core::iter::FusedIterator;
+use core::mem::MaybeUninit;
+use core::ops::{Index, IndexMut};
+use core::ptr;
+use core::slice::{self, from_raw_parts, from_raw_parts_mut};
+
+use crate::mem::DynamicBuffer;
+use crate::TryClone;
+
+
+/// Double-ended queue implemented as a ring buffer
+///
+/// ### Generic parameters
+/// 1. `T`: datatype of each element
+/// 2. `STEP`: indicates how much will the queue grow
+///     - geometrical growth is used by default
+pub struct VecDeque<T: Sized, const STEP: usize = 0> {
+    /// `data.size` is not used, the elements are not contiguous
+    data: DynamicBuffer<T, STEP>,
+    /// index of the first element in `data`
+    head: usize,
+    len: usize,
+}
+
+impl<T: Sized, const STEP: usize> VecDeque<T, STEP> {
+
+    /// Constructs new empty `VecDeque` (nothing is allocated)
+    pub const fn new() -> Self {
+        Self {
+            data: DynamicBuffer::empty(),
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Constructs new empty `VecDeque` with at least the specified capacity allocated
+    /// - **panics** if allocation fails
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::try_with_capacity(capacity).expect("failed to allocate memory for VecDeque")
+    }
+
+    /// Tries to construct new empty `VecDeque` with at least the specified capacity allocated
+    /// - returns `Err` if allocation fails
+    pub fn try_with_capacity(capacity: usize) -> Result<Self, ()> {
+        let mut deque = Self::new();
+        deque.try_reserve(capacity)?;
+        Ok(deque)
+    }
+
+    #[inline(always)] pub const fn len(&self) -> usize { self.len }
+    #[inline(always)] pub const fn is_empty(&self) -> bool { self.len == 0 }
+    #[inline(always)] pub const fn capacity(&self) -> usize { self.data.capacity() }
+    #[inline(always)] pub const fn step(&self) -> usize { STEP }
+
+    /// returns index in `data` of element `index`
+    /// - `index` must be smaller than `capacity`
+    #[inline(always)]
+    fn physical(&self, index: usize) -> usize {
+        let i = self.head + index;
+        if i >= self.capacity() { i - self.capacity() } else { i }
+    }
+
+    #[inline(always)]
+    fn slot(&self, index: usize) -> *mut T {
+        unsafe { self.data.as_ptr().add(self.physical(index)) }
+    }
+
+    /// moves the elements into a new buffer of at least `capacity` elements
+    /// - returns `Err` if allocation fails, nothing is changed then
+    fn try_grow_to(&mut self, capacity: usize) -> Result<(), ()> {
+        let new = DynamicBuffer::<T, STEP>::try_with_capacity(capacity)?;
+        let (a, b) = self.as_slices();
+        unsafe {
+            ptr::copy_nonoverlapping(a.as_ptr(), new.as_ptr(), a.len());
+            ptr::copy_nonoverlapping(b.as_ptr(), new.as_ptr().add(a.len()), b.len());
+        }
+        //  the old buffer only frees its memory, the elements were moved
+        self.data = new;
+        self.head = 0;
+        Ok(())
+    }
+
+    /// grows by `STEP` if the buffer is full
+    #[inline]
+    fn try_grow(&mut self) -> Result<(), ()> {
+        if self.len == self.capacity() {
+            self.try_grow_to(DynamicBuffer::<T, STEP>::cap_next(self.capacity()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Reserves capacity for at least `additional` more elements
+    /// - **panics** if allocation fails
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.try_reserve(additional).expect("failed to allocate memory for VecDeque")
+    }
+
+    /// Tries to reserve capacity for at least `additional` more elements
+    /// - returns `Err` if allocation fails
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), ()> {
+        let needed = self.len.checked_add(additional).ok_or(())?;
+        if needed > self.capacity() {
+            self.try_grow_to(needed)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Shrinks the capacity of the queue as much as possible
+    /// - returns `Err` if allocation fails
+    pub fn try_shrink_to_fit(&mut self) -> Result<(), ()> {
+        if self.len == 0 {
+            self.data = DynamicBuffer::empty();
+            self.head = 0;
+            Ok(())
+        } else if DynamicBuffer::<T, STEP>::new_capacity(self.len) < self.capacity() {
+            self.try_grow_to(self.len)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Appends element at the back of the queue
+    /// - **panics** if allocation fails
+    pub fn push_back(&mut self, val: T) {
+        if self.try_push_back(val).is_err() {
+            panic!("failed to allocate memory for VecDeque");
+        }
+    }
+
+    /// Tries to append element at the back of the queue
+    /// - returns `Err` if allocation fails
+    ///     - in this case returns the ownership of `val`
+    pub fn try_push_back(&mut self, val: T) -> Result<(), T> {
+        if self.try_grow().is_err() {
+            return Err(val);
+        }
+        unsafe { self.slot(self.len).write(val) };
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Prepends element at the front of the queue
+    /// - **panics** if allocation fails
+    pub fn push_front(&mut self, val: T) {
+        if self.try_push_front(val).is_err() {
+            panic!("failed to allocate memory for VecDeque");
+        }
+    }
+
+    /// Tries to prepend element at the front of the queue
+    /// - returns `Err` if allocation fails
+    ///     - in this case returns the ownership of `val`
+    pub fn try_push_front(&mut self, val: T) -> Result<(), T> {
+        if self.try_grow().is_err() {
+            return Err(val);
+        }
+        self.head = self.physical(self.capacity() - 1);
+        unsafe { self.slot(0).write(val) };
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Removes and returns the first element
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let val = unsafe { self.slot(0).read() };
+        self.head = self.physical(1);
+        self.len -= 1;
+        Some(val)
+    }
+
+    /// Removes and returns the last element
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        Some(unsafe { self.slot(self.len).read() })
+    }
+
+    /// Inserts `val` at `index`, the elements on the shorter side move
+    /// - this is `O(min(index, len - index))` operation
+    /// - **panics** if allocation fails or `index > self.len()`
+    pub fn insert(&mut self, index: usize, val: T) {
+        if index > self.len {
+            panic!("index is out of bounds");
+        }
+        if self.try_insert(index, val).is_err() {
+            panic!("failed to allocate memory for VecDeque");
+        }
+    }
+
+    /// Tries to insert `val` at `index`, the elements on the shorter side move
+    /// - this is `O(min(index, len - index))` operation
+    /// - returns `val` if allocation fails or `index > self.len()`
+    pub fn try_insert(&mut self, index: usize, val: T) -> Result<(), T> {
+        if index > self.len || self.try_grow().is_err() {
+            return Err(val);
+        }
+        unsafe {
+            if index < self.len / 2 {
+                self.head = self.physical(self.capacity() - 1);
+                for i in 0..index {
+                    ptr::copy_nonoverlapping(self.slot(i + 1), self.slot(i), 1);
+                }
+            } else {
+                for i in (index..self.len).rev() {
+                    ptr::copy_nonoverlapping(self.slot(i), self.slot(i + 1), 1);
+                }
+            }
+            self.slot(index).write(val);
+        }
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Removes and returns the element at `index`, the elements on the shorter side move
+    /// - this is `O(min(index, len - index))` operation
+    /// - returns `None` if `index >= self.len()`
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        if index >= self.len {
+            return None;
+        }
+        unsafe {
+            let val = self.slot(index).read();
+            if index < self.len / 2 {
+                for i in (0..index).rev() {
+                    ptr::copy_nonoverlapping(self.slot(i), self.slot(i + 1), 1);
+                }
+                self.head = self.physical(1);
+            } else {
+                for i in index + 1..self.len {
+                    ptr::copy_nonoverlapping(self.slot(i), self.slot(i - 1), 1);
+                }
+            }
+            self.len -= 1;
+            Some(val)
+        }
+    }
+
+    /// Swaps elements `i` and `j`
+    /// - **panics** if either is out of bounds
+    pub fn swap(&mut self, i: usize, j: usize) {
+        assert!(i < self.len && j < self.len, "index out of bounds");
+        unsafe { ptr::swap(self.slot(i), self.slot(j)) };
+    }
+
+    /// Drops all elements after the first `len`
+    /// - does not affect `capacity`
+    pub fn truncate(&mut self, len: usize) {
+        while self.len > len {
+            drop(self.pop_back());
+        }
+    }
+
+    /// Drops all elements
+    /// - does not affect `capacity`
+    pub fn clear(&mut self) {
+        self.truncate(0);
+        self.head = 0;
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index < self.len {
+            Some(unsafe { &*self.slot(index) })
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index < self.len {
+            Some(unsafe { &mut *self.slot(index) })
+        } else {
+            None
+        }
+    }
+
+    #[inline] pub fn front(&self) -> Option<&T> { self.get(0) }
+    #[inline] pub fn front_mut(&mut self) -> Option<&mut T> { self.get_mut(0) }
+    #[inline] pub fn back(&self) -> Option<&T> { self.get(self.len.wrapping_sub(1)) }
+    #[inline] pub fn back_mut(&mut self) -> Option<&mut T> { self.get_mut(self.len.wrapping_sub(1)) }
+
+    /// returns the elements in order, split where the ring buffer wraps around
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        if self.len == 0 {
+            return (&[], &[]);
+        }
+        let first = self.len.min(self.capacity() - self.head);
+        unsafe {
+            (
+                from_raw_parts(self.data.as_ptr().add(self.head), first),
+                from_raw_parts(self.data.as_ptr(), self.len - first),
+            )
+        }
+    }
+
+    /// returns the elements in order, split where the ring buffer wraps around
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        if self.len == 0 {
+            return (&mut [], &mut []);
+        }
+        let first = self.len.min(self.capacity() - self.head);
+        unsafe {
+            (
+                from_raw_parts_mut(self.data.as_ptr().add(self.head), first),
+                from_raw_parts_mut(self.data.as_ptr(), self.len - first),
+            )
+        }
+    }
+
+    /// moves the elements to the start of the buffer and returns them as one slice
+    /// - does not allocate
+    pub fn make_contiguous(&mut self) -> &mut [T] {
+        if self.head + self.len > self.capacity() {
+            //  the free slots between the two parts move to the end
+            let all = unsafe { from_raw_parts_mut(self.data.as_ptr() as *mut MaybeUninit<T>, self.capacity()) };
+            all.rotate_left(self.head);
+            self.head = 0;
+        }
+        self.as_mut_slices().0
+    }
+
+    /// iterates over the elements from front to back
+    pub fn iter(&self) -> Iter<'_, T> {
+        let (a, b) = self.as_slices();
+        Iter { a: a.iter(), b: b.iter() }
+    }
+
+    /// iterates over the elements from front to back
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        let (a, b) = self.as_mut_slices();
+        IterMut { a: a.iter_mut(), b: b.iter_mut() }
+    }
+}
+
+impl<T: Sized + PartialEq, const STEP: usize> VecDeque<T, STEP> {
+
+    pub fn contains(&self, val: &T) -> bool {
+        let (a, b) = self.as_slices();
+        a.contains(val) || b.contains(val)
+    }
+}
+
+impl<T: Sized, const STEP: usize> Drop for VecDeque<T, STEP> {
+    fn drop(&mut self) {
+        let (a, b) = self.as_mut_slices();
+        unsafe {
+            ptr::drop_in_place(a);
+            ptr::drop_in_place(b);
+        }
+    }
+}
+
+impl<T: Sized, const STEP: usize> Index<usize> for VecDeque<T, STEP> {
+    type Output = T;
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index).expect("VecDeque[]: out of bounds")
+    }
+}
+
+impl<T: Sized, const STEP: usize> IndexMut<usize> for VecDeque<T, STEP> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.get_mut(index).expect("VecDeque[]: out of bounds")
+    }
+}
+
+impl<T: Sized + Clone, const STEP: usize> Clone for VecDeque<T, STEP> {
+    /// **panics** if allocation fails
+    fn clone(&self) -> Self {
+        let mut deque = Self::with_capacity(self.len);
+        for val in self.iter() {
+            deque.push_back(val.clone());
+        }
+        deque
+    }
+}
+
+impl<T: Sized + TryClone, const STEP: usize> TryClone for VecDeque<T, STEP> {
+    type Error = ();
+
+    fn try_clone(&self) -> Result<Self, Self::Error>
+    where Self: Sized, Self::Error: Default {
+        let mut deque = Self::try_with_capacity(self.len)?;
+        for val in self.iter() {
+            //  on error `deque` drops the elements cloned so far
+            let val = val.try_clone().map_err(|_| ())?;
+            deque.try_push_back(val).map_err(|_| ())?;
+        }
+        Ok(deque)
+    }
+}
+
+impl<T: Sized, const STEP: usize> Default for VecDeque<T, STEP> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Sized + Debug, const STEP: usize> Debug for VecDeque<T, STEP> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Sized + PartialEq, const STEP: usize> PartialEq for VecDeque<T, STEP> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Sized + Eq, const STEP: usize> Eq for VecDeque<T, STEP> {}
+
+impl<T: Sized, const STEP: usize> FromIterator<T> for VecDeque<T, STEP> {
+    /// **panics** if allocation fails
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut deque = Self::new();
+        deque.extend(iter);
+        deque
+    }
+}
+
+impl<T: Sized, const STEP: usize> Extend<T> for VecDeque<T, STEP> {
+    /// **panics** if allocation fails
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for val in iter {
+            self.push_back(val);
+        }
+    }
+}
+
+impl<'l, T: Sized, const STEP: usize> IntoIterator for &'l VecDeque<T, STEP> {
+    type Item = &'l T;
+    type IntoIter = Iter<'l, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'l, T: Sized, const STEP: usize> IntoIterator for &'l mut VecDeque<T, STEP> {
+    type Item = &'l mut T;
+    type IntoIter = IterMut<'l, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T: Sized, const STEP: usize> IntoIterator for VecDeque<T, STEP> {
+    type Item = T;
+    type IntoIter = IntoIter<T, STEP>;
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+
+/// Iterator over `&T` from front to back
+pub struct Iter<'l, T> {
+    a: slice::Iter<'l, T>,
+    b: slice::Iter<'l, T>,
+}
+
+impl<'l, T> Iterator for Iter<'l, T> {
+    type Item = &'l T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.a.next().or_else(|| self.b.next())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.a.len() + self.b.len();
+        (len, Some(len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.b.next_back().or_else(|| self.a.next_back())
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+impl<T> FusedIterator for Iter<'_, T> {}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Self { a: self.a.clone(), b: self.b.clone() }
+    }
+}
+
+
+/// Iterator over `&mut T` from front to back
+pub struct IterMut<'l, T> {
+    a: slice::IterMut<'l, T>,
+    b: slice::IterMut<'l, T>,
+}
+
+impl<'l, T> Iterator for IterMut<'l, T> {
+    type Item = &'l mut T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.a.next().or_else(|| self.b.next())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.a.len() + self.b.len();
+        (len, Some(len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.b.next_back().or_else(|| self.a.next_back())
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+impl<T> FusedIterator for IterMut<'_, T> {}
+
+
+/// Owning iterator over `T` from front to back
+/// - the elements that are not taken are dropped with the iterator
+pub struct IntoIter<T: Sized, const STEP: usize>(VecDeque<T, STEP>);
+
+impl<T: Sized, const STEP: usize> Iterator for IntoIter<T, STEP> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop_front()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len(), Some(self.0.len()))
+    }
+}
+
+impl<T: Sized, const STEP: usize> DoubleEndedIterator for IntoIter<T, STEP> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.pop_back()
+    }
+}
+
+impl<T: Sized, const STEP: usize> ExactSizeIterator for IntoIter<T, STEP> {}
+impl<T: Sized, const STEP: usize> FusedIterator for IntoIter<T, STEP> {}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mem::test_util::{Rng, Counted};
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use std::collections::VecDeque as StdDeque;
+    use std::vec::Vec;
+
+    fn assert_same<const STEP: usize>(deque: &VecDeque<u32, STEP>, std: &StdDeque<u32>) {
+        assert_eq!(deque.len(), std.len());
+        assert!(deque.iter().eq(std.iter()));
+        assert!(deque.iter().rev().eq(std.iter().rev()));
+        let (a, b) = deque.as_slices();
+        assert!(a.iter().chain(b).eq(std.iter()));
+    }
+
+    fn against_std<const STEP: usize>() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        let mut deque: VecDeque<u32, STEP> = VecDeque::new();
+        let mut std = StdDeque::new();
+
+        for round in 0..30_000 {
+            let len = std.len() as u32;
+            match rng.next(12) {
+                0 | 1 => { deque.push_back(round); std.push_back(round); }
+                2 | 3 => { deque.push_front(round); std.push_front(round); }
+                4 => assert_eq!(deque.pop_front(), std.pop_front()),
+                5 => assert_eq!(deque.pop_back(), std.pop_back()),
+                6 => {
+                    let i = rng.next(len + 1) as usize;
+                    deque.insert(i, round);
+                    std.insert(i, round);
+                }
+                7 => {
+                    let i = rng.next(len + 1) as usize;
+                    assert_eq!(deque.remove(i), std.remove(i));
+                }
+                8 if len > 0 => {
+                    let (i, j) = (rng.next(len) as usize, rng.next(len) as usize);
+                    deque.swap(i, j);
+                    std.swap(i, j);
+                }
+                9 if round % 50 == 0 => {
+                    assert_eq!(deque.make_contiguous(), std.make_contiguous());
+                    assert!(deque.as_slices().1.is_empty());
+                }
+                10 if round % 200 == 0 => {
+                    deque.try_shrink_to_fit().unwrap();
+                    assert!(deque.capacity() >= deque.len());
+                }
+                11 if round % 300 == 0 => {
+                    let len = rng.next(len + 1) as usize;
+                    deque.truncate(len);
+                    std.truncate(len);
+                }
+                _ => {}
+            }
+            assert_eq!(deque.front(), std.front());
+            assert_eq!(deque.back(), std.back());
+            if round % 100 == 0 {
+                assert_same(&deque, &std);
+            }
+        }
+        assert_same(&deque, &std);
+
+        for v in deque.iter_mut() {
+            *v += 1;
+        }
+        assert!(deque.iter().zip(std.iter()).all(|(a, b)| *a == *b + 1));
+        assert!(deque.clone().into_iter().rev().eq(std.iter().rev().map(|v| v + 1)));
+    }
+
+    #[test]
+    fn geometric_growth() {
+        against_std::<0>();
+    }
+
+    #[test]
+    fn step_growth() {
+        against_std::<4>();
+        against_std::<7>();
+    }
+
+    #[test]
+    fn wrap_around() {
+        let mut deque: VecDeque<u32> = VecDeque::with_capacity(8);
+        let capacity = deque.capacity();
+        for v in 0..capacity as u32 {
+            deque.push_back(v);
+        }
+        //  the head moves to the middle, new elements wrap to the start of the buffer
+        for v in 0..capacity as u32 / 2 {
+            assert_eq!(deque.pop_front(), Some(v));
+            deque.push_back(v + 100);
+        }
+        assert_eq!(deque.capacity(), capacity);
+        assert!(!deque.as_slices().1.is_empty());
+
+        let expected: Vec<u32> = (capacity as u32 / 2..capacity as u32).chain(100..100 + capacity as u32 / 2).collect();
+        assert!(deque.iter().eq(expected.iter()));
+        assert_eq!(deque[0], expected[0]);
+        assert_eq!(deque.get(capacity), None);
+        assert!(deque.contains(&100));
+
+        //  growing keeps the order
+        deque.push_front(7);
+        assert!(deque.capacity() > capacity);
+        assert!(deque.iter().skip(1).eq(expected.iter()));
+        assert_eq!(deque.make_contiguous()[1..], expected[..]);
+    }
+
+    #[test]
+    fn drops() {
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+        let mut deque: VecDeque<Counted> = VecDeque::new();
+        for i in 0..100 {
+            if i % 2 == 0 {
+                deque.push_back(Counted(&DROPS));
+            } else {
+                deque.push_front(Counted(&DROPS));
+            }
+        }
+        drop(deque.pop_front());
+        drop(deque.remove(50));
+        assert_eq!(DROPS.load(Ordering::SeqCst), 2);
+
+        deque.truncate(90);
+        assert_eq!(DROPS.load(Ordering::SeqCst), 10);
+
+        let mut iter = deque.into_iter();
+        let taken: Vec<_> = iter.by_ref().take(10).collect();
+        drop(iter.next_back());
+        drop(iter);
+        assert_eq!(DROPS.load(Ordering::SeqCst), 10 + 80);
+        drop(taken);
+        assert_eq!(DROPS.load(Ordering::SeqCst), 100);
+    }
+}
@@ -0,0 +1,369 @@
+//	renderer/ansi.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  ANSI/VT100 escape sequence parser
+//  - splits the byte stream into printable bytes, control bytes, `ESC x` and `ESC [ ... x` (CSI) sequences
+//  - it only parses, the [`Renderer`](super::Renderer) decides what the sequences do
+//  - malformed or too long sequences are dropped without printing anything
+//
+//  the constants below can be used in ordinary strings, e.g. `println!("{}error{}", ansi::RED, ansi::RESET)`
+
+
+use crate::renderer::color::Color;
+
+
+pub const RESET: &str = "\x1b[0m";
+pub const BOLD: &str = "\x1b[1m";
+pub const RED: &str = "\x1b[31m";
+pub const GREEN: &str = "\x1b[32m";
+pub const YELLOW: &str = "\x1b[33m";
+pub const BLUE: &str = "\x1b[34m";
+pub const DEFAULT_FG: &str = "\x1b[39m";
+/// color of `eprint!` and friends
+pub const ERROR: &str = "\x1b[38;2;255;154;154m";
+pub const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+/// maximum number of CSI parameters, longer sequences are ignored
+pub const MAX_PARAMS: usize = 16;
+
+const ESC: u8 = 0x1b;
+/// cancels sequence in progress
+const CAN: u8 = 0x18;
+const SUB: u8 = 0x1a;
+const DEL: u8 = 0x7f;
+
+
+/// Parsed `ESC [ ... x` sequence
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Csi {
+    params: [u16; MAX_PARAMS],
+    len: usize,
+    /// `?`, `>`, `<` or `=` right after `[`
+    pub private: Option<u8>,
+    /// last intermediate byte (`0x20..=0x2f`)
+    pub intermediate: Option<u8>,
+    pub final_byte: u8,
+}
+
+impl Csi {
+    const fn new() -> Self {
+        Self {
+            params: [0; MAX_PARAMS],
+            len: 0,
+            private: None,
+            intermediate: None,
+            final_byte: 0,
+        }
+    }
+
+    /// parameters as given (missing ones are 0)
+    #[inline]
+    pub fn params(&self) -> &[u16] {
+        &self.params[..self.len]
+    }
+
+    /// returns parameter `index`, `default` if it is missing or 0
+    #[inline]
+    pub fn param(&self, index: usize, default: u16) -> u16 {
+        match self.params().get(index) {
+            Some(&p) if p != 0 => p,
+            _ => default,
+        }
+    }
+}
+
+
+/// Result of feeding one byte
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Action {
+    /// printable byte
+    Print(u8),
+    /// control byte like `\n`, `\r` or backspace
+    Execute(u8),
+    /// `ESC x` where `x` is the byte
+    Escape(u8),
+    Csi(Csi),
+}
+
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum State {
+    Ground,
+    Escape,
+    /// `ESC` followed by intermediate bytes, e.g. `ESC ( B`
+    EscapeIntermediate,
+    CsiEntry,
+    CsiParam,
+    /// sequence is invalid, wait for its end
+    CsiIgnore,
+}
+
+
+pub struct Parser {
+    state: State,
+    csi: Csi,
+    /// index of the parameter being read
+    index: usize,
+}
+
+impl Parser {
+    pub const fn new() -> Self {
+        Self {
+            state: State::Ground,
+            csi: Csi::new(),
+            index: 0,
+        }
+    }
+
+    /// forgets sequence in progress
+    #[inline]
+    pub fn reset(&mut self) {
+        self.state = State::Ground;
+    }
+
+    /// checks if no sequence is in progress
+    #[inline]
+    pub fn is_ground(&self) -> bool {
+        self.state == State::Ground
+    }
+
+    pub fn feed(&mut self, byte: u8) -> Option<Action> {
+        //  these work in every state
+        match byte {
+            CAN | SUB => {
+                self.state = State::Ground;
+                return None;
+            }
+            ESC => {
+                self.state = State::Escape;
+                return None;
+            }
+            _ => {}
+        }
+
+        match self.state {
+            State::Ground => match byte {
+                DEL => None,
+                0x00..=0x1f => Some(Action::Execute(byte)),
+                _ => Some(Action::Print(byte)),
+            },
+            State::Escape => match byte {
+                b'[' => {
+                    self.csi = Csi::new();
+                    self.index = 0;
+                    self.state = State::CsiEntry;
+                    None
+                }
+                0x00..=0x1f => Some(Action::Execute(byte)),
+                0x20..=0x2f => {
+                    self.state = State::EscapeIntermediate;
+                    None
+                }
+                DEL => None,
+                _ => {
+                    self.state = State::Ground;
+                    Some(Action::Escape(byte))
+                }
+            },
+            State::EscapeIntermediate => match byte {
+                0x00..=0x1f => Some(Action::Execute(byte)),
+                0x20..=0x2f | DEL => None,
+                //  character set selection etc. is not supported
+                _ => {
+                    self.state = State::Ground;
+                    None
+                }
+            },
+            State::CsiEntry | State::CsiParam | State::CsiIgnore => self.feed_csi(byte),
+        }
+    }
+
+    fn feed_csi(&mut self, byte: u8) -> Option<Action> {
+        match byte {
+            //  control bytes inside sequences are executed right away
+            0x00..=0x1f => return Some(Action::Execute(byte)),
+            DEL => return None,
+            0x40..=0x7e => {
+                let ignore = self.state == State::CsiIgnore;
+                self.state = State::Ground;
+                if ignore {
+                    return None;
+                }
+                self.csi.final_byte = byte;
+                return Some(Action::Csi(self.csi));
+            }
+            _ => {}
+        }
+        if self.state == State::CsiIgnore {
+            return None;
+        }
+
+        match byte {
+            b'0'..=b'9' => {
+                let p = &mut self.csi.params[self.index];
+                *p = p.saturating_mul(10).saturating_add((byte - b'0') as u16);
+                self.csi.len = self.index + 1;
+                self.state = State::CsiParam;
+            }
+            b';' | b':' => {
+                if self.index + 1 == MAX_PARAMS {
+                    self.state = State::CsiIgnore;
+                } else {
+                    self.index += 1;
+                    self.csi.len = self.index + 1;
+                    self.state = State::CsiParam;
+                }
+            }
+            b'<'..=b'?' if self.state == State::CsiEntry => {
+                self.csi.private = Some(byte);
+                self.state = State::CsiParam;
+            }
+            0x20..=0x2f => {
+                self.csi.intermediate = Some(byte);
+            }
+            _ => self.state = State::CsiIgnore,
+        }
+        None
+    }
+}
+
+impl Default for Parser {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// the 16 standard colors (xterm values)
+const BASIC: [u32; 16] = [
+    0x000000, 0xcd0000, 0x00cd00, 0xcdcd00, 0x0000ee, 0xcd00cd, 0x00cdcd, 0xe5e5e5,
+    0x7f7f7f, 0xff0000, 0x00ff00, 0xffff00, 0x5c5cff, 0xff00ff, 0x00ffff, 0xffffff,
+];
+
+/// returns color `index` of the 256 color palette
+/// - 0-15 standard colors, 16-231 6x6x6 cube, 232-255 gray ramp
+pub const fn palette(index: u8) -> Color {
+    const fn level(i: u8) -> u8 {
+        if i == 0 { 0 } else { 55 + i * 40 }
+    }
+    match index {
+        0..=15 => Color::new(BASIC[index as usize]),
+        16..=231 => {
+            let i = index - 16;
+            Color::new_rgb(level(i / 36), level(i / 6 % 6), level(i % 6))
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            Color::new_rgb(gray, gray, gray)
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    fn parse(parser: &mut Parser, bytes: &[u8]) -> Vec<Action> {
+        bytes.iter().filter_map(|&b| parser.feed(b)).collect()
+    }
+
+    /// parses `bytes` that must hold exactly one CSI sequence
+    fn csi(bytes: &[u8]) -> Csi {
+        let mut parser = Parser::new();
+        match parse(&mut parser, bytes)[..] {
+            [Action::Csi(csi)] if parser.is_ground() => csi,
+            ref actions => panic!("expected one CSI, got {:?}", actions),
+        }
+    }
+
+    #[test]
+    fn text_and_controls() {
+        let mut parser = Parser::new();
+        assert_eq!(parse(&mut parser, b"a\n\x7f\x1bc"),
+            [Action::Print(b'a'), Action::Execute(b'\n'), Action::Escape(b'c')]);
+        assert!(parser.is_ground());
+    }
+
+    #[test]
+    fn sgr_params() {
+        assert_eq!(csi(RESET.as_bytes()).params(), [0]);
+        assert_eq!(csi(ERROR.as_bytes()).params(), [38, 2, 255, 154, 154]);
+        assert_eq!(csi(b"\x1b[1;38;5;208m").params(), [1, 38, 5, 208]);
+        //  colons separate like semicolons
+        assert_eq!(csi(b"\x1b[38:2:1:2:3m").params(), [38, 2, 1, 2, 3]);
+        assert_eq!(csi(b"\x1b[99999m").params(), [u16::MAX]);
+
+        let sgr = csi(BOLD.as_bytes());
+        assert_eq!((sgr.final_byte, sgr.private, sgr.intermediate), (b'm', None, None));
+    }
+
+    #[test]
+    fn missing_params() {
+        let sgr = csi(b"\x1b[m");
+        assert_eq!(sgr.params(), []);
+        assert_eq!(sgr.param(0, 1), 1);
+
+        //  empty parameters are 0, which also means the default
+        let pos = csi(b"\x1b[;5H");
+        assert_eq!(pos.params(), [0, 5]);
+        assert_eq!((pos.param(0, 1), pos.param(1, 1), pos.param(2, 1)), (1, 5, 1));
+        assert_eq!(csi(b"\x1b[3;m").params(), [3, 0]);
+    }
+
+    #[test]
+    fn private_and_intermediate() {
+        let hide = csi(b"\x1b[?25l");
+        assert_eq!((hide.private, hide.params(), hide.final_byte), (Some(b'?'), &[25][..], b'l'));
+        let shape = csi(b"\x1b[2 q");
+        assert_eq!((shape.intermediate, shape.final_byte), (Some(b' '), b'q'));
+        //  unknown final bytes are still reported, the renderer ignores them
+        assert_eq!(csi(b"\x1b[5~").final_byte, b'~');
+    }
+
+    #[test]
+    fn invalid_sequences() {
+        let mut parser = Parser::new();
+
+        //  too many parameters
+        let long = [b"\x1b[".as_slice(), &[b'1', b';'].repeat(MAX_PARAMS), b"m"].concat();
+        assert_eq!(parse(&mut parser, &long), []);
+        assert!(parser.is_ground());
+        let mut most = [b"\x1b[".as_slice(), &[b'1', b';'].repeat(MAX_PARAMS - 1)].concat();
+        most.extend(b"1m");
+        assert_eq!(csi(&most).params(), [1; MAX_PARAMS]);
+
+        //  private marker after parameters
+        assert_eq!(parse(&mut parser, b"\x1b[1?mx"), [Action::Print(b'x')]);
+
+        //  cancelled and restarted sequences
+        assert_eq!(parse(&mut parser, b"\x1b[31\x18m"), [Action::Print(b'm')]);
+        assert_eq!(parse(&mut parser, b"\x1b[31\x1b[32m")[..].len(), 1);
+
+        //  character set selection is swallowed
+        assert_eq!(parse(&mut parser, b"\x1b(Bz"), [Action::Print(b'z')]);
+
+        //  control bytes inside a sequence are executed, the sequence goes on
+        assert_eq!(parse(&mut parser, b"\x1b[3\r1m"),
+            [Action::Execute(b'\r'), Action::Csi(csi(b"\x1b[31m"))]);
+
+        parse(&mut parser, b"\x1b[12");
+        assert!(!parser.is_ground());
+        parser.reset();
+        assert_eq!(parse(&mut parser, b"m"), [Action::Print(b'm')]);
+    }
+
+    #[test]
+    fn palette_colors() {
+        assert_eq!(palette(1).as_int(), 0xcd0000);
+        assert_eq!(palette(15).as_int(), 0xffffff);
+        assert_eq!(palette(16).as_int(), 0x000000);
+        assert_eq!(palette(196).as_int(), 0xff0000);
+        assert_eq!(palette(231).as_int(), 0xffffff);
+        assert_eq!(palette(232).as_int(), 0x080808);
+        assert_eq!(palette(255).as_int(), 0xeeeeee);
+    }
+}
@@ -4,16 +4,18 @@
 
 
 //  this file provides simple [`Color`] structure to use with the renderer
+//  - the integer form is `0xRRGGBB`, the framebuffer converts it to its own pixel format
 
 use core::fmt::Display;
 
 use crate::renderer::{Render, RENDERER};
 
+/// channels in the byte order of `0xRRGGBB` on little endian
 #[derive(Copy, Clone)]
 pub struct Rgb {
-    r: u8,
-    g: u8,
     b: u8,
+    g: u8,
+    r: u8,
 }
 
 #[derive(Copy, Clone)]
@@ -29,7 +31,7 @@ pub struct Color {
 
 impl Color {
     #[inline(always)]
-    pub fn as_int(&self) -> u32 {
+    pub const fn as_int(&self) -> u32 {
         unsafe { self.value.int }
     }
     #[inline(always)]
@@ -52,11 +54,12 @@ impl Color {
     pub const fn new(value: u32) -> Self {
         Self { value: Col {int: value} }
     }
+    /// the unused top byte is zeroed, so `as_int` never reads uninitialized memory
     pub const fn new_rgb(red: u8, green: u8, blue: u8) -> Self {
-        Self { value: Col { rgb: Rgb { r: red, g: green, b: blue } } }
+        Self::new_rgba(red, green, blue, 0)
     }
     pub const fn new_rgba(red: u8, green: u8, blue: u8, alpha: u8) -> Self {
-        Self {value: Col { int: red as u32 | (green as u32) << 8 | (blue as u32) << 16 | (alpha as u32) << 24 } }
+        Self {value: Col { int: blue as u32 | (green as u32) << 8 | (red as u32) << 16 | (alpha as u32) << 24 } }
     }
 
     #[inline(always)]
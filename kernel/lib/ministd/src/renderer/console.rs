@@ -0,0 +1,227 @@
+//	renderer/console.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  displays and virtual consoles
+//  - every framebuffer the bootloader reports gets its own [`Renderer`] (a display)
+//  - the first display is shared by up to [`MAX_CONSOLES`] virtual consoles, [`RENDERER`] is console 0
+//  - each console keeps its own screen in a back buffer, cursor and attributes, only the active one is shown
+//  - consoles are switched with [`switch()`] or Alt+F1..F12 (see [`request_switch()`])
+//  - each console keeps lines that scrolled out (see [`crate::renderer::scrollback`]),
+//    Shift+PageUp/PageDown scroll the view of the active one (see [`request_scroll()`])
+//  - `print!` and friends write to the console of the running thread (see [`crate::thread::set_console()`])
+
+
+use core::sync::atomic::{AtomicIsize, AtomicUsize, Ordering};
+use spin::Mutex;
+
+use crate::renderer::framebuffer::FrameBuffer;
+use crate::renderer::renderer::{Renderer, RENDERER};
+
+
+/// maximum number of virtual consoles on the first display
+pub const MAX_CONSOLES: usize = 8;
+/// maximum number of framebuffers used
+pub const MAX_DISPLAYS: usize = 4;
+
+/// no switch is pending
+const NONE: usize = usize::MAX;
+
+
+/// consoles 1.. (console 0 is [`RENDERER`])
+static CONSOLES: [Mutex<Renderer>; MAX_CONSOLES - 1] = [const { Mutex::new(Renderer::new()) }; MAX_CONSOLES - 1];
+/// displays 1.. (display 0 shows the active console)
+static DISPLAYS: [Mutex<Renderer>; MAX_DISPLAYS - 1] = [const { Mutex::new(Renderer::new()) }; MAX_DISPLAYS - 1];
+
+static CONSOLE_COUNT: AtomicUsize = AtomicUsize::new(1);
+static DISPLAY_COUNT: AtomicUsize = AtomicUsize::new(0);
+static ACTIVE: AtomicUsize = AtomicUsize::new(0);
+/// console requested while the consoles were busy
+static PENDING: AtomicUsize = AtomicUsize::new(NONE);
+/// pages to scroll the active console requested while it was busy
+static PENDING_SCROLL: AtomicIsize = AtomicIsize::new(0);
+/// held while switching
+static SWITCH: Mutex<()> = Mutex::new(());
+
+
+/// returns renderer of console `index`
+/// - returns `None` if the console was not created (see [`crate::init::consoles()`])
+#[inline]
+pub fn console(index: usize) -> Option<&'static Mutex<Renderer>> {
+    match index {
+        0 => Some(&RENDERER),
+        _ if index < count() => CONSOLES.get(index - 1),
+        _ => None,
+    }
+}
+
+/// returns the number of virtual consoles
+#[inline]
+pub fn count() -> usize {
+    CONSOLE_COUNT.load(Ordering::Acquire)
+}
+
+/// returns index of the console on the screen
+#[inline]
+pub fn active() -> usize {
+    ACTIVE.load(Ordering::Acquire)
+}
+
+/// returns renderer of display `index`, for display 0 it is the active console
+/// - returns `None` if there is no such display or it is not supported
+#[inline]
+pub fn display(index: usize) -> Option<&'static Mutex<Renderer>> {
+    match index {
+        _ if index >= display_count() => None,
+        0 => console(active()),
+        _ => DISPLAYS.get(index - 1),
+    }
+}
+
+/// returns the number of usable displays
+#[inline]
+pub fn display_count() -> usize {
+    DISPLAY_COUNT.load(Ordering::Acquire)
+}
+
+/// returns console `print!` writes to, the one of the running thread
+/// - falls back to console 0 if the thread console does not exist
+#[inline]
+pub fn output() -> &'static Mutex<Renderer> {
+    console(crate::thread::console()).unwrap_or(&RENDERER)
+}
+
+
+/// hides the active console and shows console `index`
+/// - returns `Err` if there is no such console
+pub fn switch(index: usize) -> Result<(), ()> {
+    let new = console(index).ok_or(())?;
+    let _guard = SWITCH.lock();
+    let old = active();
+    if old != index {
+        //  hide first, at most one console may draw onto the screen
+        console(old).ok_or(())?.lock().set_visible(false);
+        new.lock().set_visible(true);
+        ACTIVE.store(index, Ordering::Release);
+    }
+    Ok(())
+}
+
+/// switches consoles if no lock is taken, otherwise remembers the request for [`poll()`]
+/// - safe to call from interrupt handlers (the keyboard uses it for Alt+F1..F12)
+/// - returns `Err` if there is no such console
+pub fn request_switch(index: usize) -> Result<(), ()> {
+    let new = console(index).ok_or(())?;
+    PENDING.store(index, Ordering::Release);
+
+    let Some(_guard) = SWITCH.try_lock() else { return Ok(()) };
+    let old = active();
+    if old == index {
+        PENDING.store(NONE, Ordering::Release);
+        return Ok(());
+    }
+    let (Some(mut old), Some(mut new)) = (console(old).ok_or(())?.try_lock(), new.try_lock()) else {
+        return Ok(());
+    };
+    old.set_visible(false);
+    new.set_visible(true);
+    ACTIVE.store(index, Ordering::Release);
+    let _ = PENDING.compare_exchange(index, NONE, Ordering::AcqRel, Ordering::Relaxed);
+    Ok(())
+}
+
+/// scrolls the view of the active console `pages` screens back into its history (forward if negative)
+/// - safe to call from interrupt handlers (the keyboard uses it for Shift+PageUp/PageDown)
+/// - if a lock is taken the request is remembered for [`poll()`]
+pub fn request_scroll(pages: isize) {
+    PENDING_SCROLL.fetch_add(pages, Ordering::AcqRel);
+
+    let Some(_guard) = SWITCH.try_lock() else { return };
+    let Some(mut console) = console(active()).and_then(|c| c.try_lock()) else { return };
+    let pages = PENDING_SCROLL.swap(0, Ordering::AcqRel);
+    let lines = console.lines() as isize;
+    console.scroll_view(pages * lines);
+}
+
+/// carries out switch and scroll requested with [`request_switch()`] and [`request_scroll()`]
+/// that could not be done right away
+/// - called whenever keyboard events are polled
+pub fn poll() {
+    let index = PENDING.swap(NONE, Ordering::AcqRel);
+    if index != NONE {
+        let _ = switch(index);
+    }
+
+    let pages = PENDING_SCROLL.swap(0, Ordering::AcqRel);
+    if pages != 0 {
+        let _guard = SWITCH.lock();
+        if let Some(console) = console(active()) {
+            let mut console = console.lock();
+            let lines = console.lines() as isize;
+            console.scroll_view(pages * lines);
+        }
+    }
+}
+
+/// shows console `index` even if its lock or the lock of the active console is held
+/// - returns `Err` if there is no such console
+/// # Safety
+/// meant for the panic handler, the lock holders must never run again
+pub unsafe fn force_switch(index: usize) -> Result<(), ()> {
+    let new = console(index).ok_or(())?;
+    let old = console(active()).ok_or(())?;
+    unsafe {
+        if SWITCH.is_locked() {
+            SWITCH.force_unlock();
+        }
+        if old.is_locked() {
+            old.force_unlock();
+        }
+        if new.is_locked() {
+            new.force_unlock();
+        }
+    }
+    PENDING.store(NONE, Ordering::Release);
+    switch(index)
+}
+
+
+/// sets up renderers of the framebuffers after the first one
+/// - unsupported framebuffers are skipped
+pub(crate) fn init_displays() {
+    if !RENDERER.lock().is_initialized() {
+        return;
+    }
+    let mut count = 1;
+    let total = FrameBuffer::count(&bootloader::FRAMEBUFFER);
+    for index in 1..total {
+        let Some(display) = DISPLAYS.get(count - 1) else { break };
+        if display.lock().init(&bootloader::FRAMEBUFFER, index).is_ok() {
+            count += 1;
+        }
+    }
+    DISPLAY_COUNT.store(count, Ordering::Release);
+}
+
+/// gives back buffers to the displays after the first one
+/// - displays without one still work, only slower
+pub(crate) fn init_display_back_buffers() {
+    for display in DISPLAYS.iter().take(display_count().saturating_sub(1)) {
+        let _ = display.lock().attach_back_buffer();
+    }
+}
+
+/// creates consoles up to `count` (console 0 always exists)
+/// - returns `Err` if there is no framebuffer or not enough memory, the consoles made so far are kept
+pub(crate) fn init_consoles(count: usize) -> Result<(), ()> {
+    let count = count.min(MAX_CONSOLES);
+    while self::count() < count {
+        let index = self::count();
+        {
+            let primary = RENDERER.lock();
+            CONSOLES[index - 1].lock().init_console(&primary)?;
+        }
+        CONSOLE_COUNT.store(index + 1, Ordering::Release);
+    }
+    Ok(())
+}
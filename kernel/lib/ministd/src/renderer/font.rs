@@ -4,27 +4,55 @@
 
 
 //  This file provides simple 8x8 font for the renderer
+//  - PSF1/PSF2 fonts with any glyph size can be used instead (see [`Font::from_psf`] and [`Font::from_module`])
 //  You can change the font
 //  NOTE: the font contains only printable characters (according to the ASCII table)
 
 
+use crate::Box;
 use crate::mem::ReadOnly;
+use crate::renderer::psf::Psf;
 
 pub const FONT_BITS: usize = 8;
 pub const PLACE_SUB: usize = 31;
 pub const FONT_SIZE: usize = 96;
 pub type FontChar = [u8; FONT_BITS];
 const GET_CHAR_END: usize = FONT_SIZE + PLACE_SUB;
-pub struct Font([FontChar; FONT_SIZE]);
+pub struct BuiltinFont([FontChar; FONT_SIZE]);
 
 pub const ERR_CHAR: FontChar = [0b10111011, 0b10111011, 0b11010111, 0b11101111, 0b11010111, 0b11010111, 0b10111011, 0b10111011];
+static ERR_GLYPH: FontChar = ERR_CHAR;
 
+/// glyphs of the built-in font outside ASCII
+static EXTRA: [(char, FontChar); 12] = [
+    /*replacement character, inverted ?*/
+    (REPLACEMENT_CHARACTER, [0b11000011, 0b10111101, 0b11111101, 0b11110011, 0b11101111, 0b11111111, 0b11101111, 0b11111111]),
+    /*box drawing*/
+    ('─', [0, 0, 0, 0b11111111, 0, 0, 0, 0]),
+    ('│', [0b00010000; FONT_BITS]),
+    ('┌', [0, 0, 0, 0b00011111, 0b00010000, 0b00010000, 0b00010000, 0b00010000]),
+    ('┐', [0, 0, 0, 0b11110000, 0b00010000, 0b00010000, 0b00010000, 0b00010000]),
+    ('└', [0b00010000, 0b00010000, 0b00010000, 0b00011111, 0, 0, 0, 0]),
+    ('┘', [0b00010000, 0b00010000, 0b00010000, 0b11110000, 0, 0, 0, 0]),
+    ('├', [0b00010000, 0b00010000, 0b00010000, 0b00011111, 0b00010000, 0b00010000, 0b00010000, 0b00010000]),
+    ('┤', [0b00010000, 0b00010000, 0b00010000, 0b11110000, 0b00010000, 0b00010000, 0b00010000, 0b00010000]),
+    ('┬', [0, 0, 0, 0b11111111, 0b00010000, 0b00010000, 0b00010000, 0b00010000]),
+    ('┴', [0b00010000, 0b00010000, 0b00010000, 0b11111111, 0, 0, 0, 0]),
+    ('┼', [0b00010000, 0b00010000, 0b00010000, 0b11111111, 0b00010000, 0b00010000, 0b00010000, 0b00010000]),
+];
 
-impl Font {
+/// ASCII look-alikes of U+00C0..=U+00FF (the built-in font drops the accents)
+const LATIN1_FOLD: &[u8; 64] = b"AAAAAAACEEEEIIIIDNOOOOOxOUUUUYPsaaaaaaaceeeeiiiidnooooo/ouuuuypy";
+
+/// drawn when the font has no glyph for a character
+pub const REPLACEMENT_CHARACTER: char = '\u{fffd}';
+
+
+impl BuiltinFont {
     #[inline(always)]
     pub fn get_char(&self, i: u8) -> Option<&FontChar> {
         match i as usize {
-            PLACE_SUB..GET_CHAR_END => Some(&self.0[i as usize - PLACE_SUB as usize]),
+            PLACE_SUB..GET_CHAR_END => Some(&self.0[i as usize - PLACE_SUB]),
             _ => {
                 None
             }
@@ -33,8 +61,126 @@ impl Font {
 }
 
 
+/// Bitmap of one character
+/// - rows are `stride` bytes, the most significant bit is the leftmost pixel
+#[derive(Copy, Clone)]
+pub struct Glyph {
+    rows: &'static [u8],
+    width: usize,
+    height: usize,
+    stride: usize,
+}
+
+impl Glyph {
+    #[inline(always)] pub fn width(&self) -> usize { self.width }
+    #[inline(always)] pub fn height(&self) -> usize { self.height }
+
+    /// checks if pixel is set, pixels outside the glyph are not
+    #[inline]
+    pub fn pixel(&self, x: usize, y: usize) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+        self.rows.get(y * self.stride + x / 8).is_some_and(|b| b & (0x80 >> (x % 8)) != 0)
+    }
+}
+
+
+#[derive(Clone)]
+enum Source {
+    Builtin,
+    /// the glyph cache makes `Psf` large, it is kept on heap
+    Psf(Box<Psf>),
+}
+
+/// Font used by the renderer
+#[derive(Clone)]
+pub struct Font {
+    source: Source,
+}
+
+impl Font {
+    pub const BUILTIN: Font = Font { source: Source::Builtin };
+
+    /// parses PSF1 or PSF2 font, e.g. `Font::from_psf(include_bytes!("font.psf"))`
+    /// - returns `Err` if the data is not a valid font or allocation fails
+    pub fn from_psf(data: &'static [u8]) -> Result<Self, ()> {
+        Ok(Self { source: Source::Psf(Box::try_new(Psf::parse(data)?)?) })
+    }
+
+    /// parses font loaded as bootloader module whose path ends with `name`
+    /// - e.g. `module_path: boot():/boot/font.psf` in `limine.conf` and `Font::from_module("font.psf")`
+    /// - returns `Err` if there is no such module or it is not a valid font
+    pub fn from_module(name: &str) -> Result<Self, ()> {
+        let response = bootloader::MODULES.get_response().ok_or(())?;
+        let file = response.modules().iter()
+            .find(|f| f.path().to_bytes().ends_with(name.as_bytes()))
+            .ok_or(())?;
+        //  modules are never reclaimed
+        let data = unsafe { core::slice::from_raw_parts(file.addr(), file.size() as usize) };
+        Self::from_psf(data)
+    }
+
+    #[inline]
+    pub fn is_builtin(&self) -> bool {
+        matches!(self.source, Source::Builtin)
+    }
+
+    /// returns parsed PSF font if it is not the built-in one
+    #[inline]
+    pub fn psf(&self) -> Option<&Psf> {
+        match &self.source {
+            Source::Builtin => None,
+            Source::Psf(psf) => Some(psf),
+        }
+    }
+
+    #[inline]
+    pub fn width(&self) -> usize {
+        match &self.source {
+            Source::Builtin => FONT_BITS,
+            Source::Psf(psf) => psf.width(),
+        }
+    }
+
+    #[inline]
+    pub fn height(&self) -> usize {
+        match &self.source {
+            Source::Builtin => FONT_BITS,
+            Source::Psf(psf) => psf.height(),
+        }
+    }
+
+    /// returns glyph of `c` if the font has one
+    pub fn glyph(&self, c: char) -> Option<Glyph> {
+        match &self.source {
+            Source::Builtin => {
+                let rows = match c {
+                    '\0'..='\x7f' => FONT.as_ref().get_char(c as u8)?,
+                    '\u{c0}'..='\u{ff}' => FONT.as_ref().get_char(LATIN1_FOLD[c as usize - 0xc0])?,
+                    _ => &EXTRA.iter().find(|(e, _)| *e == c)?.1,
+                };
+                Some(Glyph { rows, width: FONT_BITS, height: FONT_BITS, stride: 1 })
+            }
+            Source::Psf(psf) => {
+                let rows = psf.glyph(psf.find(c)?)?;
+                Some(Glyph { rows, width: psf.width(), height: psf.height(), stride: psf.stride() })
+            }
+        }
+    }
+
+    /// returns glyph of `c`, [`REPLACEMENT_CHARACTER`], `?` or a built-in error glyph
+    pub fn glyph_or_replacement(&self, c: char) -> Glyph {
+        self.glyph(c)
+            .or_else(|| self.glyph(REPLACEMENT_CHARACTER))
+            .or_else(|| self.glyph('?'))
+            .unwrap_or(Glyph { rows: &ERR_GLYPH, width: FONT_BITS, height: FONT_BITS, stride: 1 })
+    }
+}
+
+
 
-pub static FONT: ReadOnly<Font> = ReadOnly::new(Font {0: [
+pub static FONT: ReadOnly<BuiltinFont> = ReadOnly::new(BuiltinFont {0: [
     /*each bit is one pixel, set bits will be drawn*/
     /*null byte*/[0, 0, 0, 0, 0, 0, 0, 0],
     /*space*/[0, 0, 0, 0, 0, 0, 0, 0],
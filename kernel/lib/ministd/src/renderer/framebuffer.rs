@@ -0,0 +1,404 @@
+//	renderer/framebuffer.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  linear framebuffer
+//  - rows are `pitch` bytes apart, which may be more than `width * bytes per pixel`
+//  - pixels are 16, 24 or 32 bits wide, channel positions come from the bootloader masks
+//  - colors are always given as [`Color`] (0xRRGGBB) and converted with [`PixelFormat::encode`]
+//  - once a back buffer is attached it is the source of truth, drawing only touches RAM
+//    and [`FrameBuffer::flush`] copies the dirty rectangle to video memory (which is never read)
+//  - several framebuffers may share video memory (virtual consoles), only the visible one flushes
+
+
+use limine_rs as limine;
+use limine::framebuffer::MemoryModel;
+
+use crate::renderer::color::Color;
+use crate::mem::frame::{self, FRAME_SIZE};
+
+
+/// Position and width of one color channel in a pixel
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Channel {
+    pub shift: u8,
+    pub size: u8,
+}
+
+impl Channel {
+    pub const fn new(shift: u8, size: u8) -> Self {
+        Self { shift, size }
+    }
+
+    /// scales 8-bit `value` to the channel width and moves it into place
+    #[inline]
+    pub const fn encode(&self, value: u8) -> u32 {
+        let value = value as u32;
+        let scaled = if self.size == 0 {
+            0
+        } else if self.size <= 8 {
+            value >> (8 - self.size)
+        } else {
+            value << (self.size - 8)
+        };
+        scaled << self.shift
+    }
+}
+
+
+/// Layout of one pixel
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct PixelFormat {
+    /// 2, 3 or 4
+    pub bytes_per_pixel: usize,
+    pub red: Channel,
+    pub green: Channel,
+    pub blue: Channel,
+}
+
+impl PixelFormat {
+    /// 32-bit `0x00RRGGBB` (the usual UEFI GOP format)
+    pub const XRGB8888: Self = Self::new(4, Channel::new(16, 8), Channel::new(8, 8), Channel::new(0, 8));
+    /// 32-bit `0x00BBGGRR`
+    pub const XBGR8888: Self = Self::new(4, Channel::new(0, 8), Channel::new(8, 8), Channel::new(16, 8));
+    /// 24-bit, blue in the first byte
+    pub const RGB888: Self = Self::new(3, Channel::new(16, 8), Channel::new(8, 8), Channel::new(0, 8));
+    /// 16-bit 5:6:5
+    pub const RGB565: Self = Self::new(2, Channel::new(11, 5), Channel::new(5, 6), Channel::new(0, 5));
+
+    pub const fn new(bytes_per_pixel: usize, red: Channel, green: Channel, blue: Channel) -> Self {
+        Self { bytes_per_pixel, red, green, blue }
+    }
+
+    /// converts `color` to the raw pixel value
+    #[inline]
+    pub const fn encode(&self, color: Color) -> u32 {
+        let c = color.as_int();
+        self.red.encode((c >> 16) as u8) | self.green.encode((c >> 8) as u8) | self.blue.encode(c as u8)
+    }
+}
+
+
+/// Rectangle in pixels
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    pub const EMPTY: Self = Self::new(0, 0, 0, 0);
+
+    pub const fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Self { x, y, width, height }
+    }
+
+    #[inline(always)] pub const fn is_empty(&self) -> bool { self.width == 0 || self.height == 0 }
+    #[inline(always)] pub const fn right(&self) -> usize { self.x + self.width }
+    #[inline(always)] pub const fn bottom(&self) -> usize { self.y + self.height }
+
+    /// smallest rectangle containing both
+    pub fn union(&self, other: &Rect) -> Rect {
+        if self.is_empty() {
+            return *other;
+        }
+        if other.is_empty() {
+            return *self;
+        }
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        Rect::new(x, y, self.right().max(other.right()) - x, self.bottom().max(other.bottom()) - y)
+    }
+}
+
+
+pub struct FrameBuffer {
+    width: usize,
+    height: usize,
+    /// bytes between rows
+    pitch: usize,
+    address: *mut u8,
+    bpp: usize,
+    format: PixelFormat,
+    /// RAM copy of the screen with rows `width * bytes_per_pixel` apart, null if not attached
+    back: *mut u8,
+    /// part of the back buffer that differs from video memory
+    dirty: Rect,
+    /// hidden framebuffers never touch video memory
+    visible: bool,
+}
+
+unsafe impl Send for FrameBuffer {}
+unsafe impl Sync for FrameBuffer {}
+
+impl FrameBuffer {
+    pub const fn new() -> Self {
+        Self {
+            width: 0,
+            height: 0,
+            pitch: 0,
+            address: core::ptr::null_mut(),
+            bpp: 0,
+            format: PixelFormat::XRGB8888,
+            back: core::ptr::null_mut(),
+            dirty: Rect::EMPTY,
+            visible: true,
+        }
+    }
+
+    /// describes framebuffer reported by the bootloader
+    /// - returns `Err` if the memory model is not RGB, bpp is not 16, 24 or 32 or pitch is too small
+    pub fn from_limine(fb: &limine::framebuffer::Framebuffer) -> Result<Self, ()> {
+        let bpp = fb.bpp() as usize;
+        if fb.memory_model() != MemoryModel::RGB || !matches!(bpp, 16 | 24 | 32) {
+            return Err(());
+        }
+        let format = PixelFormat::new(
+            bpp / 8,
+            Channel::new(fb.red_mask_shift(), fb.red_mask_size()),
+            Channel::new(fb.green_mask_shift(), fb.green_mask_size()),
+            Channel::new(fb.blue_mask_shift(), fb.blue_mask_size()),
+        );
+        let (width, height, pitch) = (fb.width() as usize, fb.height() as usize, fb.pitch() as usize);
+        if fb.addr().is_null() || pitch < width * format.bytes_per_pixel {
+            return Err(());
+        }
+
+        Ok(Self {
+            width,
+            height,
+            pitch,
+            address: fb.addr(),
+            bpp,
+            format,
+            back: core::ptr::null_mut(),
+            dirty: Rect::EMPTY,
+            visible: true,
+        })
+    }
+
+    /// takes framebuffer `index` of the bootloader response
+    /// - returns `Err` if there is none or it is not supported (see [`FrameBuffer::from_limine`])
+    /// - an attached back buffer is dropped
+    pub fn init(&mut self, fb: &limine::request::FramebufferRequest, index: usize) -> Result<(), ()> {
+        let framebuffer = fb.get_response().ok_or(())?.framebuffers().nth(index).ok_or(())?;
+        let new = Self::from_limine(&framebuffer)?;
+        self.detach_back_buffer();
+        *self = new;
+        Ok(())
+    }
+
+    /// returns the number of framebuffers the bootloader reports (supported or not)
+    pub fn count(fb: &limine::request::FramebufferRequest) -> usize {
+        fb.get_response().map_or(0, |res| res.framebuffers().count())
+    }
+
+    /// hidden framebuffer on the same video memory, without back buffer
+    pub fn shadow(&self) -> Self {
+        Self {
+            back: core::ptr::null_mut(),
+            dirty: Rect::EMPTY,
+            visible: false,
+            ..*self
+        }
+    }
+
+    /// bytes between rows of the back buffer
+    #[inline(always)]
+    fn back_pitch(&self) -> usize {
+        self.width * self.format.bytes_per_pixel
+    }
+
+    /// number of frames the back buffer takes
+    #[inline(always)]
+    fn back_frames(&self) -> usize {
+        (self.back_pitch() * self.height).div_ceil(FRAME_SIZE)
+    }
+
+    /// allocates the back buffer from physical frames and fills it with the current screen
+    /// - the screen is read from video memory only this once, hidden framebuffers start black
+    /// - returns `Err` if the framebuffer is not valid, a back buffer is attached already
+    ///   or there is not enough memory
+    /// - requires the frame allocator (see [`crate::init::frames()`])
+    pub fn attach_back_buffer(&mut self) -> Result<(), ()> {
+        if !self.is_valid() || self.has_back_buffer() {
+            return Err(());
+        }
+        let hhdm = crate::mem::hhdm_offset().ok_or(())?;
+        let back = (frame::alloc_frames(self.back_frames())? + hhdm) as *mut u8;
+
+        let row = self.back_pitch();
+        if self.visible {
+            for y in 0..self.height {
+                unsafe { core::ptr::copy_nonoverlapping(self.address.add(y * self.pitch), back.add(y * row), row) };
+            }
+        } else {
+            unsafe { core::ptr::write_bytes(back, 0, row * self.height) };
+        }
+        self.back = back;
+        self.dirty = Rect::EMPTY;
+        Ok(())
+    }
+
+    /// flushes and frees the back buffer, drawing goes directly to video memory again
+    pub fn detach_back_buffer(&mut self) {
+        if !self.has_back_buffer() {
+            return;
+        }
+        self.flush();
+        let hhdm = crate::mem::hhdm_offset().unwrap_or(0);
+        unsafe { frame::free_frames(self.back as usize - hhdm, self.back_frames()) };
+        self.back = core::ptr::null_mut();
+    }
+
+    #[inline(always)]
+    pub fn has_back_buffer(&self) -> bool {
+        !self.back.is_null()
+    }
+
+    /// part of the screen that is not flushed yet
+    #[inline(always)]
+    pub fn dirty(&self) -> Rect {
+        self.dirty
+    }
+
+    /// marks `rect` as changed (cut to the screen)
+    #[inline]
+    pub fn mark_dirty(&mut self, rect: Rect) {
+        if self.has_back_buffer() {
+            let x = rect.x.min(self.width);
+            let y = rect.y.min(self.height);
+            let rect = Rect::new(x, y, rect.width.min(self.width - x), rect.height.min(self.height - y));
+            self.dirty = self.dirty.union(&rect);
+        }
+    }
+
+    #[inline(always)]
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// shows or hides the framebuffer
+    /// - a shown back buffer is copied to the screen, without one the screen is cleared
+    /// - hidden framebuffers without back buffer drop everything drawn
+    pub fn set_visible(&mut self, visible: bool) {
+        if self.visible == visible {
+            return;
+        }
+        self.visible = visible;
+        if !visible {
+            return;
+        }
+        if self.has_back_buffer() {
+            self.dirty = Rect::new(0, 0, self.width, self.height);
+            self.flush();
+        } else {
+            self.clear(Color::new(0));
+        }
+    }
+
+    /// copies the dirty part of the back buffer to video memory
+    /// - hidden framebuffers keep the dirty rectangle until they are shown
+    pub fn flush(&mut self) {
+        if !self.has_back_buffer() || !self.visible || self.dirty.is_empty() {
+            return;
+        }
+        let bytes = self.format.bytes_per_pixel;
+        let row = self.back_pitch();
+        let Rect { x, y, width, height } = self.dirty;
+        for y in y..y + height {
+            unsafe {
+                core::ptr::copy_nonoverlapping(
+                    self.back.add(y * row + x * bytes),
+                    self.address.add(y * self.pitch + x * bytes),
+                    width * bytes,
+                );
+            }
+        }
+        self.dirty = Rect::EMPTY;
+    }
+
+    /// moves the content `pixels` rows up and fills the freed rows with `color`
+    /// - without back buffer video memory is read, which is very slow
+    pub fn scroll_up(&mut self, pixels: usize, color: Color) {
+        let pixels = pixels.min(self.height);
+        let (base, row) = if self.has_back_buffer() {
+            (self.back, self.back_pitch())
+        } else {
+            (self.address, self.pitch)
+        };
+        if self.is_valid() && (self.visible || self.has_back_buffer()) && pixels < self.height {
+            unsafe { core::ptr::copy(base.add(pixels * row), base, (self.height - pixels) * row) };
+        }
+        self.fill_rect(0, self.height - pixels, self.width, pixels, color);
+        self.mark_dirty(Rect::new(0, 0, self.width, self.height));
+    }
+
+    #[inline(always)] pub fn width(&self) -> usize { self.width }
+    #[inline(always)] pub fn height(&self) -> usize { self.height }
+    #[inline(always)] pub fn pitch(&self) -> usize { self.pitch }
+    #[inline(always)] pub fn bpp(&self) -> usize { self.bpp }
+    #[inline(always)] pub fn format(&self) -> PixelFormat { self.format }
+    #[inline(always)] pub fn address(&self) -> *mut u8 { self.address }
+    #[inline(always)] pub fn is_valid(&self) -> bool { !self.address.is_null() }
+
+    /// size of the mapped memory in bytes
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.pitch * self.height
+    }
+
+    /// writes raw pixel value (see [`PixelFormat::encode`])
+    /// - pixels outside the framebuffer are ignored
+    /// - with back buffer the pixel is not visible until the area is marked dirty and flushed
+    #[inline]
+    pub fn write_pixel(&mut self, x: usize, y: usize, pixel: u32) {
+        if x >= self.width || y >= self.height || !(self.visible || self.has_back_buffer()) {
+            return;
+        }
+        let bytes = self.format.bytes_per_pixel;
+        unsafe {
+            let p = if self.has_back_buffer() {
+                self.back.add(y * self.back_pitch() + x * bytes)
+            } else {
+                self.address.add(y * self.pitch + x * bytes)
+            };
+            match bytes {
+                4 => p.cast::<u32>().write_volatile(pixel),
+                3 => {
+                    p.write_volatile(pixel as u8);
+                    p.add(1).write_volatile((pixel >> 8) as u8);
+                    p.add(2).write_volatile((pixel >> 16) as u8);
+                }
+                _ => p.cast::<u16>().write_volatile(pixel as u16),
+            }
+        }
+    }
+
+    #[inline]
+    pub fn put_pixel(&mut self, x: usize, y: usize, color: Color) {
+        self.write_pixel(x, y, self.format.encode(color));
+        self.mark_dirty(Rect::new(x, y, 1, 1));
+    }
+
+    /// fills rectangle, the parts outside the framebuffer are cut off
+    /// - the rectangle is marked dirty
+    pub fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: Color) {
+        let pixel = self.format.encode(color);
+        let x_end = x.saturating_add(width).min(self.width);
+        let y_end = y.saturating_add(height).min(self.height);
+        for y in y..y_end {
+            for x in x..x_end {
+                self.write_pixel(x, y, pixel);
+            }
+        }
+        self.mark_dirty(Rect::new(x, y, width, height));
+    }
+
+    /// fills the whole framebuffer
+    pub fn clear(&mut self, color: Color) {
+        self.fill_rect(0, 0, self.width, self.height, color);
+    }
+}
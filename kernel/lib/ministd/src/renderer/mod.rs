@@ -4,16 +4,35 @@
 
 //  this file is used as target in the util script
 pub mod font;
+pub mod psf;
+pub mod utf8;
 
 #[macro_use]
 pub mod color;
 pub use color::Color;
+pub use font::Font;
 
 
+pub mod ansi;
+pub mod console;
+pub mod framebuffer;
 pub mod renderer;
+pub mod scrollback;
 pub use renderer::RENDERER;
 pub use renderer::Render;
+pub use framebuffer::{FrameBuffer, PixelFormat, Rect};
 
 pub(crate) fn init() -> Result<(), ()> {
-    renderer::init()
+    renderer::init()?;
+    console::init_displays();
+    Ok(())
+}
+
+pub(crate) fn init_back_buffer() -> Result<(), ()> {
+    console::init_display_back_buffers();
+    renderer::init_back_buffer()
+}
+
+pub(crate) fn init_consoles(count: usize) -> Result<(), ()> {
+    console::init_consoles(count)
 }
\ No newline at end of file
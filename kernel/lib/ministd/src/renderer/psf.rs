@@ -0,0 +1,386 @@
+//	renderer/psf.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  PC Screen Font parser (version 1 and 2)
+//  - the font data is only borrowed, it must live forever (`include_bytes!` or a bootloader module)
+//  - glyph rows are `(width + 7) / 8` bytes, the most significant bit is the leftmost pixel
+//  - the unicode table maps characters to glyphs, without it glyph `n` is character `n`
+//  - Latin-1 lookups are cached, other characters scan the table
+
+
+const PSF1_MAGIC: [u8; 2] = [0x36, 0x04];
+const PSF1_HEADER_SIZE: usize = 4;
+const PSF1_MODE_512: u8 = 0x01;
+const PSF1_MODE_HAS_TAB: u8 = 0x02;
+const PSF1_MODE_SEQ: u8 = 0x04;
+const PSF1_SEPARATOR: u16 = 0xffff;
+const PSF1_START_SEQ: u16 = 0xfffe;
+
+const PSF2_MAGIC: [u8; 4] = [0x72, 0xb5, 0x4a, 0x86];
+const PSF2_HEADER_SIZE: usize = 32;
+const PSF2_HAS_UNICODE_TABLE: u32 = 0x01;
+const PSF2_SEPARATOR: u8 = 0xff;
+const PSF2_START_SEQ: u8 = 0xfe;
+
+/// no glyph in the cache
+const NONE: u16 = u16::MAX;
+const CACHE_SIZE: usize = 256;
+
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Version {
+    Psf1,
+    Psf2,
+}
+
+
+/// Parsed PSF font
+#[derive(Copy, Clone)]
+pub struct Psf {
+    version: Version,
+    width: usize,
+    height: usize,
+    count: usize,
+    bytes_per_glyph: usize,
+    glyphs: &'static [u8],
+    unicode: Option<&'static [u8]>,
+    /// glyph index of characters below [`CACHE_SIZE`]
+    cache: [u16; CACHE_SIZE],
+}
+
+impl Psf {
+    /// parses font file
+    /// - returns `Err` if the magic is unknown or the file is truncated
+    pub fn parse(data: &'static [u8]) -> Result<Self, ()> {
+        let mut font = if data.starts_with(&PSF2_MAGIC) {
+            Self::parse_psf2(data)?
+        } else if data.starts_with(&PSF1_MAGIC) {
+            Self::parse_psf1(data)?
+        } else {
+            return Err(());
+        };
+        font.fill_cache();
+        Ok(font)
+    }
+
+    fn parse_psf1(data: &'static [u8]) -> Result<Self, ()> {
+        if data.len() < PSF1_HEADER_SIZE {
+            return Err(());
+        }
+        let mode = data[2];
+        let height = data[3] as usize;
+        let count = if mode & PSF1_MODE_512 != 0 { 512 } else { 256 };
+        let end = PSF1_HEADER_SIZE + count * height;
+        if height == 0 || data.len() < end {
+            return Err(());
+        }
+
+        Ok(Self {
+            version: Version::Psf1,
+            width: 8,
+            height,
+            count,
+            bytes_per_glyph: height,
+            glyphs: &data[PSF1_HEADER_SIZE..end],
+            unicode: if mode & (PSF1_MODE_HAS_TAB | PSF1_MODE_SEQ) != 0 { Some(&data[end..]) } else { None },
+            cache: [NONE; CACHE_SIZE],
+        })
+    }
+
+    fn parse_psf2(data: &'static [u8]) -> Result<Self, ()> {
+        if data.len() < PSF2_HEADER_SIZE {
+            return Err(());
+        }
+        let field = |i: usize| u32::from_le_bytes([data[i * 4], data[i * 4 + 1], data[i * 4 + 2], data[i * 4 + 3]]) as usize;
+        let header_size = field(2);
+        let flags = field(3) as u32;
+        let count = field(4);
+        let bytes_per_glyph = field(5);
+        let height = field(6);
+        let width = field(7);
+
+        if width == 0 || height == 0 || count == 0 || bytes_per_glyph < width.div_ceil(8) * height {
+            return Err(());
+        }
+        let end = count.checked_mul(bytes_per_glyph).and_then(|s| s.checked_add(header_size)).ok_or(())?;
+        if header_size < PSF2_HEADER_SIZE || data.len() < end {
+            return Err(());
+        }
+
+        Ok(Self {
+            version: Version::Psf2,
+            width,
+            height,
+            count,
+            bytes_per_glyph,
+            glyphs: &data[header_size..end],
+            unicode: if flags & PSF2_HAS_UNICODE_TABLE != 0 { Some(&data[end..]) } else { None },
+            cache: [NONE; CACHE_SIZE],
+        })
+    }
+
+    #[inline(always)] pub fn version(&self) -> Version { self.version }
+    #[inline(always)] pub fn width(&self) -> usize { self.width }
+    #[inline(always)] pub fn height(&self) -> usize { self.height }
+    #[inline(always)] pub fn glyph_count(&self) -> usize { self.count }
+    #[inline(always)] pub fn has_unicode_table(&self) -> bool { self.unicode.is_some() }
+
+    /// bytes of one glyph row
+    #[inline(always)]
+    pub fn stride(&self) -> usize {
+        self.width.div_ceil(8)
+    }
+
+    /// returns bitmap of glyph `index`
+    #[inline]
+    pub fn glyph(&self, index: usize) -> Option<&'static [u8]> {
+        let glyphs = self.glyphs;
+        let start = index.checked_mul(self.bytes_per_glyph)?;
+        glyphs.get(start..start + self.stride() * self.height)
+    }
+
+    /// returns glyph index of `c`
+    pub fn find(&self, c: char) -> Option<usize> {
+        let code = c as usize;
+        if code < CACHE_SIZE {
+            return match self.cache[code] {
+                NONE => None,
+                index => Some(index as usize),
+            };
+        }
+        self.lookup(c)
+    }
+
+    fn fill_cache(&mut self) {
+        match self.unicode {
+            None => {
+                for (code, entry) in self.cache.iter_mut().enumerate() {
+                    if code < self.count {
+                        *entry = code as u16;
+                    }
+                }
+            }
+            Some(_) => {
+                let mut cache = [NONE; CACHE_SIZE];
+                self.for_each_mapping(|c, index| {
+                    let code = c as usize;
+                    if code < CACHE_SIZE && cache[code] == NONE && index < NONE as usize {
+                        cache[code] = index as u16;
+                    }
+                    false
+                });
+                self.cache = cache;
+            }
+        }
+    }
+
+    /// scans the unicode table
+    fn lookup(&self, c: char) -> Option<usize> {
+        if self.unicode.is_none() {
+            return if (c as usize) < self.count { Some(c as usize) } else { None };
+        }
+        let mut found = None;
+        self.for_each_mapping(|mapped, index| {
+            if mapped == c {
+                found = Some(index);
+            }
+            found.is_some()
+        });
+        found
+    }
+
+    /// calls `f` with every single character mapping (sequences are skipped) until it returns `true`
+    fn for_each_mapping<F: FnMut(char, usize) -> bool>(&self, mut f: F) {
+        let Some(table) = self.unicode else { return };
+
+        match self.version {
+            Version::Psf1 => {
+                let mut index = 0;
+                let mut in_sequence = false;
+                for pair in table.chunks_exact(2) {
+                    match u16::from_le_bytes([pair[0], pair[1]]) {
+                        PSF1_SEPARATOR => {
+                            index += 1;
+                            in_sequence = false;
+                            if index == self.count {
+                                return;
+                            }
+                        }
+                        PSF1_START_SEQ => in_sequence = true,
+                        code if !in_sequence => {
+                            if let Some(c) = char::from_u32(code as u32)
+                                && f(c, index) {
+                                return;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Version::Psf2 => {
+                let mut index = 0;
+                let mut at = 0;
+                while at < table.len() && index < self.count {
+                    match table[at] {
+                        PSF2_SEPARATOR => {
+                            index += 1;
+                            at += 1;
+                        }
+                        PSF2_START_SEQ => {
+                            //  skip to the end of the entry
+                            while at < table.len() && table[at] != PSF2_SEPARATOR {
+                                at += 1;
+                            }
+                        }
+                        lead => {
+                            let len = match lead {
+                                0x00..=0x7f => 1,
+                                0xc0..=0xdf => 2,
+                                0xe0..=0xef => 3,
+                                0xf0..=0xf7 => 4,
+                                _ => {
+                                    at += 1;
+                                    continue;
+                                }
+                            };
+                            let c = table.get(at..at + len)
+                                .and_then(|s| core::str::from_utf8(s).ok())
+                                .and_then(|s| s.chars().next());
+                            at += len;
+                            if let Some(c) = c
+                                && f(c, index) {
+                                return;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    /// PSF1 font whose glyph `n` is filled with byte `n`
+    fn psf1(mode: u8, height: u8, table: &[u16]) -> &'static [u8] {
+        let count = if mode & PSF1_MODE_512 != 0 { 512 } else { 256 };
+        let mut data = Vec::from([PSF1_MAGIC[0], PSF1_MAGIC[1], mode, height]);
+        for glyph in 0..count {
+            data.extend(core::iter::repeat_n(glyph as u8, height as usize));
+        }
+        data.extend(table.iter().flat_map(|code| code.to_le_bytes()));
+        data.leak()
+    }
+
+    /// PSF2 font whose glyph `n` is filled with byte `n`
+    fn psf2(width: u32, height: u32, count: u32, table: Option<&[u8]>) -> Vec<u8> {
+        let bytes_per_glyph = width.div_ceil(8) * height;
+        let flags = if table.is_some() { PSF2_HAS_UNICODE_TABLE } else { 0 };
+        let mut data = Vec::from(PSF2_MAGIC);
+        for field in [0, PSF2_HEADER_SIZE as u32, flags, count, bytes_per_glyph, height, width] {
+            data.extend(field.to_le_bytes());
+        }
+        for glyph in 0..count {
+            data.extend(core::iter::repeat_n(glyph as u8, bytes_per_glyph as usize));
+        }
+        data.extend(table.unwrap_or_default());
+        data
+    }
+
+    #[test]
+    fn psf1_header() {
+        let font = Psf::parse(psf1(0, 16, &[])).unwrap();
+        assert_eq!(font.version(), Version::Psf1);
+        assert_eq!((font.width(), font.height(), font.stride()), (8, 16, 1));
+        assert_eq!(font.glyph_count(), 256);
+        assert!(!font.has_unicode_table());
+
+        //  without a table glyph `n` is character `n`
+        assert_eq!(font.find('A'), Some(0x41));
+        assert_eq!(font.find('\u{ff}'), Some(0xff));
+        assert_eq!(font.find('─'), None);
+        assert_eq!(font.glyph(0x41), Some(&[0x41; 16][..]));
+        assert_eq!(font.glyph(256), None);
+
+        assert_eq!(Psf::parse(psf1(PSF1_MODE_512, 8, &[])).unwrap().glyph_count(), 512);
+    }
+
+    #[test]
+    fn psf1_unicode_table() {
+        let mut table = Vec::from([0x41, PSF1_SEPARATOR]);
+        //  glyph 1 maps two characters and a sequence
+        table.extend([0xe9, 0x2500, PSF1_START_SEQ, 0x65, 0x301, PSF1_SEPARATOR]);
+        table.extend(core::iter::repeat_n(PSF1_SEPARATOR, 254));
+        let font = Psf::parse(psf1(PSF1_MODE_HAS_TAB, 8, &table)).unwrap();
+
+        assert!(font.has_unicode_table());
+        assert_eq!(font.find('A'), Some(0));
+        assert_eq!(font.find('é'), Some(1));
+        assert_eq!(font.find('─'), Some(1));
+        assert_eq!(font.find('e'), None);
+        assert_eq!(font.find('\u{301}'), None);
+        assert_eq!(font.find('B'), None);
+    }
+
+    #[test]
+    fn psf2_header() {
+        let font = Psf::parse(psf2(10, 12, 4, None).leak()).unwrap();
+        assert_eq!(font.version(), Version::Psf2);
+        assert_eq!((font.width(), font.height(), font.stride()), (10, 12, 2));
+        assert_eq!(font.glyph_count(), 4);
+        assert_eq!(font.glyph(3), Some(&[3; 24][..]));
+        assert_eq!(font.glyph(4), None);
+        assert_eq!(font.find('\u{3}'), Some(3));
+        assert_eq!(font.find('A'), None);
+    }
+
+    #[test]
+    fn psf2_unicode_table() {
+        let mut table = Vec::from(*b"A");
+        table.push(PSF2_SEPARATOR);
+        table.extend("─é".as_bytes());
+        table.push(PSF2_START_SEQ);
+        table.extend("e\u{301}".as_bytes());
+        table.push(PSF2_SEPARATOR);
+        //  invalid UTF-8 is skipped
+        table.extend([0x80, b'B', PSF2_SEPARATOR]);
+        let font = Psf::parse(psf2(8, 8, 3, Some(&table)).leak()).unwrap();
+
+        assert!(font.has_unicode_table());
+        assert_eq!(font.find('A'), Some(0));
+        assert_eq!(font.find('─'), Some(1));
+        assert_eq!(font.find('é'), Some(1));
+        assert_eq!(font.find('e'), None);
+        assert_eq!(font.find('B'), Some(2));
+        assert_eq!(font.find('\u{2}'), None);
+    }
+
+    #[test]
+    fn truncated() {
+        assert!(Psf::parse(&[]).is_err());
+        assert!(Psf::parse(b"not a font").is_err());
+        assert!(Psf::parse(&PSF1_MAGIC).is_err());
+        assert!(Psf::parse(&psf1(0, 8, &[])[..PSF1_HEADER_SIZE + 255 * 8]).is_err());
+        assert!(Psf::parse(psf1(0, 0, &[])).is_err());
+
+        let font = psf2(8, 8, 4, None).leak();
+        assert!(Psf::parse(&font[..PSF2_HEADER_SIZE - 1]).is_err());
+        assert!(Psf::parse(&font[..font.len() - 1]).is_err());
+
+        //  glyphs smaller than their bitmap
+        let mut font = psf2(8, 8, 4, None);
+        font[20] = 4;
+        assert!(Psf::parse(font.leak()).is_err());
+
+        //  glyph table far larger than the data
+        let mut font = psf2(8, 8, 4, None);
+        font[16..20].copy_from_slice(&u32::MAX.to_le_bytes());
+        font[20..24].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(Psf::parse(font.leak()).is_err());
+    }
+}
@@ -4,14 +4,22 @@
 
 use limine_rs as limine;
 use spin::MutexGuard;
-use crate::renderer::font;
+use crate::renderer::font::{Font, REPLACEMENT_CHARACTER};
 
 
 use crate::renderer::color::Color;
+use crate::renderer::ansi::{self, Action, Csi, Parser};
+use crate::renderer::utf8::Decoder;
+use crate::renderer::scrollback::{Cell, Scrollback};
+use crate::mem::frame::{self, FRAME_SIZE};
+pub use crate::renderer::framebuffer::{FrameBuffer, PixelFormat, Rect};
 
 pub const TAB_SIZE: usize = 6;
 pub const SPACE_BETWEEN_LINES: u16 = 3;
 
+pub const DEFAULT_FOREGROUND: Color = Color::new(0xffffff);
+pub const DEFAULT_BACKGROUND: Color = Color::new(0x000000);
+
 
 /// Helper trait for the [`Renderer`]
 /// - classic [`core::fmt::Display`] should be prefered
@@ -26,13 +34,56 @@ pub static RENDERER: spin::Mutex<Renderer> = spin::Mutex::new(Renderer::new());
 //pub static mut RENDERER: SyncCell<Renderer> = SyncCell::new(Renderer::new());
 
 
+/// Text attributes set by SGR sequences
+#[derive(Copy, Clone)]
+struct Attributes {
+    fg: Color,
+    bg: Color,
+    /// palette index of `fg` if it is one of the 8 basic colors (bold makes them bright)
+    fg_basic: Option<u8>,
+    bold: bool,
+    inverse: bool,
+}
+
+impl Attributes {
+    const fn new() -> Self {
+        Self {
+            fg: DEFAULT_FOREGROUND,
+            bg: DEFAULT_BACKGROUND,
+            fg_basic: None,
+            bold: false,
+            inverse: false,
+        }
+    }
+
+    /// colors used for drawing
+    fn colors(&self) -> (Color, Color) {
+        let fg = match self.fg_basic {
+            Some(i) if self.bold => ansi::palette(i + 8),
+            _ => self.fg,
+        };
+        if self.inverse { (self.bg, fg) } else { (fg, self.bg) }
+    }
+}
+
+
 pub struct Renderer {
     row: usize,
     line: usize,
     fb: FrameBuffer,
-    col: Color,
+    attr: Attributes,
     space: u16,
     initialized: bool,
+    parser: Parser,
+    /// printable bytes are UTF-8, a character may be split between calls
+    utf8: Decoder,
+    font: Font,
+    /// cursor and attributes stored by `ESC 7` / `CSI s`
+    saved: (usize, usize, Attributes),
+    /// text of the screen and the lines above it
+    scrollback: Scrollback,
+    /// number of lines the view is scrolled back (0 shows the screen)
+    view: usize,
 }
 
 unsafe impl Sync for Renderer {}
@@ -44,18 +95,33 @@ impl Renderer {
             row: 0,
             line: 0,
             fb: FrameBuffer::new(),
-            col: Color::new(0xffffff),
+            attr: Attributes::new(),
             space: 0,
             initialized: false,
+            parser: Parser::new(),
+            utf8: Decoder::new(),
+            font: Font::BUILTIN,
+            saved: (0, 0, Attributes::new()),
+            scrollback: Scrollback::new(),
+            view: 0,
         }
     }
 
-    fn init(&mut self, fb: &limine::request::FramebufferRequest) -> Result<(), ()> {
-        self.col = Color::new_rgb(255, 255, 255);
+    fn reset(&mut self) {
+        self.attr = Attributes::new();
+        self.parser.reset();
+        self.utf8.reset();
         self.row = 0;
         self.line = 0;
-        self.space = SPACE_BETWEEN_LINES;
-        if let Ok(_) = FrameBuffer::init(&mut self.fb, fb) {
+        self.saved = (0, 0, Attributes::new());
+        self.view = 0;
+    }
+
+    /// draws onto framebuffer `index` of the bootloader response
+    pub(crate) fn init(&mut self, fb: &limine::request::FramebufferRequest, index: usize) -> Result<(), ()> {
+        self.reset();
+        self.space = if self.font.is_builtin() { SPACE_BETWEEN_LINES } else { 0 };
+        if let Ok(_) = FrameBuffer::init(&mut self.fb, fb, index) {
             self.initialized = true;
             Ok(())
         } else {
@@ -64,42 +130,197 @@ impl Renderer {
         }
     }
 
+    /// makes hidden virtual console on the screen of `primary`, with its own back buffer and font of `primary`
+    /// - returns `Err` if `primary` has no framebuffer or there is not enough memory
+    pub(crate) fn init_console(&mut self, primary: &Renderer) -> Result<(), ()> {
+        if !primary.initialized {
+            return Err(());
+        }
+        self.reset();
+        self.fb.detach_back_buffer();
+        self.fb = primary.fb.shadow();
+        self.set_font(primary.font.clone());
+        self.fb.attach_back_buffer()?;
+        self.initialized = true;
+        //  the console works without history
+        let _ = self.attach_scrollback();
+        Ok(())
+    }
+
     #[inline(always)] pub fn column(&self) -> usize { self.row }
     #[inline(always)] pub fn line(&self) -> usize { self.line }
     #[inline(always)] pub fn fb(&self) -> &FrameBuffer { &self.fb }
-    #[inline(always)] pub fn color(&self) -> Color { self.col }
-    #[inline(always)] pub fn set_color(&mut self, color: u32) {self.col.set_int(color);}
+    #[inline(always)] pub fn color(&self) -> Color { self.attr.fg }
+    #[inline(always)] pub fn background(&self) -> Color { self.attr.bg }
+    #[inline(always)] pub fn set_color(&mut self, color: u32) { self.attr.fg.set_int(color); self.attr.fg_basic = None; }
+    #[inline(always)] pub fn set_background(&mut self, color: u32) { self.attr.bg.set_int(color); }
+
+    #[inline(always)] pub fn font(&self) -> &Font { &self.font }
+    #[inline(always)] pub fn is_initialized(&self) -> bool { self.initialized }
+    #[inline(always)] pub fn is_visible(&self) -> bool { self.fb.is_visible() }
+
+    /// shows or hides the screen (see [`FrameBuffer::set_visible`])
+    #[inline(always)]
+    pub fn set_visible(&mut self, visible: bool) {
+        self.fb.set_visible(visible);
+    }
+
+    /// switches to `font`, the cursor is moved to stay on the screen
+    /// - text already on the screen is not redrawn
+    /// - the built-in font gets [`SPACE_BETWEEN_LINES`], PSF fonts have their own spacing
+    pub fn set_font(&mut self, font: Font) {
+        let y = self.line * self.line_height();
+        self.space = if font.is_builtin() { SPACE_BETWEEN_LINES } else { 0 };
+        self.font = font;
+        let (line, row) = (y / self.line_height(), self.row);
+        self.move_to(line, row);
+
+        //  the history does not fit the new screen size
+        if self.scrollback.is_attached() {
+            self.detach_scrollback();
+            let _ = self.attach_scrollback();
+        }
+    }
+
+    /// height of one text line in pixels
+    #[inline(always)]
+    fn line_height(&self) -> usize {
+        self.font.height() + self.space as usize
+    }
+    /// width of one character in pixels
+    #[inline(always)]
+    fn column_width(&self) -> usize {
+        self.font.width()
+    }
+    /// number of characters that fit on one line
+    #[inline(always)]
+    pub fn columns(&self) -> usize {
+        self.fb.width() / self.column_width()
+    }
+    /// number of lines that fit on the screen
+    #[inline(always)]
+    pub fn lines(&self) -> usize {
+        self.fb.height() / self.line_height()
+    }
 
 
 
     fn space(&mut self) {
         self.row += 1;
-        if self.row >= self.fb.width {
+        if self.row >= self.columns() {
             self.row = 0;
             self.line += 1;
         }
     }
+    /// moves to the next line, scrolls if it is below the screen
     #[inline]
     pub fn endl(&mut self) {
         self.line += 1;
         self.row = 0;
+        let lines = self.lines();
+        if lines > 0 && self.line >= lines {
+            let count = self.line + 1 - lines;
+            self.fb.scroll_up(count * self.line_height(), self.attr.bg);
+            for _ in 0..count {
+                self.scrollback.scroll(self.attr.bg);
+            }
+            self.line = lines - 1;
+        }
     }
     #[inline]
     pub fn tab(&mut self) {
         self.row += TAB_SIZE - (self.row % TAB_SIZE);
-        if self.row >= self.fb.width {
+        if self.row >= self.columns() {
             self.endl();
         }
     }
 
     pub fn clear(&mut self) {
-        for i in unsafe { core::slice::from_raw_parts_mut(self.fb.address, self.fb.width*self.fb.height) } {
-            i.set_int(0);       //  black
+        self.fb.clear(Color::new(0));       //  black
+        for line in 0..self.lines() {
+            self.scrollback.fill(line, 0, self.columns(), Cell::blank(Color::new(0)));
+        }
+        self.fb.flush();
+    }
+
+    /// copies changes in the back buffer to the screen
+    /// - printing functions flush on their own
+    #[inline(always)]
+    pub fn flush(&mut self) {
+        self.fb.flush();
+    }
+
+    /// gives the framebuffer a RAM copy which makes scrolling fast
+    /// - returns `Err` if there is no framebuffer or not enough memory
+    #[inline]
+    pub fn attach_back_buffer(&mut self) -> Result<(), ()> {
+        self.fb.attach_back_buffer()
+    }
+
+    /// starts keeping the text of the screen and [`SCROLLBACK_LINES`](crate::renderer::scrollback::SCROLLBACK_LINES) lines above it
+    /// - text printed before is not recorded
+    /// - returns `Err` if there is no framebuffer or not enough memory
+    pub fn attach_scrollback(&mut self) -> Result<(), ()> {
+        if !self.initialized || self.scrollback.is_attached() {
+            return Err(());
+        }
+        let (columns, lines) = (self.columns(), self.lines());
+        let size = Scrollback::size(columns, lines) * size_of::<Cell>();
+        let hhdm = crate::mem::hhdm_offset().ok_or(())?;
+        let cells = (frame::alloc_frames(size.div_ceil(FRAME_SIZE))? + hhdm) as *mut Cell;
+
+        unsafe { self.scrollback.attach(cells, columns, lines, self.attr.bg) };
+        Ok(())
+    }
+
+    /// frees the scrollback, the view goes back to the screen
+    pub fn detach_scrollback(&mut self) {
+        self.show_screen();
+        let size = self.scrollback.capacity() * size_of::<Cell>();
+        let cells = self.scrollback.detach();
+        if let (false, Some(hhdm)) = (cells.is_null(), crate::mem::hhdm_offset()) {
+            unsafe { frame::free_frames(cells as usize - hhdm, size.div_ceil(FRAME_SIZE)) };
+        }
+    }
+
+    /// returns the number of lines the view is scrolled back
+    #[inline(always)]
+    pub fn view(&self) -> usize {
+        self.view
+    }
+
+    /// scrolls the view `lines` back into the history (forward if negative)
+    /// - the view is limited by the history and the screen, new output shows the screen again
+    pub fn scroll_view(&mut self, lines: isize) {
+        let view = self.view.saturating_add_signed(lines).min(self.scrollback.history());
+        if view != self.view && self.initialized {
+            self.view = view;
+            self.redraw();
+            self.fb.flush();
+        }
+    }
+
+    /// returns to the screen if the view is scrolled back
+    fn show_screen(&mut self) {
+        if self.view != 0 {
+            self.view = 0;
+            self.redraw();
+        }
+    }
+
+    /// draws the recorded text seen in the view
+    fn redraw(&mut self) {
+        for line in 0..self.lines() {
+            for row in 0..self.columns() {
+                if let Some(cell) = self.scrollback.get(line, row, self.view) {
+                    self.draw_cell(line, row, cell);
+                }
+            }
         }
     }
 
     pub fn set_pos(&mut self, line: usize, row: usize) -> Result<(), ()> {
-        if line < self.fb.height && row < self.fb.width {
+        if line < self.lines() && row < self.columns() {
             self.line = line;
             self.row = row;
             Ok(())
@@ -109,62 +330,244 @@ impl Renderer {
     }
 
     fn rend(&mut self, c: u8) {
+        let action = self.parser.feed(c);
+        match action {
+            Some(Action::Print(_)) | None => {}
+            //  anything else ends an unfinished character
+            Some(_) => if self.utf8.reset() {
+                self.glyph(REPLACEMENT_CHARACTER);
+            },
+        }
+
+        match action {
+            Some(Action::Print(c)) => {
+                let mut decoder = self.utf8;
+                decoder.feed(c, |c| self.glyph(c));
+                self.utf8 = decoder;
+            }
+            Some(Action::Execute(c)) => self.control(c),
+            Some(Action::Escape(c)) => self.escape(c),
+            Some(Action::Csi(csi)) => self.csi(&csi),
+            None => {}
+        }
+    }
+
+    fn glyph(&mut self, c: char) {
+        let (fg, bg) = self.attr.colors();
+        let cell = Cell { ch: c, fg, bg };
+        self.draw_cell(self.line, self.row, cell);
+        self.scrollback.set(self.line, self.row, cell);
+
+        self.row += 1;
+        if self.row >= self.columns() {
+            self.endl();
+        }
+    }
+
+    fn draw_cell(&mut self, line: usize, row: usize, cell: Cell) {
+        let glyph = self.font.glyph_or_replacement(cell.ch);
+        let width = self.column_width();
+        let height = self.line_height();
+        let x = row * width;
+        let y = line * height;
+        let format = self.fb.format();
+        let (fg, bg) = (format.encode(cell.fg), format.encode(cell.bg));
+
+        //  the whole cell is drawn, so the space between lines gets the background too
+        for i in 0..height {
+            for ii in 0..width {
+                let pixel = if glyph.pixel(ii, i) { fg } else { bg };
+                self.fb.write_pixel(x + ii, y + i, pixel);
+            }
+        }
+        self.fb.mark_dirty(Rect::new(x, y, width, height));
+    }
+
+    fn control(&mut self, c: u8) {
+        match c {
+            b'\n' => self.endl(),
+            b'\t' => self.tab(),
+            b'\r' => self.row = 0,
+            //  backspace only moves the cursor
+            0x08 => self.row = self.row.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    fn escape(&mut self, c: u8) {
         match c {
-            0..31 => {
-                match c {
-                    b'\n' => {
-                        self.endl();
-                        return;
-                    },
-                    b'\t' => {
-                        self.tab();
-                        return;
-                    },
-                    _ => return,
+            b'7' => self.save_cursor(),
+            b'8' => self.restore_cursor(),
+            //  full reset
+            b'c' => {
+                self.attr = Attributes::new();
+                self.clear();
+                self.row = 0;
+                self.line = 0;
+            }
+            _ => {}
+        }
+    }
+
+    fn save_cursor(&mut self) {
+        self.saved = (self.line, self.row, self.attr);
+    }
+
+    fn restore_cursor(&mut self) {
+        let (line, row, attr) = self.saved;
+        self.attr = attr;
+        self.move_to(line, row);
+    }
+
+    /// moves the cursor, the position is clamped to the screen
+    fn move_to(&mut self, line: usize, row: usize) {
+        self.line = line.min(self.lines().saturating_sub(1));
+        self.row = row.min(self.columns().saturating_sub(1));
+    }
+
+    /// fills cells `from..to` of `line` with the background
+    fn erase_cells(&mut self, line: usize, from: usize, to: usize) {
+        let (width, height) = (self.column_width(), self.line_height());
+        let bg = self.attr.colors().1;
+        self.fb.fill_rect(from * width, line * height, to.saturating_sub(from) * width, height, bg);
+        self.scrollback.fill(line, from, to, Cell::blank(bg));
+    }
+
+    /// fills lines `from..to` with the background
+    fn erase_lines(&mut self, from: usize, to: usize) {
+        let height = self.line_height();
+        let bg = self.attr.colors().1;
+        let width = self.fb.width();
+        self.fb.fill_rect(0, from * height, width, to.saturating_sub(from) * height, bg);
+        for line in from..to.min(self.lines()) {
+            self.scrollback.fill(line, 0, self.columns(), Cell::blank(bg));
+        }
+    }
+
+    fn csi(&mut self, csi: &Csi) {
+        if csi.private.is_some() || csi.intermediate.is_some() {
+            //  e.g. `CSI ? 25 l` (cursor visibility), there is no cursor to hide
+            return;
+        }
+        let n = csi.param(0, 1) as usize;
+        let (line, row) = (self.line, self.row);
+        match csi.final_byte {
+            b'A' => self.move_to(line.saturating_sub(n), row),
+            b'B' | b'e' => self.move_to(line + n, row),
+            b'C' | b'a' => self.move_to(line, row + n),
+            b'D' => self.move_to(line, row.saturating_sub(n)),
+            b'E' => self.move_to(line + n, 0),
+            b'F' => self.move_to(line.saturating_sub(n), 0),
+            b'G' | b'`' => self.move_to(line, n - 1),
+            b'd' => self.move_to(n - 1, row),
+            b'H' | b'f' => self.move_to(n - 1, csi.param(1, 1) as usize - 1),
+            b'J' => match csi.param(0, 0) {
+                0 => {
+                    self.erase_cells(line, row, self.columns());
+                    self.erase_lines(line + 1, self.lines());
+                }
+                1 => {
+                    self.erase_lines(0, line);
+                    self.erase_cells(line, 0, row + 1);
                 }
+                2 | 3 => self.erase_lines(0, self.lines()),
+                _ => {}
             },
-            _ => {
-                let fnt = match crate::renderer::font::FONT.as_ref().get_char(c) {
-                    Some(f) => f,
-                    None => &font::ERR_CHAR,
-                };
-                let fb = self.fb();
-                let arr = unsafe {fb.address().cast::<u32>().add((self.line * fb.width * (font::FONT_BITS + self.space as usize)) + self.row * font::FONT_BITS)};
-
-                for i in 0..font::FONT_BITS {
-                    for ii in 0..font::FONT_BITS {
-                        unsafe {arr.add((i as usize * fb.width) + (font::FONT_BITS - ii as usize)).write(self.color().as_int() * ((fnt[i] as u32 >> ii as u32) & 1))};
-                    }
+            b'K' => match csi.param(0, 0) {
+                0 => self.erase_cells(line, row, self.columns()),
+                1 => self.erase_cells(line, 0, row + 1),
+                2 => self.erase_cells(line, 0, self.columns()),
+                _ => {}
+            },
+            b'm' => self.sgr(csi.params()),
+            b's' => self.save_cursor(),
+            b'u' => self.restore_cursor(),
+            _ => {}
+        }
+    }
+
+    /// applies "select graphic rendition" parameters
+    fn sgr(&mut self, params: &[u16]) {
+        if params.is_empty() {
+            self.attr = Attributes::new();
+            return;
+        }
+        let mut i = 0;
+        while i < params.len() {
+            let p = params[i];
+            match p {
+                0 => self.attr = Attributes::new(),
+                1 => self.attr.bold = true,
+                22 => self.attr.bold = false,
+                7 => self.attr.inverse = true,
+                27 => self.attr.inverse = false,
+                30..=37 => {
+                    self.attr.fg = ansi::palette((p - 30) as u8);
+                    self.attr.fg_basic = Some((p - 30) as u8);
                 }
-                self.row += 1;
-                if self.row >= self.fb.height {
-                    self.endl();
+                90..=97 => {
+                    self.attr.fg = ansi::palette((p - 90 + 8) as u8);
+                    self.attr.fg_basic = None;
                 }
+                39 => {
+                    self.attr.fg = DEFAULT_FOREGROUND;
+                    self.attr.fg_basic = None;
+                }
+                40..=47 => self.attr.bg = ansi::palette((p - 40) as u8),
+                100..=107 => self.attr.bg = ansi::palette((p - 100 + 8) as u8),
+                49 => self.attr.bg = DEFAULT_BACKGROUND,
+                38 | 48 => {
+                    let (color, used) = match params.get(i + 1) {
+                        Some(5) => (params.get(i + 2).map(|&c| ansi::palette(c as u8)), 2),
+                        Some(2) => match params.get(i + 2..i + 5) {
+                            Some(&[r, g, b]) => (Some(Color::new_rgb(r as u8, g as u8, b as u8)), 4),
+                            _ => (None, params.len()),
+                        },
+                        _ => (None, params.len()),
+                    };
+                    if let Some(color) = color {
+                        if p == 38 {
+                            self.attr.fg = color;
+                            self.attr.fg_basic = None;
+                        } else {
+                            self.attr.bg = color;
+                        }
+                    }
+                    i += used;
+                }
+                _ => {}
             }
+            i += 1;
         }
     }
 
     #[inline(always)]
     pub fn render(&mut self, c: u8) {
         if self.initialized {
+            self.show_screen();
             self.rend(c);
+            self.fb.flush();
         }
     }
 
     #[inline(always)]
     pub fn print(&mut self, str: &[u8]) {
         if self.initialized {
+            self.show_screen();
             for i in 0..str.len() {
                 self.rend(str[i]);
             }
+            self.fb.flush();
         }
     }
     pub fn println(&mut self, str: &[u8]) {
         if self.initialized {
+            self.show_screen();
             for i in 0..str.len() {
                 self.rend(str[i]);
             }
             self.endl();
+            self.fb.flush();
         }
     }
 }
@@ -183,56 +586,24 @@ impl AsMut<Renderer> for Renderer {
     }
 }
 
-pub struct FrameBuffer {
-    width: usize,
-    height: usize,
-    address: *mut Color,
-    bpp: usize,
-}
-
-impl FrameBuffer {
-    pub const fn new() -> Self {
-        Self {
-            width: 0,
-            height: 0,
-            address: core::ptr::null_mut(),
-            bpp: 0,
-        }
-    }
-    pub fn init(&mut self, fb: &limine::request::FramebufferRequest) -> Result<(), ()> {
-        if let Some(res) = fb.get_response() {
-            if let Some(framebuffer) = res.framebuffers().next() {
-                self.bpp = framebuffer.bpp() as usize;
-                self.width = framebuffer.width() as usize;
-                self.height = framebuffer.height() as usize;
-                self.address = framebuffer.addr() as *mut Color;
-                Ok(())
-            } else {
-                Err(())
-            }
-        } else {
-            Err(())
-        }
-    }
-    pub fn width(&self) -> usize { self.width }
-    pub fn height(&self) -> usize { self.height }
-    pub fn bpp(&self) -> usize { self.bpp }
-    pub fn address(&self) -> *mut Color {
-        self.address
-    }
+#[inline(always)]
+pub fn init() -> Result<(), ()> {
+    RENDERER.lock().init(&bootloader::FRAMEBUFFER, 0)
 }
 
-
 #[inline(always)]
-pub fn init() -> Result<(), ()> {
-    RENDERER.lock().init(&bootloader::FRAMEBUFFER)
+pub fn init_back_buffer() -> Result<(), ()> {
+    let mut renderer = RENDERER.lock();
+    //  the console works without history
+    let _ = renderer.attach_scrollback();
+    renderer.attach_back_buffer()
 }
 
 
 impl core::fmt::Write for Renderer {
     #[inline]
     fn write_char(&mut self, c: char) -> core::fmt::Result {
-        self.render(c as u8);
+        self.print(c.encode_utf8(&mut [0; 4]).as_bytes());
         Ok(())
     }
 
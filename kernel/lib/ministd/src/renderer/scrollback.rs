@@ -0,0 +1,227 @@
+//	renderer/scrollback.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  text history of a console
+//  - every cell of the screen is recorded (character and colors), so lines that scrolled out can be drawn again
+//  - rows are kept in a ring, the last `lines` rows are the screen, the older ones are the history
+//  - the memory is given by the owner (the renderer uses frames, like for its back buffer)
+
+
+use crate::renderer::color::Color;
+
+
+/// number of lines kept above the screen
+pub const SCROLLBACK_LINES: usize = 200;
+
+
+/// one character on the screen
+#[derive(Copy, Clone)]
+pub struct Cell {
+    pub ch: char,
+    pub fg: Color,
+    pub bg: Color,
+}
+
+impl Cell {
+    /// empty cell filled with `bg`
+    #[inline(always)]
+    pub const fn blank(bg: Color) -> Self {
+        Self { ch: ' ', fg: bg, bg }
+    }
+}
+
+
+/// # Ring of text rows
+/// - screen lines are counted from the top of the screen
+/// - views are counted in lines scrolled back from the screen
+pub struct Scrollback {
+    cells: *mut Cell,
+    /// number of rows in the ring
+    rows: usize,
+    columns: usize,
+    /// number of lines on the screen
+    lines: usize,
+    /// ring index of the first screen line
+    top: usize,
+    /// number of rows above the screen that hold history
+    history: usize,
+}
+
+impl Scrollback {
+    pub const fn new() -> Self {
+        Self {
+            cells: core::ptr::null_mut(),
+            rows: 0,
+            columns: 0,
+            lines: 0,
+            top: 0,
+            history: 0,
+        }
+    }
+
+    /// returns the number of cells needed for a screen of `columns` x `lines`
+    #[inline]
+    pub const fn size(columns: usize, lines: usize) -> usize {
+        columns * (lines + SCROLLBACK_LINES)
+    }
+
+    /// starts recording a screen of `columns` x `lines` filled with `bg`
+    /// # Safety
+    /// - `cells` must be valid for [`Scrollback::size`] cells until it is returned by [`Scrollback::detach`]
+    pub unsafe fn attach(&mut self, cells: *mut Cell, columns: usize, lines: usize, bg: Color) {
+        *self = Self {
+            cells,
+            rows: lines + SCROLLBACK_LINES,
+            columns,
+            lines,
+            top: 0,
+            history: 0,
+        };
+        for i in 0..Self::size(columns, lines) {
+            unsafe { cells.add(i).write(Cell::blank(bg)) };
+        }
+    }
+
+    /// stops recording
+    /// - returns the memory given to [`Scrollback::attach`] (null if there was none)
+    pub fn detach(&mut self) -> *mut Cell {
+        let cells = self.cells;
+        *self = Self::new();
+        cells
+    }
+
+    #[inline(always)]
+    pub fn is_attached(&self) -> bool {
+        !self.cells.is_null()
+    }
+
+    /// returns the number of cells of the attached memory
+    #[inline(always)]
+    pub fn capacity(&self) -> usize {
+        self.rows * self.columns
+    }
+
+    /// returns the number of lines that can be scrolled back
+    #[inline(always)]
+    pub fn history(&self) -> usize {
+        self.history
+    }
+
+    /// returns pointer to the first cell of screen line `line`, `view` lines back
+    fn row(&self, line: isize, view: usize) -> *mut Cell {
+        let index = (self.top + self.rows) as isize + line - view as isize;
+        unsafe { self.cells.add((index as usize % self.rows) * self.columns) }
+    }
+
+    /// records `cell` at screen `line` and `column`
+    /// - ignored outside of the screen or if nothing is attached
+    pub fn set(&mut self, line: usize, column: usize, cell: Cell) {
+        if self.is_attached() && line < self.lines && column < self.columns {
+            unsafe { self.row(line as isize, 0).add(column).write(cell) };
+        }
+    }
+
+    /// records `cell` in columns `from..to` of screen `line`
+    pub fn fill(&mut self, line: usize, from: usize, to: usize, cell: Cell) {
+        for column in from..to.min(self.columns) {
+            self.set(line, column, cell);
+        }
+    }
+
+    /// moves the screen one line down, its first line becomes history
+    /// - the new last line is filled with `bg`
+    pub fn scroll(&mut self, bg: Color) {
+        if !self.is_attached() {
+            return;
+        }
+        self.top = (self.top + 1) % self.rows;
+        self.history = (self.history + 1).min(self.rows - self.lines);
+        self.fill(self.lines - 1, 0, self.columns, Cell::blank(bg));
+    }
+
+    /// returns cell at screen `line` and `column` as seen `view` lines back
+    /// - returns `None` outside of the screen, above the history or if nothing is attached
+    pub fn get(&self, line: usize, column: usize, view: usize) -> Option<Cell> {
+        if !self.is_attached() || line >= self.lines || column >= self.columns || view > self.history {
+            return None;
+        }
+        Some(unsafe { self.row(line as isize, view).add(column).read() })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLACK: Color = Color::new(0);
+    const WHITE: Color = Color::new(0xffffff);
+
+    fn scrollback(columns: usize, lines: usize) -> Scrollback {
+        let cells = std::vec![Cell::blank(BLACK); Scrollback::size(columns, lines)].leak();
+        let mut scrollback = Scrollback::new();
+        unsafe { scrollback.attach(cells.as_mut_ptr(), columns, lines, BLACK) };
+        scrollback
+    }
+
+    fn ch(scrollback: &Scrollback, line: usize, column: usize, view: usize) -> Option<char> {
+        scrollback.get(line, column, view).map(|c| c.ch)
+    }
+
+    #[test]
+    fn detached() {
+        let mut scrollback = Scrollback::new();
+        scrollback.set(0, 0, Cell { ch: 'a', fg: WHITE, bg: BLACK });
+        scrollback.scroll(BLACK);
+        assert_eq!(scrollback.history(), 0);
+        assert_eq!(ch(&scrollback, 0, 0, 0), None);
+        assert!(scrollback.detach().is_null());
+    }
+
+    #[test]
+    fn set_and_scroll() {
+        let mut scrollback = scrollback(4, 3);
+        scrollback.set(0, 1, Cell { ch: 'a', fg: WHITE, bg: BLACK });
+        scrollback.set(2, 3, Cell { ch: 'b', fg: WHITE, bg: BLACK });
+        scrollback.set(3, 0, Cell { ch: 'x', fg: WHITE, bg: BLACK });
+        assert_eq!(ch(&scrollback, 0, 1, 0), Some('a'));
+        assert_eq!(ch(&scrollback, 3, 0, 0), None);
+        assert_eq!(ch(&scrollback, 0, 1, 1), None);
+
+        scrollback.scroll(WHITE);
+        assert_eq!(scrollback.history(), 1);
+        assert_eq!(ch(&scrollback, 1, 3, 0), Some('b'));
+        assert_eq!(scrollback.get(2, 0, 0).unwrap().bg.as_int(), WHITE.as_int());
+
+        //  one line back the old screen is seen again
+        assert_eq!(ch(&scrollback, 0, 1, 1), Some('a'));
+        assert_eq!(ch(&scrollback, 2, 3, 1), Some('b'));
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let mut scrollback = scrollback(2, 2);
+        for i in 0..SCROLLBACK_LINES + 10 {
+            let ch = char::from_digit((i % 10) as u32, 10).unwrap();
+            scrollback.set(1, 0, Cell { ch, fg: WHITE, bg: BLACK });
+            scrollback.scroll(BLACK);
+        }
+        assert_eq!(scrollback.history(), SCROLLBACK_LINES);
+
+        //  each digit moved one line up per scroll
+        let last = SCROLLBACK_LINES + 9;
+        assert_eq!(ch(&scrollback, 0, 0, 0), char::from_digit((last % 10) as u32, 10));
+        assert_eq!(ch(&scrollback, 1, 0, 2), char::from_digit(((last - 1) % 10) as u32, 10));
+        assert!(scrollback.get(0, 0, SCROLLBACK_LINES).is_some());
+        assert!(scrollback.get(0, 0, SCROLLBACK_LINES + 1).is_none());
+    }
+
+    #[test]
+    fn fill() {
+        let mut scrollback = scrollback(4, 2);
+        scrollback.fill(1, 1, 10, Cell { ch: 'f', fg: WHITE, bg: BLACK });
+        let line: std::string::String = (0..4).map(|c| ch(&scrollback, 1, c, 0).unwrap()).collect();
+        assert_eq!(line, " fff");
+    }
+}
@@ -0,0 +1,167 @@
+//	renderer/utf8.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  incremental UTF-8 decoder
+//  - keeps a partial sequence between calls, so text may be split anywhere
+//  - every malformed sequence yields one U+FFFD (overlong forms and surrogates included)
+
+
+use crate::renderer::font::REPLACEMENT_CHARACTER as REPLACEMENT;
+
+
+#[derive(Copy, Clone)]
+pub struct Decoder {
+    /// code point bits collected so far
+    code: u32,
+    /// continuation bytes still expected
+    needed: u8,
+    /// smallest code point of the current sequence length (rejects overlong forms)
+    min: u32,
+}
+
+impl Decoder {
+    pub const fn new() -> Self {
+        Self {
+            code: 0,
+            needed: 0,
+            min: 0,
+        }
+    }
+
+    /// checks if a sequence is started but not finished
+    #[inline(always)]
+    pub fn is_pending(&self) -> bool {
+        self.needed != 0
+    }
+
+    /// drops the partial sequence
+    /// - returns `true` if there was one (the caller should show U+FFFD)
+    #[inline]
+    pub fn reset(&mut self) -> bool {
+        let pending = self.is_pending();
+        self.needed = 0;
+        pending
+    }
+
+    /// feeds one byte, `f` is called for every decoded character (at most two)
+    pub fn feed<F: FnMut(char)>(&mut self, byte: u8, mut f: F) {
+        if self.is_pending() {
+            if byte & 0xc0 == 0x80 {
+                self.code = self.code << 6 | (byte & 0x3f) as u32;
+                self.needed -= 1;
+                if self.needed == 0 {
+                    let c = Some(self.code)
+                        .filter(|&c| c >= self.min)
+                        .and_then(char::from_u32)
+                        .unwrap_or(REPLACEMENT);
+                    f(c);
+                }
+                return;
+            }
+            //  sequence cut short, the byte starts something new
+            self.needed = 0;
+            f(REPLACEMENT);
+        }
+
+        let (needed, code, min) = match byte {
+            0x00..=0x7f => {
+                f(byte as char);
+                return;
+            }
+            0xc2..=0xdf => (1, byte & 0x1f, 0x80),
+            0xe0..=0xef => (2, byte & 0x0f, 0x800),
+            0xf0..=0xf4 => (3, byte & 0x07, 0x10000),
+            //  continuation without start, 0xc0, 0xc1 and 0xf5..
+            _ => {
+                f(REPLACEMENT);
+                return;
+            }
+        };
+        self.needed = needed;
+        self.code = code as u32;
+        self.min = min;
+    }
+}
+
+impl Default for Decoder {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::string::String;
+
+    fn decode(bytes: &[u8]) -> String {
+        let mut decoder = Decoder::new();
+        let mut text = String::new();
+        for &byte in bytes {
+            decoder.feed(byte, |c| text.push(c));
+        }
+        if decoder.reset() {
+            text.push(REPLACEMENT);
+        }
+        text
+    }
+
+    #[test]
+    fn valid() {
+        let text = "a é ─ 😀";
+        assert_eq!(decode(text.as_bytes()), text);
+        assert_eq!(decode("\u{7f}\u{80}\u{7ff}\u{800}\u{ffff}\u{10000}\u{10ffff}".as_bytes()),
+            "\u{7f}\u{80}\u{7ff}\u{800}\u{ffff}\u{10000}\u{10ffff}");
+    }
+
+    #[test]
+    fn split_sequence() {
+        let mut decoder = Decoder::new();
+        let mut text = String::new();
+        for &byte in "─".as_bytes() {
+            assert!(text.is_empty());
+            decoder.feed(byte, |c| text.push(c));
+        }
+        assert!(!decoder.is_pending());
+        assert_eq!(text, "─");
+    }
+
+    #[test]
+    fn overlong() {
+        //  '/' and NUL encoded in two, three and four bytes
+        assert_eq!(decode(&[0xc0, 0xaf]), "\u{fffd}\u{fffd}");
+        assert_eq!(decode(&[0xc1, 0x80]), "\u{fffd}\u{fffd}");
+        assert_eq!(decode(&[0xe0, 0x80, 0xaf]), "\u{fffd}");
+        assert_eq!(decode(&[0xf0, 0x80, 0x80, 0x80]), "\u{fffd}");
+        assert_eq!(decode(&[0xe0, 0x9f, 0xbf]), "\u{fffd}");
+    }
+
+    #[test]
+    fn surrogates_and_out_of_range() {
+        assert_eq!(decode(&[0xed, 0xa0, 0x80]), "\u{fffd}");
+        assert_eq!(decode(&[0xed, 0xbf, 0xbf]), "\u{fffd}");
+        assert_eq!(decode(&[0xf4, 0x90, 0x80, 0x80]), "\u{fffd}");
+        assert_eq!(decode(&[0xf5, b'a']), "\u{fffd}a");
+        assert_eq!(decode(&[0xff]), "\u{fffd}");
+    }
+
+    #[test]
+    fn truncated() {
+        //  the cut sequence yields one replacement and the next byte is kept
+        assert_eq!(decode(&[0xe2, 0x94, b'a']), "\u{fffd}a");
+        assert_eq!(decode(&[0xf0, 0x9f, 0xe2, 0x94, 0x80]), "\u{fffd}─");
+        assert_eq!(decode(&[b'a', 0xc3]), "a\u{fffd}");
+
+        //  continuation bytes without a start
+        assert_eq!(decode(&[0x80, 0xbf, b'b']), "\u{fffd}\u{fffd}b");
+
+        let mut decoder = Decoder::new();
+        decoder.feed(0xc3, |_| panic!("sequence is not finished"));
+        assert!(decoder.is_pending());
+        assert!(decoder.reset());
+        assert!(!decoder.reset());
+    }
+}
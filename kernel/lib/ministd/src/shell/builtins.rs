@@ -0,0 +1,201 @@
+//	shell/builtins.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  built-in shell commands
+//  - kernel specific commands (like `state`) are registered by the kernel itself
+
+
+use bootloader::MEMMAP;
+use limine_rs::memory_map::EntryType;
+
+use crate::mem::{KB, MB, frame};
+use crate::renderer::console;
+use crate::io::{keyboard, pci};
+use crate::shell::{self, Command};
+use crate::ALLOCATOR;
+
+
+pub(super) static COMMANDS: [Command; 12] = [
+    Command::new("help", "lists commands", help),
+    Command::new("history", "prints entered lines", history),
+    Command::new("clear", "clears the screen", clear),
+    Command::new("echo", "prints arguments", echo),
+    Command::new("heap", "prints heap and frame usage", heap),
+    Command::new("memmap", "prints the bootloader memory map", memmap),
+    Command::new("fb", "prints framebuffer info", fb),
+    Command::new("console", "prints or switches virtual console (console [index])", console_cmd),
+    Command::new("uptime", "prints time since boot", uptime),
+    Command::new("cpus", "lists processors", cpus),
+    Command::new("pci", "lists PCI functions", pci_list),
+    Command::new("keymap", "prints or sets keyboard layout (keymap [name])", keymap),
+];
+
+
+/// prints byte count in the largest fitting unit
+struct Size(usize);
+
+impl core::fmt::Display for Size {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.0 >= MB {
+            write!(f, "{} MiB", self.0 / MB)
+        } else if self.0 >= KB {
+            write!(f, "{} KiB", self.0 / KB)
+        } else {
+            write!(f, "{} B", self.0)
+        }
+    }
+}
+
+
+fn help(_: &[&str]) -> Result<(), ()> {
+    shell::for_each_command(|c| println!("{:<10} {}", c.name, c.help));
+    Ok(())
+}
+
+fn history(_: &[&str]) -> Result<(), ()> {
+    let mut i = 0;
+    shell::for_each_history(|l| {
+        i += 1;
+        println!("{:>3} {}", i, l);
+    });
+    Ok(())
+}
+
+fn clear(_: &[&str]) -> Result<(), ()> {
+    shell::clear_screen();
+    Ok(())
+}
+
+fn echo(args: &[&str]) -> Result<(), ()> {
+    if let Some((first, rest)) = args[1..].split_first() {
+        crate::print!("{}", first);
+        rest.iter().for_each(|a| crate::print!(" {}", a));
+    }
+    println!();
+    Ok(())
+}
+
+fn heap(_: &[&str]) -> Result<(), ()> {
+    let total = ALLOCATOR.total_bytes();
+    let used = ALLOCATOR.allocated_bytes();
+    println!("heap:   {} used of {} ({} free) in {} region(s)",
+        Size(used), Size(total), Size(total.saturating_sub(used)), ALLOCATOR.regions().len());
+
+    let frames = frame::stats();
+    println!("frames: {} used of {} ({} free)",
+        Size(frames.used * frame::FRAME_SIZE), Size(frames.total * frame::FRAME_SIZE),
+        Size(frames.free() * frame::FRAME_SIZE));
+    Ok(())
+}
+
+fn entry_name(t: EntryType) -> &'static str {
+    match t {
+        EntryType::USABLE => "usable",
+        EntryType::RESERVED => "reserved",
+        EntryType::ACPI_RECLAIMABLE => "ACPI reclaimable",
+        EntryType::ACPI_NVS => "ACPI NVS",
+        EntryType::BAD_MEMORY => "bad memory",
+        EntryType::BOOTLOADER_RECLAIMABLE => "bootloader reclaimable",
+        EntryType::EXECUTABLE_AND_MODULES => "kernel and modules",
+        EntryType::FRAMEBUFFER => "framebuffer",
+        _ => "unknown",
+    }
+}
+
+fn memmap(_: &[&str]) -> Result<(), ()> {
+    let Some(response) = MEMMAP.get_response() else {
+        println!("memory map is not available");
+        return Err(());
+    };
+    let mut usable = 0;
+    for entry in response.entries() {
+        println!("{:#014x}-{:#014x} {} {}",
+            entry.base, entry.base + entry.length, Size(entry.length as usize), entry_name(entry.entry_type));
+        if entry.entry_type == EntryType::USABLE {
+            usable += entry.length as usize;
+        }
+    }
+    println!("{} usable", Size(usable));
+    Ok(())
+}
+
+fn fb(_: &[&str]) -> Result<(), ()> {
+    let renderer = console::output().lock();
+    let fb = renderer.fb();
+    if !fb.is_valid() {
+        drop(renderer);
+        println!("no framebuffer");
+        return Err(());
+    }
+    let (width, height, pitch, bpp, address, format) = (fb.width(), fb.height(), fb.pitch(), fb.bpp(), fb.address(), fb.format());
+    let (lines, columns, line, column) = (renderer.lines(), renderer.columns(), renderer.line(), renderer.column());
+    let font = renderer.font().clone();
+    drop(renderer);
+
+    println!("{}x{} {} bpp at {:p}, pitch {} bytes", width, height, bpp, address, pitch);
+    println!("red {}:{} green {}:{} blue {}:{} (shift:size)",
+        format.red.shift, format.red.size, format.green.shift, format.green.size, format.blue.shift, format.blue.size);
+    match font.psf() {
+        Some(psf) => println!("font {}x{} {:?} with {} glyphs", font.width(), font.height(), psf.version(), psf.glyph_count()),
+        None => println!("font {}x{} built-in", font.width(), font.height()),
+    }
+    println!("{}x{} characters, cursor at line {} column {}", columns, lines, line, column);
+    println!("{} display(s)", console::display_count());
+    Ok(())
+}
+
+fn console_cmd(args: &[&str]) -> Result<(), ()> {
+    match args.get(1) {
+        None => {
+            println!("console {} of {} shown, this shell prints to {}", console::active(), console::count(), crate::thread::console());
+            println!("Alt+F1..F{} switches", console::count());
+            Ok(())
+        }
+        Some(index) => {
+            let index: usize = index.parse().map_err(|_| println!("invalid index {}", index))?;
+            console::switch(index).map_err(|_| println!("no console {}", index))
+        }
+    }
+}
+
+fn uptime(_: &[&str]) -> Result<(), ()> {
+    let t = crate::time::uptime();
+    let s = t.as_secs();
+    println!("{}:{:02}:{:02}.{:03}", s / 3600, s / 60 % 60, s % 60, t.subsec_millis());
+    Ok(())
+}
+
+fn cpus(_: &[&str]) -> Result<(), ()> {
+    for cpu in crate::smp::cpus().iter().flatten() {
+        println!("cpu{} APIC {} ACPI {}{}", cpu.index, cpu.lapic_id, cpu.processor_id,
+            if cpu.online { "" } else { " (offline)" });
+    }
+    println!("{} of {} online", crate::smp::online_count(), crate::smp::cpu_count());
+    Ok(())
+}
+
+fn pci_list(_: &[&str]) -> Result<(), ()> {
+    pci::for_each(|d| println!("{}", d));
+    println!("{} function(s)", pci::count());
+    Ok(())
+}
+
+fn keymap(args: &[&str]) -> Result<(), ()> {
+    match args.get(1) {
+        None => {
+            crate::print!("{} (available:", keyboard::keymap().name);
+            keyboard::keymap::for_each_keymap(|k| crate::print!(" {}", k.name));
+            println!(")");
+            Ok(())
+        }
+        Some(name) => {
+            let Some(keymap) = keyboard::keymap::find(name) else {
+                println!("unknown layout {}", name);
+                return Err(());
+            };
+            keyboard::set_keymap(keymap);
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,79 @@
+//	shell/history.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  command history
+//  - fixed ring of the last [`HISTORY_SIZE`] lines, the oldest one is overwritten
+//  - empty lines and repeats of the last line are not stored
+
+
+use crate::shell::line::Line;
+
+
+pub const HISTORY_SIZE: usize = 32;
+
+
+pub struct History {
+    lines: [Line; HISTORY_SIZE],
+    /// index of the oldest line
+    start: usize,
+    len: usize,
+}
+
+impl History {
+    pub const fn new() -> Self {
+        Self {
+            lines: [Line::new(); HISTORY_SIZE],
+            start: 0,
+            len: 0,
+        }
+    }
+
+    #[inline(always)] pub fn len(&self) -> usize { self.len }
+    #[inline(always)] pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// returns line at `index` (0 is the oldest)
+    pub fn get(&self, index: usize) -> Option<&Line> {
+        if index < self.len {
+            Some(&self.lines[(self.start + index) % HISTORY_SIZE])
+        } else {
+            None
+        }
+    }
+
+    /// returns the last stored line
+    #[inline]
+    pub fn last(&self) -> Option<&Line> {
+        self.len.checked_sub(1).and_then(|i| self.get(i))
+    }
+
+    /// stores `line`
+    pub fn push(&mut self, line: &Line) {
+        if line.is_empty() || self.last().is_some_and(|l| l.chars() == line.chars()) {
+            return;
+        }
+        if self.len == HISTORY_SIZE {
+            self.lines[self.start] = *line;
+            self.start = (self.start + 1) % HISTORY_SIZE;
+        } else {
+            self.lines[(self.start + self.len) % HISTORY_SIZE] = *line;
+            self.len += 1;
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Line> {
+        (0..self.len).filter_map(|i| self.get(i))
+    }
+
+    pub fn clear(&mut self) {
+        self.start = 0;
+        self.len = 0;
+    }
+}
+
+impl Default for History {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,434 @@
+//	shell/line.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  line editor
+//  - input comes from the PS/2 keyboard and COM1 (VT100 escape sequences are understood)
+//  - output only relies on backspace moving the cursor left, so it works on the framebuffer and serial
+//  - lines do not wrap while edited, keep them shorter than the screen
+
+
+use core::fmt;
+use spin::Mutex;
+
+use crate::io::{keyboard, serial::COM1, int, KeyCode};
+use crate::shell::history::History;
+
+
+/// maximum number of characters of one line
+pub const MAX_LINE: usize = 128;
+
+
+/// Edited line
+#[derive(Copy, Clone)]
+pub struct Line {
+    chars: [char; MAX_LINE],
+    len: usize,
+}
+
+impl Line {
+    pub const fn new() -> Self {
+        Self {
+            chars: ['\0'; MAX_LINE],
+            len: 0,
+        }
+    }
+
+    #[inline(always)] pub fn len(&self) -> usize { self.len }
+    #[inline(always)] pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    #[inline]
+    pub fn chars(&self) -> &[char] {
+        &self.chars[..self.len]
+    }
+
+    /// encodes the line as UTF-8 into `buffer`
+    /// - returns `Err` if `buffer` is too small
+    pub fn encode<'l>(&self, buffer: &'l mut [u8]) -> Result<&'l str, ()> {
+        let mut at = 0;
+        for c in self.chars() {
+            let len = c.len_utf8();
+            if at + len > buffer.len() {
+                return Err(());
+            }
+            c.encode_utf8(&mut buffer[at..at + len]);
+            at += len;
+        }
+        core::str::from_utf8(&buffer[..at]).map_err(|_| ())
+    }
+
+    fn insert(&mut self, at: usize, c: char) -> Result<(), ()> {
+        if self.len == MAX_LINE || at > self.len {
+            return Err(());
+        }
+        self.chars.copy_within(at..self.len, at + 1);
+        self.chars[at] = c;
+        self.len += 1;
+        Ok(())
+    }
+
+    fn remove(&mut self, at: usize) {
+        if at < self.len {
+            self.chars.copy_within(at + 1..self.len, at);
+            self.len -= 1;
+        }
+    }
+}
+
+impl Default for Line {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.chars().iter().try_for_each(|c| fmt::Write::write_char(f, *c))
+    }
+}
+
+
+/// Editing action
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Input {
+    Char(char),
+    Enter,
+    Backspace,
+    Delete,
+    Left,
+    Right,
+    Home,
+    End,
+    Up,
+    Down,
+    /// Ctrl+C, drops the line
+    Cancel,
+    /// Ctrl+U, clears the line
+    Kill,
+    /// Ctrl+L, clears the screen
+    ClearScreen,
+}
+
+impl Input {
+    /// translates key press
+    fn from_key(e: keyboard::KeyEvent) -> Option<Self> {
+        if !e.pressed {
+            return None;
+        }
+        if e.modifiers.ctrl() {
+            return match e.key {
+                KeyCode::C => Some(Self::Cancel),
+                KeyCode::U => Some(Self::Kill),
+                KeyCode::L => Some(Self::ClearScreen),
+                KeyCode::A => Some(Self::Home),
+                KeyCode::E => Some(Self::End),
+                _ => None,
+            };
+        }
+        let input = match e.key {
+            KeyCode::Enter | KeyCode::KeypadEnter => Self::Enter,
+            KeyCode::Backspace => Self::Backspace,
+            KeyCode::Delete => Self::Delete,
+            KeyCode::Left => Self::Left,
+            KeyCode::Right => Self::Right,
+            KeyCode::Home => Self::Home,
+            KeyCode::End => Self::End,
+            KeyCode::Up => Self::Up,
+            KeyCode::Down => Self::Down,
+            _ => Self::Char(e.ch.filter(|c| !c.is_control())?),
+        };
+        Some(input)
+    }
+}
+
+
+/// escape sequence state of [`SerialInput`]
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum SerialState {
+    Normal,
+    Escape,
+    /// `ESC [` with optional numeric parameter
+    Csi(u8),
+}
+
+/// Parser of bytes received over serial line
+#[derive(Copy, Clone)]
+struct SerialInput {
+    state: SerialState,
+    utf8: [u8; 4],
+    utf8_len: usize,
+}
+
+impl SerialInput {
+    const fn new() -> Self {
+        Self {
+            state: SerialState::Normal,
+            utf8: [0; 4],
+            utf8_len: 0,
+        }
+    }
+
+    fn feed(&mut self, byte: u8) -> Option<Input> {
+        match self.state {
+            SerialState::Escape => {
+                self.state = if byte == b'[' { SerialState::Csi(0) } else { SerialState::Normal };
+                return None;
+            }
+            SerialState::Csi(param) => {
+                self.state = SerialState::Normal;
+                return match byte {
+                    b'0'..=b'9' => {
+                        self.state = SerialState::Csi(param.saturating_mul(10).saturating_add(byte - b'0'));
+                        None
+                    }
+                    b'A' => Some(Input::Up),
+                    b'B' => Some(Input::Down),
+                    b'C' => Some(Input::Right),
+                    b'D' => Some(Input::Left),
+                    b'H' => Some(Input::Home),
+                    b'F' => Some(Input::End),
+                    b'~' => match param {
+                        1 | 7 => Some(Input::Home),
+                        3 => Some(Input::Delete),
+                        4 | 8 => Some(Input::End),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+            }
+            SerialState::Normal => {}
+        }
+
+        match byte {
+            0x1b => {
+                self.state = SerialState::Escape;
+                None
+            }
+            b'\r' | b'\n' => Some(Input::Enter),
+            0x08 | 0x7f => Some(Input::Backspace),
+            0x01 => Some(Input::Home),
+            0x03 => Some(Input::Cancel),
+            0x05 => Some(Input::End),
+            0x0c => Some(Input::ClearScreen),
+            0x15 => Some(Input::Kill),
+            0x00..=0x1f => None,
+            0x20..=0x7e => Some(Input::Char(byte as char)),
+            _ => {
+                //  multi-byte UTF-8 sequence
+                if self.utf8_len == self.utf8.len() {
+                    self.utf8_len = 0;
+                }
+                self.utf8[self.utf8_len] = byte;
+                self.utf8_len += 1;
+                match core::str::from_utf8(&self.utf8[..self.utf8_len]) {
+                    Ok(s) => {
+                        self.utf8_len = 0;
+                        s.chars().next().map(Input::Char)
+                    }
+                    Err(e) if e.error_len().is_some() => {
+                        self.utf8_len = 0;
+                        None
+                    }
+                    Err(_) => None,
+                }
+            }
+        }
+    }
+}
+
+
+/// Line editor with history
+pub struct Editor {
+    serial: SerialInput,
+}
+
+impl Editor {
+    pub const fn new() -> Self {
+        Self {
+            serial: SerialInput::new(),
+        }
+    }
+
+    /// returns the next editing action (keyboard first, then serial)
+    fn poll(&mut self) -> Option<Input> {
+        while let Some(e) = keyboard::poll_event() {
+            if let Some(i) = Input::from_key(e) {
+                return Some(i);
+            }
+        }
+        let mut com = COM1.try_lock()?;
+        while let Some(b) = com.try_receive() {
+            if let Some(i) = self.serial.feed(b) {
+                return Some(i);
+            }
+        }
+        None
+    }
+
+    /// waits for the next editing action
+    fn read(&mut self) -> Input {
+        loop {
+            if let Some(i) = self.poll() {
+                return i;
+            }
+            if int::are_enabled() {
+                crate::thread::yield_now();
+                //  serial input does not interrupt, the timer wakes us up
+                unsafe { core::arch::asm!("hlt", options(nomem, nostack)) };
+            } else {
+                core::hint::spin_loop();
+            }
+        }
+    }
+
+    /// moves the cursor `n` characters left
+    fn back(n: usize) {
+        for _ in 0..n {
+            print!("\x08");
+        }
+    }
+
+    /// prints `line` from `from` to the end, erases `extra` characters behind it
+    /// and returns the cursor to `from`
+    fn redraw_tail(line: &Line, from: usize, extra: usize) {
+        for c in &line.chars()[from..] {
+            print!("{}", c);
+        }
+        for _ in 0..extra {
+            print!(" ");
+        }
+        Self::back(line.len() - from + extra);
+    }
+
+    /// replaces the displayed line with `new`
+    fn replace(line: &mut Line, cursor: &mut usize, new: &Line) {
+        Self::back(*cursor);
+        let extra = line.len().saturating_sub(new.len());
+        *line = *new;
+        for c in line.chars() {
+            print!("{}", c);
+        }
+        for _ in 0..extra {
+            print!(" ");
+        }
+        Self::back(extra);
+        *cursor = line.len();
+    }
+
+    /// reads line, the prompt must already be printed
+    /// - `history` is only locked to look up entries, not while waiting for input
+    /// - returns `None` if the line was cancelled
+    pub fn read_line(&mut self, history: &Mutex<History>) -> Option<Line> {
+        let mut line = Line::new();
+        let mut cursor = 0;
+        //  position while browsing history (`history.len()` = the edited line)
+        let mut browsing = history.lock().len();
+        let mut draft = Line::new();
+
+        loop {
+            match self.read() {
+                Input::Enter => {
+                    print!("\n");
+                    return Some(line);
+                }
+                Input::Cancel => {
+                    print!("^C\n");
+                    return None;
+                }
+                Input::Char(c) => {
+                    if line.insert(cursor, c).is_ok() {
+                        print!("{}", c);
+                        cursor += 1;
+                        Self::redraw_tail(&line, cursor, 0);
+                    }
+                }
+                Input::Backspace => {
+                    if cursor > 0 {
+                        cursor -= 1;
+                        line.remove(cursor);
+                        Self::back(1);
+                        Self::redraw_tail(&line, cursor, 1);
+                    }
+                }
+                Input::Delete => {
+                    if cursor < line.len() {
+                        line.remove(cursor);
+                        Self::redraw_tail(&line, cursor, 1);
+                    }
+                }
+                Input::Left => {
+                    if cursor > 0 {
+                        cursor -= 1;
+                        Self::back(1);
+                    }
+                }
+                Input::Right => {
+                    if cursor < line.len() {
+                        print!("{}", line.chars()[cursor]);
+                        cursor += 1;
+                    }
+                }
+                Input::Home => {
+                    Self::back(cursor);
+                    cursor = 0;
+                }
+                Input::End => {
+                    for c in &line.chars()[cursor..] {
+                        print!("{}", c);
+                    }
+                    cursor = line.len();
+                }
+                Input::Kill => {
+                    Self::replace(&mut line, &mut cursor, &Line::new());
+                }
+                Input::Up => {
+                    let entry = {
+                        let history = history.lock();
+                        //  other shells may have cleared it meanwhile
+                        browsing = browsing.min(history.len());
+                        if browsing == 0 {
+                            None
+                        } else {
+                            if browsing == history.len() {
+                                draft = line;
+                            }
+                            browsing -= 1;
+                            history.get(browsing).copied()
+                        }
+                    };
+                    if let Some(entry) = entry {
+                        Self::replace(&mut line, &mut cursor, &entry);
+                    }
+                }
+                Input::Down => {
+                    let entry = {
+                        let history = history.lock();
+                        browsing = browsing.min(history.len());
+                        if browsing < history.len() {
+                            browsing += 1;
+                            if browsing == history.len() { Some(draft) } else { history.get(browsing).copied() }
+                        } else {
+                            None
+                        }
+                    };
+                    if let Some(entry) = entry {
+                        Self::replace(&mut line, &mut cursor, &entry);
+                    }
+                }
+                Input::ClearScreen => {
+                    crate::shell::clear_screen();
+                    return Some(Line::new());
+                }
+            }
+        }
+    }
+}
+
+impl Default for Editor {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
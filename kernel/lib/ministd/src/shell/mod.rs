@@ -0,0 +1,163 @@
+//	shell/mod.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  interactive kernel shell
+//  - reads lines from the keyboard (and COM1) and prints through the renderer
+//  - commands live in a table, the kernel adds its own with [`register_command`]
+//  - arguments are split on whitespace, there is no quoting
+//  - start it with [`run`] (usually in its own thread)
+
+
+use spin::Mutex;
+
+use crate::mem::vec::Vec;
+use crate::renderer::ansi;
+
+pub mod line;
+pub mod history;
+mod builtins;
+
+use line::{Editor, Line, MAX_LINE};
+use history::History;
+
+
+/// maximum number of arguments (including the command name)
+pub const MAX_ARGS: usize = 16;
+
+pub const PROMPT: &str = "> ";
+
+
+/// Command handler
+/// - `args[0]` is the command name
+/// - returning `Err` makes the shell report failure (print the reason first)
+pub type CommandFn = fn(args: &[&str]) -> Result<(), ()>;
+
+/// Shell command
+#[derive(Copy, Clone)]
+pub struct Command {
+    pub name: &'static str,
+    /// one line description shown by `help`
+    pub help: &'static str,
+    pub run: CommandFn,
+}
+
+impl Command {
+    pub const fn new(name: &'static str, help: &'static str, run: CommandFn) -> Self {
+        Self { name, help, run }
+    }
+}
+
+
+static COMMANDS: Mutex<Vec<Command>> = Mutex::new(Vec::new());
+static HISTORY: Mutex<History> = Mutex::new(History::new());
+
+
+/// adds `command` to the command table
+/// - returns `Err` if a command with the same name exists or the table can not grow
+pub fn register_command(command: Command) -> Result<(), ()> {
+    let mut commands = COMMANDS.lock();
+    if commands.as_slice().is_some_and(|c| c.iter().any(|c| c.name == command.name)) {
+        return Err(());
+    }
+    commands.try_push(command).map_err(|_| ())
+}
+
+/// removes command `name` from the command table
+/// - returns `Err` if there is no such command
+pub fn unregister_command(name: &str) -> Result<(), ()> {
+    let mut commands = COMMANDS.lock();
+    let index = commands.as_slice()
+        .and_then(|c| c.iter().position(|c| c.name == name))
+        .ok_or(())?;
+    commands.remove(index);
+    Ok(())
+}
+
+/// returns copy of command `name`
+pub fn find_command(name: &str) -> Option<Command> {
+    COMMANDS.lock().as_slice()?.iter().find(|c| c.name == name).copied()
+}
+
+/// calls `f` for every registered command
+/// - the command table is locked meanwhile
+pub fn for_each_command<F: FnMut(&Command)>(f: F) {
+    if let Some(commands) = COMMANDS.lock().as_slice() {
+        commands.iter().for_each(f);
+    }
+}
+
+/// calls `f` for every line in the history (oldest first)
+/// - the history is locked meanwhile
+pub fn for_each_history<F: FnMut(&Line)>(f: F) {
+    HISTORY.lock().iter().for_each(f);
+}
+
+/// clears the screen and moves the cursor to the top left corner
+/// - works on the serial console too
+#[inline]
+pub fn clear_screen() {
+    crate::print!("{}", ansi::CLEAR_SCREEN);
+}
+
+
+/// runs one command line
+/// - returns `Err` if the command is unknown, has too many arguments or fails (the reason is printed)
+pub fn execute(line: &str) -> Result<(), ()> {
+    let mut args = [""; MAX_ARGS];
+    let mut count = 0;
+    for arg in line.split_whitespace() {
+        if count == MAX_ARGS {
+            println!("too many arguments (at most {})", MAX_ARGS - 1);
+            return Err(());
+        }
+        args[count] = arg;
+        count += 1;
+    }
+    if count == 0 {
+        return Ok(());
+    }
+
+    //  the table is not locked while the command runs
+    let Some(command) = find_command(args[0]) else {
+        println!("{}: unknown command (try `help`)", args[0]);
+        return Err(());
+    };
+    (command.run)(&args[..count]).inspect_err(|_| {
+        println!("{}: failed", command.name);
+    })
+}
+
+/// reads and runs commands forever
+/// - requires the built-in commands to be registered (see [`crate::init::shell()`])
+pub fn run() -> ! {
+    let mut editor = Editor::new();
+    let mut buffer = [0u8; MAX_LINE * 4];
+
+    loop {
+        print!("{}", PROMPT);
+
+        let Some(line) = editor.read_line(&HISTORY) else {
+            continue;
+        };
+        HISTORY.lock().push(&line);
+
+        //  `MAX_LINE` characters always fit
+        if let Ok(text) = line.encode(&mut buffer) {
+            let _ = execute(text);
+        }
+    }
+}
+
+
+/// registers the built-in commands
+/// - returns `Err` if the command table can not be allocated
+pub(crate) fn init() -> Result<(), ()> {
+    for command in builtins::COMMANDS.iter() {
+        //  the kernel may have replaced a built-in command already
+        if find_command(command.name).is_none() {
+            register_command(*command)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,217 @@
+//	smp.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  application processor bring-up
+//  - processors are started by the bootloader and wait for a jump address (MP request)
+//  - each AP gets its own stack, GDT/TSS and local APIC setup, the IDT is shared
+//  - the memory of every AP is allocated by the BSP before the first AP is woken
+//  - all processors meet at a barrier, so `init` returns only after every core reported in
+//  - threads are only scheduled on the bootstrap processor, APs idle
+
+
+use core::arch::asm;
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, AtomicU32, AtomicUsize, Ordering};
+use spin::{Barrier, Mutex, Once};
+use limine_rs::mp::Cpu;
+
+use crate::arch::{gdt::{self, Gdt}, idt};
+use crate::io::int::{self, lapic};
+use crate::mem::frame::{self, FRAME_SIZE};
+
+
+/// maximum number of processors that are brought up
+pub const MAX_CPUS: usize = 64;
+/// number of frames of each AP kernel stack
+pub const AP_STACK_FRAMES: usize = 16;
+
+/// number of frames allocated for each AP (kernel stack followed by IST stacks and descriptor tables)
+const AP_FRAMES: usize = AP_STACK_FRAMES + gdt::AP_FRAMES;
+
+
+/// Processor known to the registry
+#[derive(Copy, Clone, Debug)]
+pub struct CpuInfo {
+    /// index in the registry (0 is the bootstrap processor)
+    pub index: usize,
+    /// ACPI processor ID
+    pub processor_id: u32,
+    pub lapic_id: u32,
+    pub online: bool,
+}
+
+
+static CPUS: Mutex<[Option<CpuInfo>; MAX_CPUS]> = Mutex::new([None; MAX_CPUS]);
+static CPU_COUNT: AtomicUsize = AtomicUsize::new(1);
+static ONLINE: AtomicUsize = AtomicUsize::new(1);
+static BSP_LAPIC_ID: AtomicU32 = AtomicU32::new(0);
+static RENDEZVOUS: Once<Barrier> = Once::new();
+/// top of the kernel stack of each AP (indexed like the registry)
+static AP_STACKS: [AtomicUsize; MAX_CPUS] = [const { AtomicUsize::new(0) }; MAX_CPUS];
+/// GDT of each AP (indexed like the registry)
+static AP_GDTS: [AtomicPtr<Gdt>; MAX_CPUS] = [const { AtomicPtr::new(ptr::null_mut()) }; MAX_CPUS];
+
+
+/// returns the number of processors that are (being) brought up
+#[inline]
+pub fn cpu_count() -> usize {
+    CPU_COUNT.load(Ordering::Acquire)
+}
+
+/// returns the number of processors that finished initialization
+#[inline]
+pub fn online_count() -> usize {
+    ONLINE.load(Ordering::Acquire)
+}
+
+/// returns copy of the processor registry
+pub fn cpus() -> [Option<CpuInfo>; MAX_CPUS] {
+    *CPUS.lock()
+}
+
+/// returns local APIC ID of the current processor
+#[inline]
+pub fn current_lapic_id() -> Option<u32> {
+    lapic().map(|l| l.id())
+}
+
+/// returns registry entry of the current processor
+pub fn current() -> Option<CpuInfo> {
+    let id = current_lapic_id()?;
+    CPUS.lock().iter().flatten().find(|c| c.lapic_id == id).copied()
+}
+
+/// checks if the code runs on the bootstrap processor
+/// - always `true` before interrupt controllers are initialized
+#[inline]
+pub fn is_bsp() -> bool {
+    current_lapic_id().is_none_or(|id| id == BSP_LAPIC_ID.load(Ordering::Relaxed))
+}
+
+
+/// entry point of application processors (still on the bootloader stack)
+/// - switches to the stack allocated by the BSP and calls `ap_main` with the prepared GDT
+unsafe extern "C" fn ap_entry(cpu: &Cpu) -> ! {
+    let index = CPUS.lock().iter().flatten()
+        .find(|c| c.lapic_id == cpu.lapic_id)
+        .map(|c| c.index);
+
+    let (top, gdt) = match index {
+        Some(i) => (AP_STACKS[i].load(Ordering::Acquire), AP_GDTS[i].load(Ordering::Acquire)),
+        None => (0, ptr::null_mut()),
+    };
+    if top == 0 || gdt.is_null() {
+        //  not in the registry, stay parked
+        crate::hang();
+    }
+
+    unsafe {
+        asm!(
+            "mov rsp, {top}",
+            "xor rbp, rbp",
+            "call {main}",
+            "ud2",
+            top = in(reg) top,
+            main = sym ap_main,
+            in("rdi") cpu,
+            in("rsi") gdt,
+            options(noreturn),
+        );
+    }
+}
+
+/// - must not fail, the BSP waits until every AP reaches the barrier
+extern "C" fn ap_main(cpu: &Cpu, gdt: &'static Gdt) -> ! {
+    unsafe { gdt::load_ap(gdt) };
+    idt::load();
+
+    //  presence checked by the BSP before waking the APs
+    if let Some(l) = lapic() {
+        let madt = crate::acpi::tables().and_then(|t| t.madt());
+        unsafe { l.enable(madt.as_ref()) };
+    }
+
+    if let Some(entry) = CPUS.lock().iter_mut().flatten().find(|c| c.lapic_id == cpu.lapic_id) {
+        entry.online = true;
+    }
+    ONLINE.fetch_add(1, Ordering::AcqRel);
+
+    if let Some(barrier) = RENDEZVOUS.get() {
+        barrier.wait();
+    }
+
+    int::enable();
+    loop {
+        unsafe { asm!("hlt", options(nomem, nostack)) };
+    }
+}
+
+
+/// starts all application processors and waits until they are initialized
+/// - requires the frame allocator, the heap and the interrupt controllers
+/// - returns the number of online processors
+pub(crate) fn init() -> Result<usize, ()> {
+    let response = bootloader::MP.get_response().ok_or(())?;
+    let hhdm = crate::mem::hhdm_offset().ok_or(())?;
+    lapic().ok_or(())?;
+    let bsp = response.bsp_lapic_id();
+    BSP_LAPIC_ID.store(bsp, Ordering::Relaxed);
+
+    let cpus = response.cpus();
+    let mut count = 0;
+
+    {
+        let mut registry = CPUS.lock();
+        for cpu in cpus.iter().take(MAX_CPUS) {
+            let index = count;
+            count += 1;
+            registry[index] = Some(CpuInfo {
+                index,
+                processor_id: cpu.id,
+                lapic_id: cpu.lapic_id,
+                online: cpu.lapic_id == bsp,
+            });
+        }
+    }
+
+    //  all memory is allocated before the first AP is woken,
+    //  the barrier would never open if some of them did not start
+    for (index, cpu) in cpus.iter().take(MAX_CPUS).enumerate() {
+        if cpu.lapic_id == bsp {
+            continue;
+        }
+        let Ok(memory) = frame::alloc_frames(AP_FRAMES) else {
+            free_memory(hhdm);
+            return Err(());
+        };
+        let stack = memory + hhdm;
+        let gdt = unsafe { gdt::prepare_ap(stack + AP_STACK_FRAMES * FRAME_SIZE) };
+        AP_GDTS[index].store(ptr::from_ref(gdt).cast_mut(), Ordering::Release);
+        AP_STACKS[index].store(stack + AP_STACK_FRAMES * FRAME_SIZE, Ordering::Release);
+    }
+
+    CPU_COUNT.store(count, Ordering::Release);
+    let barrier = RENDEZVOUS.call_once(|| Barrier::new(count));
+
+    for cpu in cpus.iter().take(MAX_CPUS) {
+        if cpu.lapic_id != bsp {
+            //  wakes the processor
+            cpu.goto_address.write(ap_entry);
+        }
+    }
+
+    barrier.wait();
+    Ok(online_count())
+}
+
+/// returns the AP memory allocated so far, the APs must not have been woken
+fn free_memory(hhdm: usize) {
+    for (top, gdt) in AP_STACKS.iter().zip(AP_GDTS.iter()) {
+        gdt.store(ptr::null_mut(), Ordering::Release);
+        let top = top.swap(0, Ordering::AcqRel);
+        if top != 0 {
+            unsafe { frame::free_frames(top - hhdm - AP_STACK_FRAMES * FRAME_SIZE, AP_FRAMES) };
+        }
+    }
+}
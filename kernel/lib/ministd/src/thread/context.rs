@@ -0,0 +1,61 @@
+//	thread/context.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  context switching
+//  - only callee-saved registers are stored, the rest is saved by the caller of `switch`
+//  - the kernel is built without SSE, so there is no FPU state to save
+
+
+use core::arch::naked_asm;
+
+
+/// number of registers pushed by [`switch`]
+const SAVED_REGISTERS: usize = 6;
+
+
+/// saves callee-saved registers on the current stack, stores stack pointer to `old`
+/// and continues with the context saved at `new`
+/// - must be called with interrupts disabled
+#[unsafe(naked)]
+pub(super) unsafe extern "C" fn switch(old: *mut usize, new: usize) {
+    naked_asm!(
+        "push rbp",
+        "push rbx",
+        "push r12",
+        "push r13",
+        "push r14",
+        "push r15",
+        "mov [rdi], rsp",
+        "mov rsp, rsi",
+        "pop r15",
+        "pop r14",
+        "pop r13",
+        "pop r12",
+        "pop rbx",
+        "pop rbp",
+        "ret",
+    );
+}
+
+
+/// prepares new stack so that [`switch`] to it jumps into `entry`
+/// - `top` is the end of the stack
+/// - returns the stack pointer to save
+pub(super) unsafe fn prepare_stack(top: usize, entry: extern "C" fn() -> !) -> usize {
+    let top = top & !0xf;
+
+    //  [top - 8]:  fake return address of `entry`
+    //  [top - 16]: `entry` (popped by `ret`)
+    //  below:      zeroed callee-saved registers
+    let rsp = top - (SAVED_REGISTERS + 2) * size_of::<usize>();
+    let slots = rsp as *mut usize;
+    unsafe {
+        for i in 0..SAVED_REGISTERS {
+            slots.add(i).write(0);
+        }
+        slots.add(SAVED_REGISTERS).write(entry as usize);
+        slots.add(SAVED_REGISTERS + 1).write(0);
+    }
+    rsp
+}
@@ -0,0 +1,493 @@
+//	thread/mod.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  preemptive kernel threads
+//  - round-robin scheduling, the running thread is preempted every `QUANTUM_TICKS` timer ticks
+//  - the boot context becomes thread 0
+//  - runs on the bootstrap processor only
+//  - the scheduler lock is only taken with interrupts disabled (the timer interrupt takes it too)
+//  - every thread has an output console (see [`crate::renderer::console`]), new threads inherit it
+
+
+use core::arch::asm;
+use core::fmt;
+use core::mem::ManuallyDrop;
+use core::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use spin::Mutex;
+
+use crate::Box;
+use crate::io::int;
+use crate::mem::frame::{self, FRAME_SIZE};
+use crate::time::{self, Duration, Instant};
+
+mod context;
+
+
+/// maximum number of threads (including finished ones that were not joined yet)
+pub const MAX_THREADS: usize = 64;
+/// number of frames of each thread stack
+pub const STACK_FRAMES: usize = 16;
+pub const STACK_SIZE: usize = STACK_FRAMES * FRAME_SIZE;
+/// number of timer ticks a thread runs before it is preempted
+pub const QUANTUM_TICKS: u64 = 10;
+
+
+/// Function the thread runs
+pub type ThreadEntry = fn();
+
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct ThreadId(u64);
+
+impl ThreadId {
+    /// id of the boot thread
+    pub const BOOT: ThreadId = ThreadId(0);
+
+    #[inline(always)]
+    pub const fn as_u64(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for ThreadId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ThreadState {
+    Ready,
+    Running,
+    Sleeping(Instant),
+    /// waits for another thread to finish
+    Joining(ThreadId),
+    Finished,
+}
+
+
+struct Thread {
+    id: ThreadId,
+    state: ThreadState,
+    /// saved stack pointer (valid while not running)
+    rsp: usize,
+    /// physical address of the stack (0 for the boot thread)
+    stack: usize,
+    entry: Option<ThreadEntry>,
+    /// there is no `JoinHandle` (freed once finished)
+    detached: bool,
+    /// index of the console `print!` writes to
+    console: usize,
+}
+
+impl Drop for Thread {
+    fn drop(&mut self) {
+        if self.stack != 0 {
+            unsafe { frame::free_frames(self.stack, STACK_FRAMES) };
+        }
+    }
+}
+
+
+/// (where to save current stack pointer, stack pointer to load, id of the next thread)
+type Switch = (*mut usize, usize, ThreadId);
+
+struct Scheduler {
+    threads: [Option<Box<Thread>>; MAX_THREADS],
+    current: usize,
+}
+
+unsafe impl Send for Scheduler {}
+
+impl Scheduler {
+    const fn new() -> Self {
+        Self {
+            threads: [const { None }; MAX_THREADS],
+            current: 0,
+        }
+    }
+
+    #[inline]
+    fn current(&mut self) -> &mut Thread {
+        self.threads[self.current].as_mut().expect("current thread does not exist")
+    }
+
+    /// returns index of the next thread to run
+    /// - the current thread is checked last
+    fn pick(&mut self, now: Instant) -> Option<usize> {
+        for offset in 1..=MAX_THREADS {
+            let index = (self.current + offset) % MAX_THREADS;
+            let Some(thread) = self.threads[index].as_mut() else { continue };
+
+            match thread.state {
+                ThreadState::Ready | ThreadState::Running => return Some(index),
+                ThreadState::Sleeping(until) if until <= now => {
+                    thread.state = ThreadState::Ready;
+                    return Some(index);
+                },
+                _ => {},
+            }
+        }
+        None
+    }
+
+    /// marks `next` as running
+    /// - returns `None` if `next` is the current thread
+    fn switch_to(&mut self, next: usize) -> Option<Switch> {
+        let thread = self.threads[next].as_mut()?;
+        thread.state = ThreadState::Running;
+        if next == self.current {
+            return None;
+        }
+        let (new, id) = (thread.rsp, thread.id);
+        CONSOLE.store(thread.console, Ordering::Relaxed);
+
+        let old = &raw mut self.current().rsp;
+        self.current = next;
+        Some((old, new, id))
+    }
+
+    /// finds thread by its handle
+    #[inline]
+    fn get(&mut self, index: usize, id: ThreadId) -> Option<&mut Thread> {
+        self.threads.get_mut(index)?.as_deref_mut().filter(|t| t.id == id)
+    }
+}
+
+
+static SCHEDULER: Mutex<Scheduler> = Mutex::new(Scheduler::new());
+static INITIALIZED: AtomicBool = AtomicBool::new(false);
+static NEED_RESCHED: AtomicBool = AtomicBool::new(false);
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+/// console of the running thread, read without taking the scheduler lock
+static CONSOLE: AtomicUsize = AtomicUsize::new(0);
+
+
+unsafe extern "Rust" {
+
+    //  function defined by the developer in the main crate
+
+    /// called right before switching to thread `id`
+    /// - runs with interrupts disabled
+    pub(crate) fn thread_switched(id: ThreadId);
+}
+
+
+#[inline]
+unsafe fn do_switch(switch: Switch) {
+    let (old, new, id) = switch;
+    unsafe {
+        thread_switched(id);
+        context::switch(old, new);
+    }
+}
+
+/// sets state of the current thread and switches to the next one
+/// - halts if no thread can run
+/// - interrupts must be disabled
+fn reschedule(state: ThreadState) {
+    let mut state = Some(state);
+    loop {
+        let next = {
+            let mut sched = SCHEDULER.lock();
+            if let Some(s) = state.take() {
+                sched.current().state = s;
+            }
+            sched.pick(Instant::now()).map(|next| sched.switch_to(next))
+        };
+
+        match next {
+            Some(Some(switch)) => {
+                unsafe { do_switch(switch) };
+                return;
+            },
+            Some(None) => return,
+            //  wait for an interrupt (`sti` delays interrupts by one instruction)
+            None => unsafe { asm!("sti", "hlt", "cli", options(nomem, nostack)) },
+        }
+    }
+}
+
+
+/// first code every new thread runs
+extern "C" fn thread_entry() -> ! {
+    //  interrupts are still disabled from the switch
+    let entry = SCHEDULER.lock().current().entry;
+    int::enable();
+
+    if let Some(entry) = entry {
+        entry();
+    }
+    exit();
+}
+
+
+/// Handle used to wait for a thread
+/// - the thread is freed after it finishes, if the handle is dropped
+pub struct JoinHandle {
+    index: usize,
+    id: ThreadId,
+}
+
+impl JoinHandle {
+    #[inline(always)]
+    pub fn id(&self) -> ThreadId {
+        self.id
+    }
+
+    /// checks if the thread already finished
+    pub fn is_finished(&self) -> bool {
+        int::without_interrupts(|| {
+            SCHEDULER.lock().get(self.index, self.id)
+                .is_none_or(|t| t.state == ThreadState::Finished)
+        })
+    }
+
+    /// waits until the thread finishes and frees it
+    /// - returns `Err` if called from the thread itself
+    /// - returns `Err` on application processors, they can not block (the thread is detached instead)
+    pub fn join(self) -> Result<(), ()> {
+        if !crate::smp::is_bsp() {
+            return Err(());
+        }
+        let handle = ManuallyDrop::new(self);
+        let (index, id) = (handle.index, handle.id);
+
+        let enabled = int::are_enabled();
+        int::disable();
+
+        let thread = loop {
+            let mut sched = SCHEDULER.lock();
+            if sched.current().id == id {
+                break Err(());
+            }
+            match sched.get(index, id) {
+                Some(t) if t.state == ThreadState::Finished => break Ok(sched.threads[index].take()),
+                Some(_) => {},
+                None => break Ok(None),
+            }
+            drop(sched);
+
+            reschedule(ThreadState::Joining(id));
+        };
+
+        if enabled {
+            int::enable();
+        }
+
+        //  freed with interrupts enabled (uses the heap and the frame allocator)
+        drop(thread?);
+        Ok(())
+    }
+}
+
+impl Drop for JoinHandle {
+    fn drop(&mut self) {
+        int::without_interrupts(|| {
+            if let Some(t) = SCHEDULER.lock().get(self.index, self.id) {
+                t.detached = true;
+            }
+        });
+    }
+}
+
+
+/// frees finished threads without `JoinHandle`
+fn reap() {
+    let mut finished: [Option<Box<Thread>>; MAX_THREADS] = [const { None }; MAX_THREADS];
+
+    int::without_interrupts(|| {
+        let mut sched = SCHEDULER.lock();
+        for (slot, out) in sched.threads.iter_mut().zip(finished.iter_mut()) {
+            if slot.as_ref().is_some_and(|t| t.detached && t.state == ThreadState::Finished) {
+                *out = slot.take();
+            }
+        }
+    });
+}
+
+/// starts new thread running `entry`
+/// - returns `Err` if the scheduler is not initialized, there are too many threads or allocation fails
+pub fn try_spawn(entry: ThreadEntry) -> Result<JoinHandle, ()> {
+    if !INITIALIZED.load(Ordering::Acquire) {
+        return Err(());
+    }
+    reap();
+
+    let stack = frame::alloc_frames(STACK_FRAMES)?;
+    let top = stack + crate::mem::hhdm_offset().ok_or(())? + STACK_SIZE;
+
+    let thread = Box::try_new(Thread {
+        id: ThreadId(NEXT_ID.fetch_add(1, Ordering::Relaxed)),
+        state: ThreadState::Ready,
+        rsp: unsafe { context::prepare_stack(top, thread_entry) },
+        stack,
+        entry: Some(entry),
+        detached: false,
+        console: console(),
+    })?;
+    let id = thread.id;
+
+    let index = int::without_interrupts(|| {
+        let mut sched = SCHEDULER.lock();
+        match sched.threads.iter().position(|t| t.is_none()) {
+            Some(index) => {
+                sched.threads[index] = Some(thread);
+                Ok(index)
+            },
+            None => Err(thread),
+        }
+    });
+
+    //  the rejected thread is freed here, with interrupts enabled
+    index.map(|index| JoinHandle { index, id }).map_err(|_| ())
+}
+
+/// starts new thread running `entry`
+/// - **panics** if the thread can not be created
+#[inline]
+pub fn spawn(entry: ThreadEntry) -> JoinHandle {
+    try_spawn(entry).expect("failed to spawn thread")
+}
+
+/// lets other threads run
+/// - only a spin loop hint on application processors, threads run on the BSP only
+pub fn yield_now() {
+    if !crate::smp::is_bsp() {
+        core::hint::spin_loop();
+    } else if INITIALIZED.load(Ordering::Acquire) {
+        int::without_interrupts(|| reschedule(ThreadState::Ready));
+    }
+}
+
+/// puts the current thread to sleep for at least `duration`
+/// - uses [`time::sleep`] before the scheduler is initialized
+/// - spins on application processors (they get no timer interrupts)
+pub fn sleep(duration: Duration) {
+    if !crate::smp::is_bsp() {
+        time::busy_sleep(duration);
+        return;
+    }
+    if !INITIALIZED.load(Ordering::Acquire) {
+        time::sleep(duration);
+        return;
+    }
+    let until = Instant::now() + duration;
+    int::without_interrupts(|| reschedule(ThreadState::Sleeping(until)));
+}
+
+/// finishes the current thread
+pub fn exit() -> ! {
+    int::disable();
+    {
+        let mut sched = SCHEDULER.lock();
+        let id = sched.current().id;
+        for t in sched.threads.iter_mut().flatten() {
+            if t.state == ThreadState::Joining(id) {
+                t.state = ThreadState::Ready;
+            }
+        }
+    }
+    reschedule(ThreadState::Finished);
+    unreachable!("finished thread was scheduled again");
+}
+
+/// returns id of the running thread
+pub fn current_id() -> ThreadId {
+    if !INITIALIZED.load(Ordering::Acquire) {
+        return ThreadId::BOOT;
+    }
+    int::without_interrupts(|| SCHEDULER.lock().current().id)
+}
+
+/// returns index of the console the running thread prints to
+#[inline]
+pub fn console() -> usize {
+    CONSOLE.load(Ordering::Relaxed)
+}
+
+/// makes the running thread (and threads it starts later) print to console `index`
+/// - the console is not checked, output to a missing one goes to console 0
+pub fn set_console(index: usize) {
+    int::without_interrupts(|| {
+        if INITIALIZED.load(Ordering::Acquire) {
+            SCHEDULER.lock().current().console = index;
+        }
+        CONSOLE.store(index, Ordering::Relaxed);
+    });
+}
+
+/// returns the number of existing threads
+pub fn count() -> usize {
+    int::without_interrupts(|| SCHEDULER.lock().threads.iter().flatten().count())
+}
+
+/// turns the current thread into an idle loop
+/// - other threads keep running
+pub fn idle() -> ! {
+    int::enable();
+    loop {
+        unsafe { asm!("hlt", options(nomem, nostack)) };
+    }
+}
+
+
+/// called by the timer on every tick
+#[inline]
+pub(crate) fn on_tick(ticks: u64) {
+    if ticks.is_multiple_of(QUANTUM_TICKS) {
+        NEED_RESCHED.store(true, Ordering::Relaxed);
+    }
+}
+
+/// switches to another thread if the time slice of the current one is over
+/// - called at the end of interrupt handling (after EOI)
+pub(crate) fn preempt() {
+    if !INITIALIZED.load(Ordering::Acquire) || !crate::smp::is_bsp() {
+        return;
+    }
+    if !NEED_RESCHED.swap(false, Ordering::Relaxed) {
+        return;
+    }
+
+    let switch = {
+        //  interrupted code may hold the lock
+        let Some(mut sched) = SCHEDULER.try_lock() else { return };
+
+        //  blocked threads waiting in `reschedule` are not preempted
+        if sched.current().state != ThreadState::Running {
+            return;
+        }
+        sched.current().state = ThreadState::Ready;
+        sched.pick(Instant::now()).and_then(|next| sched.switch_to(next))
+    };
+
+    if let Some(switch) = switch {
+        unsafe { do_switch(switch) };
+    }
+}
+
+
+/// makes the current context the boot thread
+/// - requires the heap and the time subsystem
+pub(crate) fn init() -> Result<(), ()> {
+    let boot = Box::try_new(Thread {
+        id: ThreadId::BOOT,
+        state: ThreadState::Running,
+        rsp: 0,
+        stack: 0,
+        entry: None,
+        detached: true,
+        console: console(),
+    })?;
+
+    int::without_interrupts(|| {
+        let mut sched = SCHEDULER.lock();
+        sched.threads[0] = Some(boot);
+        sched.current = 0;
+    });
+    INITIALIZED.store(true, Ordering::Release);
+    Ok(())
+}
@@ -0,0 +1,87 @@
+//	time/hpet.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  High Precision Event Timer
+//  - only the main counter is used (as calibration reference)
+//  - registers are accessed through HHDM
+
+
+use core::ptr::{read_volatile, write_volatile};
+
+use crate::acpi::hpet::ADDRESS_SPACE_MEMORY;
+
+
+mod reg {
+    pub const CAPABILITIES: usize = 0x00;
+    pub const CONFIG: usize = 0x10;
+    pub const MAIN_COUNTER: usize = 0xf0;
+}
+
+const CONFIG_ENABLE: u64 = 1 << 0;
+/// femtoseconds in one second
+const FS_PER_SECOND: u64 = 1_000_000_000_000_000;
+
+
+pub struct Hpet {
+    base: usize,
+    /// period of the main counter in femtoseconds
+    period: u64,
+}
+
+impl Hpet {
+    /// enables the main counter of HPET described by the ACPI table
+    /// - returns `Err` if the registers are not memory mapped or report invalid period
+    /// # Safety
+    /// - `hhdm` must be the offset of the higher half direct map, which must map the registers
+    /// - nothing else may reconfigure the HPET while it is used
+    pub unsafe fn new(table: &crate::acpi::Hpet, hhdm: usize) -> Result<Self, ()> {
+        let address = table.base_address();
+        if address.address_space != ADDRESS_SPACE_MEMORY || address.address == 0 {
+            return Err(());
+        }
+
+        let mut hpet = Self {
+            base: address.address as usize + hhdm,
+            period: 0,
+        };
+
+        hpet.period = hpet.read(reg::CAPABILITIES) >> 32;
+        if hpet.period == 0 || hpet.period > 100_000_000 {
+            return Err(());
+        }
+
+        hpet.write(reg::CONFIG, hpet.read(reg::CONFIG) | CONFIG_ENABLE);
+        Ok(hpet)
+    }
+
+    #[inline(always)]
+    fn read(&self, reg: usize) -> u64 {
+        unsafe { read_volatile((self.base + reg) as *const u64) }
+    }
+    #[inline(always)]
+    fn write(&self, reg: usize, value: u64) {
+        unsafe { write_volatile((self.base + reg) as *mut u64, value) }
+    }
+
+    /// returns the main counter value
+    #[inline]
+    pub fn counter(&self) -> u64 {
+        self.read(reg::MAIN_COUNTER)
+    }
+
+    /// returns frequency of the main counter in Hz
+    #[inline]
+    pub fn frequency(&self) -> u64 {
+        FS_PER_SECOND / self.period
+    }
+
+    /// waits `us` microseconds (busy waiting)
+    pub fn wait_us(&self, us: u64) {
+        let ticks = us * 1_000_000_000 / self.period;
+        let start = self.counter();
+        while self.counter().wrapping_sub(start) < ticks {
+            core::hint::spin_loop();
+        }
+    }
+}
@@ -0,0 +1,256 @@
+//	time/mod.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  time subsystem
+//  - TSC and local APIC timer are calibrated against HPET (or PIT if there is no HPET)
+//  - TSC is used as monotonic clock ([`Instant`])
+//  - local APIC timer ticks [`TICK_HZ`] times per second and runs timer callbacks
+
+
+use core::ops::{Add, AddAssign, Sub, SubAssign};
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use crate::arch::{rdtsc, interrupt::InterruptFrame};
+use crate::io::int::{self, lapic, TIMER_VECTOR};
+
+pub mod pit;
+pub mod hpet;
+pub mod timer;
+
+pub use core::time::Duration;
+pub use timer::{TimerCallback, TimerId};
+
+
+/// frequency of the timer interrupt
+pub const TICK_HZ: u64 = 1000;
+/// how long calibration takes
+const CALIBRATION_US: u64 = 10_000;
+
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+
+static TSC_HZ: AtomicU64 = AtomicU64::new(0);
+static BOOT_TSC: AtomicU64 = AtomicU64::new(0);
+/// local APIC timer frequency (after the divider)
+static LAPIC_TIMER_HZ: AtomicU64 = AtomicU64::new(0);
+/// number of timer interrupts on the bootstrap processor
+static TICKS: AtomicU64 = AtomicU64::new(0);
+
+
+/// # Instant
+/// Monotonic point in time (nanoseconds since calibration)
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Instant(u64);
+
+impl Instant {
+    /// returns the current time
+    /// - before the time subsystem is initialized always returns zero
+    pub fn now() -> Self {
+        let hz = TSC_HZ.load(Ordering::Relaxed);
+        if hz == 0 {
+            return Self(0);
+        }
+        let tsc = rdtsc().wrapping_sub(BOOT_TSC.load(Ordering::Relaxed));
+        Self((tsc as u128 * NANOS_PER_SEC as u128 / hz as u128) as u64)
+    }
+
+    /// time since boot (calibration)
+    #[inline]
+    pub const fn as_duration(&self) -> Duration {
+        Duration::from_nanos(self.0)
+    }
+
+    #[inline]
+    pub fn elapsed(&self) -> Duration {
+        Self::now().saturating_duration_since(*self)
+    }
+
+    /// returns `Err` if `earlier` is later than `self`
+    #[inline]
+    pub fn duration_since(&self, earlier: Instant) -> Result<Duration, ()> {
+        self.0.checked_sub(earlier.0).map(Duration::from_nanos).ok_or(())
+    }
+
+    #[inline]
+    pub fn saturating_duration_since(&self, earlier: Instant) -> Duration {
+        Duration::from_nanos(self.0.saturating_sub(earlier.0))
+    }
+
+    #[inline]
+    pub fn checked_add(&self, duration: Duration) -> Option<Instant> {
+        let nanos = u64::try_from(duration.as_nanos()).ok()?;
+        self.0.checked_add(nanos).map(Self)
+    }
+
+    #[inline]
+    pub fn checked_sub(&self, duration: Duration) -> Option<Instant> {
+        let nanos = u64::try_from(duration.as_nanos()).ok()?;
+        self.0.checked_sub(nanos).map(Self)
+    }
+}
+
+impl Add<Duration> for Instant {
+    type Output = Instant;
+    /// **panics** on overflow
+    #[inline]
+    fn add(self, rhs: Duration) -> Instant {
+        self.checked_add(rhs).expect("overflow when adding duration to instant")
+    }
+}
+
+impl AddAssign<Duration> for Instant {
+    #[inline]
+    fn add_assign(&mut self, rhs: Duration) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub<Duration> for Instant {
+    type Output = Instant;
+    /// **panics** on overflow
+    #[inline]
+    fn sub(self, rhs: Duration) -> Instant {
+        self.checked_sub(rhs).expect("overflow when subtracting duration from instant")
+    }
+}
+
+impl SubAssign<Duration> for Instant {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Duration) {
+        *self = *self - rhs;
+    }
+}
+
+impl Sub<Instant> for Instant {
+    type Output = Duration;
+    /// saturates to zero
+    #[inline]
+    fn sub(self, rhs: Instant) -> Duration {
+        self.saturating_duration_since(rhs)
+    }
+}
+
+
+/// returns time since the time subsystem was initialized
+#[inline]
+pub fn uptime() -> Duration {
+    Instant::now().as_duration()
+}
+
+/// returns the number of timer interrupts since initialization
+#[inline]
+pub fn ticks() -> u64 {
+    TICKS.load(Ordering::Relaxed)
+}
+
+/// returns calibrated TSC frequency
+#[inline]
+pub fn tsc_frequency() -> Option<u64> {
+    match TSC_HZ.load(Ordering::Relaxed) {
+        0 => None,
+        hz => Some(hz),
+    }
+}
+
+/// returns calibrated local APIC timer frequency (after the divider)
+#[inline]
+pub fn lapic_timer_frequency() -> Option<u64> {
+    match LAPIC_TIMER_HZ.load(Ordering::Relaxed) {
+        0 => None,
+        hz => Some(hz),
+    }
+}
+
+/// checks if the time subsystem is initialized
+#[inline]
+pub fn is_initialized() -> bool {
+    tsc_frequency().is_some()
+}
+
+
+/// waits for `duration` by spinning
+/// - works with interrupts disabled
+/// - uses the PIT before the time subsystem is initialized
+pub fn busy_sleep(duration: Duration) {
+    if !is_initialized() {
+        pit::wait_us(duration.as_micros() as u64);
+        return;
+    }
+    let deadline = Instant::now() + duration;
+    while Instant::now() < deadline {
+        core::hint::spin_loop();
+    }
+}
+
+/// waits for `duration`, halting the processor between timer interrupts
+/// - falls back to [`busy_sleep`] if interrupts are disabled
+pub fn sleep(duration: Duration) {
+    if !is_initialized() || !int::are_enabled() {
+        busy_sleep(duration);
+        return;
+    }
+    let deadline = Instant::now() + duration;
+    while Instant::now() < deadline {
+        unsafe { core::arch::asm!("hlt", options(nomem, nostack)) };
+    }
+}
+
+
+/// handles the local APIC timer interrupt
+fn tick(_frame: &mut InterruptFrame) {
+    let ticks = TICKS.fetch_add(1, Ordering::Relaxed) + 1;
+    timer::run_expired(Instant::now());
+    crate::thread::on_tick(ticks);
+}
+
+
+/// busy waits `us` microseconds using the best available reference
+fn reference_wait(hpet: Option<&hpet::Hpet>, us: u64) {
+    match hpet {
+        Some(h) => h.wait_us(us),
+        None => pit::wait_us(us),
+    }
+}
+
+/// starts periodic timer interrupt on the current processor
+/// - requires the timer to be calibrated
+pub(crate) fn start_local_timer() -> Result<(), ()> {
+    let lapic = lapic().ok_or(())?;
+    let hz = lapic_timer_frequency().ok_or(())?;
+    let initial = u32::try_from(hz / TICK_HZ).map_err(|_| ())?;
+    lapic.start_timer(TIMER_VECTOR, initial.max(1), true, false);
+    Ok(())
+}
+
+/// calibrates TSC and local APIC timer and starts the timer interrupt
+/// - requires the interrupt controllers to be initialized
+pub(crate) fn init() -> Result<(), ()> {
+    let lapic = lapic().ok_or(())?;
+
+    let hpet = crate::acpi::tables()
+        .and_then(|t| t.hpet())
+        .zip(crate::mem::hhdm_offset())
+        .and_then(|(t, hhdm)| unsafe { hpet::Hpet::new(&t, hhdm) }.ok());
+
+    int::without_interrupts(|| {
+        lapic.start_timer(TIMER_VECTOR, u32::MAX, false, true);
+        let tsc_start = rdtsc();
+
+        reference_wait(hpet.as_ref(), CALIBRATION_US);
+
+        let tsc_end = rdtsc();
+        let lapic_ticks = u32::MAX - lapic.timer_count();
+        lapic.stop_timer();
+
+        let tsc_hz = (tsc_end - tsc_start) * 1_000_000 / CALIBRATION_US;
+        let lapic_hz = lapic_ticks as u64 * 1_000_000 / CALIBRATION_US;
+
+        LAPIC_TIMER_HZ.store(lapic_hz, Ordering::Relaxed);
+        BOOT_TSC.store(tsc_end, Ordering::Relaxed);
+        TSC_HZ.store(tsc_hz, Ordering::Relaxed);
+    });
+
+    int::register_irq_handler(TIMER_VECTOR, tick)?;
+    start_local_timer()
+}
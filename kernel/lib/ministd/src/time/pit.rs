@@ -0,0 +1,62 @@
+//	time/pit.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  8253/8254 Programmable Interval Timer
+//  - only channel 2 is used (polled, no interrupts) as calibration reference
+
+
+use crate::io::{inb, outb};
+
+
+/// input clock of the PIT
+pub const FREQUENCY: u64 = 1_193_182;
+
+const CHANNEL2: u16 = 0x42;
+const COMMAND: u16 = 0x43;
+/// keyboard controller port B (channel 2 gate and output)
+const PORT_B: u16 = 0x61;
+
+const GATE2: u8 = 1 << 0;
+const SPEAKER: u8 = 1 << 1;
+const OUT2: u8 = 1 << 5;
+
+//  command fields
+const SELECT_CHANNEL2: u8 = 0b10 << 6;
+const ACCESS_LOBYTE_HIBYTE: u8 = 0b11 << 4;
+/// interrupt on terminal count
+const MODE0: u8 = 0b000 << 1;
+const BINARY: u8 = 0;
+
+/// channel 2, lobyte/hibyte, mode 0, binary counting
+const ONE_SHOT: u8 = SELECT_CHANNEL2 | ACCESS_LOBYTE_HIBYTE | MODE0 | BINARY;
+
+
+/// waits `ticks` PIT ticks (busy waiting)
+pub fn wait_ticks(ticks: u16) {
+    let port_b = inb(PORT_B) & !(GATE2 | SPEAKER);
+    outb(PORT_B, port_b);
+
+    outb(COMMAND, ONE_SHOT);
+    outb(CHANNEL2, ticks as u8);
+    outb(CHANNEL2, (ticks >> 8) as u8);
+
+    //  counting starts on the rising edge of the gate
+    outb(PORT_B, port_b | GATE2);
+
+    while inb(PORT_B) & OUT2 == 0 {
+        core::hint::spin_loop();
+    }
+
+    outb(PORT_B, port_b);
+}
+
+/// waits `us` microseconds (busy waiting)
+pub fn wait_us(us: u64) {
+    let mut ticks = us * FREQUENCY / 1_000_000;
+    while ticks > 0 {
+        let chunk = ticks.min(u16::MAX as u64);
+        wait_ticks(chunk as u16);
+        ticks -= chunk;
+    }
+}
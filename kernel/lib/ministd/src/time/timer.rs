@@ -0,0 +1,119 @@
+//	time/timer.rs (ministd crate)
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  one-shot and periodic timer callbacks
+//  - callbacks run in the timer interrupt handler (interrupts disabled)
+//  - resolution is one tick (see [`super::TICK_HZ`])
+
+
+use core::sync::atomic::{AtomicU64, Ordering};
+use spin::Mutex;
+
+use crate::io::int::without_interrupts;
+use super::{Duration, Instant};
+
+
+/// maximum number of active timers
+pub const MAX_TIMERS: usize = 32;
+
+/// Function called when timer expires
+pub type TimerCallback = fn();
+
+
+/// Handle of an active timer
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct TimerId {
+    index: usize,
+    id: u64,
+}
+
+#[derive(Copy, Clone)]
+struct Timer {
+    id: u64,
+    deadline: Instant,
+    period: Option<Duration>,
+    callback: TimerCallback,
+}
+
+
+static TIMERS: Mutex<[Option<Timer>; MAX_TIMERS]> = Mutex::new([None; MAX_TIMERS]);
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+
+fn add(delay: Duration, period: Option<Duration>, callback: TimerCallback) -> Result<TimerId, ()> {
+    let deadline = Instant::now().checked_add(delay).ok_or(())?;
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+
+    //  the lock is also taken by the timer interrupt
+    without_interrupts(|| {
+        let mut timers = TIMERS.lock();
+        let index = timers.iter().position(|t| t.is_none()).ok_or(())?;
+        timers[index] = Some(Timer { id, deadline, period, callback });
+        Ok(TimerId { index, id })
+    })
+}
+
+/// calls `callback` once after `delay`
+/// - returns `Err` if there are too many timers
+#[inline]
+pub fn after(delay: Duration, callback: TimerCallback) -> Result<TimerId, ()> {
+    add(delay, None, callback)
+}
+
+/// calls `callback` every `period`
+/// - returns `Err` if there are too many timers or `period` is zero
+#[inline]
+pub fn every(period: Duration, callback: TimerCallback) -> Result<TimerId, ()> {
+    if period.is_zero() {
+        return Err(());
+    }
+    add(period, Some(period), callback)
+}
+
+/// stops timer
+/// - returns `false` if the timer already expired (one-shot) or was cancelled
+pub fn cancel(timer: TimerId) -> bool {
+    without_interrupts(|| {
+        let mut timers = TIMERS.lock();
+        match timers[timer.index] {
+            Some(t) if t.id == timer.id => {
+                timers[timer.index] = None;
+                true
+            },
+            _ => false,
+        }
+    })
+}
+
+
+/// calls callbacks of all timers that expired before `now`
+/// - called from the timer interrupt
+pub(crate) fn run_expired(now: Instant) {
+    let mut expired: [Option<TimerCallback>; MAX_TIMERS] = [None; MAX_TIMERS];
+
+    //  interrupted code may hold the lock
+    let Some(mut timers) = TIMERS.try_lock() else {
+        return;
+    };
+    for (slot, out) in timers.iter_mut().zip(expired.iter_mut()) {
+        let Some(timer) = slot else { continue };
+        if timer.deadline > now {
+            continue;
+        }
+        *out = Some(timer.callback);
+
+        match timer.period {
+            //  skip missed periods instead of firing them all at once
+            Some(period) => while timer.deadline <= now {
+                timer.deadline += period;
+            },
+            None => *slot = None,
+        }
+    }
+    drop(timers);
+
+    for callback in expired.into_iter().flatten() {
+        callback();
+    }
+}
@@ -5,21 +5,90 @@
 //  module for kernel initialization
 
 
-use ministd::{dbg, io};
+use ministd::{dbg, io, thread};
+use ministd::renderer::{console, Font, RENDERER};
+use crate::manage::{self, KERNEL_STATE, KernelState, KernelRuntimeState};
 use ministd::{println, print, locked_println, eprintln, init};
 use ministd::{Box, Array, Vec, String, HashMap};
 use core::fmt::Write;
 
 fn init() -> Result<(), ()> {
 
+    //  serial console is used as fallback when there is no framebuffer
+    let serial = init::serial().is_ok();
+    if serial {
+        io::serial::set_tee(true);
+    }
+
     if let Err(_) = init::renderer() {
-        panic!("failed to initialize renderer");
+        if !serial {
+            panic!("failed to initialize renderer");
+        }
+        println!("no framebuffer available, using serial console only");
+    }
+
+    //  optional console font, add `module_path: boot():/boot/font.psf` to limine.conf
+    if let Ok(font) = Font::from_module("font.psf") {
+        RENDERER.lock().set_font(font);
     }
 
+    init::gdt();
+    init::idt();
+
     if let Err(_) = init::allocator() {
         panic!("failed to initialize heap");
     }
 
+    if let Err(_) = init::frames() {
+        panic!("failed to initialize frame allocator");
+    }
+
+    //  scrolling still works without it, only slower
+    if let Err(_) = init::back_buffer() {
+        println!("console runs without back buffer");
+    }
+
+    //  console 0 keeps the kernel log, the shell gets console 1 (Alt+F1/F2 switch)
+    if let Err(_) = init::consoles(3) {
+        println!("only {} virtual console(s) available", console::count());
+    }
+
+    if let Err(_) = init::paging() {
+        panic!("failed to initialize paging");
+    }
+
+    if let Err(_) = init::interrupts() {
+        panic!("failed to initialize interrupt controllers");
+    }
+
+    if let Err(_) = init::time() {
+        panic!("failed to initialize timer");
+    }
+
+    if let Err(_) = init::threads() {
+        panic!("failed to initialize scheduler");
+    }
+    io::int::enable();
+
+    match init::smp() {
+        Ok(count) => println!("{} processor(s) online", count),
+        Err(_) => panic!("failed to start application processors"),
+    }
+
+    //  machines without PCI are still usable
+    match init::pci() {
+        Ok(count) => println!("{} PCI function(s) found", count),
+        Err(_) => println!("PCI is not available"),
+    }
+
+    if let Err(_) = init::keyboard() {
+        println!("no PS/2 keyboard found");
+    }
+
+    if let Err(_) = init::shell().and_then(|_| manage::commands::register()) {
+        println!("failed to initialize shell");
+    }
+
     println!("hello world!");
 
     let mut h: HashMap<usize, String> = HashMap::new();
@@ -44,5 +113,16 @@ extern "C" fn _start() {
         panic!("failed to initialize the kernel");
     }
 
-    ministd::hang();
+    *KERNEL_STATE.write() = KernelState::Runtime(KernelRuntimeState::Thread(thread::current_id()));
+
+    thread::spawn(|| {
+        if console::count() > 1 {
+            thread::set_console(1);
+            let _ = console::switch(1);
+        }
+        ministd::shell::run()
+    });
+
+    //  the boot thread has nothing else to do
+    thread::idle();
 }
@@ -0,0 +1,39 @@
+//	manage/commands.rs
+//	this file originally belonged to baseOS project
+//		an OS template on which to build
+
+//  kernel specific shell commands
+//  - feel free to add your own, ministd only provides the generic ones
+
+
+use ministd::shell::{self, Command};
+use ministd::convert::strify;
+use ministd::{println, thread};
+
+use crate::manage::{KERNEL_STATE, KernelState, KernelRuntimeState};
+
+
+static COMMANDS: [Command; 1] = [
+    Command::new("state", "prints the kernel state", state),
+];
+
+
+fn state(_: &[&str]) -> Result<(), ()> {
+    let state = *KERNEL_STATE.read();
+    match state {
+        KernelState::Init(i) => println!("initializing {}", strify(i.as_str())),
+        KernelState::Runtime(KernelRuntimeState::Thread(id)) => println!("runtime, thread {}", id),
+        KernelState::Runtime(r) => println!("runtime, task {}", strify(r.as_str())),
+        KernelState::Shutdown(s) => println!("shutdown, task {}", strify(s.as_str())),
+        KernelState::Panic => println!("panic"),
+    }
+    println!("{} thread(s), current {}", thread::count(), thread::current_id());
+    Ok(())
+}
+
+
+/// adds the kernel commands to the shell
+/// - returns `Err` if the command table can not grow
+pub fn register() -> Result<(), ()> {
+    COMMANDS.iter().try_for_each(|c| shell::register_command(*c))
+}
@@ -3,6 +3,7 @@
 //		an OS template on which to build
 
 
+use ministd::thread::ThreadId;
 
 
 
@@ -28,6 +29,8 @@ pub enum KernelInitState {
 #[derive(Copy, Clone)]
 pub enum KernelRuntimeState {
     Unknown,
+    /// kernel thread with this id is running
+    Thread(ThreadId),
 }
 
 #[derive(Copy, Clone)]
@@ -65,6 +68,7 @@ impl KernelRuntimeState {
     pub fn as_str(&self) -> &'static [u8] {
         match self {
             Self::Unknown => b"unknown",
+            Self::Thread(_) => b"thread",
         }
     }
 }
@@ -3,12 +3,15 @@
 //		an OS template on which to build
 
 
-use ministd::{renderer::{RENDERER}, RwLock, hang};
+use ministd::{renderer::{console, RENDERER}, RwLock, hang};
+use ministd::io::serial::COM1;
+use ministd::thread::ThreadId;
 use core::panic::{PanicInfo};
-use ministd::{locked_print, locked_println};
+use ministd::locked_println;
 use ministd::convert::strify;
 
 pub mod kernel_state;
+pub mod commands;
 pub use kernel_state::*;
 
 
@@ -19,12 +22,31 @@ pub use kernel_state::*;
 pub static KERNEL_STATE: RwLock<KernelState> = RwLock::new(KernelState::Init(KernelInitState::Base));
 
 
+/// called by the scheduler right before it switches to thread `id`
+/// - runs with interrupts disabled, so it must not block
+/// 
+/// feel free to rewrite this function but do not change the declaration
+#[unsafe(no_mangle)]
+extern "Rust" fn thread_switched(id: ThreadId) {
+    if let Some(mut state) = KERNEL_STATE.try_write() {
+        if let KernelState::Runtime(_) = *state {
+            *state = KernelState::Runtime(KernelRuntimeState::Thread(id));
+        }
+    }
+}
+
+
 #[panic_handler]
 pub fn panic_handler(info: &PanicInfo) -> ! {
 
+    //  the report goes to the kernel log console, which is brought to the screen
+    let _ = unsafe { console::force_switch(0) };
     if RENDERER.is_locked() {
         unsafe { RENDERER.force_unlock() }
     }
+    if COM1.is_locked() {
+        unsafe { COM1.force_unlock() }
+    }
     let mut rend = RENDERER.lock();
     if rend.column() > 0 {
         rend.endl();
@@ -34,11 +56,7 @@ pub fn panic_handler(info: &PanicInfo) -> ! {
     let state = *KERNEL_STATE.read();
     *KERNEL_STATE.write() = KernelState::Panic;
 
-    //let msg = info.message().as_str();
-    let msg = match info.message().as_str() {
-        Some(m) => Some(m),
-        None => None,
-    };
+    let msg = info.message();
     let location = info.location().unwrap();
 
 
@@ -50,6 +68,9 @@ pub fn panic_handler(info: &PanicInfo) -> ! {
         KernelState::Init(i) => {
             locked_println!(rend, "while initializing {}", strify(i.as_str()) );
         },
+        KernelState::Runtime(KernelRuntimeState::Thread(id)) => {
+            locked_println!(rend, "at runtime in thread {}", id);
+        },
         KernelState::Runtime(r) => {
             locked_println!(rend, "at runtime task {}", strify(r.as_str()));
         },
@@ -61,12 +82,10 @@ pub fn panic_handler(info: &PanicInfo) -> ! {
         },
     }
 
-    match msg {
-        Some(m) => {
-            locked_print!(rend, "error message: ");
-            rend.println(m.as_bytes());
-        },
-        None => locked_println!(rend, "No error message given!"),
+    //  CPU exceptions (and other formatted panics) carry their report in the message
+    match msg.as_str() {
+        Some("") => locked_println!(rend, "No error message given!"),
+        _ => locked_println!(rend, "error message: {}", msg),
     }
 
     hang();
@@ -2,10 +2,14 @@
 //  this file originally belonged to baseOS project
 //      on OS template on which to build
 
-use ministd::{mem::{alloc::*, Region}, println, renderer::{Color, RENDERER}, MutexGuard};
+use ministd::{mem::{alloc::*, Region}, println, renderer::{Color, RENDERER}};
 use bootloader::{MEMMAP, HHDM};
 use limine_rs::memory_map::EntryType;
 use ministd::mem::{MB, GB};
+use ministd::mem::frame::{self, FRAME_SIZE};
+
+/// minimal amount of memory the heap grows by
+const HEAP_GROW_SIZE: usize = MB;
 
 /// Use this function to find an valid spot for heap
 /// Feel free to rewrite it!
@@ -38,23 +42,28 @@ extern "Rust" fn find_heap_region() -> Result<Region, ()> {
 
 /// this function is called by the allocator whenever it fails to allocate memory
 /// - if it succees (returns Ok) it will try to allocate again  
+/// - `layout` is the allocation that failed
+/// - the heap is not locked while this runs, use `allocator` to add memory to it
 /// 
 /// feel free to rewrite this function but:
 /// - be sure you know what are you doing
 /// - do not change the declaration
+/// - memory given to the heap must not overlap its other regions
 #[unsafe(no_mangle)]
-extern "Rust" fn out_of_memory_handler(heap: &mut MutexGuard<Heap>, allocator: &Allocator) -> Result<(), ()> {
-    //  use heap to access heap data
+extern "Rust" fn out_of_memory_handler(allocator: &Allocator, layout: Layout) -> Result<(), ()> {
+    let hhdm = match HHDM.get_response() {
+        Some(res) => res.offset(),
+        None => return Err(()),
+    } as usize;
 
+    //  the buddy heap needs a whole power-of-two block for the allocation
+    let size = layout.size().max(layout.align()).next_power_of_two().max(HEAP_GROW_SIZE);
+    let count = size.div_ceil(FRAME_SIZE);
 
-    if let Ok(reg) = find_heap_region() {
+    //  fresh frames never overlap memory that is already in the heap
+    //  - the heap may run out while the frame allocator is locked, do not wait for it
+    let phys = frame::try_alloc_frames(count)?;
+    let reg = Region::new(phys + hhdm, count.next_power_of_two() * FRAME_SIZE);
 
-        //  TODO: push into the regions vector
-        unsafe { heap.add_to_heap(reg.start(), reg.start() + reg.size()) }
-        Ok(())
-    } else {
-        Err(())
-    }
+    unsafe { allocator.add_region(reg) }
 }
-
-